@@ -0,0 +1,21 @@
+#![no_main]
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin_slices::bsl::{ArbitraryBlock, Block, Corruption};
+use bitcoin_slices::{Error, Parse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (ArbitraryBlock, Corruption)| {
+    let (block, corruption) = input;
+    let parsed = Block::parse_all(block.as_ref()).expect("valid block");
+    let block_bitcoin: bitcoin::Block = deserialize(block.as_ref()).expect("valid block");
+    assert_eq!(serialize(&block_bitcoin), block.as_ref());
+    assert_eq!(parsed.total_transactions(), block_bitcoin.txdata.len());
+
+    let corrupted = block.corrupted(corruption);
+    let err = Block::parse_all(&corrupted).expect_err("corrupted block");
+    match corruption {
+        Corruption::Truncate(_) => assert!(matches!(err, Error::Needed(_))),
+        Corruption::NonMinimalVarInt(_) => assert_eq!(err, Error::NonMinimalVarInt),
+    }
+    assert!(deserialize::<bitcoin::Block>(&corrupted).is_err());
+});
@@ -0,0 +1,24 @@
+#![no_main]
+use bitcoin::consensus::{deserialize, serialize};
+use bitcoin::hashes::Hash;
+use bitcoin_slices::bsl::{ArbitraryTransaction, Corruption, Transaction};
+use bitcoin_slices::{Error, Parse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (ArbitraryTransaction, Corruption)| {
+    let (tx, corruption) = input;
+    let parsed = Transaction::parse_all(tx.as_ref()).expect("valid transaction");
+    let tx_bitcoin: bitcoin::Transaction = deserialize(tx.as_ref()).expect("valid transaction");
+    assert_eq!(serialize(&tx_bitcoin), tx.as_ref());
+    assert_eq!(parsed.input_count(), tx_bitcoin.input.len());
+    assert_eq!(parsed.output_count(), tx_bitcoin.output.len());
+    assert_eq!(parsed.txid().into_inner(), tx_bitcoin.txid().into_inner());
+
+    let corrupted = tx.corrupted(corruption);
+    let err = Transaction::parse_all(&corrupted).expect_err("corrupted transaction");
+    match corruption {
+        Corruption::Truncate(_) => assert!(matches!(err, Error::Needed(_))),
+        Corruption::NonMinimalVarInt(_) => assert_eq!(err, Error::NonMinimalVarInt),
+    }
+    assert!(deserialize::<bitcoin::Transaction>(&corrupted).is_err());
+});
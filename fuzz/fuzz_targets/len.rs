@@ -1,9 +1,9 @@
 #![no_main]
-use bitcoin_slices::bsl::Len;
-use bitcoin_slices::fuzzing::check;
+use bitcoin_slices::bsl::parse_len;
 use libfuzzer_sys::fuzz_target;
 
 fuzz_target!(|data: &[u8]| {
-    let p = Len::parse(data);
-    check(data, p);
+    if let Ok(len) = parse_len(data) {
+        assert!(len.consumed() <= data.len());
+    }
 });
@@ -0,0 +1,245 @@
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use alloc::vec::Vec;
+
+use crate::bsl::writer::{TxParts, Writer};
+
+/// The maximum number of inputs and outputs of the generated transactions.
+const MAX_INPUTS_OUTPUTS: u32 = 8;
+
+/// The maximum number of transactions of the generated blocks.
+const MAX_TRANSACTIONS: u32 = 8;
+
+/// The maximum number of elements of the generated witnesses.
+const MAX_WITNESS_ELEMENTS: u32 = 4;
+
+/// The maximum length of the generated scripts and witness elements.
+const MAX_SCRIPT_LEN: u32 = 300;
+
+/// A valid consensus serialized transaction, generated from fuzzer input.
+///
+/// Transactions with no inputs are serialized with the segwit marker, `00 01 00...`, since in the
+/// legacy serialization the zero inputs count would be read as the marker, segwit ones with
+/// inputs have at least a non-empty witness. The transactions with no inputs are thus valid for
+/// parsing but not for consensus, Bitcoin Core rejects a segwit serialization without witness data
+/// as "Superfluous witness record".
+///
+/// Useful for structure aware fuzzing, reaching deeper than random bytes, and to generate invalid
+/// data close to valid with [`ArbitraryTransaction::corrupted()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryTransaction {
+    bytes: Vec<u8>,
+    /// The offsets of the compact ints in `bytes`.
+    lens: Vec<usize>,
+}
+
+/// A valid consensus serialized block, with an arbitrary header and at least a transaction,
+/// generated from fuzzer input.
+///
+/// The block is valid for parsing, no consensus rule is checked, like the merkle root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitraryBlock {
+    bytes: Vec<u8>,
+    /// The offsets of the compact ints in `bytes`.
+    lens: Vec<usize>,
+}
+
+/// A targeted corruption of [`ArbitraryTransaction`] or [`ArbitraryBlock`] serialization, see
+/// [`ArbitraryTransaction::corrupted()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// Truncate the serialization, at the contained value modulo its length so that at least a
+    /// byte is removed.
+    Truncate(usize),
+
+    /// Encode a compact int with more bytes than needed, the contained value modulo the number of
+    /// compact ints chooses which.
+    NonMinimalVarInt(usize),
+}
+
+impl<'a> Arbitrary<'a> for Corruption {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(if u.arbitrary()? {
+            Corruption::Truncate(u.arbitrary()?)
+        } else {
+            Corruption::NonMinimalVarInt(u.arbitrary()?)
+        })
+    }
+}
+
+/// Returns variable length bytes, like a script, of at most [`MAX_SCRIPT_LEN`] bytes.
+fn var_bytes(u: &mut Unstructured) -> Result<Vec<u8>> {
+    let len = u.int_in_range(0..=MAX_SCRIPT_LEN)? as usize;
+    Ok(u.bytes(len)?.to_vec())
+}
+
+/// Returns the parts of a transaction, serialized with [`Writer::push_transaction()`].
+fn tx_parts(u: &mut Unstructured) -> Result<TxParts> {
+    let version = u.arbitrary()?;
+    let inputs = u.int_in_range(0..=MAX_INPUTS_OUTPUTS)? as usize;
+    let outputs = u.int_in_range(0..=MAX_INPUTS_OUTPUTS)? as usize;
+    let segwit = u.arbitrary()?;
+    let inputs = (0..inputs)
+        .map(|_| Ok((u.bytes(36)?.to_vec(), var_bytes(u)?, u.arbitrary()?)))
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = (0..outputs)
+        .map(|_| Ok((u.arbitrary()?, var_bytes(u)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut witnesses = Vec::new();
+    if segwit {
+        for _ in 0..inputs.len() {
+            let elements = u.int_in_range(0..=MAX_WITNESS_ELEMENTS)?;
+            witnesses.push((0..elements).map(|_| var_bytes(u)).collect::<Result<_>>()?);
+        }
+    }
+    Ok(TxParts {
+        version,
+        inputs,
+        outputs,
+        segwit,
+        witnesses,
+        locktime: u.arbitrary()?,
+    })
+}
+
+/// Returns `bytes` with the `corruption` applied.
+fn corrupt(bytes: &[u8], lens: &[usize], corruption: Corruption) -> Vec<u8> {
+    match corruption {
+        Corruption::Truncate(len) => bytes[..len % bytes.len()].to_vec(),
+        Corruption::NonMinimalVarInt(index) => {
+            let offset = lens[index % lens.len()];
+            let len = crate::bsl::parse_len(&bytes[offset..]).expect("valid serialization");
+            let mut corrupted = bytes[..offset].to_vec();
+            // the compact ints generated are lower than 0xFFFF
+            if len.consumed() == 1 {
+                corrupted.push(0xFD);
+                corrupted.extend_from_slice(&(len.n() as u16).to_le_bytes());
+            } else {
+                corrupted.push(0xFE);
+                corrupted.extend_from_slice(&(len.n() as u32).to_le_bytes());
+            }
+            corrupted.extend_from_slice(&bytes[offset + len.consumed()..]);
+            corrupted
+        }
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryTransaction {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut writer = Writer::default();
+        writer.push_transaction(&tx_parts(u)?);
+        Ok(ArbitraryTransaction {
+            bytes: writer.bytes,
+            lens: writer.lens,
+        })
+    }
+}
+
+impl ArbitraryTransaction {
+    /// Returns the serialization with `corruption` applied, which must fail parsing with
+    /// [`crate::Error::Needed`] or [`crate::Error::NonMinimalVarInt`] respectively.
+    pub fn corrupted(&self, corruption: Corruption) -> Vec<u8> {
+        corrupt(&self.bytes, &self.lens, corruption)
+    }
+
+    /// Returns the serialized transaction bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for ArbitraryTransaction {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+}
+
+impl<'a> Arbitrary<'a> for ArbitraryBlock {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut writer = Writer::default();
+        writer.push_bytes(u.bytes(80)?);
+        let transactions = u.int_in_range(1..=MAX_TRANSACTIONS)? as usize;
+        writer.push_len(transactions);
+        for _ in 0..transactions {
+            writer.push_transaction(&tx_parts(u)?);
+        }
+        Ok(ArbitraryBlock {
+            bytes: writer.bytes,
+            lens: writer.lens,
+        })
+    }
+}
+
+impl ArbitraryBlock {
+    /// Returns the serialization with `corruption` applied, see
+    /// [`ArbitraryTransaction::corrupted()`].
+    pub fn corrupted(&self, corruption: Corruption) -> Vec<u8> {
+        corrupt(&self.bytes, &self.lens, corruption)
+    }
+
+    /// Returns the serialized block bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl AsRef<[u8]> for ArbitraryBlock {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ::arbitrary::{Arbitrary, Unstructured};
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin::secp256k1::rand::{rngs::StdRng, RngCore, SeedableRng};
+
+    use crate::{
+        bsl::{ArbitraryBlock, ArbitraryTransaction, Block, Corruption, Transaction},
+        Error, Parse,
+    };
+
+    #[test]
+    fn arbitrary_transaction() {
+        // seeded so that a failure can be reproduced
+        let mut rng = StdRng::seed_from_u64(100);
+        let mut data = vec![0u8; 10_000];
+        for _ in 0..100 {
+            rng.fill_bytes(&mut data);
+            let mut u = Unstructured::new(&data);
+            let tx = ArbitraryTransaction::arbitrary(&mut u).unwrap();
+            let parsed = Transaction::parse_all(tx.as_ref()).unwrap();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx.as_ref()).unwrap();
+            assert_eq!(serialize(&tx_bitcoin), tx.as_ref());
+            assert_eq!(parsed.input_count(), tx_bitcoin.input.len());
+
+            let corruption = Corruption::arbitrary(&mut u).unwrap();
+            let corrupted = tx.corrupted(corruption);
+            let err = Transaction::parse_all(&corrupted).unwrap_err();
+            match corruption {
+                Corruption::Truncate(_) => assert!(matches!(err, Error::Needed(_))),
+                Corruption::NonMinimalVarInt(_) => assert_eq!(err, Error::NonMinimalVarInt),
+            }
+            assert!(deserialize::<bitcoin::Transaction>(&corrupted).is_err());
+        }
+    }
+
+    #[test]
+    fn arbitrary_block() {
+        let mut rng = StdRng::seed_from_u64(20);
+        let mut data = vec![0u8; 50_000];
+        for _ in 0..20 {
+            rng.fill_bytes(&mut data);
+            let mut u = Unstructured::new(&data);
+            let block = ArbitraryBlock::arbitrary(&mut u).unwrap();
+            let parsed = Block::parse_all(block.as_ref()).unwrap();
+            let block_bitcoin: bitcoin::Block = deserialize(block.as_ref()).unwrap();
+            assert_eq!(parsed.total_transactions(), block_bitcoin.txdata.len());
+
+            let corrupted = block.corrupted(Corruption::NonMinimalVarInt(0));
+            assert_eq!(Block::parse(&corrupted), Err(Error::NonMinimalVarInt));
+            let corrupted = block.corrupted(Corruption::Truncate(usize::MAX));
+            assert!(matches!(Block::parse(&corrupted), Err(Error::Needed(_))));
+        }
+    }
+}
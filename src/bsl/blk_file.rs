@@ -0,0 +1,212 @@
+use crate::bsl::Block;
+use crate::{Error, Parse};
+
+/// Iterator over the blocks of a Bitcoin Core `blk*.dat` file, created with [`BlkFileIter::new`].
+///
+/// Every block in the file is prefixed by the 4 bytes network magic and its length as a 4 bytes
+/// little-endian number. Zero bytes before a record are skipped, since Core preallocates the files
+/// and leaves them filled with zeros at the end.
+///
+/// After an error the iterator always returns `None`, because the position of the next record
+/// can't be known.
+pub struct BlkFileIter<'a> {
+    remaining: &'a [u8],
+    magic: [u8; 4],
+    /// The padding bytes once de-obfuscated, all zeros for files not obfuscated.
+    xor_key: [u8; 8],
+    /// The position of `remaining` in the file.
+    position: usize,
+}
+
+impl<'a> BlkFileIter<'a> {
+    /// Creates the iterator over the content of a block file, like a memory mapped one, whose
+    /// records must start with the given network `magic`, for example
+    /// [`MAINNET_MAGIC`](crate::bsl::constants::MAINNET_MAGIC).
+    pub fn new(slice: &'a [u8], magic: [u8; 4]) -> Self {
+        BlkFileIter {
+            remaining: slice,
+            magic,
+            xor_key: [0u8; 8],
+            position: 0,
+        }
+    }
+
+    /// Creates the iterator over the content of a block file obfuscated with the 8 bytes `key`,
+    /// as written by Bitcoin Core 28 and later, which stores the key in `blocks/xor.dat`.
+    ///
+    /// The whole `slice` is de-obfuscated in place with [`xor_slice`] before iterating, and must
+    /// start at the beginning of the file. The zero padding, which Core doesn't obfuscate, is
+    /// still skipped.
+    pub fn new_obfuscated(slice: &'a mut [u8], magic: [u8; 4], key: [u8; 8]) -> Self {
+        xor_slice(slice, key, 0);
+        BlkFileIter {
+            remaining: slice,
+            magic,
+            xor_key: key,
+            position: 0,
+        }
+    }
+
+    fn next_block(&mut self) -> Result<Block<'a>, Error> {
+        let (header, rest) = split_at(self.remaining, 8)?;
+        if header[..4] != self.magic {
+            return Err(Error::InvalidMagic);
+        }
+        let len = u32::from_le_bytes(header[4..8].try_into().expect("4 bytes"));
+        let (record, rest) = split_at(rest, len as usize)?;
+        let block = Block::parse_all(record)?;
+        self.position += 8 + record.len();
+        self.remaining = rest;
+        Ok(block)
+    }
+}
+
+/// XORs `slice` with the repeated 8 bytes `key`, as used by Bitcoin Core to obfuscate block files.
+///
+/// Since the key is applied from the start of the file, `offset` is the position of `slice` in the
+/// file, so that parts not aligned to 8 bytes can be processed. Applying it twice gives back the
+/// original data.
+pub fn xor_slice(slice: &mut [u8], key: [u8; 8], offset: u64) {
+    let start = (offset % 8) as usize;
+    for (byte, key_byte) in slice.iter_mut().zip(key.iter().cycle().skip(start)) {
+        *byte ^= key_byte;
+    }
+}
+
+fn split_at(slice: &[u8], len: usize) -> Result<(&[u8], &[u8]), Error> {
+    if slice.len() < len {
+        // `len` comes from a u32 or is 8, so the difference fits
+        Err(Error::Needed((len - slice.len()) as u32))
+    } else {
+        Ok(slice.split_at(len))
+    }
+}
+
+impl<'a> Iterator for BlkFileIter<'a> {
+    type Item = Result<Block<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.xor_key;
+        let position = self.position;
+        let start = self
+            .remaining
+            .iter()
+            .enumerate()
+            .position(|(i, b)| *b != key[(position + i) % 8])?;
+        self.remaining = &self.remaining[start..];
+        self.position += start;
+        let result = self.next_block();
+        if result.is_err() {
+            self.remaining = &[];
+        }
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{xor_slice, BlkFileIter, Block},
+        test_common::GENESIS_BLOCK,
+        Error, Parse,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    const MAGIC: [u8; 4] = crate::bsl::constants::MAINNET_MAGIC;
+
+    fn record(block: &[u8]) -> Vec<u8> {
+        let len = block.len() as u32;
+        [&MAGIC[..], &len.to_le_bytes()[..], block].concat()
+    }
+
+    #[test]
+    fn blk_file_iter() {
+        let mut file = [record(&GENESIS_BLOCK), record(mainnet_702861())].concat();
+        file.resize(file.len() + 1000, 0);
+
+        let blocks: Vec<_> = BlkFileIter::new(&file, MAGIC).collect();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::parse_all(&GENESIS_BLOCK[..]),
+                Block::parse_all(mainnet_702861()),
+            ]
+        );
+        assert_eq!(BlkFileIter::new(&[], MAGIC).count(), 0);
+        assert_eq!(BlkFileIter::new(&[0u8; 100], MAGIC).count(), 0);
+
+        let testnet_magic = crate::bsl::constants::TESTNET3_MAGIC;
+        let mut iter = BlkFileIter::new(&file, testnet_magic);
+        assert_eq!(iter.next(), Some(Err(Error::InvalidMagic)));
+        assert_eq!(iter.next(), None);
+
+        // length longer than the file
+        let mut corrupted = record(&GENESIS_BLOCK);
+        corrupted[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut iter = BlkFileIter::new(&corrupted, MAGIC);
+        assert_eq!(
+            iter.next(),
+            Some(Err(Error::Needed(u32::MAX - GENESIS_BLOCK.len() as u32)))
+        );
+        assert_eq!(iter.next(), None);
+
+        // length shorter and longer than the block
+        let mut corrupted = record(&GENESIS_BLOCK);
+        corrupted[4..8].copy_from_slice(&100u32.to_le_bytes());
+        let mut iter = BlkFileIter::new(&corrupted, MAGIC);
+        assert!(matches!(iter.next(), Some(Err(Error::Needed(_)))));
+        assert_eq!(iter.next(), None);
+
+        let mut corrupted = record(&[&GENESIS_BLOCK[..], &[1u8][..]].concat());
+        let mut iter = BlkFileIter::new(&corrupted, MAGIC);
+        assert_eq!(iter.next(), Some(Err(Error::TrailingBytes(1))));
+        corrupted.truncate(4);
+        let mut iter = BlkFileIter::new(&corrupted, MAGIC);
+        assert_eq!(iter.next(), Some(Err(Error::Needed(4))));
+    }
+
+    #[test]
+    fn test_xor_slice() {
+        let key = [1, 2, 3, 4, 5, 6, 7, 8];
+        let data: Vec<u8> = (0..30).collect();
+        let mut whole = data.clone();
+        xor_slice(&mut whole, key, 0);
+        assert_eq!(&whole[..10], &[1, 3, 1, 7, 1, 3, 1, 15, 9, 11]);
+
+        let mut parts = data.clone();
+        let (first, second) = parts.split_at_mut(13);
+        xor_slice(first, key, 0);
+        xor_slice(second, key, 13);
+        assert_eq!(parts, whole);
+
+        xor_slice(&mut whole, key, 0);
+        assert_eq!(whole, data);
+    }
+
+    #[test]
+    fn blk_file_iter_obfuscated() {
+        let key = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+        let mut file = [record(&GENESIS_BLOCK), record(mainnet_702861())].concat();
+        xor_slice(&mut file, key, 0);
+        // the padding is not obfuscated, and the data length is not a multiple of 8
+        file.resize(file.len() + 1001, 0);
+
+        let mut iter = BlkFileIter::new(&file, MAGIC);
+        assert_eq!(iter.next(), Some(Err(Error::InvalidMagic)));
+
+        let mut wrong_key = key;
+        wrong_key[7] ^= 1;
+        let mut copy = file.clone();
+        let mut iter = BlkFileIter::new_obfuscated(&mut copy, MAGIC, wrong_key);
+        assert!(iter.next().unwrap().is_err());
+
+        let blocks: Vec<_> = BlkFileIter::new_obfuscated(&mut file, MAGIC, key).collect();
+        assert_eq!(
+            blocks,
+            vec![
+                Block::parse_all(&GENESIS_BLOCK[..]),
+                Block::parse_all(mainnet_702861()),
+            ]
+        );
+    }
+}
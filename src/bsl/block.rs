@@ -1,35 +1,95 @@
-use super::len::{parse_len, Len};
-use crate::bsl::{BlockHeader, Transaction};
-use crate::{ParseResult, SResult, Visit, Visitor};
+use super::len::{parse_len_with, Len, LenKind};
+use super::merkle::MerkleComputation;
+use super::script::witness_commitment;
+use crate::bsl::{
+    visitors::FindTxidVisitor, BlockHeader, OutPoint, ParseConfig, Transaction, TxidHasher,
+};
+use crate::visit::check;
+use crate::{
+    EmptyVisitor, Error, MerkleError, Parse, ParseResult, SResult, Visit, Visitor,
+    WitnessCommitmentError,
+};
+
+/// The maximum weight of a block allowed by consensus, as defined in BIP141.
+pub const MAX_BLOCK_WEIGHT: usize = 4_000_000;
+
+/// The maximum signature operations cost of a block allowed by consensus, as defined in BIP141.
+pub const MAX_BLOCK_SIGOPS_COST: u64 = 80_000;
 
 /// A Bitcoin block.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Equality compares the bytes of the blocks, regardless of the [`ParseConfig`] they were parsed
+/// with.
+#[derive(Debug, Clone)]
 pub struct Block<'a> {
-    slice: &'a [u8],
-    header: BlockHeader<'a>,
-    total_txs: usize,
+    pub(crate) slice: &'a [u8],
+    pub(crate) header: BlockHeader<'a>,
+
+    /// The number of transactions, a `u32` is enough since every transaction is at least 60 bytes.
+    pub(crate) total_txs: u32,
+
+    /// The [`ParseConfig`] options the transactions were parsed with, to visit them again the same
+    /// way, see [`Block::reparse_config()`].
+    pub(crate) allow_zero_inputs: bool,
+    pub(crate) allow_non_minimal_compact_sizes: bool,
+}
+
+impl PartialEq for Block<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.slice == other.slice
+    }
 }
 
+impl Eq for Block<'_> {}
+
 impl<'a> Visit<'a> for Block<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        Self::visit_with_config(slice, visit, &ParseConfig::default())
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Parse a block like [`Parse::parse`], parsing its transactions with the options in
+    /// `config`, see [`Transaction::parse_with_config`].
+    pub fn parse_with_config(slice: &'a [u8], config: &ParseConfig) -> SResult<'a, Self> {
+        Self::visit_with_config(slice, &mut EmptyVisitor {}, config)
+    }
+
+    /// Visit a block like [`Visit::visit`], parsing its transactions with the options in
+    /// `config`.
+    pub fn visit_with_config<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        config: &ParseConfig,
+    ) -> SResult<'a, Self> {
         let header = BlockHeader::visit(slice, visit)?;
-        let Len { mut consumed, n } = parse_len(header.remaining())?;
+        let Len { mut consumed, n } =
+            parse_len_with(header.remaining(), config.allow_non_minimal_compact_sizes)?;
+        visit.visit_len(LenKind::BlockTxCount, n, consumed);
+        check(visit)?;
         consumed += 80;
         let total_txs = n as usize;
         let mut remaining = &slice[consumed..];
 
         visit.visit_block_begin(total_txs);
-        for _ in 0..total_txs {
-            let tx = Transaction::visit(remaining, visit)?;
+        check(visit)?;
+        for i in 0..total_txs {
+            let tx = Transaction::visit_at(remaining, i, visit, config)?;
+            visit.visit_transaction_at(consumed, tx.parsed());
+            check(visit)?;
             remaining = tx.remaining();
             consumed += tx.consumed();
         }
+        visit.visit_block_end();
+        check(visit)?;
 
         let (slice, remaining) = slice.split_at(consumed);
         let parsed = Block {
             slice,
             header: header.parsed_owned(),
-            total_txs,
+            total_txs: total_txs as u32,
+            allow_zero_inputs: config.allow_zero_inputs,
+            allow_non_minimal_compact_sizes: config.allow_non_minimal_compact_sizes,
         };
         Ok(ParseResult::new(remaining, parsed))
     }
@@ -54,15 +114,338 @@ impl<'a> Block<'a> {
         self.header.block_hash_sha2()
     }
 
-    /// Returns the total transactions in this block
+    /// Computes the merkle root of the transactions txids, in byte order (not reversed).
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn compute_merkle_root(&self) -> [u8; 32] {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.compute_merkle_root_with(sha256d::Hash::engine())
+    }
+
+    /// Verifies the merkle root in the header, see [`Block::verify_merkle_root_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn verify_merkle_root(&self) -> Result<(), MerkleError> {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.verify_merkle_root_with(sha256d::Hash::engine())
+    }
+
+    /// Computes the merkle root of the transactions txids, every txid and node of the tree is
+    /// hashed with a clone of `hasher`, see [`TxidHasher`].
+    ///
+    /// The transactions are visited again, but only one hash per level of the tree is kept.
+    pub fn compute_merkle_root_with<H: TxidHasher + Clone>(&self, hasher: H) -> [u8; 32] {
+        self.merkle_root_with(hasher).0
+    }
+
+    /// Verifies the merkle root in the header matches the one computed from the transactions,
+    /// returning [`MerkleError::Mutated`] if it matches but the transaction list is mutated
+    /// (CVE-2012-2459).
+    pub fn verify_merkle_root_with<H: TxidHasher + Clone>(
+        &self,
+        hasher: H,
+    ) -> Result<(), MerkleError> {
+        let (merkle_root, mutated) = self.merkle_root_with(hasher);
+        if &merkle_root != self.header.merkle_root() {
+            Err(MerkleError::Mismatch)
+        } else if mutated {
+            Err(MerkleError::Mutated)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn merkle_root_with<H: TxidHasher + Clone>(&self, hasher: H) -> ([u8; 32], bool) {
+        let mut merkle = MerkleComputation::new(hasher.clone());
+        self.for_each_txid_with(hasher, |txid| merkle.push(txid));
+        merkle.finalize()
+    }
+
+    /// Appends the txids of the transactions to `out`, in byte order (not reversed), returns the
+    /// number of txids appended.
+    #[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "bitcoin_hashes"))))]
+    pub fn txids_into(&self, out: &mut alloc::vec::Vec<[u8; 32]>) -> usize {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        out.reserve(self.total_transactions());
+        self.for_each_txid_with(sha256d::Hash::engine(), |txid| out.push(txid));
+        self.total_transactions()
+    }
+
+    /// Calls `f` with the txid of every transaction, in order, see
+    /// [`Block::for_each_txid_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn for_each_txid<F: FnMut([u8; 32])>(&self, f: F) {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.for_each_txid_with(sha256d::Hash::engine(), f)
+    }
+
+    /// Calls `f` with the txid of every transaction, in order and in byte order (not reversed),
+    /// every txid is computed with a clone of `hasher` from the txid preimage segments, so that
+    /// nothing is allocated.
+    pub fn for_each_txid_with<H: TxidHasher + Clone, F: FnMut([u8; 32])>(&self, hasher: H, f: F) {
+        struct Txids<H, F> {
+            hasher: H,
+            f: F,
+        }
+        impl<H: TxidHasher + Clone, F: FnMut([u8; 32])> Visitor<'_> for Txids<H, F> {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                (self.f)(tx.txid_with(self.hasher.clone()));
+            }
+        }
+        let mut txids = Txids { hasher, f };
+        self.visit_again(&mut txids);
+    }
+
+    /// Verifies the BIP141 witness commitment, see [`Block::verify_witness_commitment_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn verify_witness_commitment(&self) -> Result<(), WitnessCommitmentError> {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.verify_witness_commitment_with(sha256d::Hash::engine())
+    }
+
+    /// Verifies the BIP141 witness commitment in the last coinbase output starting with
+    /// `OP_RETURN 0xaa21a9ed`, every hash is computed with a clone of `hasher`.
+    ///
+    /// The committed hash must be the double hash of the witness merkle root, computed from the
+    /// wtxids with the coinbase one replaced by zeros, followed by the witness reserved value found
+    /// in the coinbase input witness. Blocks without a commitment are valid only if no transaction
+    /// has witness data.
+    pub fn verify_witness_commitment_with<H: TxidHasher + Clone>(
+        &self,
+        hasher: H,
+    ) -> Result<(), WitnessCommitmentError> {
+        struct Wtxids<H> {
+            hasher: H,
+            merkle: MerkleComputation<H>,
+            coinbase: bool,
+            has_witness: bool,
+        }
+        impl<H: TxidHasher + Clone> Visitor<'_> for Wtxids<H> {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                let wtxid = if self.coinbase {
+                    [0u8; 32]
+                } else {
+                    tx.wtxid_with(self.hasher.clone())
+                };
+                self.merkle.push(wtxid);
+                self.coinbase = false;
+                self.has_witness |= tx.has_witness();
+            }
+        }
+
+        let coinbase = self
+            .coinbase()
+            .map_err(|_| WitnessCommitmentError::NoTransactions)?;
+        let commitment = coinbase
+            .outputs()
+            .filter_map(|output| witness_commitment(output.script_pubkey()))
+            .last();
+
+        let mut wtxids = Wtxids {
+            merkle: MerkleComputation::new(hasher.clone()),
+            hasher: hasher.clone(),
+            coinbase: true,
+            has_witness: false,
+        };
+        self.visit_again(&mut wtxids);
+
+        let commitment = match commitment {
+            Some(commitment) => commitment,
+            None if wtxids.has_witness => return Err(WitnessCommitmentError::UnexpectedWitness),
+            None => return Ok(()),
+        };
+        let reserved_value = coinbase
+            .witness_reserved_value()
+            .ok_or(WitnessCommitmentError::InvalidReservedValue)?;
+
+        let (witness_root, _) = wtxids.merkle.finalize();
+        let mut hasher = hasher;
+        hasher.update(&witness_root);
+        hasher.update(&reserved_value[..]);
+        if &hasher.finalize_double() == commitment {
+            Ok(())
+        } else {
+            Err(WitnessCommitmentError::Mismatch)
+        }
+    }
+
+    /// Returns the legacy signature operations cost of the block, the sum of
+    /// [`Transaction::legacy_sigop_count()`] of all the transactions times 4.
+    ///
+    /// It doesn't need the previous outputs but it's a lower bound of [`Block::sigop_cost()`],
+    /// useful as a sanity check of block templates.
+    pub fn legacy_sigop_cost(&self) -> u64 {
+        struct Legacy(u64);
+        impl Visitor<'_> for Legacy {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                self.0 += tx.legacy_sigop_count() as u64 * 4;
+            }
+        }
+        let mut legacy = Legacy(0);
+        self.visit_again(&mut legacy);
+        legacy.0
+    }
+
+    /// Returns the signature operations cost of the block, the sum of
+    /// [`Transaction::sigop_cost()`] of all the transactions, which must not exceed
+    /// [`MAX_BLOCK_SIGOPS_COST`].
+    ///
+    /// `prevout_script` must return the script pubkey of the previous output spent by an input,
+    /// including the outputs created earlier in the same block, [`Error::MissingPrevout`] is
+    /// returned otherwise.
+    pub fn sigop_cost<'p, F>(&self, prevout_script: F) -> Result<u64, Error>
+    where
+        F: FnMut(&OutPoint) -> Option<&'p [u8]>,
+    {
+        struct Cost<F> {
+            prevout_script: F,
+            cost: Result<u64, Error>,
+        }
+        impl<'p, F: FnMut(&OutPoint) -> Option<&'p [u8]>> Visitor<'_> for Cost<F> {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                if let Ok(cost) = self.cost {
+                    self.cost = tx
+                        .sigop_cost(&mut self.prevout_script)
+                        .map(|tx_cost| cost + tx_cost);
+                }
+            }
+        }
+        let mut cost = Cost {
+            prevout_script,
+            cost: Ok(0),
+        };
+        self.visit_again(&mut cost);
+        cost.cost
+    }
+
+    /// Returns the BIP141 witness reserved value in the witness of the coinbase, see
+    /// [`Transaction::witness_reserved_value()`], or `None` if the block has no transactions.
+    pub fn witness_reserved_value(&self) -> Option<&'a [u8; 32]> {
+        self.coinbase().ok()?.witness_reserved_value()
+    }
+
+    /// Returns the index and the transaction with the given `txid`, in byte order (not reversed).
+    ///
+    /// See [`Block::find_transaction_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn find_transaction(&self, txid: &[u8; 32]) -> Option<(usize, Transaction<'a>)> {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.find_transaction_with(txid, sha256d::Hash::engine())
+    }
+
+    /// Returns the index and the transaction with the given `txid`, computing the txids with
+    /// clones of `hasher`.
+    ///
+    /// This is a linear search: the transactions are visited and hashed until the one with `txid`
+    /// is found, so it's O(n) hashes of the block data.
+    pub fn find_transaction_with<H: TxidHasher + Clone>(
+        &self,
+        txid: &[u8; 32],
+        hasher: H,
+    ) -> Option<(usize, Transaction<'a>)> {
+        let mut find = FindTxidVisitor::new_with(*txid, hasher);
+        match Block::visit_with_config(self.slice, &mut find, &self.reparse_config()) {
+            Ok(_) | Err(Error::VisitBreak) => (),
+            Err(_) => unreachable!("granted during parsing"),
+        }
+        let (index, range) = find.found()?;
+        Some((index, self.transaction_at(range.start)))
+    }
+
+    /// Returns the length of the block at the start of `slice`, validating it like
+    /// [`Parse::parse`], see [`Transaction::skip()`].
+    pub fn skip(slice: &'a [u8]) -> Result<usize, Error> {
+        Ok(Self::parse(slice)?.consumed())
+    }
+
+    /// Returns the first transaction, which must be the coinbase, without visiting the others.
+    ///
+    /// Returns [`Error::MissingCoinbase`] if the block has no transactions, which is invalid.
+    pub fn coinbase(&self) -> Result<Transaction<'a>, Error> {
+        if self.total_txs == 0 {
+            return Err(Error::MissingCoinbase);
+        }
+        Ok(self.transaction_at(self.transactions_offset()))
+    }
+
+    /// Returns the block height encoded in the coinbase as required by BIP34, see
+    /// [`Transaction::coinbase_height()`], or `None` if the block has no transactions.
+    pub fn height(&self) -> Option<u32> {
+        self.coinbase().ok()?.coinbase_height()
+    }
+
+    /// Returns the size of the full serialized block, including witnesses.
+    pub fn total_size(&self) -> usize {
+        self.slice.len()
+    }
+
+    /// Returns the size of the block serialized without the transactions segwit markers, flags
+    /// and witnesses.
+    ///
+    /// The transactions are visited again to sum their witness data sizes.
+    pub fn stripped_size(&self) -> usize {
+        struct WitnessSize(usize);
+        impl Visitor<'_> for WitnessSize {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                self.0 += tx.total_size() - tx.base_size();
+            }
+        }
+        let mut witness_size = WitnessSize(0);
+        self.visit_again(&mut witness_size);
+        self.total_size() - witness_size.0
+    }
+
+    /// Returns the weight of the block, as defined in BIP141: the stripped size multiplied by 3
+    /// plus the total size. It must not be greater than [`MAX_BLOCK_WEIGHT`].
+    pub fn weight(&self) -> usize {
+        self.stripped_size() * 3 + self.total_size()
+    }
+
+    /// Returns the total transactions in this block, as encoded after the header and stored
+    /// during parsing, so it doesn't require visiting the transactions.
     pub fn total_transactions(&self) -> usize {
-        self.total_txs
+        self.total_txs as usize
     }
 
     /// Returns the header in this block
-    pub fn header(&self) -> &BlockHeader {
+    pub fn header(&self) -> &BlockHeader<'a> {
         &self.header
     }
+
+    /// Returns the options accepting the transactions of this block, the ones it was parsed with
+    /// without the checks already done, like [`ParseConfig::allowed_versions`].
+    pub(crate) fn reparse_config(&self) -> ParseConfig {
+        ParseConfig {
+            allow_zero_inputs: self.allow_zero_inputs,
+            allow_non_minimal_compact_sizes: self.allow_non_minimal_compact_sizes,
+            ..Default::default()
+        }
+    }
+
+    /// Visit again the block with `visit`, which must not stop the visit.
+    pub(crate) fn visit_again<V: Visitor<'a>>(&self, visit: &mut V) {
+        Block::visit_with_config(self.slice, visit, &self.reparse_config())
+            .expect("granted during parsing");
+    }
+
+    /// Returns the offset of the first transaction, after the header and the transactions count.
+    pub(crate) fn transactions_offset(&self) -> usize {
+        let lenient = self.allow_non_minimal_compact_sizes;
+        let Len { consumed, .. } =
+            parse_len_with(&self.slice[80..], lenient).expect("granted during parsing");
+        80 + consumed
+    }
+
+    /// Returns the transaction starting at `offset` in the block, which must be the start of one.
+    pub(crate) fn transaction_at(&self, offset: usize) -> Transaction<'a> {
+        Transaction::parse_with_config(&self.slice[offset..], &self.reparse_config())
+            .expect("granted during parsing")
+            .parsed_owned()
+    }
 }
 
 impl<'a> AsRef<[u8]> for Block<'a> {
@@ -71,26 +454,95 @@ impl<'a> AsRef<[u8]> for Block<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for Block<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> Block<'a> {
+    /// Serializes the rust-bitcoin block in `buffer`, replacing its content, and parses it. Fails
+    /// if the serialized block can't be parsed back.
+    pub fn from_bitcoin(
+        block: &bitcoin::Block,
+        buffer: &'a mut alloc::vec::Vec<u8>,
+    ) -> Result<Self, Error> {
+        *buffer = bitcoin::consensus::serialize(block);
+        Block::parse_all(&buffer[..])
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> TryFrom<&Block<'a>> for bitcoin::Block {
+    type Error = bitcoin::consensus::encode::Error;
+
+    /// Deserialize the block slice with rust-bitcoin, it doesn't fail for blocks parsed by this
+    /// crate unless they exceed rust-bitcoin allocation limits.
+    fn try_from(block: &Block<'a>) -> Result<Self, Self::Error> {
+        bitcoin::consensus::deserialize(block.as_ref())
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> TryFrom<Block<'a>> for bitcoin::Block {
+    type Error = bitcoin::consensus::encode::Error;
+
+    fn try_from(block: Block<'a>) -> Result<Self, Self::Error> {
+        (&block).try_into()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{
-        bsl::{Block, BlockHeader},
-        test_common::GENESIS_BLOCK,
-        Parse,
+        bsl::{
+            Block, BlockHeader, ParseConfig, Transaction, TxIn, TxOut, MAX_BLOCK_SIGOPS_COST,
+            MAX_BLOCK_WEIGHT,
+        },
+        test_common::{GENESIS_BLOCK, LEGACY_TX},
+        Error, Parse, Visit, Visitor,
     };
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin_test_data::blocks::mainnet_702861;
+    use core::ops::ControlFlow;
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn block_bitcoin() {
+        for bytes in [&GENESIS_BLOCK[..], mainnet_702861()] {
+            let block = Block::parse_all(bytes).unwrap();
+            let expected: bitcoin::Block = deserialize(bytes).unwrap();
+            let block_bitcoin = bitcoin::Block::try_from(&block).unwrap();
+            assert_eq!(block_bitcoin, expected);
+            let block_bitcoin: bitcoin::Block = block.clone().try_into().unwrap();
+
+            let mut buffer = vec![1, 2, 3];
+            let back = Block::from_bitcoin(&block_bitcoin, &mut buffer).unwrap();
+            assert_eq!(back, block);
+        }
+    }
 
     #[test]
     fn parse_block() {
         let block_header = BlockHeader::parse(&GENESIS_BLOCK).unwrap();
         let block = Block::parse(&GENESIS_BLOCK).unwrap();
 
-        assert_eq!(block.remaining(), &[][..]);
+        assert_eq!(block.remaining(), &[0u8; 0][..]);
         assert_eq!(
             block.parsed(),
             &Block {
                 slice: &GENESIS_BLOCK,
                 header: block_header.parsed_owned(),
-                total_txs: 1
+                total_txs: 1,
+                allow_zero_inputs: false,
+                allow_non_minimal_compact_sizes: false,
             }
         );
         assert_eq!(block.consumed(), 285);
@@ -101,6 +553,558 @@ mod test {
         // assert!(iter.next().is_none())
     }
 
+    #[test]
+    fn parse_with_config() {
+        // the genesis header followed by a transaction with a non-minimal inputs count
+        let block = [
+            &GENESIS_BLOCK[..81],
+            &LEGACY_TX[..4],
+            &[0xFD, 1, 0],
+            &LEGACY_TX[5..],
+        ]
+        .concat();
+        assert_eq!(Block::parse(&block), Err(Error::NonMinimalVarInt));
+        let config = ParseConfig {
+            allow_non_minimal_compact_sizes: true,
+            ..Default::default()
+        };
+        let parsed = Block::parse_with_config(&block, &config).unwrap();
+        assert_eq!(parsed.consumed(), block.len());
+        assert_eq!(parsed.parsed().total_transactions(), 1);
+
+        let config = ParseConfig {
+            allowed_versions: Some(2..=2),
+            ..Default::default()
+        };
+        assert_eq!(
+            Block::parse_with_config(&GENESIS_BLOCK, &config),
+            Err(Error::UnsupportedVersion(1))
+        );
+        assert_eq!(
+            Block::parse_with_config(&GENESIS_BLOCK, &ParseConfig::default()),
+            Block::parse(&GENESIS_BLOCK)
+        );
+    }
+
+    /// A block with a non-minimal transactions count, the genesis coinbase, [`LEGACY_TX`] with a
+    /// non-minimal inputs count and a legacy transaction with zero inputs, with their offsets.
+    fn lenient_block() -> (Vec<u8>, [usize; 3]) {
+        let zero_inputs = [
+            &LEGACY_TX[..4],
+            &[0, 2],
+            &LEGACY_TX[155..189].repeat(2),
+            &[0; 4],
+        ]
+        .concat();
+        let coinbase = &GENESIS_BLOCK[81..];
+        let legacy = [&LEGACY_TX[..4], &[0xFD, 1, 0], &LEGACY_TX[5..]].concat();
+        let offsets = [83, 83 + coinbase.len(), 83 + coinbase.len() + legacy.len()];
+        let block = [
+            &GENESIS_BLOCK[..80],
+            &[0xFD, 3, 0],
+            coinbase,
+            &legacy,
+            &zero_inputs,
+        ]
+        .concat();
+        (block, offsets)
+    }
+
+    #[test]
+    fn lenient_block_helpers() {
+        let (bytes, offsets) = lenient_block();
+        assert_eq!(Block::parse(&bytes), Err(Error::NonMinimalVarInt));
+        let config = ParseConfig {
+            allow_non_minimal_compact_sizes: true,
+            ..Default::default()
+        };
+        assert!(Block::parse_with_config(&bytes, &config).is_err());
+        let config = ParseConfig {
+            allow_zero_inputs: true,
+            allow_non_minimal_compact_sizes: true,
+            ..Default::default()
+        };
+        let block = Block::parse_with_config(&bytes, &config)
+            .unwrap()
+            .require_empty()
+            .unwrap();
+        assert_eq!(block.total_transactions(), 3);
+        let txs: Vec<_> = offsets
+            .iter()
+            .map(|offset| {
+                Transaction::parse_with_config(&bytes[*offset..], &config)
+                    .unwrap()
+                    .parsed_owned()
+            })
+            .collect();
+        assert_eq!(txs[2].input_count(), 0);
+
+        assert_eq!(block.coinbase(), Ok(txs[0].clone()));
+        assert_eq!(block.height(), txs[0].coinbase_height());
+        assert_eq!(block.witness_reserved_value(), None);
+        assert_eq!(block.stripped_size(), bytes.len());
+        assert_eq!(block.weight(), bytes.len() * 4);
+        let legacy: u64 = txs
+            .iter()
+            .map(|tx| tx.legacy_sigop_count() as u64 * 4)
+            .sum();
+        assert_eq!(block.legacy_sigop_cost(), legacy);
+        assert_eq!(block.sigop_cost(|_| Some(&[])), Ok(legacy));
+
+        #[cfg(feature = "alloc")]
+        {
+            let indexed = crate::bsl::IndexedBlock::new(block.clone());
+            assert_eq!(indexed.iter().collect::<Vec<_>>(), txs);
+            let owned = crate::bsl::BlockOwned::from(&block);
+            assert_eq!(owned.as_block().coinbase(), Ok(txs[0].clone()));
+            assert_eq!(owned.as_block().stripped_size(), bytes.len());
+        }
+
+        #[cfg(feature = "bitcoin_hashes")]
+        {
+            use crate::bitcoin_hashes::{sha256d, Hash};
+
+            let txids: Vec<_> = txs.iter().map(|tx| tx.txid().into_inner()).collect();
+            let mut visited = vec![];
+            block.for_each_txid_with(sha256d::Hash::engine(), |txid| visited.push(txid));
+            assert_eq!(visited, txids);
+            let mut merkle = super::MerkleComputation::new(sha256d::Hash::engine());
+            txids.iter().for_each(|txid| merkle.push(*txid));
+            assert_eq!(block.compute_merkle_root(), merkle.finalize().0);
+            assert_eq!(block.verify_witness_commitment(), Ok(()));
+            assert_eq!(block.find_transaction(&txids[2]), Some((2, txs[2].clone())));
+            #[cfg(feature = "alloc")]
+            assert!(!crate::bsl::build_basic_filter(&block, []).is_empty());
+            #[cfg(feature = "alloc")]
+            assert_eq!(block.txids_into(&mut vec![]), 3);
+        }
+    }
+
+    #[test]
+    fn visit_transaction_at() {
+        struct Offsets(Vec<(usize, usize)>);
+        impl Visitor<'_> for Offsets {
+            fn visit_transaction_at(&mut self, offset: usize, tx: &Transaction) {
+                self.0.push((offset, tx.as_ref().len()));
+            }
+        }
+
+        let mut offsets = Offsets(vec![]);
+        Block::visit(&GENESIS_BLOCK, &mut offsets).unwrap();
+        assert_eq!(offsets.0, vec![(81, GENESIS_BLOCK.len() - 81)]);
+
+        let block_bytes = mainnet_702861();
+        let mut offsets = Offsets(vec![]);
+        let block = Block::visit(block_bytes, &mut offsets).unwrap();
+        assert_eq!(offsets.0.len(), block.parsed().total_transactions());
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        for ((offset, len), tx_bitcoin) in offsets.0.iter().zip(block_bitcoin.txdata.iter()) {
+            let tx = Transaction::parse_all(&block_bytes[*offset..offset + len]).unwrap();
+            assert_eq!(tx.as_ref(), &serialize(tx_bitcoin)[..]);
+        }
+        let (last_offset, last_len) = offsets.0.last().unwrap();
+        assert_eq!(last_offset + last_len, block_bytes.len());
+    }
+
+    #[cfg(any(feature = "bitcoin_hashes", feature = "sha2"))]
+    #[test]
+    fn block_hash() {
+        for block_bytes in [&GENESIS_BLOCK[..], mainnet_702861()] {
+            let block = Block::parse(block_bytes).unwrap().parsed_owned();
+            let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+            let expected = block_bitcoin.block_hash();
+            #[cfg(feature = "bitcoin_hashes")]
+            assert_eq!(&block.block_hash()[..], &expected[..]);
+            #[cfg(feature = "sha2")]
+            assert_eq!(&block.block_hash_sha2()[..], &expected[..]);
+        }
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn merkle_root() {
+        use crate::{bitcoin_hashes::Hash, MerkleError};
+
+        for block_bytes in [&GENESIS_BLOCK[..], mainnet_702861()] {
+            let block = Block::parse(block_bytes).unwrap().parsed_owned();
+            let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+            let expected = block_bitcoin.compute_merkle_root().unwrap();
+            assert_eq!(block.compute_merkle_root(), expected.into_inner());
+            assert_eq!(&block.compute_merkle_root(), block.header().merkle_root());
+            assert_eq!(block.verify_merkle_root(), Ok(()));
+
+            let mut block_bytes = block_bytes.to_vec();
+            block_bytes[36] ^= 1;
+            let block = Block::parse(&block_bytes).unwrap().parsed_owned();
+            assert_eq!(block.verify_merkle_root(), Err(MerkleError::Mismatch));
+        }
+
+        // a block with the first 3 transactions of block 702861, then duplicating the last one
+        let block_702861: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        let mut block_bitcoin = block_702861.clone();
+        block_bitcoin.txdata.truncate(3);
+        block_bitcoin.header.merkle_root = block_bitcoin.compute_merkle_root().unwrap();
+        let block_bytes = serialize(&block_bitcoin);
+        let block = Block::parse(&block_bytes).unwrap().parsed_owned();
+        assert_eq!(block.verify_merkle_root(), Ok(()));
+
+        block_bitcoin.txdata.push(block_bitcoin.txdata[2].clone());
+        let block_bytes = serialize(&block_bitcoin);
+        let block = Block::parse(&block_bytes).unwrap().parsed_owned();
+        assert_eq!(block.total_transactions(), 4);
+        assert_eq!(block.verify_merkle_root(), Err(MerkleError::Mutated));
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn witness_commitment() {
+        use crate::WitnessCommitmentError;
+
+        let block = Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned();
+        assert_eq!(block.verify_witness_commitment(), Ok(()));
+
+        let empty_block = [&GENESIS_BLOCK[..80], &[0u8][..]].concat();
+        let block = Block::parse(&empty_block).unwrap().parsed_owned();
+        assert_eq!(
+            block.verify_witness_commitment(),
+            Err(WitnessCommitmentError::NoTransactions)
+        );
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse(block_bytes).unwrap().parsed_owned();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        assert!(block_bitcoin.check_witness_commitment());
+        assert_eq!(block.verify_witness_commitment(), Ok(()));
+
+        // changing the committed hash
+        let prefix = hex_lit::hex!("6a24aa21a9ed");
+        let position = block_bytes
+            .windows(prefix.len())
+            .position(|w| w == prefix)
+            .unwrap();
+        let mut modified = block_bytes.to_vec();
+        modified[position + prefix.len()] ^= 1;
+        let block = Block::parse(&modified).unwrap().parsed_owned();
+        assert_eq!(
+            block.verify_witness_commitment(),
+            Err(WitnessCommitmentError::Mismatch)
+        );
+
+        // changing a witness of a transaction, doesn't change the merkle root
+        let mut modified_bitcoin = block_bitcoin.clone();
+        let tx = modified_bitcoin
+            .txdata
+            .iter_mut()
+            .skip(1)
+            .find(|tx| !tx.input[0].witness.is_empty())
+            .unwrap();
+        let mut witness = tx.input[0].witness.to_vec();
+        witness[0][0] ^= 1;
+        tx.input[0].witness = bitcoin::Witness::from_vec(witness);
+        let modified = serialize(&modified_bitcoin);
+        let block = Block::parse(&modified).unwrap().parsed_owned();
+        assert_eq!(block.verify_merkle_root(), Ok(()));
+        assert_eq!(
+            block.verify_witness_commitment(),
+            Err(WitnessCommitmentError::Mismatch)
+        );
+
+        // the witness reserved value is missing
+        let mut modified_bitcoin = block_bitcoin.clone();
+        modified_bitcoin.txdata[0].input[0].witness = bitcoin::Witness::default();
+        let modified = serialize(&modified_bitcoin);
+        let block = Block::parse(&modified).unwrap().parsed_owned();
+        assert_eq!(
+            block.verify_witness_commitment(),
+            Err(WitnessCommitmentError::InvalidReservedValue)
+        );
+
+        // transactions with witnesses, but no commitment
+        let mut modified_bitcoin = block_bitcoin;
+        modified_bitcoin.txdata[0]
+            .output
+            .retain(|output| !output.script_pubkey.as_bytes().starts_with(&prefix));
+        let modified = serialize(&modified_bitcoin);
+        let block = Block::parse(&modified).unwrap().parsed_owned();
+        assert_eq!(
+            block.verify_witness_commitment(),
+            Err(WitnessCommitmentError::UnexpectedWitness)
+        );
+    }
+
+    #[test]
+    fn witness_reserved_value() {
+        let block = Block::parse_all(&GENESIS_BLOCK[..]).unwrap();
+        assert_eq!(block.witness_reserved_value(), None);
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse_all(block_bytes).unwrap();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let expected = block_bitcoin.txdata[0].input[0].witness.to_vec();
+        assert_eq!(expected.len(), 1);
+        assert_eq!(
+            block.witness_reserved_value().map(|v| &v[..]),
+            Some(&expected[0][..])
+        );
+        assert_eq!(
+            block.coinbase().unwrap().witness_reserved_value(),
+            block.witness_reserved_value()
+        );
+
+        // a segwit transaction which is not a coinbase
+        let tx_bytes = serialize(&block_bitcoin.txdata[1]);
+        let tx = Transaction::parse_all(&tx_bytes).unwrap();
+        assert!(tx.has_witness());
+        assert_eq!(tx.witness_reserved_value(), None);
+
+        for witness in [
+            vec![vec![0u8; 32], vec![]],
+            vec![vec![0u8; 31]],
+            vec![vec![0u8; 33]],
+        ] {
+            let mut modified_bitcoin = block_bitcoin.clone();
+            modified_bitcoin.txdata[0].input[0].witness = bitcoin::Witness::from_vec(witness);
+            let modified = serialize(&modified_bitcoin);
+            let block = Block::parse_all(&modified).unwrap();
+            assert_eq!(block.witness_reserved_value(), None);
+        }
+    }
+
+    #[test]
+    fn visit_block_begin_end() {
+        #[derive(Default)]
+        struct BeginEnd {
+            total_transactions: Option<usize>,
+            transactions: usize,
+            ends: usize,
+        }
+        impl Visitor<'_> for BeginEnd {
+            fn visit_block_begin(&mut self, total_transactions: usize) {
+                assert_eq!(self.total_transactions, None);
+                self.total_transactions = Some(total_transactions);
+            }
+            fn visit_transaction(&mut self, _tx: &Transaction) {
+                assert_eq!(self.ends, 0);
+                self.transactions += 1;
+            }
+            fn visit_block_end(&mut self) {
+                self.ends += 1;
+            }
+        }
+        for (bytes, total) in [(&GENESIS_BLOCK[..], 1), (mainnet_702861(), 2500)] {
+            let mut visitor = BeginEnd::default();
+            Block::visit(bytes, &mut visitor).unwrap();
+            assert_eq!(visitor.total_transactions, Some(total));
+            assert_eq!(visitor.transactions, total);
+            assert_eq!(visitor.ends, 1);
+        }
+
+        // an empty block is still begun and ended, a truncated one is not ended
+        let empty_block = [&GENESIS_BLOCK[..80], &[0u8][..]].concat();
+        let mut visitor = BeginEnd::default();
+        Block::visit(&empty_block, &mut visitor).unwrap();
+        assert_eq!((visitor.total_transactions, visitor.ends), (Some(0), 1));
+        let mut visitor = BeginEnd::default();
+        Block::visit(&GENESIS_BLOCK[..GENESIS_BLOCK.len() - 1], &mut visitor).unwrap_err();
+        assert_eq!((visitor.total_transactions, visitor.ends), (Some(1), 0));
+    }
+
+    #[test]
+    fn visit_break() {
+        #[derive(Default)]
+        struct FirstOutput {
+            outputs: usize,
+            transactions: usize,
+            inputs: usize,
+        }
+        impl Visitor<'_> for FirstOutput {
+            fn visit_tx_in(&mut self, _vin: usize, _tx_in: &TxIn) {
+                self.inputs += 1;
+            }
+            fn visit_tx_out(&mut self, _vout: usize, _tx_out: &TxOut) {
+                self.outputs += 1;
+            }
+            fn visit_transaction(&mut self, _tx: &Transaction) {
+                self.transactions += 1;
+            }
+            fn visit_witness(&mut self, _vin: usize) {
+                panic!("visited after break")
+            }
+            fn control_flow(&self) -> ControlFlow<()> {
+                if self.outputs > 0 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+        }
+        let mut visitor = FirstOutput::default();
+        let result = Block::visit(mainnet_702861(), &mut visitor);
+        assert_eq!(result, Err(Error::VisitBreak));
+        assert_eq!(visitor.outputs, 1);
+        assert_eq!(visitor.transactions, 0);
+        assert_eq!(visitor.inputs, 1);
+    }
+
+    #[test]
+    fn sigop_cost() {
+        let block = Block::parse_all(&GENESIS_BLOCK[..]).unwrap();
+        assert_eq!(block.legacy_sigop_cost(), 4);
+        // the prevouts of the coinbase are not needed
+        assert_eq!(block.sigop_cost(|_| None), Ok(4));
+
+        let block = Block::parse_all(mainnet_702861()).unwrap();
+        let legacy = block.legacy_sigop_cost();
+        assert!(legacy > 0);
+        assert!(legacy <= MAX_BLOCK_SIGOPS_COST);
+        // without scripts in the prevouts only the legacy sigops count
+        assert_eq!(block.sigop_cost(|_| Some(&[])), Ok(legacy));
+        assert_eq!(block.sigop_cost(|_| None), Err(Error::MissingPrevout));
+
+        // all spending P2WPKH outputs count one sigop per input
+        let p2wpkh = [&[0u8, 20][..], &[1u8; 20][..]].concat();
+        let block_bitcoin: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        let inputs: usize = block_bitcoin.txdata[1..]
+            .iter()
+            .map(|tx| tx.input.len())
+            .sum();
+        assert_eq!(
+            block.sigop_cost(|_| Some(&p2wpkh)),
+            Ok(legacy + inputs as u64)
+        );
+    }
+
+    #[test]
+    fn block_sizes() {
+        for block_bytes in [&GENESIS_BLOCK[..], mainnet_702861()] {
+            let block = Block::parse(block_bytes).unwrap().parsed_owned();
+            let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+            assert_eq!(block.total_size(), block_bitcoin.size());
+            assert_eq!(block.stripped_size(), block_bitcoin.strippedsize());
+            assert_eq!(block.weight(), block_bitcoin.weight());
+            assert!(block.weight() <= MAX_BLOCK_WEIGHT);
+        }
+        let block = Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned();
+        assert_eq!(block.stripped_size(), block.total_size());
+    }
+
+    #[test]
+    fn coinbase() {
+        let block = Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned();
+        let coinbase = block.coinbase().unwrap();
+        assert_eq!(coinbase.as_ref(), &GENESIS_BLOCK[81..]);
+        assert!(coinbase.is_coinbase());
+        assert_eq!(block.height(), None);
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse(block_bytes).unwrap().parsed_owned();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let coinbase = block.coinbase().unwrap();
+        assert_eq!(coinbase.as_ref(), &serialize(&block_bitcoin.txdata[0])[..]);
+        assert_eq!(block.height(), Some(702_861));
+
+        let empty_block = [&GENESIS_BLOCK[..80], &[0u8][..]].concat();
+        let block = Block::parse(&empty_block).unwrap().parsed_owned();
+        assert_eq!(block.coinbase(), Err(Error::MissingCoinbase));
+        assert_eq!(block.height(), None);
+    }
+
+    #[test]
+    fn total_transactions() {
+        let block = Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned();
+        assert_eq!(block.total_transactions(), 1);
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse(block_bytes).unwrap().parsed_owned();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        assert_eq!(block.total_transactions(), block_bitcoin.txdata.len());
+        assert_eq!(block.total_transactions(), 2500);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn find_transaction() {
+        use crate::bitcoin_hashes::Hash;
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse(block_bytes).unwrap().parsed_owned();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        for index in [0, 1, 1234, 2499] {
+            let tx_bitcoin = &block_bitcoin.txdata[index];
+            let txid = tx_bitcoin.txid().into_inner();
+            let (found_index, tx) = block.find_transaction(&txid).unwrap();
+            assert_eq!(found_index, index);
+            assert_eq!(tx.as_ref(), &serialize(tx_bitcoin)[..]);
+        }
+        assert!(block.find_transaction(&[0u8; 32]).is_none());
+
+        let block = Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned();
+        let txid = block.coinbase().unwrap().txid().into_inner();
+        assert!(block.find_transaction(&txid).is_some());
+        let mut reversed = txid;
+        reversed.reverse();
+        assert!(block.find_transaction(&reversed).is_none());
+    }
+
+    #[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+    #[test]
+    fn txids() {
+        use crate::bitcoin_hashes::Hash;
+
+        let block_bytes = mainnet_702861();
+        let block = Block::parse_all(block_bytes).unwrap();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let expected: Vec<[u8; 32]> = block_bitcoin
+            .txdata
+            .iter()
+            .map(|tx| tx.txid().into_inner())
+            .collect();
+
+        let mut txids = vec![[0u8; 32]];
+        assert_eq!(block.txids_into(&mut txids), 2500);
+        assert_eq!(txids[0], [0u8; 32]);
+        assert_eq!(&txids[1..], &expected[..]);
+
+        let mut txids = vec![];
+        block.for_each_txid(|txid| txids.push(txid));
+        assert_eq!(txids, expected);
+
+        let block = Block::parse_all(&GENESIS_BLOCK[..]).unwrap();
+        let mut count = 0;
+        block.for_each_txid(|txid| {
+            assert_eq!(&txid, block.header().merkle_root());
+            count += 1;
+        });
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn parse_all_block() {
+        assert_eq!(
+            Block::parse_all(&GENESIS_BLOCK),
+            Ok(Block::parse(&GENESIS_BLOCK).unwrap().parsed_owned())
+        );
+        let mut block = GENESIS_BLOCK.to_vec();
+        block.push(0);
+        assert_eq!(Block::parse_all(&block), Err(Error::TrailingBytes(1)));
+        assert_eq!(
+            Block::parse_all(&GENESIS_BLOCK[..284]),
+            Err(Error::Needed(1))
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let mut data = GENESIS_BLOCK.to_vec();
+        data.extend_from_slice(mainnet_702861());
+        assert_eq!(Block::skip(&data), Ok(GENESIS_BLOCK.len()));
+        assert_eq!(
+            Block::skip(&data[GENESIS_BLOCK.len()..]),
+            Ok(mainnet_702861().len())
+        );
+        assert_eq!(Block::skip(&GENESIS_BLOCK[..284]), Err(Error::Needed(1)));
+    }
+
     #[cfg(target_pointer_width = "64")]
     #[test]
     fn size_of() {
@@ -114,6 +1118,7 @@ mod bench {
     use crate::{Parse, Visit, Visitor};
     use bitcoin::consensus::deserialize;
     use bitcoin_test_data::blocks::mainnet_702861;
+    use core::ops::ControlFlow;
     use test::{black_box, Bencher};
 
     #[bench]
@@ -124,6 +1129,13 @@ mod bench {
         });
     }
 
+    #[bench]
+    pub fn block_skip(bh: &mut Bencher) {
+        bh.iter(|| {
+            black_box(Block::skip(mainnet_702861()).unwrap());
+        });
+    }
+
     #[bench]
     pub fn block_deserialize_bitcoin(bh: &mut Bencher) {
         bh.iter(|| {
@@ -136,7 +1148,7 @@ mod bench {
     pub fn block_sum_outputs(bh: &mut Bencher) {
         bh.iter(|| {
             struct Sum(u64);
-            impl Visitor for Sum {
+            impl Visitor<'_> for Sum {
                 fn visit_tx_out(&mut self, _vout: usize, tx_out: &TxOut) {
                     self.0 += tx_out.value();
                 }
@@ -148,6 +1160,49 @@ mod bench {
         });
     }
 
+    /// Counts the transactions, called dynamically so the other calls aren't inlined away
+    struct DynCount {
+        need: bool,
+        count: usize,
+    }
+    impl Visitor<'_> for DynCount {
+        fn need_tx_outs(&self) -> bool {
+            self.need
+        }
+        fn need_witnesses(&self) -> bool {
+            self.need
+        }
+        fn visit_transaction(&mut self, _tx: &crate::bsl::Transaction) {
+            self.count += 1;
+        }
+    }
+
+    #[bench]
+    pub fn block_visit_dyn(bh: &mut Bencher) {
+        bh.iter(|| {
+            let mut count = DynCount {
+                need: true,
+                count: 0,
+            };
+            let mut visitor: &mut dyn Visitor<'_> = &mut count;
+            black_box(Block::visit(mainnet_702861(), &mut visitor).unwrap());
+            assert_eq!(count.count, 2500);
+        });
+    }
+
+    #[bench]
+    pub fn block_visit_dyn_no_outputs_witnesses(bh: &mut Bencher) {
+        bh.iter(|| {
+            let mut count = DynCount {
+                need: false,
+                count: 0,
+            };
+            let mut visitor: &mut dyn Visitor<'_> = &mut count;
+            black_box(Block::visit(mainnet_702861(), &mut visitor).unwrap());
+            assert_eq!(count.count, 2500);
+        });
+    }
+
     #[bench]
     pub fn block_sum_outputs_bitcoin(bh: &mut Bencher) {
         bh.iter(|| {
@@ -171,7 +1226,7 @@ mod bench {
         bh.iter(|| {
             struct VisitTx(Vec<sha256d::Hash>);
             let mut v = VisitTx(vec![]);
-            impl crate::Visitor for VisitTx {
+            impl crate::Visitor<'_> for VisitTx {
                 fn visit_block_begin(&mut self, total_transactions: usize) {
                     self.0.reserve(total_transactions);
                 }
@@ -188,6 +1243,28 @@ mod bench {
         });
     }
 
+    #[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+    #[bench]
+    pub fn block_txids_into(bh: &mut Bencher) {
+        let block = Block::parse_all(mainnet_702861()).unwrap();
+        let mut txids = Vec::new();
+        bh.iter(|| {
+            txids.clear();
+            assert_eq!(block.txids_into(&mut txids), 2500);
+            black_box(&txids);
+        });
+    }
+
+    #[bench]
+    pub fn block_txids_bitcoin(bh: &mut Bencher) {
+        bh.iter(|| {
+            let block: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+            let txids: Vec<_> = block.txdata.iter().map(|tx| tx.txid()).collect();
+            assert_eq!(txids.len(), 2500);
+            black_box(&txids);
+        });
+    }
+
     #[cfg(feature = "sha2")]
     #[bench]
     pub fn hash_block_txs_sha2(bh: &mut Bencher) {
@@ -201,7 +1278,7 @@ mod bench {
                 >,
             );
             let mut v = VisitTx(vec![]);
-            impl crate::Visitor for VisitTx {
+            impl crate::Visitor<'_> for VisitTx {
                 fn visit_block_begin(&mut self, total_transactions: usize) {
                     self.0.reserve(total_transactions);
                 }
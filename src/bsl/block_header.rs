@@ -1,9 +1,31 @@
+use crate::visit::check;
 use crate::{
+    bsl::TxidHasher,
     number::{I32, U32},
     slice::read_slice,
-    Parse, ParseResult, SResult, Visit, Visitor,
+    Error, Parse, ParseResult, PowError, SResult, TargetError, Visit, Visitor,
 };
 
+/// The maximum target allowed by the proof of work on mainnet and testnet, as a 256 bits
+/// big-endian number: `0x00000000ffff...ff`.
+pub const POW_LIMIT: [u8; 32] = {
+    let mut limit = [0xffu8; 32];
+    limit[0] = 0;
+    limit[1] = 0;
+    limit[2] = 0;
+    limit[3] = 0;
+    limit
+};
+
+/// The bits of the version that must be set to [`VERSION_BITS_TOP_BITS`] in BIP9 version bits.
+const VERSION_BITS_TOP_MASK: u32 = 0xe000_0000;
+
+/// The value of the top 3 bits of the version signalling BIP9 version bits.
+const VERSION_BITS_TOP_BITS: u32 = 0x2000_0000;
+
+/// The number of bits of the version available for BIP9 deployments.
+pub const VERSION_BITS_NUM_BITS: u8 = 29;
+
 /// The block header.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BlockHeader<'a> {
@@ -15,7 +37,7 @@ pub struct BlockHeader<'a> {
 }
 
 impl<'a> Visit<'a> for BlockHeader<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
         let version = I32::parse(slice)?;
         let hashes = read_slice(version.remaining(), 64)?;
         let time = U32::parse(hashes.remaining())?;
@@ -29,24 +51,57 @@ impl<'a> Visit<'a> for BlockHeader<'a> {
             nonce: nonce.parsed().into(),
         };
         visit.visit_block_header(&header);
+        check(visit)?;
         Ok(ParseResult::new(nonce.remaining(), header))
     }
 }
 
 impl<'a> BlockHeader<'a> {
+    /// Returns the length of the block header at the start of `slice`, which is always 80 when
+    /// `slice` is long enough, see [`Transaction::skip()`](crate::bsl::Transaction::skip).
+    pub fn skip(slice: &'a [u8]) -> Result<usize, Error> {
+        Ok(Self::parse(slice)?.consumed())
+    }
+
     /// Returns the block header version.
     pub fn version(&self) -> i32 {
         self.version
     }
 
-    /// Returns the hash of the previous block header.
-    pub fn prev_blockhash(&self) -> &[u8] {
-        &self.slice[4..36]
+    /// Returns the 29 bits available for BIP9 deployments if the version signals version bits,
+    /// meaning its top 3 bits are `001`, `None` otherwise.
+    pub fn version_bits(&self) -> Option<u32> {
+        let version = self.version as u32;
+        if version & VERSION_BITS_TOP_MASK == VERSION_BITS_TOP_BITS {
+            Some(version & !VERSION_BITS_TOP_MASK)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if the version signals version bits and the given deployment `bit` is set,
+    /// like Bitcoin Core `VersionBitsConditionChecker::Condition`. Always false if `bit` is not
+    /// lower than [`VERSION_BITS_NUM_BITS`].
+    pub fn is_signalling(&self, bit: u8) -> bool {
+        bit < VERSION_BITS_NUM_BITS
+            && self
+                .version_bits()
+                .is_some_and(|bits| bits & (1 << bit) != 0)
     }
 
-    /// Returns the hash of the root of the merkle tree of the transactions in this block.
-    pub fn merkle_root(&self) -> &[u8] {
-        &self.slice[36..68]
+    /// Returns the hash of the previous block header, in byte order (not reversed).
+    pub fn prev_blockhash(&self) -> &'a [u8; 32] {
+        self.slice[4..36]
+            .try_into()
+            .expect("slice length granted during parsing")
+    }
+
+    /// Returns the hash of the root of the merkle tree of the transactions in this block, in byte
+    /// order (not reversed).
+    pub fn merkle_root(&self) -> &'a [u8; 32] {
+        self.slice[36..68]
+            .try_into()
+            .expect("slice length granted during parsing")
     }
 
     /// Returns the UNIX timestamp of the block header.
@@ -54,6 +109,40 @@ impl<'a> BlockHeader<'a> {
         self.time
     }
 
+    /// Returns the target in compact format of this block header.
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// Returns the target decoded from the compact format [`BlockHeader::bits()`] as a 256 bits
+    /// big-endian number.
+    ///
+    /// Like in Bitcoin Core a zero mantissa, after shifting it right if the exponent is lower than
+    /// 3, is a zero target whatever the sign is, otherwise [`TargetError::Negative`] is returned if
+    /// the sign bit is set and [`TargetError::Overflow`] if the target doesn't fit in 256 bits.
+    pub fn target(&self) -> Result<[u8; 32], TargetError> {
+        target_from_compact(self.bits)
+    }
+
+    /// Returns the difficulty, the ratio between the target of difficulty 1 (`0x1d00ffff` in
+    /// compact format) and the target of this block header.
+    ///
+    /// It's computed from [`BlockHeader::bits()`] like Bitcoin Core `GetDifficulty` and it's not
+    /// finite if the mantissa is zero.
+    pub fn difficulty(&self) -> f64 {
+        let mut shift = (self.bits >> 24) & 0xff;
+        let mut difficulty = 0x0000ffff as f64 / (self.bits & 0x00ffffff) as f64;
+        while shift < 29 {
+            difficulty *= 256.0;
+            shift += 1;
+        }
+        while shift > 29 {
+            difficulty /= 256.0;
+            shift -= 1;
+        }
+        difficulty
+    }
+
     /// Returns the nonce of this block header.
     pub fn nonce(&self) -> u32 {
         self.nonce
@@ -65,6 +154,48 @@ impl<'a> BlockHeader<'a> {
         self.slice
     }
 
+    /// Returns the hash of this block header computed with the given double sha256 `hasher`, in
+    /// byte order (not reversed), see [`TxidHasher`].
+    pub fn block_hash_with<H: TxidHasher>(&self, mut hasher: H) -> [u8; 32] {
+        hasher.update(self.block_hash_preimage());
+        hasher.finalize_double()
+    }
+
+    /// Checks the proof of work of this block header against the mainnet and testnet [`POW_LIMIT`],
+    /// see [`BlockHeader::validate_pow_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn validate_pow(&self) -> Result<(), PowError> {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.validate_pow_with(sha256d::Hash::engine(), &POW_LIMIT)
+    }
+
+    /// Checks the proof of work of this block header like Bitcoin Core `CheckProofOfWork`: the
+    /// target must be valid, not zero and not above `pow_limit` (a 256 bits big-endian number),
+    /// and the block hash computed with `hasher`, interpreted as a little-endian number, must not
+    /// be above the target.
+    ///
+    /// Note the target itself is not checked against the difficulty adjustment rules.
+    pub fn validate_pow_with<H: TxidHasher>(
+        &self,
+        hasher: H,
+        pow_limit: &[u8; 32],
+    ) -> Result<(), PowError> {
+        let target = self.target()?;
+        if target == [0u8; 32] {
+            return Err(PowError::ZeroTarget);
+        }
+        if &target > pow_limit {
+            return Err(PowError::TargetAboveLimit);
+        }
+        let mut hash = self.block_hash_with(hasher);
+        hash.reverse();
+        if hash > target {
+            return Err(PowError::HashAboveTarget);
+        }
+        Ok(())
+    }
+
     /// Returns the hash of this block header
     #[cfg(feature = "bitcoin_hashes")]
     #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
@@ -89,15 +220,84 @@ impl<'a> BlockHeader<'a> {
     }
 }
 
+/// Decodes the compact format of a target, see [`BlockHeader::target()`].
+fn target_from_compact(bits: u32) -> Result<[u8; 32], TargetError> {
+    let exponent = (bits >> 24) as usize;
+    let mut mantissa = bits & 0x007fffff;
+    if exponent <= 3 {
+        mantissa >>= 8 * (3 - exponent);
+    }
+    let mut target = [0u8; 32];
+    if mantissa == 0 {
+        return Ok(target);
+    }
+    if bits & 0x00800000 != 0 {
+        return Err(TargetError::Negative);
+    }
+    if exponent > 34 || (mantissa > 0xff && exponent > 33) || (mantissa > 0xffff && exponent > 32) {
+        return Err(TargetError::Overflow);
+    }
+    // the mantissa bytes, least significant first, at their position from the right
+    let shift = exponent.saturating_sub(3);
+    for (i, byte) in mantissa.to_le_bytes()[..3].iter().enumerate() {
+        if let Some(index) = 31usize.checked_sub(shift + i) {
+            target[index] = *byte;
+        }
+    }
+    Ok(target)
+}
+
 impl<'a> AsRef<[u8]> for BlockHeader<'a> {
     fn as_ref(&self) -> &[u8] {
         self.slice
     }
 }
 
+crate::hex::impl_hex_fmt!(BlockHeader);
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> BlockHeader<'a> {
+    /// Serializes the rust-bitcoin header in `buffer` and parses it, which never fails.
+    pub fn from_bitcoin(header: &bitcoin::BlockHeader, buffer: &'a mut [u8; 80]) -> Self {
+        buffer.copy_from_slice(&bitcoin::consensus::serialize(header));
+        BlockHeader::parse(&buffer[..])
+            .expect("80 bytes are a valid header")
+            .parsed_owned()
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> From<&BlockHeader<'a>> for bitcoin::BlockHeader {
+    /// Deserialize the header slice with rust-bitcoin, which never fails for 80 bytes.
+    fn from(header: &BlockHeader<'a>) -> Self {
+        bitcoin::consensus::deserialize(header.as_ref()).expect("80 bytes are a valid header")
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> From<BlockHeader<'a>> for bitcoin::BlockHeader {
+    fn from(header: BlockHeader<'a>) -> Self {
+        (&header).into()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for BlockHeader<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{bsl::BlockHeader, test_common::GENESIS_BLOCK_HEADER, Parse};
+    use super::target_from_compact;
+    use crate::{bsl::BlockHeader, test_common::GENESIS_BLOCK_HEADER, Error, Parse, TargetError};
+    use bitcoin_test_data::blocks::mainnet_702861;
 
     use hex_lit::hex;
 
@@ -106,7 +306,7 @@ mod test {
         // genesis block
         let block_header = BlockHeader::parse(&GENESIS_BLOCK_HEADER).unwrap();
 
-        assert_eq!(block_header.remaining(), &[][..]);
+        assert_eq!(block_header.remaining(), &[0u8; 0][..]);
         assert_eq!(
             block_header.parsed(),
             &BlockHeader {
@@ -118,28 +318,286 @@ mod test {
             }
         );
         assert_eq!(block_header.consumed(), 80);
+        assert_eq!(BlockHeader::skip(&GENESIS_BLOCK_HEADER), Ok(80));
+        assert_eq!(
+            BlockHeader::skip(&GENESIS_BLOCK_HEADER[..79]),
+            Err(Error::Needed(1))
+        );
 
+        assert_eq!(block_header.parsed().version(), 1);
         assert_eq!(
             block_header.parsed().prev_blockhash(),
-            hex!("0000000000000000000000000000000000000000000000000000000000000000")
+            &hex!("0000000000000000000000000000000000000000000000000000000000000000")
         );
         assert_eq!(
             block_header.parsed().merkle_root(),
-            hex!("3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a")
+            &hex!("3ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a")
         );
+        assert_eq!(block_header.parsed().time(), 1231006505);
+        assert_eq!(block_header.parsed().bits(), 0x1d00ffff);
+        assert_eq!(block_header.parsed().nonce(), 2083236893);
 
         check_hash(
-            &block_header.parsed(),
+            block_header.parsed(),
             hex!("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"),
         );
     }
 
+    #[test]
+    fn block_hash_mainnet_702861() {
+        let block_header = BlockHeader::parse(mainnet_702861()).unwrap();
+        assert_eq!(block_header.parsed().as_ref(), &mainnet_702861()[..80]);
+        check_hash(
+            block_header.parsed(),
+            hex!("000000000000000000000c835b2adcaedc20fdf6ee440009c249452c726dafae"),
+        );
+    }
+
+    #[test]
+    fn fields_mainnet_702861() {
+        let block_header = BlockHeader::parse(mainnet_702861()).unwrap().parsed_owned();
+        let header_bitcoin: bitcoin::BlockHeader =
+            bitcoin::consensus::deserialize(&mainnet_702861()[..80]).unwrap();
+        assert_eq!(block_header.version(), header_bitcoin.version);
+        assert_eq!(
+            &block_header.prev_blockhash()[..],
+            &header_bitcoin.prev_blockhash[..]
+        );
+        assert_eq!(
+            &block_header.merkle_root()[..],
+            &header_bitcoin.merkle_root[..]
+        );
+        assert_eq!(block_header.time(), header_bitcoin.time);
+        assert_eq!(block_header.bits(), header_bitcoin.bits);
+        assert_eq!(block_header.nonce(), header_bitcoin.nonce);
+    }
+
+    #[test]
+    fn version_bits() {
+        let genesis = BlockHeader::parse(&GENESIS_BLOCK_HEADER)
+            .unwrap()
+            .parsed_owned();
+        assert_eq!(genesis.version_bits(), None);
+        assert!(!genesis.is_signalling(0));
+
+        // version 0x3fffe004: taproot signalling (bit 2) with version rolling bits
+        let header = BlockHeader::parse(mainnet_702861()).unwrap().parsed_owned();
+        assert_eq!(header.version_bits(), Some(0x1fff_e004));
+        assert!(header.is_signalling(2));
+        assert!(!header.is_signalling(1));
+        assert!(header.is_signalling(28));
+        assert!(!header.is_signalling(29));
+        assert!(!header.is_signalling(255));
+
+        for (version, expected) in [
+            (0x2000_0004u32, Some(4)),
+            (0x2000_0000, Some(0)),
+            (0x4000_0004, None),
+            (0x6000_0004, None),
+            (0xe000_0004, None),
+            (0x0000_0004, None),
+        ] {
+            let mut bytes = GENESIS_BLOCK_HEADER;
+            bytes[..4].copy_from_slice(&version.to_le_bytes());
+            let header = BlockHeader::parse(&bytes).unwrap().parsed_owned();
+            assert_eq!(header.version_bits(), expected, "{:x}", version);
+            assert_eq!(
+                header.is_signalling(2),
+                expected == Some(4),
+                "{:x}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn test_target_from_compact() {
+        use bitcoin::hashes::hex::FromHex;
+        let target = |hex: &str| {
+            let mut target = [0u8; 32];
+            let bytes = Vec::<u8>::from_hex(hex).unwrap();
+            target[32 - bytes.len()..].copy_from_slice(&bytes);
+            Ok(target)
+        };
+        // vectors from Bitcoin Core arith_uint256_tests
+        for bits in [
+            0u32, 0x00123456, 0x01003456, 0x02000056, 0x03000000, 0x04000000, 0x00923456,
+            0x01803456, 0x02800056, 0x03800000, 0x04800000,
+        ] {
+            assert_eq!(target_from_compact(bits), Ok([0u8; 32]), "{:x}", bits);
+        }
+        assert_eq!(target_from_compact(0x01123456), target("12"));
+        assert_eq!(target_from_compact(0x02123456), target("1234"));
+        assert_eq!(target_from_compact(0x03123456), target("123456"));
+        assert_eq!(target_from_compact(0x04123456), target("12345600"));
+        assert_eq!(target_from_compact(0x05009234), target("92340000"));
+        assert_eq!(
+            target_from_compact(0x20123456),
+            target("1234560000000000000000000000000000000000000000000000000000000000")
+        );
+        assert_eq!(target_from_compact(0x01fedcba), Err(TargetError::Negative));
+        assert_eq!(target_from_compact(0x04923456), Err(TargetError::Negative));
+        assert_eq!(target_from_compact(0xff123456), Err(TargetError::Overflow));
+
+        // limits of the overflow
+        assert_eq!(
+            target_from_compact(0x22000001),
+            target("0100000000000000000000000000000000000000000000000000000000000000")
+        );
+        assert_eq!(target_from_compact(0x23000001), Err(TargetError::Overflow));
+        assert_eq!(target_from_compact(0x22000100), Err(TargetError::Overflow));
+        assert_eq!(target_from_compact(0x21010000), Err(TargetError::Overflow));
+        assert_eq!(
+            target_from_compact(0x2100ffff),
+            target("ffff000000000000000000000000000000000000000000000000000000000000")
+        );
+
+        for bits in [0x1d00ffff, 0x170b3ce9, 0x1b0404cb, 0x01003456, 0x20123456] {
+            let expected = bitcoin::BlockHeader::u256_from_compact_target(bits).to_be_bytes();
+            assert_eq!(target_from_compact(bits), Ok(expected), "{:x}", bits);
+        }
+    }
+
+    #[test]
+    fn target_and_difficulty() {
+        let genesis = BlockHeader::parse(&GENESIS_BLOCK_HEADER)
+            .unwrap()
+            .parsed_owned();
+        assert_eq!(
+            genesis.target(),
+            Ok(hex!(
+                "00000000ffff0000000000000000000000000000000000000000000000000000"
+            ))
+        );
+        assert_eq!(genesis.difficulty(), 1.0);
+
+        let block_header = BlockHeader::parse(mainnet_702861()).unwrap().parsed_owned();
+        let header_bitcoin: bitcoin::BlockHeader =
+            bitcoin::consensus::deserialize(&mainnet_702861()[..80]).unwrap();
+        assert_eq!(
+            block_header.target(),
+            Ok(header_bitcoin.target().to_be_bytes())
+        );
+        let difficulty_bitcoin = header_bitcoin.difficulty(bitcoin::Network::Bitcoin) as f64;
+        assert!((block_header.difficulty() - difficulty_bitcoin).abs() < 1.0);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn validate_pow() {
+        use crate::{bsl::POW_LIMIT, PowError};
+
+        let genesis = BlockHeader::parse(&GENESIS_BLOCK_HEADER)
+            .unwrap()
+            .parsed_owned();
+        assert_eq!(genesis.validate_pow(), Ok(()));
+        let block_header = BlockHeader::parse(mainnet_702861()).unwrap().parsed_owned();
+        assert_eq!(block_header.validate_pow(), Ok(()));
+
+        let with_header = |f: &dyn Fn(&mut [u8])| {
+            let mut bytes: [u8; 80] = mainnet_702861()[..80].try_into().unwrap();
+            f(&mut bytes);
+            let header = BlockHeader::parse(&bytes).unwrap().parsed_owned();
+            header.validate_pow()
+        };
+        // flipped nonce
+        assert_eq!(with_header(&|b| b[76] ^= 1), Err(PowError::HashAboveTarget));
+        // the bits of the genesis with a 0x01ffff mantissa, above the limit
+        assert_eq!(
+            with_header(&|b| b[72..76].copy_from_slice(&0x1d01ffffu32.to_le_bytes())),
+            Err(PowError::TargetAboveLimit)
+        );
+        assert_eq!(
+            with_header(&|b| b[72..76].copy_from_slice(&0x1d80ffffu32.to_le_bytes())),
+            Err(PowError::InvalidTarget(TargetError::Negative))
+        );
+        assert_eq!(
+            with_header(&|b| b[72..76].copy_from_slice(&0xff00ffffu32.to_le_bytes())),
+            Err(PowError::InvalidTarget(TargetError::Overflow))
+        );
+        assert_eq!(
+            with_header(&|b| b[72..76].copy_from_slice(&0x1d000000u32.to_le_bytes())),
+            Err(PowError::ZeroTarget)
+        );
+
+        // the regtest genesis, its target is above the mainnet limit
+        let mut bytes = GENESIS_BLOCK_HEADER;
+        bytes[68..72].copy_from_slice(&1296688602u32.to_le_bytes());
+        bytes[72..76].copy_from_slice(&0x207fffffu32.to_le_bytes());
+        bytes[76..].copy_from_slice(&2u32.to_le_bytes());
+        let header = BlockHeader::parse(&bytes).unwrap().parsed_owned();
+        check_hash(
+            &header,
+            hex!("0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"),
+        );
+        let mut regtest_limit = [0xffu8; 32];
+        regtest_limit[0] = 0x7f;
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        let engine = sha256d::Hash::engine;
+        assert_eq!(header.validate_pow_with(engine(), &regtest_limit), Ok(()));
+        assert_eq!(
+            header.validate_pow_with(engine(), &POW_LIMIT),
+            Err(PowError::TargetAboveLimit)
+        );
+    }
+
+    #[test]
+    fn parse_all_block_header() {
+        let block_header = BlockHeader::parse_all(&GENESIS_BLOCK_HEADER).unwrap();
+        assert_eq!(block_header.as_ref(), &GENESIS_BLOCK_HEADER[..]);
+        let mut bytes = GENESIS_BLOCK_HEADER.to_vec();
+        bytes.push(0);
+        assert_eq!(BlockHeader::parse_all(&bytes), Err(Error::TrailingBytes(1)));
+    }
+
+    #[test]
+    fn fmt_block_header() {
+        let block_header = BlockHeader::parse(&GENESIS_BLOCK_HEADER)
+            .unwrap()
+            .parsed_owned();
+        let expected = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+        assert_eq!(format!("{:x}", block_header), expected);
+        assert_eq!(format!("{:X}", block_header), expected.to_uppercase());
+        assert_eq!(block_header.to_string(), expected);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_block_header() {
+        let block_header = BlockHeader::parse(&GENESIS_BLOCK_HEADER)
+            .unwrap()
+            .parsed_owned();
+        assert_eq!(
+            serde_json::to_string(&block_header).unwrap(),
+            "\"0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c\""
+        );
+        let bin = bincode::serialize(&block_header).unwrap();
+        assert_eq!(&bin[..8], &80u64.to_le_bytes());
+        assert_eq!(&bin[8..], &GENESIS_BLOCK_HEADER[..]);
+    }
+
     #[cfg(target_pointer_width = "64")]
     #[test]
     fn size_of() {
         assert_eq!(std::mem::size_of::<BlockHeader>(), 32);
     }
 
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn block_header_bitcoin() {
+        for bytes in [&GENESIS_BLOCK_HEADER[..], &mainnet_702861()[..80]] {
+            let header = BlockHeader::parse(bytes).unwrap().parsed_owned();
+            let expected: bitcoin::BlockHeader = bitcoin::consensus::deserialize(bytes).unwrap();
+            let header_bitcoin = bitcoin::BlockHeader::from(&header);
+            assert_eq!(header_bitcoin, expected);
+            assert_eq!(bitcoin::BlockHeader::from(header.clone()), expected);
+
+            let mut buffer = [0u8; 80];
+            let back = BlockHeader::from_bitcoin(&header_bitcoin, &mut buffer);
+            assert_eq!(back, header);
+        }
+    }
+
     #[cfg(all(not(feature = "sha2"), not(feature = "bitcoin_hashes")))]
     fn check_hash(_block: &BlockHeader, _expected: [u8; 32]) {}
 
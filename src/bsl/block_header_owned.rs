@@ -0,0 +1,186 @@
+use crate::bsl::{BlockHeader, IntoOwned};
+use crate::Parse;
+
+/// An owned block header, keeping a copy of the 80 bytes of a parsed [`BlockHeader`].
+///
+/// Useful to keep a header past the lifetime of the buffer it has been parsed from, it doesn't
+/// need allocation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BlockHeaderOwned {
+    data: [u8; 80],
+}
+
+impl BlockHeaderOwned {
+    /// Creates the owned header from its serialization, any 80 bytes are a valid header.
+    pub fn new(data: [u8; 80]) -> Self {
+        BlockHeaderOwned { data }
+    }
+
+    /// Returns the borrowed header.
+    pub fn as_block_header(&self) -> BlockHeader<'_> {
+        BlockHeader::parse(&self.data[..])
+            .expect("80 bytes are a valid header")
+            .parsed_owned()
+    }
+
+    /// Returns the hash of this block header, see [`BlockHeader::block_hash()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn block_hash(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        self.as_block_header().block_hash()
+    }
+
+    /// Returns the serialized header bytes.
+    pub fn into_bytes(self) -> [u8; 80] {
+        self.data
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<'a> BlockHeader<'a> {
+    /// Parse the block header in `data`, which must be exactly 80 bytes, fails with
+    /// [`crate::Error::TrailingBytes`] otherwise, like [`Parse::parse_all`].
+    ///
+    /// On error `data` is given back along with the error, so the buffer isn't lost.
+    pub fn parse_owned(
+        data: alloc::vec::Vec<u8>,
+    ) -> Result<BlockHeaderOwned, (alloc::vec::Vec<u8>, crate::Error)> {
+        match BlockHeader::parse_all(&data[..]) {
+            Ok(header) => Ok(header.into()),
+            Err(e) => Err((data, e)),
+        }
+    }
+}
+
+impl<'a> BlockHeader<'a> {
+    /// Copies the 80 bytes of the header.
+    pub fn to_owned(&self) -> BlockHeaderOwned {
+        self.into()
+    }
+}
+
+impl<'a> IntoOwned for BlockHeader<'a> {
+    type Owned = BlockHeaderOwned;
+
+    fn into_owned(self) -> BlockHeaderOwned {
+        self.into()
+    }
+}
+
+impl<'a> From<BlockHeader<'a>> for BlockHeaderOwned {
+    fn from(header: BlockHeader<'a>) -> Self {
+        (&header).into()
+    }
+}
+
+impl<'a> From<&BlockHeader<'a>> for BlockHeaderOwned {
+    fn from(header: &BlockHeader<'a>) -> Self {
+        let data = header
+            .as_ref()
+            .try_into()
+            .expect("slice length granted during parsing");
+        BlockHeaderOwned { data }
+    }
+}
+
+impl AsRef<[u8]> for BlockHeaderOwned {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+impl redb::RedbValue for BlockHeaderOwned {
+    type SelfType<'a>
+        = BlockHeaderOwned
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        Some(80)
+    }
+
+    /// Parse the stored bytes, panics if they are not a BlockHeader, like with a corrupted database.
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        BlockHeader::parse_all(data)
+            .expect("inserted data is not a BlockHeader")
+            .into()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.as_ref()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("BlockHeaderOwned")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{BlockHeader, BlockHeaderOwned},
+        test_common::GENESIS_BLOCK_HEADER,
+        Parse,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn block_header_owned() {
+        let vectors = [&GENESIS_BLOCK_HEADER[..], &mainnet_702861()[..80]];
+        let mut owned = vec![];
+        for bytes in vectors {
+            let buffer = bytes.to_vec();
+            let header = BlockHeader::parse(&buffer[..]).unwrap().parsed_owned();
+            assert_eq!(
+                BlockHeaderOwned::from(&header),
+                BlockHeaderOwned::from(header.clone())
+            );
+            owned.push(BlockHeaderOwned::from(header));
+        }
+        // the owned headers outlive the buffers
+        for (owned, bytes) in owned.into_iter().zip(vectors) {
+            let expected = BlockHeader::parse(bytes).unwrap().parsed_owned();
+            assert_eq!(owned.as_block_header(), expected);
+            assert_eq!(owned.as_ref(), bytes);
+            assert_eq!(owned, BlockHeaderOwned::new(bytes.try_into().unwrap()));
+            #[cfg(feature = "bitcoin_hashes")]
+            assert_eq!(owned.block_hash(), expected.block_hash());
+            assert_eq!(&owned.into_bytes()[..], bytes);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn parse_owned() {
+        use crate::Error;
+
+        let owned = BlockHeader::parse_owned(GENESIS_BLOCK_HEADER.to_vec()).unwrap();
+        assert_eq!(owned.as_ref(), &GENESIS_BLOCK_HEADER[..]);
+
+        let short = GENESIS_BLOCK_HEADER[..79].to_vec();
+        assert_eq!(
+            BlockHeader::parse_owned(short.clone()),
+            Err((short, Error::Needed(1)))
+        );
+        let long = mainnet_702861()[..81].to_vec();
+        assert_eq!(
+            BlockHeader::parse_owned(long.clone()),
+            Err((long, Error::TrailingBytes(1)))
+        );
+    }
+}
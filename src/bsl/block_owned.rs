@@ -0,0 +1,283 @@
+use alloc::vec::Vec;
+
+use crate::bsl::{Block, BlockHeader, IntoOwned};
+use crate::{Error, Parse};
+
+/// An owned Bitcoin block, keeping a copy of the bytes of a parsed [`Block`].
+///
+/// Useful to keep a block past the lifetime of the buffer it has been parsed from, like a reused
+/// buffer when scanning block files. Equality compares the bytes of the blocks.
+#[derive(Debug, Clone)]
+pub struct BlockOwned {
+    data: Vec<u8>,
+    total_txs: u32,
+    allow_zero_inputs: bool,
+    allow_non_minimal_compact_sizes: bool,
+}
+
+impl PartialEq for BlockOwned {
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl Eq for BlockOwned {}
+
+impl BlockOwned {
+    /// Parse the block in `data` keeping it, fails with [`Error::TrailingBytes`] if there are
+    /// bytes after the block, like [`Parse::parse_all`].
+    ///
+    /// See [`Block::parse_owned()`] to get `data` back on error.
+    pub fn parse_owned(data: Vec<u8>) -> Result<Self, Error> {
+        Block::parse_owned(data).map_err(|(_, e)| e)
+    }
+
+    /// Returns the borrowed block.
+    pub fn as_block(&self) -> Block<'_> {
+        Block {
+            slice: &self.data[..],
+            header: self.header(),
+            total_txs: self.total_txs,
+            allow_zero_inputs: self.allow_zero_inputs,
+            allow_non_minimal_compact_sizes: self.allow_non_minimal_compact_sizes,
+        }
+    }
+
+    /// Returns the header of the block.
+    pub fn header(&self) -> BlockHeader<'_> {
+        BlockHeader::parse(&self.data[..])
+            .expect("granted during parsing")
+            .parsed_owned()
+    }
+
+    /// Returns the hash of this block, see [`Block::block_hash()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn block_hash(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        self.header().block_hash()
+    }
+
+    /// Returns the serialized block bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<'a> Block<'a> {
+    /// Parse the block in `data` keeping it without copying, fails with [`Error::TrailingBytes`]
+    /// if there are bytes after the block, like [`Parse::parse_all`].
+    ///
+    /// On error `data` is given back along with the error, so the buffer isn't lost.
+    pub fn parse_owned(data: Vec<u8>) -> Result<BlockOwned, (Vec<u8>, Error)> {
+        match Block::parse_all(&data[..]) {
+            Ok(block) => {
+                let (total_txs, allow_zero_inputs, allow_non_minimal_compact_sizes) = (
+                    block.total_txs,
+                    block.allow_zero_inputs,
+                    block.allow_non_minimal_compact_sizes,
+                );
+                Ok(BlockOwned {
+                    data,
+                    total_txs,
+                    allow_zero_inputs,
+                    allow_non_minimal_compact_sizes,
+                })
+            }
+            Err(e) => Err((data, e)),
+        }
+    }
+
+    /// Copies the block bytes, keeping the metadata computed during parsing.
+    pub fn to_owned(&self) -> BlockOwned {
+        self.into()
+    }
+}
+
+impl<'a> IntoOwned for Block<'a> {
+    type Owned = BlockOwned;
+
+    fn into_owned(self) -> BlockOwned {
+        self.into()
+    }
+}
+
+impl<'a> From<Block<'a>> for BlockOwned {
+    fn from(block: Block<'a>) -> Self {
+        (&block).into()
+    }
+}
+
+impl<'a> From<&Block<'a>> for BlockOwned {
+    fn from(block: &Block<'a>) -> Self {
+        BlockOwned {
+            data: block.slice.to_vec(),
+            total_txs: block.total_txs,
+            allow_zero_inputs: block.allow_zero_inputs,
+            allow_non_minimal_compact_sizes: block.allow_non_minimal_compact_sizes,
+        }
+    }
+}
+
+impl AsRef<[u8]> for BlockOwned {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+impl redb::RedbValue for BlockOwned {
+    type SelfType<'a>
+        = BlockOwned
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    /// Parse the stored bytes, panics if they are not a Block, like with a corrupted database.
+    ///
+    /// The block may have been parsed with any [`crate::bsl::ParseConfig`], so the transactions are
+    /// parsed accepting what any config accepts.
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        Block::parse_with_config(data, &crate::bsl::transaction_owned::REPARSE_CONFIG)
+            .and_then(|parsed| parsed.require_empty())
+            .expect("inserted data is not a Block")
+            .into()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.as_ref()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("BlockOwned")
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl TryFrom<&bitcoin::Block> for BlockOwned {
+    type Error = Error;
+
+    /// Serialize the rust-bitcoin block and parse it, fails if the serialized block can't be
+    /// parsed back.
+    fn try_from(block: &bitcoin::Block) -> Result<Self, Self::Error> {
+        BlockOwned::parse_owned(bitcoin::consensus::serialize(block))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{Block, BlockOwned},
+        test_common::GENESIS_BLOCK,
+        Error, Parse,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn block_owned() {
+        let vectors = [&GENESIS_BLOCK[..], mainnet_702861()];
+        let mut owned = vec![];
+        for bytes in vectors {
+            let buffer = bytes.to_vec();
+            let block = Block::parse_all(&buffer[..]).unwrap();
+            assert_eq!(BlockOwned::from(&block), BlockOwned::from(block.clone()));
+            owned.push(BlockOwned::from(block));
+        }
+        // the owned blocks outlive the buffers
+        for (owned, bytes) in owned.into_iter().zip(vectors) {
+            let expected = Block::parse_all(bytes).unwrap();
+            assert_eq!(owned.as_block(), expected);
+            assert_eq!(&owned.header(), expected.header());
+            assert_eq!(
+                owned.as_block().total_transactions(),
+                expected.total_transactions()
+            );
+            assert_eq!(owned.as_block().weight(), expected.weight());
+            assert_eq!(owned.as_ref(), bytes);
+            #[cfg(feature = "bitcoin_hashes")]
+            assert_eq!(owned.block_hash(), expected.block_hash());
+            assert_eq!(BlockOwned::parse_owned(bytes.to_vec()), Ok(owned.clone()));
+            assert_eq!(Block::parse_owned(bytes.to_vec()), Ok(owned.clone()));
+            assert_eq!(owned.into_bytes(), bytes);
+        }
+
+        let mut with_trailing = GENESIS_BLOCK.to_vec();
+        with_trailing.push(0);
+        assert_eq!(
+            Block::parse_owned(with_trailing.clone()),
+            Err((with_trailing.clone(), Error::TrailingBytes(1)))
+        );
+        assert_eq!(
+            BlockOwned::parse_owned(with_trailing),
+            Err(Error::TrailingBytes(1))
+        );
+        assert_eq!(
+            BlockOwned::parse_owned(GENESIS_BLOCK[..100].to_vec()),
+            Err(Error::Needed(22))
+        );
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn block_owned_bitcoin() {
+        let block_bitcoin: bitcoin::Block =
+            bitcoin::consensus::deserialize(mainnet_702861()).unwrap();
+        let owned = BlockOwned::try_from(&block_bitcoin).unwrap();
+        assert_eq!(
+            owned.as_block(),
+            Block::parse_all(mainnet_702861()).unwrap()
+        );
+        let back = bitcoin::Block::try_from(owned.as_block()).unwrap();
+        assert_eq!(back, block_bitcoin);
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    fn block_owned_redb() {
+        use crate::bsl::BlockHeaderOwned;
+        use redb::ReadableTable;
+
+        const BLOCKS: redb::TableDefinition<u32, BlockOwned> = redb::TableDefinition::new("blocks");
+        const HEADERS: redb::TableDefinition<u32, BlockHeaderOwned> =
+            redb::TableDefinition::new("headers");
+        let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        let db = redb::Database::create(path).unwrap();
+        let block = BlockOwned::parse_owned(mainnet_702861().to_vec()).unwrap();
+        let header = BlockHeaderOwned::from(block.header());
+
+        let write_txn = db.begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(BLOCKS).unwrap();
+            table.insert(&702861, &block).unwrap();
+            let mut table = write_txn.open_table(HEADERS).unwrap();
+            table.insert(&702861, &header).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let read_txn = db.begin_read().unwrap();
+        let table = read_txn.open_table(BLOCKS).unwrap();
+        let read = table.get(&702861).unwrap().unwrap().value();
+        assert_eq!(read, block);
+        #[cfg(feature = "bitcoin_hashes")]
+        read.as_block().verify_merkle_root().unwrap();
+        let table = read_txn.open_table(HEADERS).unwrap();
+        assert_eq!(table.get(&702861).unwrap().unwrap().value(), header);
+    }
+}
@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+use bytes::Buf;
+
+use crate::bsl::{BlockHeader, BlockHeaderOwned, Transaction, TransactionOwned};
+use crate::{Error, Parse};
+
+impl<'a> Transaction<'a> {
+    /// Parse a transaction from the possibly non-contiguous `buf`, advancing it past the
+    /// transaction.
+    ///
+    /// If the transaction is in the first chunk of `buf` it's parsed from there, otherwise it's
+    /// copied chunk by chunk, never reading bytes after the transaction.
+    ///
+    /// In the latter case, on error `buf` has been advanced by the bytes copied, so an
+    /// [`Error::Needed`] can't be retried when more bytes are available, thus this is meant to be
+    /// used when the whole transaction is in `buf`, like in a P2P message of known length.
+    ///
+    /// Copying only what the [`Error::Needed`] of the last attempt asks for, the copied bytes are
+    /// parsed again from the start after every copy, so the cost is quadratic in the number of
+    /// inputs, outputs and witness elements. When the transaction length is known, like for a
+    /// P2P `tx` message, it's cheaper to copy it with [`Buf::copy_to_bytes`] and parse it.
+    pub fn parse_buf<B: Buf>(buf: &mut B) -> Result<TransactionOwned, Error> {
+        match Transaction::parse(buf.chunk()) {
+            Ok(tx) => {
+                let consumed = tx.consumed();
+                let owned = tx.parsed().into();
+                buf.advance(consumed);
+                return Ok(owned);
+            }
+            Err(Error::Needed(_)) if buf.chunk().len() < buf.remaining() => (),
+            Err(e) => return Err(e),
+        }
+        let mut data = Vec::new();
+        loop {
+            let needed = match Transaction::parse(&data[..]) {
+                Ok(tx) => return Ok(tx.parsed().into()),
+                Err(Error::Needed(needed)) => needed as usize,
+                Err(e) => return Err(e),
+            };
+            if buf.remaining() < needed {
+                // fits since `needed` is a u32
+                return Err(Error::Needed((needed - buf.remaining()) as u32));
+            }
+            let start = data.len();
+            data.resize(start + needed, 0);
+            buf.copy_to_slice(&mut data[start..]);
+        }
+    }
+}
+
+impl<'a> BlockHeader<'a> {
+    /// Parse a block header from the possibly non-contiguous `buf`, advancing it past the header.
+    ///
+    /// If `buf` contains less than 80 bytes it's not advanced and [`Error::Needed`] is returned.
+    pub fn parse_buf<B: Buf>(buf: &mut B) -> Result<BlockHeaderOwned, Error> {
+        let mut data = [0u8; 80];
+        if buf.remaining() < data.len() {
+            // lower than 80
+            return Err(Error::Needed((data.len() - buf.remaining()) as u32));
+        }
+        buf.copy_to_slice(&mut data);
+        Ok(BlockHeaderOwned::new(data))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bytes::{Buf, Bytes};
+
+    use crate::{
+        bsl::{BlockHeader, Transaction, TransactionOwned},
+        test_common::{GENESIS_BLOCK, GENESIS_BLOCK_HEADER, LEGACY_TX, SEGWIT_TX},
+        Error, Parse,
+    };
+
+    #[test]
+    fn transaction_parse_buf() {
+        let expected: TransactionOwned = Transaction::parse_all(&SEGWIT_TX[..]).unwrap().into();
+        let data = [&SEGWIT_TX[..], &LEGACY_TX[..]].concat();
+        for i in 0..=SEGWIT_TX.len() {
+            let (first, second) = data.split_at(i);
+            let mut buf = Bytes::copy_from_slice(first).chain(Bytes::copy_from_slice(second));
+            assert_eq!(Transaction::parse_buf(&mut buf), Ok(expected.clone()));
+            assert_eq!(buf.remaining(), LEGACY_TX.len());
+            assert_eq!(buf.chunk(), &data[SEGWIT_TX.len().max(i)..]);
+        }
+
+        // three chunks
+        let (first, rest) = SEGWIT_TX.split_at(50);
+        let (second, third) = rest.split_at(100);
+        let mut buf = first.chain(second).chain(third);
+        assert_eq!(Transaction::parse_buf(&mut buf), Ok(expected));
+        assert!(!buf.has_remaining());
+
+        let truncated = &LEGACY_TX[..LEGACY_TX.len() - 1];
+        let mut buf = truncated;
+        assert_eq!(Transaction::parse_buf(&mut buf), Err(Error::Needed(1)));
+        // the first chunk is not advanced on errors
+        assert_eq!(buf, truncated);
+        let mut buf = truncated[..10].chain(&truncated[10..]);
+        assert_eq!(Transaction::parse_buf(&mut buf), Err(Error::Needed(1)));
+
+        let mut invalid = SEGWIT_TX;
+        invalid[5] = 2;
+        let mut buf = invalid[..10].chain(&invalid[10..]);
+        assert_eq!(
+            Transaction::parse_buf(&mut buf),
+            Err(Error::UnknownSegwitFlag(2))
+        );
+    }
+
+    #[test]
+    fn block_header_parse_buf() {
+        for i in 0..=80 {
+            let (first, second) = GENESIS_BLOCK.split_at(i);
+            let mut buf = first.chain(second);
+            let header = BlockHeader::parse_buf(&mut buf).unwrap();
+            assert_eq!(header.as_ref(), &GENESIS_BLOCK_HEADER[..]);
+            assert_eq!(buf.remaining(), GENESIS_BLOCK.len() - 80);
+        }
+        let mut buf = &GENESIS_BLOCK_HEADER[..79];
+        assert_eq!(BlockHeader::parse_buf(&mut buf), Err(Error::Needed(1)));
+        assert_eq!(buf.remaining(), 79);
+    }
+}
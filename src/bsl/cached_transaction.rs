@@ -0,0 +1,107 @@
+use core::cell::OnceCell;
+
+use crate::{bitcoin_hashes::sha256d, bsl::Transaction};
+
+/// A [`Transaction`] which computes its txid and wtxid at most once.
+///
+/// Useful when the identifiers are needed many times, for example as keys of several indexes, the
+/// first call of [`CachedTransaction::txid()`] hashes the transaction while the following ones
+/// return the cached value.
+#[derive(Debug, Clone)]
+pub struct CachedTransaction<'a> {
+    tx: Transaction<'a>,
+    txid: OnceCell<sha256d::Hash>,
+    wtxid: OnceCell<sha256d::Hash>,
+}
+
+impl<'a> CachedTransaction<'a> {
+    /// Wraps `tx`, no hash is computed until requested.
+    pub fn new(tx: Transaction<'a>) -> Self {
+        CachedTransaction {
+            tx,
+            txid: OnceCell::new(),
+            wtxid: OnceCell::new(),
+        }
+    }
+
+    /// Returns the wrapped transaction.
+    pub fn transaction(&self) -> &Transaction<'a> {
+        &self.tx
+    }
+
+    /// Returns the wrapped transaction, dropping the cached hashes.
+    pub fn into_inner(self) -> Transaction<'a> {
+        self.tx
+    }
+
+    /// Returns the transaction identifier, see [`Transaction::txid()`].
+    pub fn txid(&self) -> sha256d::Hash {
+        *self.txid.get_or_init(|| self.tx.txid())
+    }
+
+    /// Returns the witness transaction identifier, see [`Transaction::wtxid()`].
+    ///
+    /// For legacy transactions it's the txid, which is cached also for [`CachedTransaction::txid()`].
+    pub fn wtxid(&self) -> sha256d::Hash {
+        if self.tx.has_witness() {
+            *self.wtxid.get_or_init(|| self.tx.wtxid())
+        } else {
+            self.txid()
+        }
+    }
+}
+
+impl<'a> From<Transaction<'a>> for CachedTransaction<'a> {
+    fn from(tx: Transaction<'a>) -> Self {
+        CachedTransaction::new(tx)
+    }
+}
+
+impl<'a> AsRef<[u8]> for CachedTransaction<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.tx.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{CachedTransaction, Transaction},
+        test_common::{GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+        Parse,
+    };
+
+    #[test]
+    fn cached_transaction() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let cached = CachedTransaction::new(tx.clone());
+            assert!(cached.txid.get().is_none());
+            assert_eq!(cached.txid(), tx.txid());
+            assert_eq!(cached.txid.get(), Some(&tx.txid()));
+            assert_eq!(cached.txid(), tx.txid());
+            assert_eq!(cached.wtxid(), tx.wtxid());
+            assert_eq!(cached.wtxid(), tx.wtxid());
+            assert_eq!(cached.wtxid.get().is_some(), tx.has_witness());
+            assert_eq!(cached.as_ref(), tx_bytes);
+            assert_eq!(cached.into_inner(), tx);
+        }
+    }
+}
+
+#[cfg(bench)]
+mod bench {
+    use crate::bsl::{CachedTransaction, Transaction};
+    use crate::test_common::LEGACY_TX;
+    use crate::Parse;
+    use test::{black_box, Bencher};
+
+    #[bench]
+    pub fn txid_cached(bh: &mut Bencher) {
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let tx = CachedTransaction::new(tx);
+        bh.iter(|| {
+            black_box(&tx.txid());
+        });
+    }
+}
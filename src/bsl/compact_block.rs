@@ -0,0 +1,457 @@
+use super::len::{parse_len, Len};
+use crate::bsl::{BlockHeader, Transaction};
+use crate::number::U64;
+use crate::slice::read_slice;
+use crate::{Error, Parse, ParseResult, SResult, Visit, Visitor};
+
+/// The length of a BIP152 short transaction id.
+const SHORT_ID_LEN: usize = 6;
+
+/// A BIP152 compact block, sent in the `cmpctblock` message: a block header, the short ids of the
+/// transactions and some prefilled transactions, usually the coinbase.
+///
+/// The visitor is called for the header and the prefilled transactions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderAndShortIds<'a> {
+    slice: &'a [u8],
+    header: BlockHeader<'a>,
+    nonce: u64,
+    short_ids: &'a [u8],
+    prefilled: &'a [u8],
+    total_prefilled: usize,
+}
+
+impl<'a> Visit<'a> for HeaderAndShortIds<'a> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        let header = BlockHeader::visit(slice, visit)?;
+        let nonce = U64::parse(header.remaining())?;
+        let Len { consumed, n } = parse_len(nonce.remaining())?;
+        let short_ids_len = usize::try_from(n)
+            .ok()
+            .and_then(|n| n.checked_mul(SHORT_ID_LEN))
+            .ok_or(Error::TooLarge)?;
+        let short_ids = read_slice(&nonce.remaining()[consumed..], short_ids_len)?;
+
+        let prefilled_start = short_ids.remaining();
+        let Len {
+            consumed: mut prefilled_len,
+            n: total_prefilled,
+        } = parse_len(prefilled_start)?;
+        if n.saturating_add(total_prefilled) > u16::MAX as u64 {
+            return Err(Error::IndexOverflow);
+        }
+        let mut remaining = &prefilled_start[prefilled_len..];
+        let mut last_index = None;
+        for _ in 0..total_prefilled {
+            let diff = parse_len(remaining)?;
+            last_index = Some(next_index(last_index, diff.n())?);
+            let tx = Transaction::visit(&remaining[diff.consumed()..], visit)?;
+            remaining = tx.remaining();
+            prefilled_len += diff.consumed() + tx.consumed();
+        }
+
+        let consumed = slice.len() - remaining.len();
+        let parsed = HeaderAndShortIds {
+            slice: &slice[..consumed],
+            header: header.parsed_owned(),
+            nonce: nonce.parsed_owned().into(),
+            short_ids: short_ids.parsed_owned(),
+            prefilled: &prefilled_start[..prefilled_len],
+            total_prefilled: total_prefilled as usize,
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> HeaderAndShortIds<'a> {
+    /// Returns the header of the block.
+    pub fn header(&self) -> &BlockHeader<'a> {
+        &self.header
+    }
+
+    /// Returns the nonce used to compute the short ids.
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    /// Returns the number of short ids.
+    pub fn total_short_ids(&self) -> usize {
+        self.short_ids.len() / SHORT_ID_LEN
+    }
+
+    /// Returns the number of prefilled transactions.
+    pub fn total_prefilled(&self) -> usize {
+        self.total_prefilled
+    }
+
+    /// Returns the number of transactions in the block, short ids plus prefilled transactions.
+    pub fn total_transactions(&self) -> usize {
+        self.total_short_ids() + self.total_prefilled
+    }
+
+    /// Returns an iterator over the short ids, of the transactions not prefilled in block order.
+    pub fn short_ids(&self) -> impl ExactSizeIterator<Item = &'a [u8; 6]> {
+        self.short_ids
+            .chunks_exact(SHORT_ID_LEN)
+            .map(|id| id.try_into().expect("chunks of 6 bytes"))
+    }
+
+    /// Returns an iterator over the prefilled transactions and their absolute index in the block,
+    /// decoded from the differential encoding.
+    pub fn prefilled_transactions(&self) -> impl Iterator<Item = (u16, Transaction<'a>)> {
+        let Len { consumed, n } = parse_len(self.prefilled).expect("granted during parsing");
+        let mut remaining = &self.prefilled[consumed..];
+        let mut last_index = None;
+        (0..n).map(move |_| {
+            let diff = parse_len(remaining).expect("granted during parsing");
+            let index = next_index(last_index, diff.n()).expect("granted during parsing");
+            last_index = Some(index);
+            let tx =
+                Transaction::parse(&remaining[diff.consumed()..]).expect("granted during parsing");
+            remaining = tx.remaining();
+            (index, tx.parsed_owned())
+        })
+    }
+
+    /// Returns the SipHash-2-4 keys used to compute the short ids: the first two little-endian
+    /// 64 bits numbers of the sha256 of the header followed by the nonce.
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn siphash_keys(&self) -> (u64, u64) {
+        use crate::bitcoin_hashes::{sha256, Hash, HashEngine};
+        let mut engine = sha256::Hash::engine();
+        engine.input(self.header.as_ref());
+        engine.input(&self.nonce.to_le_bytes());
+        let hash = sha256::Hash::from_engine(engine).into_inner();
+        let k0 = u64::from_le_bytes(hash[..8].try_into().expect("8 bytes"));
+        let k1 = u64::from_le_bytes(hash[8..16].try_into().expect("8 bytes"));
+        (k0, k1)
+    }
+
+    /// Returns the short id of the transaction with the given `txid`, which is the wtxid for
+    /// compact blocks version 2, in byte order (not reversed).
+    ///
+    /// To compute many short ids prefer computing [`HeaderAndShortIds::siphash_keys()`] once and
+    /// then use [`short_id_with_keys()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn short_id(&self, txid: &[u8; 32]) -> [u8; 6] {
+        short_id_with_keys(txid, self.siphash_keys())
+    }
+}
+
+/// Returns the short id of the transaction with the given `txid` using the SipHash-2-4 `keys`
+/// returned by [`HeaderAndShortIds::siphash_keys()`]: the 6 least significant bytes of the hash.
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub fn short_id_with_keys(txid: &[u8; 32], keys: (u64, u64)) -> [u8; 6] {
+    use crate::bitcoin_hashes::siphash24;
+    let hash = siphash24::Hash::hash_with_keys(keys.0, keys.1, txid).as_u64();
+    let mut short_id = [0u8; 6];
+    short_id.copy_from_slice(&hash.to_le_bytes()[..6]);
+    short_id
+}
+
+impl<'a> AsRef<[u8]> for HeaderAndShortIds<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// The request of some transactions of a block, sent in the `getblocktxn` message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTransactionsRequest<'a> {
+    slice: &'a [u8],
+    total_indexes: usize,
+}
+
+impl<'a> Parse<'a> for BlockTransactionsRequest<'a> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        let block_hash = read_slice(slice, 32)?;
+        let Len { mut consumed, n } = parse_len(block_hash.remaining())?;
+        consumed += 32;
+        let mut last_index = None;
+        for _ in 0..n {
+            let diff = parse_len(&slice[consumed..])?;
+            last_index = Some(next_index(last_index, diff.n())?);
+            consumed += diff.consumed();
+        }
+        let (slice, remaining) = slice.split_at(consumed);
+        let parsed = BlockTransactionsRequest {
+            slice,
+            total_indexes: n as usize,
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> BlockTransactionsRequest<'a> {
+    /// Returns the hash of the block containing the requested transactions, in byte order (not
+    /// reversed).
+    pub fn block_hash(&self) -> &'a [u8; 32] {
+        self.slice[..32]
+            .try_into()
+            .expect("slice length granted during parsing")
+    }
+
+    /// Returns the number of requested transactions.
+    pub fn total_indexes(&self) -> usize {
+        self.total_indexes
+    }
+
+    /// Returns an iterator over the absolute indexes of the requested transactions, decoded from the
+    /// differential encoding.
+    pub fn indexes(&self) -> impl ExactSizeIterator<Item = u16> + 'a {
+        let Len { consumed, .. } = parse_len(&self.slice[32..]).expect("granted during parsing");
+        let mut remaining = &self.slice[32 + consumed..];
+        let mut last_index = None;
+        (0..self.total_indexes).map(move |_| {
+            let diff = parse_len(remaining).expect("granted during parsing");
+            remaining = &remaining[diff.consumed()..];
+            let index = next_index(last_index, diff.n()).expect("granted during parsing");
+            last_index = Some(index);
+            index
+        })
+    }
+}
+
+impl<'a> AsRef<[u8]> for BlockTransactionsRequest<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// Some transactions of a block, sent in the `blocktxn` message in response to a
+/// [`BlockTransactionsRequest`].
+///
+/// The visitor is called for every transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTransactions<'a> {
+    slice: &'a [u8],
+    total_txs: usize,
+}
+
+impl<'a> Visit<'a> for BlockTransactions<'a> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        let block_hash = read_slice(slice, 32)?;
+        let Len { mut consumed, n } = parse_len(block_hash.remaining())?;
+        consumed += 32;
+        let total_txs = n as usize;
+        let mut remaining = &slice[consumed..];
+        for _ in 0..total_txs {
+            let tx = Transaction::visit(remaining, visit)?;
+            remaining = tx.remaining();
+            consumed += tx.consumed();
+        }
+        let (slice, remaining) = slice.split_at(consumed);
+        let parsed = BlockTransactions { slice, total_txs };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> BlockTransactions<'a> {
+    /// Returns the hash of the block containing the transactions, in byte order (not reversed).
+    pub fn block_hash(&self) -> &'a [u8; 32] {
+        self.slice[..32]
+            .try_into()
+            .expect("slice length granted during parsing")
+    }
+
+    /// Returns the number of transactions.
+    pub fn total_transactions(&self) -> usize {
+        self.total_txs
+    }
+}
+
+impl<'a> AsRef<[u8]> for BlockTransactions<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// Decodes a differentially encoded index: the first is `diff`, the following are the previous
+/// plus one plus `diff`. Like Bitcoin Core, indexes must fit in 16 bits.
+fn next_index(last_index: Option<u16>, diff: u64) -> Result<u16, Error> {
+    let index = match last_index {
+        None => diff,
+        Some(last) => (last as u64 + 1)
+            .checked_add(diff)
+            .ok_or(Error::IndexOverflow)?,
+    };
+    u16::try_from(index).map_err(|_| Error::IndexOverflow)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::bsl::len::encode_len;
+    use crate::{
+        bsl::{BlockTransactions, BlockTransactionsRequest, HeaderAndShortIds, Transaction},
+        Error, Parse, Visit, Visitor,
+    };
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin::util::bip152;
+    use hex_lit::hex;
+
+    // from rust-bitcoin, tested with the Elements implementation of compact blocks
+    const RAW_BLOCK: [u8; 647] = hex!("000000206c750a364035aefd5f81508a08769975116d9195312ee4520dceac39e1fdc62c4dc67473b8e354358c1e610afeaff7410858bd45df43e2940f8a62bd3d5e3ac943c2975cffff7f200000000002020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff04016b0101ffffffff020006062a0100000001510000000000000000266a24aa21a9ed4a3d9f3343dafcc0d6f6d4310f2ee5ce273ed34edca6c75db3a73e7f368734200120000000000000000000000000000000000000000000000000000000000000000000000000020000000001021fc20ba2bd745507b8e00679e3b362558f9457db374ca28ffa5243f4c23a4d5f00000000171600147c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff915d689be87b43337f42e26033df59807b768223368f189a023d0242d837768900000000171600147c9dea14ffbcaec4b575e03f05ceb7a81cd3fcbffdffffff0200cdf5050000000017a9146803c72d9154a6a20f404bed6d3dcee07986235a8700e1f5050000000017a9144e6a4c7cb5b5562904843bdf816342f4db9f5797870247304402205e9bf6e70eb0e4b495bf483fd8e6e02da64900f290ef8aaa64bb32600d973c450220670896f5d0e5f33473e5f399ab680cc1d25c2d2afd15abd722f04978f28be887012103e4e4d9312b2261af508b367d8ba9be4f01b61d6d6e78bec499845b4f410bcf2702473044022045ac80596a6ac9c8c572f94708709adaf106677221122e08daf8b9741a04f66a022003ccd52a3b78f8fd08058fc04fc0cffa5f4c196c84eae9e37e2a85babe731b57012103e4e4d9312b2261af508b367d8ba9be4f01b61d6d6e78bec499845b4f410bcf276a000000");
+    const RAW_COMPACT: [u8; 245] = hex!("000000206c750a364035aefd5f81508a08769975116d9195312ee4520dceac39e1fdc62c4dc67473b8e354358c1e610afeaff7410858bd45df43e2940f8a62bd3d5e3ac943c2975cffff7f2000000000a4df3c3744da89fa010a6979e971450100020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff04016b0101ffffffff020006062a0100000001510000000000000000266a24aa21a9ed4a3d9f3343dafcc0d6f6d4310f2ee5ce273ed34edca6c75db3a73e7f368734200120000000000000000000000000000000000000000000000000000000000000000000000000");
+
+    #[test]
+    fn parse_header_and_short_ids() {
+        struct Count(usize, usize);
+        impl Visitor<'_> for Count {
+            fn visit_block_header(&mut self, _header: &crate::bsl::BlockHeader) {
+                self.0 += 1;
+            }
+            fn visit_transaction(&mut self, _tx: &Transaction) {
+                self.1 += 1;
+            }
+        }
+        let mut count = Count(0, 0);
+        let compact = HeaderAndShortIds::visit(&RAW_COMPACT, &mut count).unwrap();
+        assert_eq!(compact.remaining(), &[0u8; 0][..]);
+        assert_eq!((count.0, count.1), (1, 1));
+        let compact = compact.parsed_owned();
+        let compact_bitcoin: bip152::HeaderAndShortIds = deserialize(&RAW_COMPACT).unwrap();
+
+        assert_eq!(compact.header().as_ref(), &RAW_COMPACT[..80]);
+        assert_eq!(compact.nonce(), 18053200567810711460);
+        assert_eq!(compact.nonce(), compact_bitcoin.nonce);
+        assert_eq!(compact.total_short_ids(), 1);
+        assert_eq!(compact.total_prefilled(), 1);
+        assert_eq!(compact.total_transactions(), 2);
+        let short_ids: Vec<_> = compact.short_ids().collect();
+        assert_eq!(short_ids.len(), 1);
+        assert_eq!(&short_ids[0][..], &compact_bitcoin.short_ids[0][..]);
+        let prefilled: Vec<_> = compact.prefilled_transactions().collect();
+        assert_eq!(prefilled.len(), 1);
+        assert_eq!(prefilled[0].0, 0);
+        assert_eq!(
+            prefilled[0].1.as_ref(),
+            &serialize(&compact_bitcoin.prefilled_txs[0].tx)[..]
+        );
+
+        for i in 0..RAW_COMPACT.len() {
+            assert!(HeaderAndShortIds::parse(&RAW_COMPACT[..i]).is_err());
+        }
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn short_ids() {
+        use crate::bsl::short_id_with_keys;
+
+        let compact = HeaderAndShortIds::parse_all(&RAW_COMPACT).unwrap();
+        let block: bitcoin::Block = deserialize(&RAW_BLOCK).unwrap();
+        let keys = bip152::ShortId::calculate_siphash_keys(&block.header, compact.nonce());
+        assert_eq!(compact.siphash_keys(), keys);
+
+        let wtxid = block.txdata[1].wtxid();
+        let expected = bip152::ShortId::with_siphash_keys(&wtxid, keys);
+        let wtxid: [u8; 32] = wtxid[..].try_into().unwrap();
+        assert_eq!(&compact.short_id(&wtxid)[..], &expected[..]);
+        assert_eq!(&short_id_with_keys(&wtxid, keys)[..], &expected[..]);
+        assert_eq!(&compact.short_ids().next().unwrap()[..], &expected[..]);
+    }
+
+    #[test]
+    fn prefilled_differential_indexes() {
+        let coinbase = &RAW_COMPACT[80 + 8 + 1 + 6 + 1 + 1..];
+        let header_and_nonce = &RAW_COMPACT[..88];
+        let compact = |short_ids: u16, prefilled: &[&[u8]]| {
+            let mut bytes = header_and_nonce.to_vec();
+            let (len, used) = encode_len(short_ids as u64);
+            bytes.extend(&len[..used]);
+            bytes.resize(bytes.len() + short_ids as usize * 6, 0);
+            bytes.push(prefilled.len() as u8);
+            for diff in prefilled {
+                bytes.extend(*diff);
+                bytes.extend(coinbase);
+            }
+            bytes
+        };
+
+        let bytes = compact(10, &[&[0][..], &[0], &[3], &fd_u16(65520)]);
+        let parsed = HeaderAndShortIds::parse_all(&bytes).unwrap();
+        let indexes: Vec<_> = parsed.prefilled_transactions().map(|(i, _)| i).collect();
+        assert_eq!(indexes, vec![0, 1, 5, 65526]);
+
+        let bytes = compact(10, &[&[0][..], &fd_u16(65535)]);
+        assert_eq!(HeaderAndShortIds::parse(&bytes), Err(Error::IndexOverflow));
+        let bytes = compact(10, &[&[0][..], &hex!("ffffffffffffffffff")]);
+        assert_eq!(HeaderAndShortIds::parse(&bytes), Err(Error::IndexOverflow));
+
+        // more transactions than 16 bits indexes allow
+        let bytes = compact(65535, &[&[0]]);
+        assert_eq!(HeaderAndShortIds::parse(&bytes), Err(Error::IndexOverflow));
+        let bytes = compact(65534, &[&[0]]);
+        assert!(HeaderAndShortIds::parse_all(&bytes).is_ok());
+    }
+
+    fn fd_u16(n: u16) -> [u8; 3] {
+        let b = n.to_le_bytes();
+        [0xfd, b[0], b[1]]
+    }
+
+    #[test]
+    fn parse_block_transactions_request() {
+        // vectors from rust-bitcoin
+        for (encoded, expected) in [
+            (&[4u8, 0, 5, 1, 10][..], &[0u16, 6, 8, 19][..]),
+            (&[1, 0], &[0]),
+            (&[5, 0, 0, 0, 0, 0], &[0, 1, 2, 3, 4]),
+            (&[3, 1, 1, 1], &[1, 3, 5]),
+            (&[3, 0, 0, 253, 0, 1], &[0, 1, 258]),
+            (&[0], &[]),
+        ] {
+            let bytes = [&[7u8; 32][..], encoded].concat();
+            let request = BlockTransactionsRequest::parse_all(&bytes).unwrap();
+            assert_eq!(request.block_hash(), &[7u8; 32]);
+            assert_eq!(request.total_indexes(), expected.len());
+            let indexes: Vec<_> = request.indexes().collect();
+            assert_eq!(&indexes[..], expected);
+
+            let request_bitcoin: bip152::BlockTransactionsRequest = deserialize(&bytes).unwrap();
+            let expected: Vec<u64> = expected.iter().map(|i| *i as u64).collect();
+            assert_eq!(request_bitcoin.indexes, expected);
+        }
+
+        for encoded in [
+            &[2u8, 255, 254, 255, 255, 255, 255, 255, 255, 255, 0][..],
+            &[1, 255, 255, 255, 255, 255, 255, 255, 255, 255],
+            &[1, 0xfe, 0, 0, 1, 0],
+            &[2, 0xfd, 0xff, 0xff, 0],
+        ] {
+            let bytes = [&[0u8; 32][..], encoded].concat();
+            assert_eq!(
+                BlockTransactionsRequest::parse(&bytes),
+                Err(Error::IndexOverflow)
+            );
+        }
+        let bytes = [&[0u8; 32][..], &[1, 0xfd, 0xff, 0xff][..]].concat();
+        let request = BlockTransactionsRequest::parse_all(&bytes).unwrap();
+        assert_eq!(request.indexes().collect::<Vec<_>>(), vec![65535]);
+        assert_eq!(
+            BlockTransactionsRequest::parse(&bytes[..34]),
+            Err(Error::Needed(2))
+        );
+    }
+
+    #[test]
+    fn parse_block_transactions() {
+        let block: bitcoin::Block = deserialize(&RAW_BLOCK).unwrap();
+        let request = bip152::BlockTransactionsRequest {
+            block_hash: block.block_hash(),
+            indexes: vec![0, 1],
+        };
+        let block_txs = bip152::BlockTransactions::from_request(&request, &block).unwrap();
+        let bytes = serialize(&block_txs);
+
+        let parsed = BlockTransactions::parse_all(&bytes).unwrap();
+        assert_eq!(&parsed.block_hash()[..], &block.block_hash()[..]);
+        assert_eq!(parsed.total_transactions(), 2);
+        for i in 0..bytes.len() {
+            assert!(BlockTransactions::parse(&bytes[..i]).is_err());
+        }
+    }
+}
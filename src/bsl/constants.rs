@@ -0,0 +1,156 @@
+//! Well-known constants of the Bitcoin networks: the genesis blocks and the network magics.
+
+use crate::{bsl::Block, Parse};
+
+/// A Bitcoin network, used to select its constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Network {
+    /// The main network.
+    Mainnet,
+    /// The third test network.
+    Testnet3,
+    /// The fourth test network, defined in BIP94.
+    Testnet4,
+    /// The default signet, defined in BIP325.
+    Signet,
+    /// The regression test network.
+    Regtest,
+}
+
+impl Network {
+    /// Returns the magic bytes prefixing the P2P messages and the block file records of this
+    /// network.
+    pub fn magic(self) -> [u8; 4] {
+        match self {
+            Network::Mainnet => MAINNET_MAGIC,
+            Network::Testnet3 => TESTNET3_MAGIC,
+            Network::Testnet4 => TESTNET4_MAGIC,
+            Network::Signet => SIGNET_MAGIC,
+            Network::Regtest => REGTEST_MAGIC,
+        }
+    }
+
+    /// Returns the serialized genesis block of this network.
+    pub fn genesis_block_bytes(self) -> &'static [u8] {
+        match self {
+            Network::Mainnet => MAINNET_GENESIS_BLOCK,
+            Network::Testnet3 => TESTNET3_GENESIS_BLOCK,
+            Network::Testnet4 => TESTNET4_GENESIS_BLOCK,
+            Network::Signet => SIGNET_GENESIS_BLOCK,
+            Network::Regtest => REGTEST_GENESIS_BLOCK,
+        }
+    }
+}
+
+/// The network magic of mainnet.
+pub const MAINNET_MAGIC: [u8; 4] = [0xf9, 0xbe, 0xb4, 0xd9];
+/// The network magic of testnet3.
+pub const TESTNET3_MAGIC: [u8; 4] = [0x0b, 0x11, 0x09, 0x07];
+/// The network magic of testnet4.
+pub const TESTNET4_MAGIC: [u8; 4] = [0x1c, 0x16, 0x3f, 0x28];
+/// The network magic of the default signet.
+pub const SIGNET_MAGIC: [u8; 4] = [0x0a, 0x03, 0xcf, 0x40];
+/// The network magic of regtest.
+pub const REGTEST_MAGIC: [u8; 4] = [0xfa, 0xbf, 0xb5, 0xda];
+
+/// The serialized mainnet genesis block.
+pub const MAINNET_GENESIS_BLOCK: &[u8] = &decode_hex::<285>("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
+/// The serialized testnet3 genesis block, sharing the coinbase of mainnet.
+pub const TESTNET3_GENESIS_BLOCK: &[u8] = &decode_hex::<285>("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4adae5494dffff001d1aa4ae180101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
+/// The serialized testnet4 genesis block.
+pub const TESTNET4_GENESIS_BLOCK: &[u8] = &decode_hex::<261>("0100000000000000000000000000000000000000000000000000000000000000000000004e7b2b9128fe0291db0693af2ae418b767e657cd407e80cb1434221eaea7a07a046f3566ffff001dbb0c78170101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff5504ffff001d01044c4c30332f4d61792f323032342030303030303030303030303030303030303030303165626435386332343439373062336161396437383362623030313031316662653865613865393865303065ffffffff0100f2052a010000002321000000000000000000000000000000000000000000000000000000000000000000ac00000000");
+/// The serialized genesis block of the default signet, sharing the coinbase of mainnet.
+pub const SIGNET_GENESIS_BLOCK: &[u8] = &decode_hex::<285>("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a008f4d5fae77031e8ad222030101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
+/// The serialized regtest genesis block, sharing the coinbase of mainnet.
+pub const REGTEST_GENESIS_BLOCK: &[u8] = &decode_hex::<285>("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4adae5494dffff7f20020000000101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
+
+/// Returns the parsed genesis block of the given `network`.
+pub fn genesis_block(network: Network) -> Block<'static> {
+    Block::parse_all(network.genesis_block_bytes()).expect("genesis blocks are valid")
+}
+
+/// Decodes `hex` at compile time, a wrong length or an invalid character fails the build.
+const fn decode_hex<const N: usize>(hex: &str) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex character"),
+        }
+    }
+    let hex = hex.as_bytes();
+    assert!(hex.len() == N * 2, "invalid hex length");
+    let mut result = [0u8; N];
+    let mut i = 0;
+    while i < N {
+        result[i] = nibble(hex[2 * i]) << 4 | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::constants::{genesis_block, Network, MAINNET_GENESIS_BLOCK},
+        test_common::{reverse, GENESIS_BLOCK},
+    };
+    use bitcoin::{
+        blockdata::constants::genesis_block as genesis_block_bitcoin,
+        consensus::serialize,
+        hashes::{sha256d, Hash},
+    };
+    use hex_lit::hex;
+
+    #[test]
+    fn genesis_blocks() {
+        assert_eq!(MAINNET_GENESIS_BLOCK, &GENESIS_BLOCK[..]);
+        let expected = [
+            (
+                Network::Mainnet,
+                hex!("000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f"),
+            ),
+            (
+                Network::Testnet3,
+                hex!("000000000933ea01ad0ee984209779baaec3ced90fa3f408719526f8d77f4943"),
+            ),
+            (
+                Network::Testnet4,
+                hex!("00000000da84f2bafbbc53dee25a72ae507ff4914b867c565be350b0da8bf043"),
+            ),
+            (
+                Network::Signet,
+                hex!("00000008819873e925422c1ff0f99f7cc9bbb232af63a077a480a3633bee1ef6"),
+            ),
+            (
+                Network::Regtest,
+                hex!("0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"),
+            ),
+        ];
+        for (network, hash) in expected {
+            let block = genesis_block(network);
+            assert_eq!(block.as_ref(), network.genesis_block_bytes());
+            let header_hash = sha256d::Hash::hash(block.header().as_ref());
+            assert_eq!(header_hash.into_inner(), reverse(hash));
+            assert_eq!(block.total_transactions(), 1);
+            let txid = sha256d::Hash::hash(&block.as_ref()[81..]);
+            assert_eq!(&txid.into_inner(), block.header().merkle_root());
+        }
+    }
+
+    #[test]
+    fn rust_bitcoin() {
+        for (network, network_bitcoin) in [
+            (Network::Mainnet, bitcoin::Network::Bitcoin),
+            (Network::Testnet3, bitcoin::Network::Testnet),
+            (Network::Signet, bitcoin::Network::Signet),
+            (Network::Regtest, bitcoin::Network::Regtest),
+        ] {
+            assert_eq!(
+                network.genesis_block_bytes(),
+                &serialize(&genesis_block_bitcoin(network_bitcoin))[..]
+            );
+            assert_eq!(network.magic(), network_bitcoin.magic().to_le_bytes());
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::bsl::len::encode_len;
+use crate::bsl::{
+    Block, BlockFilter, BlockHeader, BlockHeaderOwned, BlockTransactions, BlockTransactionsRequest,
+    BlockUndo, FilterHeaders, HeaderAndShortIds, MerkleBlock, OutPoint, OutPointOwned, Script,
+    SpentCoin, Transaction, TxIn, TxIns, TxOut, TxOuts, TxUndo, Witness, Witnesses,
+};
+use crate::Error;
+
+/// Write a parsed object back in its consensus serialization, which is the slice it has been
+/// parsed from, so encoding is a copy.
+pub trait Encode: AsRef<[u8]> {
+    /// The number of bytes written by [`Encode::encode_into`].
+    fn encoded_len(&self) -> usize {
+        self.as_ref().len()
+    }
+
+    /// Write the serialization at the start of `out`, returning the number of bytes written.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if `out` is shorter than [`Encode::encoded_len`], in
+    /// which case `out` is untouched.
+    fn encode_into(&self, out: &mut [u8]) -> Result<usize, Error> {
+        write_into(self.as_ref(), out)
+    }
+
+    /// Write the serialization to `w`, returning the number of bytes written.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    fn encode_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> std::io::Result<usize> {
+        let bytes = self.as_ref();
+        w.write_all(bytes)?;
+        Ok(bytes.len())
+    }
+}
+
+/// Copy `bytes` at the start of `out`.
+fn write_into(bytes: &[u8], out: &mut [u8]) -> Result<usize, Error> {
+    match out.get_mut(..bytes.len()) {
+        Some(out) => {
+            out.copy_from_slice(bytes);
+            Ok(bytes.len())
+        }
+        None => Err(Error::BufferTooSmall(
+            bytes.len().try_into().unwrap_or(u32::MAX),
+        )),
+    }
+}
+
+macro_rules! impl_encode {
+    ($($ty:ty),*) => {
+        $(impl<'a> Encode for $ty {})*
+    };
+}
+
+impl_encode!(
+    Block<'a>,
+    BlockFilter<'a>,
+    BlockHeader<'a>,
+    BlockTransactions<'a>,
+    BlockTransactionsRequest<'a>,
+    BlockUndo<'a>,
+    FilterHeaders<'a>,
+    HeaderAndShortIds<'a>,
+    MerkleBlock<'a>,
+    OutPoint<'a>,
+    Script<'a>,
+    SpentCoin<'a>,
+    Transaction<'a>,
+    TxIn<'a>,
+    TxIns<'a>,
+    TxOut<'a>,
+    TxOuts<'a>,
+    TxUndo<'a>,
+    Witness<'a>,
+    Witnesses<'a>
+);
+
+impl Encode for BlockHeaderOwned {}
+
+impl Encode for OutPointOwned {}
+
+#[cfg(feature = "alloc")]
+impl Encode for crate::bsl::BlockOwned {}
+
+#[cfg(feature = "alloc")]
+impl Encode for crate::bsl::TransactionOwned {}
+
+#[cfg(feature = "alloc")]
+impl<'a> Encode for crate::bsl::IndexedBlock<'a> {}
+
+#[cfg(feature = "bitcoin_hashes")]
+impl<'a> Encode for crate::bsl::CachedTransaction<'a> {}
+
+impl<'a> Block<'a> {
+    /// The number of bytes written by [`Block::encode_with_transactions_into`].
+    pub fn encoded_len_with_transactions<T: Encode>(&self, transactions: &[T]) -> usize {
+        let (_, len_used) = encode_len(transactions.len() as u64);
+        let transactions: usize = transactions.iter().map(Encode::encoded_len).sum();
+        self.header().encoded_len() + len_used + transactions
+    }
+
+    /// Write a block with the header of this block and the given `transactions` in place of its
+    /// own, for example a subset of them, returning the number of bytes written.
+    ///
+    /// The transaction count is encoded for `transactions`, nothing else changes: the merkle root
+    /// in the header is not updated.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if `out` is shorter than
+    /// [`Block::encoded_len_with_transactions`], in which case `out` is untouched.
+    pub fn encode_with_transactions_into<T: Encode>(
+        &self,
+        transactions: &[T],
+        out: &mut [u8],
+    ) -> Result<usize, Error> {
+        let total = self.encoded_len_with_transactions(transactions);
+        if out.len() < total {
+            return Err(Error::BufferTooSmall(total.try_into().unwrap_or(u32::MAX)));
+        }
+        let mut written = self.header().encode_into(out)?;
+        let (len, len_used) = encode_len(transactions.len() as u64);
+        written += write_into(&len[..len_used], &mut out[written..])?;
+        for tx in transactions {
+            written += tx.encode_into(&mut out[written..])?;
+        }
+        Ok(written)
+    }
+
+    /// Write a block with the header of this block and the given `transactions` to `w`, see
+    /// [`Block::encode_with_transactions_into`].
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn encode_with_transactions_to<T: Encode, W: std::io::Write + ?Sized>(
+        &self,
+        transactions: &[T],
+        w: &mut W,
+    ) -> std::io::Result<usize> {
+        let mut written = self.header().encode_to(w)?;
+        let (len, len_used) = encode_len(transactions.len() as u64);
+        w.write_all(&len[..len_used])?;
+        written += len_used;
+        for tx in transactions {
+            written += tx.encode_to(w)?;
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::ops::Range;
+
+    use crate::{
+        bsl::{Block, Encode, Transaction},
+        test_common::{GENESIS_BLOCK, SEGWIT_TX},
+        Error, Parse, Visit, Visitor,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn encode_into() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.encoded_len(), SEGWIT_TX.len());
+        let mut out = [0u8; 300];
+        assert_eq!(tx.encode_into(&mut out), Ok(SEGWIT_TX.len()));
+        assert_eq!(&out[..SEGWIT_TX.len()], &SEGWIT_TX[..]);
+        let mut out = [0u8; 200];
+        assert_eq!(tx.encode_into(&mut out), Err(Error::BufferTooSmall(222)));
+        assert_eq!(out, [0u8; 200]);
+    }
+
+    #[test]
+    fn encode_with_transactions() {
+        struct Ranges(Vec<Range<usize>>);
+        impl Visitor<'_> for Ranges {
+            fn visit_transaction_at(&mut self, offset: usize, tx: &Transaction) {
+                self.0.push(offset..offset + tx.as_ref().len());
+            }
+        }
+
+        let slice = mainnet_702861();
+        let mut ranges = Ranges(vec![]);
+        let block = Block::visit(slice, &mut ranges).unwrap().parsed_owned();
+        let all: Vec<_> = ranges
+            .0
+            .iter()
+            .map(|r| {
+                Transaction::parse(&slice[r.clone()])
+                    .unwrap()
+                    .parsed_owned()
+            })
+            .collect();
+
+        // the count encoding shrinks from 3 bytes to 1, or stays 3
+        for subset in [
+            vec![],
+            all[..1].to_vec(),
+            all.iter().step_by(7).cloned().collect(),
+            all.clone(),
+        ] {
+            let len = block.encoded_len_with_transactions(&subset);
+            let mut out = vec![0u8; len];
+            assert_eq!(
+                block.encode_with_transactions_into(&subset, &mut out),
+                Ok(len)
+            );
+            #[cfg(feature = "std")]
+            {
+                let mut written = vec![];
+                assert_eq!(
+                    block
+                        .encode_with_transactions_to(&subset, &mut written)
+                        .unwrap(),
+                    len
+                );
+                assert_eq!(written, out);
+            }
+
+            let mut reparsed = Ranges(vec![]);
+            let parsed = Block::visit(&out, &mut reparsed).unwrap();
+            assert!(parsed.remaining().is_empty());
+            assert_eq!(parsed.parsed().header(), block.header());
+            assert_eq!(parsed.parsed().total_transactions(), subset.len());
+            for (range, tx) in reparsed.0.iter().zip(subset.iter()) {
+                assert_eq!(&out[range.clone()], tx.as_ref());
+            }
+        }
+        assert_eq!(
+            block.encoded_len_with_transactions(&all),
+            block.encoded_len()
+        );
+
+        let block = Block::parse(&GENESIS_BLOCK[..]).unwrap().parsed_owned();
+        let coinbase = [block.coinbase().unwrap()];
+        let mut out = [0u8; 285];
+        assert_eq!(
+            block.encode_with_transactions_into(&coinbase, &mut out[..284]),
+            Err(Error::BufferTooSmall(285))
+        );
+        assert_eq!(out, [0u8; 285]);
+        assert_eq!(
+            block.encode_with_transactions_into(&coinbase, &mut out),
+            Ok(285)
+        );
+        assert_eq!(out, GENESIS_BLOCK);
+    }
+}
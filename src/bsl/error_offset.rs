@@ -0,0 +1,313 @@
+use crate::{
+    bsl::{Block, LenKind, Transaction, TxIn, TxOut},
+    ElementKind, Error, OffsetError, Parse, SResult, Visit, Visitor,
+};
+
+/// Follows the visits of the parser keeping the offset where the element being parsed starts,
+/// so that when the parser fails it's the offset of the element failing and the indexes of the
+/// transaction and of the element containing it.
+///
+/// It runs only after the parsing failed, so the common path doesn't pay for the tracking.
+struct OffsetTracker<'a> {
+    slice: &'a [u8],
+    offset: usize,
+    tx_start: usize,
+    transaction: Option<u32>,
+    total_transactions: usize,
+    element: Option<(ElementKind, u32)>,
+    total_elements: usize,
+}
+
+impl<'a> OffsetTracker<'a> {
+    fn position(&self, bytes: &[u8]) -> usize {
+        bytes.as_ptr() as usize - self.slice.as_ptr() as usize
+    }
+
+    /// The element after the `i`-th of `kind`, if there is one.
+    fn next_element(&self, kind: ElementKind, i: usize) -> Option<(ElementKind, u32)> {
+        (i + 1 < self.total_elements).then_some((kind, i as u32 + 1))
+    }
+}
+
+impl<'a> Visitor<'a> for OffsetTracker<'a> {
+    fn visit_len(&mut self, kind: LenKind, _n: u64, encoded_len: usize) {
+        match kind {
+            LenKind::BlockTxCount => {
+                self.offset = 80 + encoded_len;
+                self.tx_start = self.offset;
+            }
+            LenKind::TxInCount => {
+                // The parser got to the inputs count, so with the default config a zero after the
+                // version is the segwit marker followed by a valid flag.
+                let marker = self.slice.get(self.tx_start + 4) == Some(&0);
+                self.offset = self.tx_start + 4 + 2 * marker as usize + encoded_len;
+            }
+            LenKind::TxOutCount | LenKind::WitnessElementCount | LenKind::WitnessElementLen => {
+                self.offset += encoded_len
+            }
+            // visited after the input or the output containing the script
+            LenKind::ScriptSigLen | LenKind::ScriptPubKeyLen => {}
+        }
+    }
+    fn visit_block_begin(&mut self, total_transactions: usize) {
+        self.total_transactions = total_transactions;
+        self.transaction = (total_transactions > 0).then_some(0);
+    }
+    fn visit_transaction_at(&mut self, offset: usize, tx: &Transaction<'a>) {
+        self.offset = offset + tx.as_ref().len();
+        self.tx_start = self.offset;
+        let next = self.transaction.map_or(0, |i| i as usize + 1);
+        self.transaction = (next < self.total_transactions).then_some(next as u32);
+    }
+    fn visit_tx_ins(&mut self, total_inputs: usize) {
+        self.total_elements = total_inputs;
+        self.element = (total_inputs > 0).then_some((ElementKind::Input, 0));
+    }
+    fn visit_tx_in(&mut self, vin: usize, tx_in: &TxIn<'a>) {
+        self.offset = self.position(tx_in.as_ref()) + tx_in.as_ref().len();
+        self.element = self.next_element(ElementKind::Input, vin);
+    }
+    fn visit_tx_outs(&mut self, total_outputs: usize) {
+        self.total_elements = total_outputs;
+        self.element = (total_outputs > 0).then_some((ElementKind::Output, 0));
+    }
+    fn visit_tx_out(&mut self, vout: usize, tx_out: &TxOut<'a>) {
+        self.offset = self.position(tx_out.as_ref()) + tx_out.as_ref().len();
+        self.element = self.next_element(ElementKind::Output, vout);
+    }
+    fn visit_witness(&mut self, vin: usize) {
+        self.element = Some((ElementKind::Witness, vin as u32));
+    }
+    fn visit_witness_element(&mut self, _i: usize, witness_element: &'a [u8]) {
+        self.offset = self.position(witness_element) + witness_element.len();
+    }
+    fn visit_witness_end(&mut self) {
+        self.element = None;
+    }
+}
+
+/// Returns the offset of the element failing to parse with `error`, visiting `slice` again with
+/// `visit`, the same parser which returned `result`.
+fn with_offset<'a, T: AsRef<[u8]>>(
+    slice: &'a [u8],
+    result: SResult<'a, T>,
+    visit: fn(&'a [u8], &mut OffsetTracker<'a>) -> SResult<'a, T>,
+) -> Result<crate::ParseResult<'a, T>, OffsetError> {
+    result.map_err(|error| {
+        let mut tracker = OffsetTracker {
+            slice,
+            offset: 0,
+            tx_start: 0,
+            transaction: None,
+            total_transactions: 0,
+            element: None,
+            total_elements: 0,
+        };
+        let _ = visit(slice, &mut tracker);
+        match error {
+            // the error is about the whole transaction, not about the element after the last one
+            // parsed
+            Error::SegwitFlagWithoutWitnesses => OffsetError {
+                offset: tracker.tx_start,
+                error,
+                transaction: tracker.transaction,
+                element: None,
+            },
+            _ => OffsetError {
+                offset: tracker.offset,
+                error,
+                transaction: tracker.transaction,
+                element: tracker.element,
+            },
+        }
+    })
+}
+
+impl<'a> Transaction<'a> {
+    /// Parse a transaction like [`Parse::parse`], on error returns also the offset from the start
+    /// of `slice` of the element which failed, like an input or a compact int, and the input,
+    /// output or witness containing it.
+    pub fn parse_with_offset(slice: &'a [u8]) -> Result<crate::ParseResult<'a, Self>, OffsetError> {
+        with_offset(slice, Transaction::parse(slice), Transaction::visit)
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Parse a block like [`Parse::parse`], on error returns also the offset from the start of
+    /// `slice` of the element which failed, like an input or a compact int, and the transaction
+    /// and its input, output or witness containing it.
+    pub fn parse_with_offset(slice: &'a [u8]) -> Result<crate::ParseResult<'a, Self>, OffsetError> {
+        with_offset(slice, Block::parse(slice), Block::visit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    use crate::{
+        bsl::{Block, Transaction},
+        test_common::{GENESIS_BLOCK, LEGACY_TX, SEGWIT_TX},
+        ElementKind, Error, OffsetError, Parse,
+    };
+
+    fn error(offset: usize, error: Error, element: Option<(ElementKind, u32)>) -> OffsetError {
+        OffsetError {
+            offset,
+            error,
+            transaction: None,
+            element,
+        }
+    }
+
+    #[test]
+    fn transaction_error_offset() {
+        assert_eq!(
+            Transaction::parse_with_offset(&SEGWIT_TX[..]),
+            Transaction::parse(&SEGWIT_TX[..]).map_err(|_| unreachable!())
+        );
+
+        let cases = [
+            // segwit flag, reported at the transaction start
+            (5, 2, 0, Error::UnknownSegwitFlag(2), None),
+            // first output script pubkey length, longer than the transaction, reported at the
+            // output start
+            (
+                111,
+                0xfc,
+                103,
+                Error::Needed(142),
+                Some((ElementKind::Output, 0)),
+            ),
+            // witness element count of the only input
+            (184, 0, 0, Error::SegwitFlagWithoutWitnesses, None),
+            // witness element length, reading 0 from the first 2 zero bytes of the element
+            (
+                185,
+                0xfd,
+                185,
+                Error::NonMinimalVarInt,
+                Some((ElementKind::Witness, 0)),
+            ),
+        ];
+        for (at, value, offset, e, element) in cases {
+            let mut bytes = SEGWIT_TX;
+            bytes[at] = value;
+            let expected = error(offset, e, element);
+            assert_eq!(Transaction::parse_with_offset(&bytes), Err(expected));
+        }
+
+        // truncated in the locktime
+        assert_eq!(
+            Transaction::parse_with_offset(&SEGWIT_TX[..220]),
+            Err(error(218, Error::Needed(2), None))
+        );
+    }
+
+    /// A legacy transaction with 4 inputs and 3 outputs, copies of the ones of [`LEGACY_TX`].
+    fn many_inputs_outputs() -> Vec<u8> {
+        let (input, output) = (&LEGACY_TX[5..154], &LEGACY_TX[155..189]);
+        let mut tx = [&LEGACY_TX[..4], &[4]].concat();
+        tx.extend(input.repeat(4));
+        tx.push(3);
+        tx.extend(output.repeat(3));
+        tx.extend_from_slice(&LEGACY_TX[189..]);
+        tx
+    }
+
+    #[test]
+    fn transaction_error_context() {
+        let tx = many_inputs_outputs();
+        assert_eq!(Transaction::parse_all(&tx).unwrap().input_count(), 4);
+
+        // the script sig length of the last input, reading 0x3049 from the script
+        let last_input = 5 + 3 * 149;
+        let mut bytes = tx.clone();
+        bytes[last_input + 36] = 0xfd;
+        let e = Transaction::parse(&bytes).unwrap_err();
+        assert_eq!(
+            Transaction::parse_with_offset(&bytes),
+            Err(error(last_input, e, Some((ElementKind::Input, 3))))
+        );
+
+        // the script pubkey length of the last output
+        let last_output = 5 + 4 * 149 + 1 + 2 * 34;
+        let mut bytes = tx.clone();
+        bytes[last_output + 8] = 0xfe;
+        let e = Transaction::parse(&bytes).unwrap_err();
+        assert_eq!(
+            Transaction::parse_with_offset(&bytes),
+            Err(error(last_output, e, Some((ElementKind::Output, 2))))
+        );
+
+        // the outputs count, truncated
+        let outputs = 5 + 4 * 149;
+        assert_eq!(
+            Transaction::parse_with_offset(&tx[..outputs]),
+            Err(error(outputs, Error::Needed(1), None))
+        );
+
+        // in a block after the genesis transaction
+        let mut block = [&GENESIS_BLOCK[..80], &[2], &GENESIS_BLOCK[81..]].concat();
+        assert_eq!(Block::parse(&block).unwrap_err(), Error::Needed(4));
+        let tx_offset = block.len();
+        block.extend_from_slice(&bytes);
+        let e = Block::parse(&block).unwrap_err();
+        assert_eq!(
+            Block::parse_with_offset(&block),
+            Err(OffsetError {
+                offset: tx_offset + last_output,
+                error: e,
+                transaction: Some(1),
+                element: Some((ElementKind::Output, 2)),
+            })
+        );
+    }
+
+    #[test]
+    fn block_error_offset() {
+        let block = Block::parse_all(mainnet_702861()).unwrap();
+        assert!(block.coinbase().unwrap().has_witness());
+        // the coinbase starts after the header and the 3 bytes transactions count
+        let coinbase = 83;
+        let mut bytes = mainnet_702861().to_vec();
+        bytes[coinbase + 5] = 2;
+        assert_eq!(
+            Block::parse_with_offset(&bytes),
+            Err(OffsetError {
+                offset: coinbase,
+                error: Error::UnknownSegwitFlag(2),
+                transaction: Some(0),
+                element: None,
+            })
+        );
+
+        let second = coinbase + block.coinbase().unwrap().as_ref().len();
+        assert_eq!(
+            Block::parse_with_offset(&mainnet_702861()[..second + 2]),
+            Err(OffsetError {
+                offset: second,
+                error: Error::Needed(2),
+                transaction: Some(1),
+                element: None,
+            })
+        );
+
+        // the outpoint of the coinbase input
+        assert_eq!(
+            Block::parse_with_offset(&GENESIS_BLOCK[..100]),
+            Err(OffsetError {
+                offset: 86,
+                error: Error::Needed(22),
+                transaction: Some(0),
+                element: Some((ElementKind::Input, 0)),
+            })
+        );
+
+        // the header
+        assert_eq!(
+            Block::parse_with_offset(&GENESIS_BLOCK[..50]),
+            Err(error(0, Error::Needed(18), None))
+        );
+    }
+}
@@ -0,0 +1,620 @@
+use super::len::{parse_len, Len};
+use crate::bsl::TxidHasher;
+use crate::slice::read_slice;
+use crate::{Error, Parse, ParseResult, SResult};
+
+/// The Golomb-Rice coding parameter of the basic filter.
+const P: u8 = 19;
+
+/// The inverse of the false positive rate of the basic filter.
+#[cfg(feature = "bitcoin_hashes")]
+const M: u64 = 784931;
+
+/// A BIP158 basic block filter: the number of elements followed by the Golomb-Rice coded
+/// differences of their sorted hashes, as contained in the `cfilter` message.
+///
+/// Parsing decodes all the elements to find the end of the filter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockFilter<'a> {
+    slice: &'a [u8],
+    n: u32,
+    /// The position of the coded elements in `slice`.
+    data_start: usize,
+}
+
+impl<'a> Parse<'a> for BlockFilter<'a> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        let Len { consumed, n } = parse_len(slice)?;
+        let n = u32::try_from(n).map_err(|_| Error::TooLarge)?;
+        let mut values = Values::new(&slice[consumed..], n);
+        for value in &mut values {
+            value?;
+        }
+        let (slice, remaining) = slice.split_at(consumed + values.reader.consumed());
+        let parsed = BlockFilter {
+            slice,
+            n,
+            data_start: consumed,
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> BlockFilter<'a> {
+    /// Returns the number of elements in the filter.
+    pub fn element_count(&self) -> usize {
+        self.n as usize
+    }
+
+    /// Returns the sorted values of the elements, their hashes mapped to the range `[0, N * M)`.
+    #[cfg(feature = "bitcoin_hashes")]
+    fn values(&self) -> impl Iterator<Item = u64> + 'a {
+        Values::new(&self.slice[self.data_start..], self.n)
+            .map(|v| v.expect("granted during parsing"))
+    }
+
+    /// Returns whether any of the `queries`, usually scripts, may be in the filter of the block
+    /// with the given hash, in byte order (not reversed). Returns `false` if `queries` is empty.
+    ///
+    /// False positives happen with probability `1/M` for every query, while there are no false
+    /// negatives. Since the queries are not sorted, which would require allocation, the filter is
+    /// decoded once for every query.
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn match_any<'q>(
+        &self,
+        block_hash: &[u8; 32],
+        mut queries: impl Iterator<Item = &'q [u8]>,
+    ) -> bool {
+        let keys = siphash_keys(block_hash);
+        let range = self.n as u64 * M;
+        queries.any(|query| {
+            let target = map_to_range(keys, range, query);
+            self.values()
+                .find(|value| *value >= target)
+                .is_some_and(|value| value == target)
+        })
+    }
+}
+
+impl<'a> AsRef<[u8]> for BlockFilter<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// Builds the BIP158 basic filter of `block`, containing the scripts created by the outputs,
+/// excluding the ones starting with `OP_RETURN`, and the `spent_scripts` of the outputs spent by
+/// the inputs, for example from the block undo data. Empty scripts are excluded.
+///
+/// Returns the encoding of the filter, which can be parsed with [`BlockFilter`].
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "bitcoin_hashes"))))]
+pub fn build_basic_filter<'s>(
+    block: &'s crate::bsl::Block,
+    spent_scripts: impl IntoIterator<Item = &'s [u8]>,
+) -> alloc::vec::Vec<u8> {
+    use crate::bitcoin_hashes::{sha256d, Hash};
+    use crate::bsl::script::OP_RETURN;
+    use alloc::vec::Vec;
+
+    let mut offset = block.transactions_offset();
+    let mut elements: Vec<&'s [u8]> = Vec::new();
+    for _ in 0..block.total_transactions() {
+        let tx = block.transaction_at(offset);
+        offset += tx.as_ref().len();
+        let outputs = tx.outputs().map(|output| output.script_pubkey());
+        elements.extend(outputs.filter(|script| script.first() != Some(&OP_RETURN)));
+    }
+    elements.extend(spent_scripts);
+    elements.retain(|script| !script.is_empty());
+    elements.sort_unstable();
+    elements.dedup();
+
+    let keys = siphash_keys(&block.header().block_hash_with(sha256d::Hash::engine()));
+    let range = elements.len() as u64 * M;
+    let mut values: Vec<u64> = elements
+        .iter()
+        .map(|element| map_to_range(keys, range, element))
+        .collect();
+    values.sort_unstable();
+
+    let (len, used) = crate::bsl::len::encode_len(values.len() as u64);
+    let mut writer = BitWriter {
+        bytes: len[..used].to_vec(),
+        bits: 0,
+    };
+    let mut last = 0;
+    for value in values {
+        let delta = value - last;
+        last = value;
+        for _ in 0..delta >> P {
+            writer.write(1, 1);
+        }
+        writer.write(0, 1);
+        writer.write(delta, P);
+    }
+    writer.bytes
+}
+
+/// The maximum number of filter hashes in a `cfheaders` message.
+pub const MAX_FILTER_HEADERS: usize = 2000;
+
+/// Returns the BIP157 filter header of a filter, see [`filter_header_with()`].
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub fn filter_header(filter_hash: &[u8; 32], prev_header: &[u8; 32]) -> [u8; 32] {
+    use crate::bitcoin_hashes::{sha256d, Hash};
+    filter_header_with(filter_hash, prev_header, sha256d::Hash::engine())
+}
+
+/// Returns the BIP157 filter header of a filter given its hash and the filter header of the
+/// previous block, all zeros for the genesis block: the double SHA256 of their concatenation.
+///
+/// All the hashes are in byte order (not reversed).
+pub fn filter_header_with<H: TxidHasher>(
+    filter_hash: &[u8; 32],
+    prev_header: &[u8; 32],
+    mut hasher: H,
+) -> [u8; 32] {
+    hasher.update(filter_hash);
+    hasher.update(prev_header);
+    hasher.finalize_double()
+}
+
+/// The filter hashes of a range of blocks, sent in the BIP157 `cfheaders` message, from which the
+/// filter headers are computed starting from the previous filter header.
+///
+/// Parsing fails with [`Error::TooLarge`] if there are more than [`MAX_FILTER_HEADERS`] hashes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterHeaders<'a> {
+    slice: &'a [u8],
+    total_filter_hashes: usize,
+}
+
+impl<'a> Parse<'a> for FilterHeaders<'a> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        let fixed = read_slice(slice, 1 + 32 + 32)?;
+        let Len { consumed, n } = parse_len(fixed.remaining())?;
+        if n > MAX_FILTER_HEADERS as u64 {
+            return Err(Error::TooLarge);
+        }
+        let hashes = read_slice(&fixed.remaining()[consumed..], n as usize * 32)?;
+        let remaining = hashes.remaining();
+        let (slice, remaining) = slice.split_at(slice.len() - remaining.len());
+        let parsed = FilterHeaders {
+            slice,
+            total_filter_hashes: n as usize,
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> FilterHeaders<'a> {
+    /// Returns the filter type, `0` for the basic filter.
+    pub fn filter_type(&self) -> u8 {
+        self.slice[0]
+    }
+
+    /// Returns the hash of the last block of the range, in byte order (not reversed).
+    pub fn stop_hash(&self) -> &'a [u8; 32] {
+        self.slice[1..33]
+            .try_into()
+            .expect("slice length granted during parsing")
+    }
+
+    /// Returns the filter header of the block before the first of the range, in byte order (not
+    /// reversed).
+    pub fn previous_filter_header(&self) -> &'a [u8; 32] {
+        self.slice[33..65]
+            .try_into()
+            .expect("slice length granted during parsing")
+    }
+
+    /// Returns the number of filter hashes.
+    pub fn total_filter_hashes(&self) -> usize {
+        self.total_filter_hashes
+    }
+
+    /// Returns an iterator over the filter hashes, in byte order (not reversed).
+    pub fn filter_hashes(&self) -> impl ExactSizeIterator<Item = &'a [u8; 32]> {
+        let start = self.slice.len() - self.total_filter_hashes * 32;
+        self.slice[start..]
+            .chunks_exact(32)
+            .map(|hash| hash.try_into().expect("32 bytes"))
+    }
+
+    /// Returns the filter header of the last block of the range, see
+    /// [`FilterHeaders::last_filter_header_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn last_filter_header(&self) -> [u8; 32] {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.last_filter_header_with(sha256d::Hash::engine())
+    }
+
+    /// Returns the filter header of the last block of the range computing the chain of filter
+    /// headers from the previous one, every header is computed with a clone of `hasher`.
+    ///
+    /// Returns the previous filter header if there are no filter hashes.
+    pub fn last_filter_header_with<H: TxidHasher + Clone>(&self, hasher: H) -> [u8; 32] {
+        self.filter_hashes()
+            .fold(*self.previous_filter_header(), |prev, filter_hash| {
+                filter_header_with(filter_hash, &prev, hasher.clone())
+            })
+    }
+}
+
+impl<'a> AsRef<[u8]> for FilterHeaders<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// The SipHash keys of the filter of the block with the given hash, its first 16 bytes.
+#[cfg(feature = "bitcoin_hashes")]
+fn siphash_keys(block_hash: &[u8; 32]) -> (u64, u64) {
+    let k0 = u64::from_le_bytes(block_hash[..8].try_into().expect("8 bytes"));
+    let k1 = u64::from_le_bytes(block_hash[8..16].try_into().expect("8 bytes"));
+    (k0, k1)
+}
+
+/// Hashes `element` and maps it uniformly to `[0, range)`.
+#[cfg(feature = "bitcoin_hashes")]
+fn map_to_range(keys: (u64, u64), range: u64, element: &[u8]) -> u64 {
+    use crate::bitcoin_hashes::siphash24;
+    let hash = siphash24::Hash::hash_to_u64_with_keys(keys.0, keys.1, element);
+    ((hash as u128 * range as u128) >> 64) as u64
+}
+
+/// Iterator decoding the Golomb-Rice coded differences of a filter into the values.
+struct Values<'a> {
+    reader: BitReader<'a>,
+    remaining: u32,
+    last: u64,
+}
+
+impl<'a> Values<'a> {
+    fn new(slice: &'a [u8], n: u32) -> Self {
+        Values {
+            reader: BitReader { slice, bits: 0 },
+            remaining: n,
+            last: 0,
+        }
+    }
+
+    fn decode(&mut self) -> Result<u64, Error> {
+        let mut quotient = 0u64;
+        while self.reader.read(1)? == 1 {
+            quotient += 1;
+        }
+        if quotient > u64::MAX >> P {
+            return Err(Error::TooLarge);
+        }
+        let delta = (quotient << P) + self.reader.read(P)?;
+        self.last = self.last.checked_add(delta).ok_or(Error::TooLarge)?;
+        Ok(self.last)
+    }
+}
+
+impl<'a> Iterator for Values<'a> {
+    type Item = Result<u64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.decode())
+    }
+}
+
+/// Reads bits of a slice starting from the most significant bit of every byte.
+struct BitReader<'a> {
+    slice: &'a [u8],
+    /// The number of bits read.
+    bits: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Reads `n` bits, at most 64, as a big-endian number.
+    fn read(&mut self, n: u8) -> Result<u64, Error> {
+        let mut value = 0u64;
+        for _ in 0..n {
+            let byte = self.slice.get(self.bits / 8).ok_or(Error::Needed(1))?;
+            let bit = (byte >> (7 - self.bits % 8)) & 1;
+            value = (value << 1) | bit as u64;
+            self.bits += 1;
+        }
+        Ok(value)
+    }
+
+    /// The number of bytes containing the bits read.
+    fn consumed(&self) -> usize {
+        self.bits.div_ceil(8)
+    }
+}
+
+/// Writes bits starting from the most significant bit of every byte.
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+struct BitWriter {
+    bytes: alloc::vec::Vec<u8>,
+    /// The number of bits written in the last byte, 0 if a new byte is needed.
+    bits: u8,
+}
+
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+impl BitWriter {
+    /// Writes the `n` least significant bits of `value`, the most significant first.
+    fn write(&mut self, value: u64, n: u8) {
+        for i in (0..n).rev() {
+            if self.bits == 0 {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().expect("pushed") |= bit << (7 - self.bits);
+            self.bits = (self.bits + 1) % 8;
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc", feature = "bitcoin_hashes"))]
+mod test {
+    use crate::{
+        bsl::{build_basic_filter, filter_header, Block, BlockFilter, FilterHeaders},
+        test_common::reverse,
+        Error, Parse,
+    };
+    use bitcoin::{
+        blockdata::constants::genesis_block,
+        consensus::{deserialize, serialize},
+        hashes::{sha256d, Hash},
+        network::message_filter::CFHeaders,
+        util::bip158,
+        BlockHash, FilterHash, FilterHeader, Network, OutPoint, Script,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    /// A fake script for the output spent by `outpoint`, a p2wsh with the txid as the hash.
+    fn spent_script(outpoint: &OutPoint) -> Script {
+        let mut script = vec![0x00, 0x20];
+        script.extend(outpoint.txid.into_inner());
+        Script::from(script)
+    }
+
+    #[test]
+    fn block_filter_genesis() {
+        // the first vector of BIP158, the testnet genesis block
+        let bytes = serialize(&genesis_block(Network::Testnet));
+        let block = Block::parse_all(&bytes[..]).unwrap();
+        let filter = build_basic_filter(&block, []);
+        assert_eq!(filter, hex_lit::hex!("019dfca8"));
+
+        let parsed = BlockFilter::parse_all(&filter[..]).unwrap();
+        assert_eq!(parsed.element_count(), 1);
+        let block_hash = block.block_hash().into_inner();
+        let coinbase = block.coinbase().unwrap();
+        let script = coinbase.output(0).unwrap().script_pubkey();
+        assert!(parsed.match_any(&block_hash, [script].into_iter()));
+        assert!(parsed.match_any(&block_hash, [&[1u8][..], script].into_iter()));
+        assert!(!parsed.match_any(&block_hash, [&[1u8][..]].into_iter()));
+        assert!(!parsed.match_any(&block_hash, [].into_iter()));
+
+        let empty = BlockFilter::parse_all(&[0u8][..]).unwrap();
+        assert_eq!(empty.element_count(), 0);
+        assert!(!empty.match_any(&block_hash, [script].into_iter()));
+
+        // the basic filter header of the testnet genesis block, from the BIP158 vectors
+        let filter_hash = sha256d::Hash::hash(&filter).into_inner();
+        assert_eq!(
+            filter_header(&filter_hash, &[0u8; 32]),
+            reverse(hex_lit::hex!(
+                "21584579b7eb08997773e5aeff3a7f932700042d0ed2a6129012b7d7ae81b750"
+            ))
+        );
+    }
+
+    /// Returns a transaction spending `inputs` with the scripts `outputs`.
+    fn transaction(inputs: &[OutPoint], outputs: &[&Script]) -> bitcoin::Transaction {
+        bitcoin::Transaction {
+            version: 1,
+            lock_time: bitcoin::PackedLockTime(0),
+            input: inputs
+                .iter()
+                .map(|previous_output| bitcoin::TxIn {
+                    previous_output: *previous_output,
+                    script_sig: Script::new(),
+                    sequence: bitcoin::Sequence::MAX,
+                    witness: bitcoin::Witness::new(),
+                })
+                .collect(),
+            output: outputs
+                .iter()
+                .map(|script| bitcoin::TxOut {
+                    value: 1,
+                    script_pubkey: (*script).clone(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn block_filter_cases() {
+        let p2pkh = Script::from(
+            hex_lit::hex!("76a914000102030405060708090a0b0c0d0e0f1011121388ac").to_vec(),
+        );
+        let p2wpkh =
+            Script::from(hex_lit::hex!("00140102030405060708090a0b0c0d0e0f1011121314").to_vec());
+        let spent =
+            Script::from(hex_lit::hex!("a914141312111000090807060504030201000a0b0c0d87").to_vec());
+        let op_return = Script::from(hex_lit::hex!("6a0401020304").to_vec());
+        // a non-standard OP_RETURN followed by other opcodes is excluded too
+        let op_return_opcodes = Script::from(hex_lit::hex!("6a5151").to_vec());
+        let empty = Script::new();
+
+        let coinbase = transaction(&[OutPoint::null()], &[&p2pkh, &op_return, &empty, &p2pkh]);
+        let previous = [
+            OutPoint::new(coinbase.txid(), 0),
+            OutPoint::new(coinbase.txid(), 2),
+            OutPoint::new(bitcoin::Txid::hash(&[1]), 7),
+        ];
+        // spends outputs with the scripts p2pkh, empty and spent
+        let spending = transaction(&previous, &[&p2wpkh, &op_return_opcodes, &p2wpkh]);
+        let block = bitcoin::Block {
+            header: genesis_block(Network::Testnet).header,
+            txdata: vec![coinbase, spending],
+        };
+        let script_for = |o: &OutPoint| match previous.iter().position(|p| p == o) {
+            Some(0) => Ok(p2pkh.clone()),
+            Some(1) => Ok(empty.clone()),
+            Some(2) => Ok(spent.clone()),
+            _ => Err(bip158::Error::UtxoMissing(*o)),
+        };
+        let bytes = serialize(&block);
+        let parsed_block = Block::parse_all(&bytes[..]).unwrap();
+        let spent_scripts = [p2pkh.as_bytes(), empty.as_bytes(), spent.as_bytes()];
+        let filter = build_basic_filter(&parsed_block, spent_scripts);
+
+        // the expected elements, without OP_RETURN and empty scripts and duplicates
+        let mut expected = vec![];
+        let mut writer = bip158::BlockFilterWriter::new(&mut expected, &block);
+        for script in [&p2pkh, &p2wpkh, &spent] {
+            writer.add_element(script.as_bytes());
+        }
+        writer.finish().unwrap();
+        assert_eq!(filter, expected);
+        assert_eq!(
+            BlockFilter::parse_all(&filter[..]).unwrap().element_count(),
+            3
+        );
+        let oracle = bip158::BlockFilter::new_script_filter(&block, script_for).unwrap();
+        assert_eq!(filter, oracle.content);
+
+        let block_hash = parsed_block.block_hash().into_inner();
+        let parsed = BlockFilter::parse_all(&filter[..]).unwrap();
+        for script in [&p2pkh, &p2wpkh, &spent] {
+            assert!(parsed.match_any(&block_hash, [script.as_bytes()].into_iter()));
+        }
+        for script in [&op_return, &op_return_opcodes] {
+            assert!(!parsed.match_any(&block_hash, [script.as_bytes()].into_iter()));
+        }
+
+        // the filter headers chain of the two filters
+        let genesis = serialize(&genesis_block(Network::Testnet));
+        let genesis_filter = build_basic_filter(&Block::parse_all(&genesis[..]).unwrap(), []);
+        let mut previous_header = FilterHeader::all_zeros();
+        let mut header = [0u8; 32];
+        for filter in [&genesis_filter, &filter] {
+            let filter_hash = FilterHash::hash(filter);
+            header = filter_header(&filter_hash.into_inner(), &header);
+            previous_header = filter_hash.filter_header(&previous_header);
+            assert_eq!(header, previous_header.into_inner());
+        }
+    }
+
+    #[test]
+    fn block_filter() {
+        let block: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        let expected = bip158::BlockFilter::new_script_filter(&block, |o| Ok(spent_script(o)))
+            .unwrap()
+            .content;
+
+        let spent: Vec<Script> = block
+            .txdata
+            .iter()
+            .skip(1)
+            .flat_map(|tx| tx.input.iter().map(|i| spent_script(&i.previous_output)))
+            .collect();
+        let parsed_block = Block::parse_all(mainnet_702861()).unwrap();
+        let filter = build_basic_filter(&parsed_block, spent.iter().map(|s| s.as_bytes()));
+        assert_eq!(filter, expected);
+
+        let parsed = BlockFilter::parse(&filter[..]).unwrap();
+        assert_eq!(parsed.remaining(), &[0u8; 0][..]);
+        assert_eq!(parsed.parsed().as_ref(), &filter[..]);
+        let parsed = parsed.parsed_owned();
+
+        let block_hash = block.block_hash();
+        let oracle = bip158::BlockFilter::new(&filter);
+        let output = block.txdata[10].output[0].script_pubkey.as_bytes();
+        let queries: [&[u8]; 4] = [output, spent[5].as_bytes(), &[0x51], &[0x00, 0x14, 0x01]];
+        for query in queries {
+            let expected = oracle
+                .match_any(&block_hash, &mut [query].into_iter())
+                .unwrap();
+            assert_eq!(
+                parsed.match_any(&block_hash.into_inner(), [query].into_iter()),
+                expected
+            );
+        }
+        assert!(parsed.match_any(&block_hash.into_inner(), queries.into_iter()));
+
+        // the filter is followed by other data
+        let with_trailing = [&filter[..], &[1u8, 2, 3][..]].concat();
+        let parsed = BlockFilter::parse(&with_trailing[..]).unwrap();
+        assert_eq!(parsed.remaining(), &[1u8, 2, 3][..]);
+
+        assert_eq!(
+            BlockFilter::parse(&filter[..filter.len() - 1]),
+            Err(Error::Needed(1))
+        );
+    }
+
+    #[test]
+    fn filter_headers() {
+        let filter_hashes: Vec<FilterHash> = (0..3u8).map(|i| FilterHash::hash(&[i])).collect();
+        let previous_filter_header = FilterHeader::hash(&[42]);
+        let message = CFHeaders {
+            filter_type: 0,
+            stop_hash: BlockHash::hash(&[1]),
+            previous_filter_header,
+            filter_hashes: filter_hashes.clone(),
+        };
+        let bytes = serialize(&message);
+
+        let headers = FilterHeaders::parse_all(&bytes[..]).unwrap();
+        assert_eq!(headers.as_ref(), &bytes[..]);
+        assert_eq!(headers.filter_type(), 0);
+        assert_eq!(headers.stop_hash(), &message.stop_hash.into_inner());
+        assert_eq!(
+            headers.previous_filter_header(),
+            &previous_filter_header.into_inner()
+        );
+        assert_eq!(headers.total_filter_hashes(), 3);
+        let hashes: Vec<[u8; 32]> = headers.filter_hashes().copied().collect();
+        let expected: Vec<[u8; 32]> = filter_hashes.iter().map(|h| h.into_inner()).collect();
+        assert_eq!(hashes, expected);
+
+        let mut expected = previous_filter_header;
+        for filter_hash in filter_hashes.iter() {
+            let next = filter_hash.filter_header(&expected);
+            assert_eq!(
+                filter_header(&filter_hash.into_inner(), &expected.into_inner()),
+                next.into_inner()
+            );
+            expected = next;
+        }
+        assert_eq!(headers.last_filter_header(), expected.into_inner());
+
+        let message = CFHeaders {
+            filter_hashes: vec![],
+            ..message
+        };
+        let bytes = serialize(&message);
+        let headers = FilterHeaders::parse_all(&bytes[..]).unwrap();
+        assert_eq!(
+            headers.last_filter_header(),
+            previous_filter_header.into_inner()
+        );
+
+        let message = CFHeaders {
+            filter_hashes: vec![FilterHash::hash(&[]); 2000],
+            ..message
+        };
+        let mut bytes = serialize(&message);
+        assert!(FilterHeaders::parse_all(&bytes[..]).is_ok());
+        bytes[65..68].copy_from_slice(&[0xfd, 0xd1, 0x07]);
+        bytes.extend([0u8; 32]);
+        assert_eq!(FilterHeaders::parse_all(&bytes[..]), Err(Error::TooLarge));
+    }
+}
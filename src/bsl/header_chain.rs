@@ -0,0 +1,277 @@
+use crate::bsl::{parse_len, BlockHeader, TxidHasher};
+use crate::{HeaderChainError, Parse};
+
+/// Information about a validated chain of block headers, returned by [`validate_header_chain_with`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainInfo {
+    /// The number of headers in the chain.
+    pub count: usize,
+
+    /// The hash of the last header in the chain, in byte order (not reversed), `None` if the chain
+    /// is empty.
+    pub last_hash: Option<[u8; 32]>,
+}
+
+/// Validates that `slice` is a concatenation of 80 bytes block headers, each one linked to the
+/// previous by its `prev_blockhash`, and that the first links to `expected_prev` if given.
+///
+/// See [`validate_header_chain_with()`].
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub fn validate_header_chain(
+    slice: &[u8],
+    expected_prev: Option<&[u8; 32]>,
+) -> Result<ChainInfo, HeaderChainError> {
+    use crate::bitcoin_hashes::{sha256d, Hash};
+    validate_header_chain_with(slice, expected_prev, false, sha256d::Hash::engine())
+}
+
+/// Like [`validate_header_chain()`] but for the encoding of the P2P `headers` message, where
+/// every header is followed by a transaction count which must be zero. The leading count of
+/// headers of the message must not be included in `slice`.
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub fn validate_p2p_headers(
+    slice: &[u8],
+    expected_prev: Option<&[u8; 32]>,
+) -> Result<ChainInfo, HeaderChainError> {
+    use crate::bitcoin_hashes::{sha256d, Hash};
+    validate_header_chain_with(slice, expected_prev, true, sha256d::Hash::engine())
+}
+
+/// Validates the chain of block headers in `slice`, every header hash is computed with a clone of
+/// `hasher`. If `p2p` every header must be followed by a zero transaction count, as in the P2P
+/// `headers` message.
+///
+/// Only the linkage is checked, not the proof of work. Errors contain the index of the header
+/// that failed validation; the headers are not allocated.
+pub fn validate_header_chain_with<H: TxidHasher + Clone>(
+    slice: &[u8],
+    expected_prev: Option<&[u8; 32]>,
+    p2p: bool,
+    hasher: H,
+) -> Result<ChainInfo, HeaderChainError> {
+    let mut remaining = slice;
+    let mut count = 0;
+    let mut last_hash: Option<[u8; 32]> = None;
+    while !remaining.is_empty() {
+        let header =
+            BlockHeader::parse(remaining).map_err(|_| HeaderChainError::Truncated(count))?;
+        remaining = header.remaining();
+        if p2p {
+            let len = parse_len(remaining).map_err(|_| HeaderChainError::Truncated(count))?;
+            if len.n() != 0 {
+                return Err(HeaderChainError::NonZeroTxCount(count));
+            }
+            remaining = &remaining[len.consumed()..];
+        }
+        let header = header.parsed_owned();
+        let expected = last_hash.as_ref().or(expected_prev);
+        if expected.is_some_and(|expected| expected != header.prev_blockhash()) {
+            return Err(HeaderChainError::Disconnected(count));
+        }
+        last_hash = Some(header.block_hash_with(hasher.clone()));
+        count += 1;
+    }
+    Ok(ChainInfo { count, last_hash })
+}
+
+/// The number of headers used to compute the median time past.
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Returns the median time past like Bitcoin Core `GetMedianTimePast`: the median of the
+/// timestamps of the given headers, which should be the last [`MEDIAN_TIME_SPAN`] headers of the
+/// chain in any order.
+///
+/// Only the first [`MEDIAN_TIME_SPAN`] headers are considered; if fewer are given, like at the
+/// start of the chain, all of them are used. Returns `None` if `headers` is empty.
+pub fn median_time_past<'a, I: IntoIterator<Item = BlockHeader<'a>>>(headers: I) -> Option<u32> {
+    let mut times = [0u32; MEDIAN_TIME_SPAN];
+    let mut count = 0;
+    for header in headers.into_iter().take(MEDIAN_TIME_SPAN) {
+        times[count] = header.time();
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    let times = &mut times[..count];
+    times.sort_unstable();
+    Some(times[count / 2])
+}
+
+/// The number of blocks in a BIP9 signalling period on mainnet, a difficulty adjustment period.
+pub const SIGNALLING_PERIOD: usize = 2016;
+
+/// Returns how many of the given headers signal the BIP9 deployment `bit`, see
+/// [`BlockHeader::is_signalling`].
+///
+/// Only the first [`SIGNALLING_PERIOD`] headers are considered, which should be the headers of a
+/// whole period to compare the count with the deployment threshold.
+pub fn count_signalling<'a, I: IntoIterator<Item = BlockHeader<'a>>>(headers: I, bit: u8) -> usize {
+    headers
+        .into_iter()
+        .take(SIGNALLING_PERIOD)
+        .filter(|header| header.is_signalling(bit))
+        .count()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{count_signalling, median_time_past, BlockHeader, SIGNALLING_PERIOD},
+        test_common::GENESIS_BLOCK_HEADER,
+        Parse,
+    };
+
+    #[test]
+    fn test_count_signalling() {
+        let header_with_version = |version: u32| {
+            let mut header = GENESIS_BLOCK_HEADER;
+            header[..4].copy_from_slice(&version.to_le_bytes());
+            header
+        };
+        // a period where 90% of the blocks signal taproot (bit 2), some with version rolling
+        let headers: Vec<[u8; 80]> = (0..SIGNALLING_PERIOD + 10)
+            .map(|i| match i % 10 {
+                0 => header_with_version(0x2000_0000),
+                1 => header_with_version(0x3fff_e004),
+                _ => header_with_version(0x2000_0004),
+            })
+            .collect();
+        let parsed = || {
+            headers
+                .iter()
+                .map(|h| BlockHeader::parse(&h[..]).unwrap().parsed_owned())
+        };
+        assert_eq!(count_signalling(parsed(), 2), 1814);
+        assert_eq!(count_signalling(parsed(), 1), 0);
+        assert_eq!(count_signalling(parsed(), 13), 202);
+        assert_eq!(count_signalling(parsed().take(10), 2), 9);
+        assert_eq!(count_signalling(parsed().take(0), 2), 0);
+    }
+
+    #[test]
+    fn test_median_time_past() {
+        let headers_with_times = |times: &[u32]| -> Vec<[u8; 80]> {
+            times
+                .iter()
+                .map(|time| {
+                    let mut header = GENESIS_BLOCK_HEADER;
+                    header[68..72].copy_from_slice(&time.to_le_bytes());
+                    header
+                })
+                .collect()
+        };
+        let mtp = |times: &[u32]| {
+            let headers = headers_with_times(times);
+            median_time_past(
+                headers
+                    .iter()
+                    .map(|h| BlockHeader::parse(&h[..]).unwrap().parsed_owned()),
+            )
+        };
+
+        assert_eq!(mtp(&[]), None);
+        assert_eq!(mtp(&[5]), Some(5));
+        assert_eq!(mtp(&[5, 3]), Some(5));
+        assert_eq!(mtp(&[5, 3, 4]), Some(4));
+        assert_eq!(mtp(&[9, 1, 8, 2, 7, 3, 6, 4, 5, 11, 10]), Some(6));
+        assert_eq!(mtp(&[9, 1, 8, 2, 7, 3, 6, 4, 5, 11, 10, 0, 0, 0]), Some(6));
+        assert_eq!(mtp(&[1, 1, 1, 2, 2, 2, 2]), Some(2));
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    fn chain(len: usize) -> Vec<bitcoin::BlockHeader> {
+        let mut headers: Vec<bitcoin::BlockHeader> =
+            vec![bitcoin::consensus::deserialize(&GENESIS_BLOCK_HEADER).unwrap()];
+        for i in 1..len {
+            let mut header = headers[i - 1];
+            header.prev_blockhash = headers[i - 1].block_hash();
+            header.time += 600;
+            header.nonce = i as u32;
+            headers.push(header);
+        }
+        headers
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    fn concat(headers: &[bitcoin::BlockHeader], p2p: bool) -> Vec<u8> {
+        let mut bytes = vec![];
+        for header in headers {
+            bytes.extend(bitcoin::consensus::serialize(header));
+            if p2p {
+                bytes.push(0);
+            }
+        }
+        bytes
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn header_chain() {
+        use crate::{
+            bsl::{validate_header_chain, validate_p2p_headers, ChainInfo},
+            HeaderChainError,
+        };
+        use bitcoin::hashes::Hash;
+
+        let headers = chain(10);
+        let last_hash = Some(headers[9].block_hash().into_inner());
+        let genesis_hash = headers[0].block_hash().into_inner();
+        let expected = ChainInfo {
+            count: 10,
+            last_hash,
+        };
+
+        let bytes = concat(&headers, false);
+        assert_eq!(validate_header_chain(&bytes, None), Ok(expected.clone()));
+        assert_eq!(
+            validate_header_chain(&bytes[80..], Some(&genesis_hash)),
+            Ok(ChainInfo {
+                count: 9,
+                last_hash
+            })
+        );
+        assert_eq!(
+            validate_header_chain(&bytes, Some(&genesis_hash)),
+            Err(HeaderChainError::Disconnected(0))
+        );
+        assert_eq!(
+            validate_header_chain(&bytes[..799], None),
+            Err(HeaderChainError::Truncated(9))
+        );
+        assert_eq!(
+            validate_header_chain(&[], None),
+            Ok(ChainInfo {
+                count: 0,
+                last_hash: None
+            })
+        );
+
+        let mut broken = headers.clone();
+        broken[5].nonce += 1;
+        let bytes = concat(&broken, false);
+        assert_eq!(
+            validate_header_chain(&bytes, None),
+            Err(HeaderChainError::Disconnected(6))
+        );
+
+        let bytes = concat(&headers, true);
+        assert_eq!(validate_p2p_headers(&bytes, None), Ok(expected));
+        assert_eq!(
+            validate_header_chain(&bytes, None),
+            Err(HeaderChainError::Disconnected(1))
+        );
+        assert_eq!(
+            validate_p2p_headers(&bytes[..809], None),
+            Err(HeaderChainError::Truncated(9))
+        );
+        let mut with_txs = bytes.clone();
+        with_txs[81 * 3 + 80] = 1;
+        assert_eq!(
+            validate_p2p_headers(&with_txs, None),
+            Err(HeaderChainError::NonZeroTxCount(3))
+        );
+    }
+}
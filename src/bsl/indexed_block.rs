@@ -0,0 +1,199 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::bsl::{Block, Transaction};
+use crate::Visitor;
+
+/// A [`Block`] with the offsets of its transactions, giving random access to them.
+///
+/// Building the index visits the block once and keeps a `usize` per transaction, after that
+/// [`IndexedBlock::get`] parses only the requested transaction instead of visiting all the
+/// transactions preceding it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedBlock<'a> {
+    block: Block<'a>,
+    /// The offset of every transaction from the start of the block.
+    offsets: Vec<usize>,
+}
+
+impl<'a> IndexedBlock<'a> {
+    /// Index the transactions of `block`.
+    pub fn new(block: Block<'a>) -> Self {
+        struct Offsets(Vec<usize>);
+        impl Visitor<'_> for Offsets {
+            fn visit_block_begin(&mut self, total_transactions: usize) {
+                self.0.reserve_exact(total_transactions);
+            }
+            fn visit_transaction_at(&mut self, offset: usize, _tx: &Transaction) {
+                self.0.push(offset);
+            }
+        }
+        let mut offsets = Offsets(Vec::new());
+        block.visit_again(&mut offsets);
+        IndexedBlock {
+            block,
+            offsets: offsets.0,
+        }
+    }
+
+    /// Returns the indexed block.
+    pub fn block(&self) -> &Block<'a> {
+        &self.block
+    }
+
+    /// Returns the number of transactions in the block.
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Returns true if the block has no transactions, which is invalid since it must have a
+    /// coinbase.
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Returns the byte range of the transaction at `index` in the block.
+    pub fn range(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.offsets.get(index)?;
+        let end = self
+            .offsets
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.block.slice.len());
+        Some(start..end)
+    }
+
+    /// Returns the transaction at `index` in the block, parsing only that transaction.
+    pub fn get(&self, index: usize) -> Option<Transaction<'a>> {
+        let start = *self.offsets.get(index)?;
+        Some(self.block.transaction_at(start))
+    }
+
+    /// Returns an iterator over the transactions of the block.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = Transaction<'a>> + '_ {
+        (0..self.len()).map(move |i| self.get(i).expect("index in bounds"))
+    }
+
+    /// Returns the index of the transaction containing the byte at `offset` from the start of the
+    /// block, or `None` if `offset` is in the header, the transaction count or past the block.
+    pub fn index_of_offset(&self, offset: usize) -> Option<usize> {
+        if offset >= self.block.slice.len() {
+            return None;
+        }
+        match self.offsets.binary_search(&offset) {
+            Ok(index) => Some(index),
+            Err(0) => None,
+            Err(index) => Some(index - 1),
+        }
+    }
+}
+
+impl<'a> From<Block<'a>> for IndexedBlock<'a> {
+    fn from(block: Block<'a>) -> Self {
+        IndexedBlock::new(block)
+    }
+}
+
+impl<'a> AsRef<[u8]> for IndexedBlock<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.block.slice
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{Block, IndexedBlock, Transaction},
+        test_common::GENESIS_BLOCK,
+        Parse,
+    };
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn indexed_block() {
+        let block = Block::parse_all(&GENESIS_BLOCK[..]).unwrap();
+        let indexed = IndexedBlock::new(block.clone());
+        assert_eq!(indexed.len(), 1);
+        assert_eq!(indexed.range(0), Some(81..GENESIS_BLOCK.len()));
+        assert_eq!(indexed.get(0), Some(block.coinbase().unwrap()));
+        assert_eq!(indexed.get(1), None);
+        assert_eq!(indexed.index_of_offset(80), None);
+        assert_eq!(indexed.index_of_offset(81), Some(0));
+        assert_eq!(indexed.index_of_offset(GENESIS_BLOCK.len() - 1), Some(0));
+        assert_eq!(indexed.index_of_offset(GENESIS_BLOCK.len()), None);
+
+        let block_bytes = mainnet_702861();
+        let block_bitcoin: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let indexed: IndexedBlock = Block::parse_all(block_bytes).unwrap().into();
+        assert_eq!(indexed.as_ref(), block_bytes);
+        assert!(!indexed.is_empty());
+        assert_eq!(indexed.len(), block_bitcoin.txdata.len());
+        assert_eq!(indexed.iter().len(), indexed.len());
+        for (i, (tx, tx_bitcoin)) in indexed.iter().zip(block_bitcoin.txdata.iter()).enumerate() {
+            let tx_bytes = serialize(tx_bitcoin);
+            assert_eq!(tx.as_ref(), &tx_bytes[..]);
+            assert_eq!(indexed.get(i), Some(tx));
+
+            let range = indexed.range(i).unwrap();
+            assert_eq!(&block_bytes[range.clone()], &tx_bytes[..]);
+            assert_eq!(indexed.index_of_offset(range.start), Some(i));
+            assert_eq!(indexed.index_of_offset(range.end - 1), Some(i));
+        }
+        let last = serialize(block_bitcoin.txdata.last().unwrap());
+        assert_eq!(
+            indexed.get(2499),
+            Some(Transaction::parse_all(&last).unwrap())
+        );
+        assert_eq!(indexed.get(2500), None);
+        assert_eq!(indexed.range(2500), None);
+    }
+}
+
+#[cfg(bench)]
+mod bench {
+    use crate::bsl::{Block, IndexedBlock, Transaction};
+    use crate::{Parse, Visit, Visitor};
+    use bitcoin_test_data::blocks::mainnet_702861;
+    use core::ops::ControlFlow;
+    use test::{black_box, Bencher};
+
+    #[bench]
+    pub fn last_transaction_indexed(bh: &mut Bencher) {
+        let block = Block::parse_all(mainnet_702861()).unwrap();
+        bh.iter(|| {
+            let indexed = IndexedBlock::new(block.clone());
+            for _ in 0..1000 {
+                black_box(indexed.get(2499).unwrap());
+            }
+        });
+    }
+
+    #[bench]
+    pub fn last_transaction_linear(bh: &mut Bencher) {
+        struct Find {
+            index: usize,
+            offset: usize,
+        }
+        impl Visitor<'_> for Find {
+            fn visit_transaction_at(&mut self, offset: usize, _tx: &Transaction) {
+                if self.index == 2499 {
+                    self.offset = offset;
+                }
+                self.index += 1;
+            }
+        }
+        let block = Block::parse_all(mainnet_702861()).unwrap();
+        bh.iter(|| {
+            for _ in 0..1000 {
+                let mut find = Find {
+                    index: 0,
+                    offset: 0,
+                };
+                Block::visit(block.as_ref(), &mut find).unwrap();
+                let tx = Transaction::parse(&block.as_ref()[find.offset..]).unwrap();
+                black_box(tx.parsed_owned());
+            }
+        });
+    }
+}
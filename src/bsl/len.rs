@@ -12,6 +12,31 @@ pub struct Len {
     pub(crate) n: u64,
 }
 
+/// The object whose length or count is encoded in a compact int, see [`crate::Visitor::visit_len`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LenKind {
+    /// The number of inputs of a transaction.
+    TxInCount,
+
+    /// The number of outputs of a transaction.
+    TxOutCount,
+
+    /// The length of the script sig of an input.
+    ScriptSigLen,
+
+    /// The length of the script pubkey of an output.
+    ScriptPubKeyLen,
+
+    /// The number of elements of a witness.
+    WitnessElementCount,
+
+    /// The length of a witness element.
+    WitnessElementLen,
+
+    /// The number of transactions of a block.
+    BlockTxCount,
+}
+
 /// Parse `Len` from the slice.
 /// This is done without `Parse` trait to have better perfomance.
 #[inline(always)]
@@ -28,6 +53,52 @@ pub fn parse_len(slice: &[u8]) -> Result<Len, Error> {
     })
 }
 
+/// Parse `Len` like [`parse_len`], if `lenient` accepting also the non-minimal encodings, see
+/// [`crate::bsl::ParseConfig::allow_non_minimal_compact_sizes`].
+#[inline(always)]
+pub(crate) fn parse_len_with(slice: &[u8], lenient: bool) -> Result<Len, Error> {
+    if !lenient {
+        return parse_len(slice);
+    }
+    Ok(match slice.first() {
+        Some(0xFFu8) => Len {
+            n: U64::parse(&slice[1..])?.parsed_owned().into(),
+            consumed: 9,
+        },
+        Some(0xFEu8) => Len {
+            n: u32::from(U32::parse(&slice[1..])?.parsed_owned()) as u64,
+            consumed: 5,
+        },
+        Some(0xFDu8) => Len {
+            n: u16::from(U16::parse(&slice[1..])?.parsed_owned()) as u64,
+            consumed: 3,
+        },
+        _ => return parse_len(slice),
+    })
+}
+
+/// Encode `n` as a compact int, returns a buffer and the number of bytes used in it.
+pub(crate) fn encode_len(n: u64) -> ([u8; 9], usize) {
+    let mut buffer = [0u8; 9];
+    let used = if n < 0xFD {
+        buffer[0] = n as u8;
+        1
+    } else if n <= u16::MAX as u64 {
+        buffer[0] = 0xFD;
+        buffer[1..3].copy_from_slice(&(n as u16).to_le_bytes());
+        3
+    } else if n <= u32::MAX as u64 {
+        buffer[0] = 0xFE;
+        buffer[1..5].copy_from_slice(&(n as u32).to_le_bytes());
+        5
+    } else {
+        buffer[0] = 0xFF;
+        buffer[1..9].copy_from_slice(&n.to_le_bytes());
+        9
+    };
+    (buffer, used)
+}
+
 impl Len {
     /// The value encoded in this compact int
     pub fn n(&self) -> u64 {
@@ -48,7 +119,10 @@ impl Len {
 #[cfg(test)]
 mod test {
     use crate::{
-        bsl::{len::parse_len, Len},
+        bsl::{
+            len::{encode_len, parse_len, parse_len_with},
+            Len,
+        },
         Error,
     };
 
@@ -83,6 +157,91 @@ mod test {
         assert_eq!(parse_len(&[0xFFu8]), Err(Error::Needed(8)));
     }
 
+    #[test]
+    fn test_parse_len_lenient() {
+        let non_minimal: [&[u8]; 3] = [
+            &[0xFDu8, 0xFC, 0],
+            &[0xFEu8, 0xFF, 0xFF, 0, 0],
+            &[0xFFu8, 1, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        for (slice, n) in non_minimal.iter().zip([0xFC, 0xFFFF, 1]) {
+            assert_eq!(parse_len_with(slice, false), Err(Error::NonMinimalVarInt));
+            let len = Len {
+                consumed: slice.len(),
+                n,
+            };
+            assert_eq!(parse_len_with(slice, true), Ok(len));
+        }
+        assert_eq!(
+            parse_len_with(&[0xFDu8, 0xFD, 0x00], true),
+            parse_len(&[0xFDu8, 0xFD, 0x00])
+        );
+        assert_eq!(parse_len_with(&[10u8], true), parse_len(&[10u8]));
+        assert_eq!(parse_len_with(&[0xFEu8, 1], true), Err(Error::Needed(3)));
+        assert_eq!(parse_len_with(&[], true), Err(Error::Needed(1)));
+    }
+
+    #[test]
+    fn test_encode_len() {
+        for n in [
+            0u64,
+            1,
+            0xFC,
+            0xFD,
+            0xFFFF,
+            0x10000,
+            0xFFFFFFFF,
+            0x100000000,
+            u64::MAX,
+        ] {
+            let (buffer, used) = encode_len(n);
+            let len = parse_len(&buffer[..used]).unwrap();
+            assert_eq!(len.n(), n);
+            assert_eq!(len.consumed(), used);
+        }
+    }
+
+    #[test]
+    fn visit_len() {
+        use crate::{
+            bsl::{Block, LenKind, Transaction},
+            test_common::SEGWIT_TX,
+            Visit, Visitor,
+        };
+        use bitcoin_test_data::blocks::mainnet_702861;
+
+        #[derive(Default)]
+        struct Lens(Vec<(LenKind, u64, usize)>);
+        impl Visitor<'_> for Lens {
+            fn visit_len(&mut self, kind: LenKind, value: u64, encoded_len: usize) {
+                self.0.push((kind, value, encoded_len));
+            }
+        }
+
+        let mut lens = Lens::default();
+        Transaction::visit(&SEGWIT_TX[..], &mut lens).unwrap();
+        assert_eq!(
+            lens.0,
+            vec![
+                (LenKind::TxInCount, 1, 1),
+                (LenKind::ScriptSigLen, 54, 1),
+                (LenKind::TxOutCount, 2, 1),
+                (LenKind::ScriptPubKeyLen, 25, 1),
+                (LenKind::ScriptPubKeyLen, 38, 1),
+                (LenKind::WitnessElementCount, 1, 1),
+                (LenKind::WitnessElementLen, 32, 1),
+            ]
+        );
+
+        let mut lens = Lens::default();
+        Block::visit(mainnet_702861(), &mut lens).unwrap();
+        assert_eq!(lens.0[0], (LenKind::BlockTxCount, 2500, 3));
+        let count = |kind| lens.0.iter().filter(|(k, _, _)| *k == kind).count();
+        assert_eq!(count(LenKind::BlockTxCount), 1);
+        assert_eq!(count(LenKind::TxInCount), 2500);
+        assert_eq!(count(LenKind::TxOutCount), 2500);
+    }
+
     #[cfg(target_pointer_width = "64")]
     #[test]
     fn size_of() {
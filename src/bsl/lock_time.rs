@@ -0,0 +1,52 @@
+use core::fmt;
+
+/// Locktime values lower than this are block heights, otherwise they are unix timestamps.
+pub const LOCK_TIME_THRESHOLD: u32 = 500_000_000;
+
+/// The interpretation of a transaction locktime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockTime {
+    /// The transaction can't be included in a block with height lower than the contained value.
+    Blocks(u32),
+    /// The transaction can't be included in a block with median time past lower than the
+    /// contained unix timestamp.
+    Seconds(u32),
+}
+
+impl From<u32> for LockTime {
+    fn from(value: u32) -> Self {
+        if value < LOCK_TIME_THRESHOLD {
+            LockTime::Blocks(value)
+        } else {
+            LockTime::Seconds(value)
+        }
+    }
+}
+
+impl fmt::Display for LockTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockTime::Blocks(height) => write!(f, "block height {}", height),
+            LockTime::Seconds(time) => write!(f, "unix time {}", time),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LockTime;
+
+    #[test]
+    fn lock_time() {
+        assert_eq!(LockTime::from(0), LockTime::Blocks(0));
+        assert_eq!(LockTime::from(499_999_999), LockTime::Blocks(499_999_999));
+        assert_eq!(LockTime::from(500_000_000), LockTime::Seconds(500_000_000));
+        assert_eq!(LockTime::from(u32::MAX), LockTime::Seconds(u32::MAX));
+
+        assert_eq!(LockTime::Blocks(702861).to_string(), "block height 702861");
+        assert_eq!(
+            LockTime::Seconds(1_600_000_000).to_string(),
+            "unix time 1600000000"
+        );
+    }
+}
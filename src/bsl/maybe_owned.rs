@@ -0,0 +1,116 @@
+use crate::{ParseResult, Parsed};
+
+/// A parsed object which can be copied in an owned version, not borrowing the parsed slice.
+///
+/// The copy keeps the metadata computed during parsing, so it doesn't reparse the bytes.
+pub trait IntoOwned: AsRef<[u8]> {
+    /// The owned version of the object, serialized in the same bytes.
+    type Owned: AsRef<[u8]>;
+
+    /// Copies the bytes of the object in its owned version.
+    fn into_owned(self) -> Self::Owned;
+}
+
+/// Either a parsed object borrowing the parsed slice or its owned copy, like a
+/// [`Cow`](https://doc.rust-lang.org/std/borrow/enum.Cow.html) for the parsed objects.
+///
+/// Useful for code parsing both from long lived buffers, where borrowing is enough, and from
+/// transient ones, which must be copied to be kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaybeOwned<T: IntoOwned> {
+    /// The object borrowing the parsed slice.
+    Borrowed(T),
+
+    /// The owned copy of the object.
+    Owned(T::Owned),
+}
+
+impl<T: IntoOwned> MaybeOwned<T> {
+    /// Returns the owned object, copying the bytes if borrowed.
+    pub fn into_owned(self) -> T::Owned {
+        match self {
+            MaybeOwned::Borrowed(parsed) => parsed.into_owned(),
+            MaybeOwned::Owned(owned) => owned,
+        }
+    }
+
+    /// Returns true if the object borrows the parsed slice.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, MaybeOwned::Borrowed(_))
+    }
+
+    /// Returns true if the object is an owned copy.
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+}
+
+impl<T: IntoOwned> AsRef<[u8]> for MaybeOwned<T> {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            MaybeOwned::Borrowed(parsed) => parsed.as_ref(),
+            MaybeOwned::Owned(owned) => owned.as_ref(),
+        }
+    }
+}
+
+impl<T: IntoOwned> From<T> for MaybeOwned<T> {
+    fn from(parsed: T) -> Self {
+        MaybeOwned::Borrowed(parsed)
+    }
+}
+
+impl<'a, T: IntoOwned> ParseResult<'a, T> {
+    /// Copies the object parsed in its owned version, keeping the remaining and the consumed
+    /// bytes.
+    pub fn into_owned(self) -> Parsed<'a, T::Owned> {
+        self.map_parsed(IntoOwned::into_owned)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{BlockHeader, BlockHeaderOwned, IntoOwned, MaybeOwned},
+        test_common::GENESIS_BLOCK_HEADER,
+        Parse,
+    };
+
+    #[test]
+    fn maybe_owned() {
+        let header = BlockHeader::parse(&GENESIS_BLOCK_HEADER[..]).unwrap();
+        let owned = header.clone().into_owned();
+        assert_eq!(owned.consumed(), 80);
+        assert_eq!(owned.parsed(), &BlockHeaderOwned::from(header.parsed()));
+        assert_eq!(owned.parsed(), &header.parsed().clone().into_owned());
+        assert_eq!(owned.parsed(), &header.parsed().to_owned());
+
+        let borrowed = MaybeOwned::from(header.parsed_owned());
+        assert!(borrowed.is_borrowed());
+        let copy = MaybeOwned::<BlockHeader>::Owned(borrowed.clone().into_owned());
+        assert!(copy.is_owned());
+        assert_eq!(borrowed.as_ref(), copy.as_ref());
+        assert_eq!(borrowed.into_owned(), copy.into_owned());
+    }
+
+    #[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+    #[test]
+    fn maybe_owned_txid() {
+        use crate::{
+            bsl::{Block, Transaction},
+            test_common::{GENESIS_BLOCK, SEGWIT_TX},
+        };
+
+        let tx = Transaction::parse_all(&SEGWIT_TX[..]).unwrap();
+        let owned = MaybeOwned::Borrowed(tx.clone()).into_owned();
+        assert_eq!(owned.txid(), tx.txid());
+        assert_eq!(owned.wtxid(), tx.wtxid());
+        assert_eq!(owned.as_transaction(), tx);
+        assert_eq!(tx.to_owned().txid(), tx.txid());
+
+        let block = Block::parse_all(&GENESIS_BLOCK[..]).unwrap();
+        let owned = block.to_owned();
+        assert_eq!(owned.block_hash(), block.block_hash());
+        assert_eq!(owned.as_block(), block);
+    }
+}
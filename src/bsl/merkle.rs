@@ -0,0 +1,124 @@
+use crate::bsl::TxidHasher;
+
+/// Computes a merkle root like Bitcoin Core `ComputeMerkleRoot`, keeping only one hash per level
+/// of the tree instead of all the leaves.
+///
+/// While pushing the leaves it also detects the CVE-2012-2459 mutation: two equal hashes combined
+/// together, which happens when the last elements of a level are duplicated.
+pub(crate) struct MerkleComputation<H> {
+    hasher: H,
+    inner: [[u8; 32]; 32],
+    count: u32,
+    mutated: bool,
+}
+
+impl<H: TxidHasher + Clone> MerkleComputation<H> {
+    /// Creates the computation, every node is hashed with a clone of `hasher`.
+    pub(crate) fn new(hasher: H) -> Self {
+        MerkleComputation {
+            hasher,
+            inner: [[0u8; 32]; 32],
+            count: 0,
+            mutated: false,
+        }
+    }
+
+    fn hash_pair(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = self.hasher.clone();
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize_double()
+    }
+
+    /// Push the next leaf of the tree, in byte order (not reversed).
+    pub(crate) fn push(&mut self, leaf: [u8; 32]) {
+        let mut hash = leaf;
+        self.count += 1;
+        let mut level = 0;
+        while self.count & (1 << level) == 0 {
+            self.mutated |= self.inner[level] == hash;
+            hash = self.hash_pair(&self.inner[level], &hash);
+            level += 1;
+        }
+        self.inner[level] = hash;
+    }
+
+    /// Returns the merkle root, all zeros if no leaves have been pushed, and whether the tree is
+    /// mutated.
+    pub(crate) fn finalize(self) -> ([u8; 32], bool) {
+        if self.count == 0 {
+            return ([0u8; 32], self.mutated);
+        }
+        let mut count = self.count;
+        let mut level = 0;
+        while count & (1 << level) == 0 {
+            level += 1;
+        }
+        let mut hash = self.inner[level];
+        while count != 1 << level {
+            // odd number of elements at this level, the last is combined with itself
+            hash = self.hash_pair(&hash, &hash);
+            count += 1 << level;
+            level += 1;
+            while count & (1 << level) == 0 {
+                hash = self.hash_pair(&self.inner[level], &hash);
+                level += 1;
+            }
+        }
+        (hash, self.mutated)
+    }
+}
+
+#[cfg(all(test, feature = "bitcoin_hashes"))]
+mod test {
+    use super::MerkleComputation;
+    use bitcoin::hashes::{sha256d, Hash};
+
+    fn merkle(leaves: &[[u8; 32]]) -> ([u8; 32], bool) {
+        let mut merkle = MerkleComputation::new(sha256d::Hash::engine());
+        for leaf in leaves {
+            merkle.push(*leaf);
+        }
+        merkle.finalize()
+    }
+
+    /// Computes the merkle root keeping all the levels
+    fn merkle_naive(leaves: &[[u8; 32]]) -> [u8; 32] {
+        let mut level = leaves.to_vec();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let right = pair.get(1).unwrap_or(&pair[0]);
+                    sha256d::Hash::hash(&[pair[0], *right].concat()).into_inner()
+                })
+                .collect();
+        }
+        level[0]
+    }
+
+    #[test]
+    fn merkle_computation() {
+        assert_eq!(merkle(&[]), ([0u8; 32], false));
+        let leaves: Vec<[u8; 32]> = (0..20u8).map(|i| [i; 32]).collect();
+        for len in 1..leaves.len() {
+            assert_eq!(
+                merkle(&leaves[..len]),
+                (merkle_naive(&leaves[..len]), false),
+                "{} leaves",
+                len
+            );
+        }
+
+        // duplicating the last leaf of an odd level gives the same root, but mutated
+        let (root, mutated) = merkle(&leaves[..3]);
+        assert!(!mutated);
+        let duplicated = [leaves[0], leaves[1], leaves[2], leaves[2]];
+        assert_eq!(merkle(&duplicated), (root, true));
+
+        // also at upper levels
+        let (root, _) = merkle(&leaves[..6]);
+        let duplicated = [&leaves[..6], &leaves[4..6]].concat();
+        assert_eq!(merkle(&duplicated), (root, true));
+    }
+}
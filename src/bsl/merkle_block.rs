@@ -0,0 +1,353 @@
+use super::len::{parse_len, Len};
+use crate::bsl::{BlockHeader, TxidHasher, MAX_BLOCK_WEIGHT};
+use crate::number::U32;
+use crate::slice::read_slice;
+use crate::{MerkleBlockError, Parse, ParseResult, SResult, Visit, Visitor};
+
+/// The weight of the smallest possible transaction, 60 bytes without witness.
+const MIN_TRANSACTION_WEIGHT: u32 = 4 * 60;
+
+/// The maximum number of transactions of a block.
+const MAX_TRANSACTIONS: u32 = MAX_BLOCK_WEIGHT as u32 / MIN_TRANSACTION_WEIGHT;
+
+/// Bigger than the height of a tree with [`MAX_TRANSACTIONS`] leaves.
+const MAX_HEIGHT: usize = 32;
+
+/// A BIP37 merkle block, sent in the `merkleblock` message to SPV clients: a block header and a
+/// partial merkle tree proving which transactions matched the filter are in the block.
+///
+/// The visitor is called for the header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MerkleBlock<'a> {
+    slice: &'a [u8],
+    header: BlockHeader<'a>,
+    total_transactions: u32,
+    hashes: &'a [u8],
+    flags: &'a [u8],
+}
+
+impl<'a> Visit<'a> for MerkleBlock<'a> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        let header = BlockHeader::visit(slice, visit)?;
+        let total_transactions = U32::parse(header.remaining())?;
+        let Len { consumed, n } = parse_len(total_transactions.remaining())?;
+        let hashes_len = usize::try_from(n)
+            .ok()
+            .and_then(|n| n.checked_mul(32))
+            .ok_or(crate::Error::TooLarge)?;
+        let hashes = read_slice(&total_transactions.remaining()[consumed..], hashes_len)?;
+        let Len { consumed, n } = parse_len(hashes.remaining())?;
+        let flags = read_slice(&hashes.remaining()[consumed..], n as usize)?;
+
+        let remaining = flags.remaining();
+        let consumed = slice.len() - remaining.len();
+        let parsed = MerkleBlock {
+            slice: &slice[..consumed],
+            header: header.parsed_owned(),
+            total_transactions: total_transactions.parsed_owned().into(),
+            hashes: hashes.parsed_owned(),
+            flags: flags.parsed_owned(),
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> MerkleBlock<'a> {
+    /// Returns the header of the block.
+    pub fn header(&self) -> &BlockHeader<'a> {
+        &self.header
+    }
+
+    /// Returns the number of transactions in the block.
+    pub fn total_transactions(&self) -> u32 {
+        self.total_transactions
+    }
+
+    /// Returns the number of hashes of the partial merkle tree.
+    pub fn total_hashes(&self) -> usize {
+        self.hashes.len() / 32
+    }
+
+    /// Validates the partial merkle tree and returns an iterator over the txids of the matched
+    /// transactions, see [`MerkleBlock::extract_matches_with()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn extract_matches(&self) -> Result<impl Iterator<Item = [u8; 32]> + 'a, MerkleBlockError> {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.extract_matches_with(sha256d::Hash::engine())
+    }
+
+    /// Validates the partial merkle tree like Bitcoin Core `CPartialMerkleTree::ExtractMatches`,
+    /// every node is hashed with a clone of `hasher`, and returns an iterator over the txids of
+    /// the matched transactions, in byte order (not reversed) and in the order of the block.
+    ///
+    /// The tree is valid if its root is the merkle root in the header, all the flag bits and
+    /// hashes are used and no two sibling nodes have the same hash.
+    pub fn extract_matches_with<H: TxidHasher + Clone>(
+        &self,
+        hasher: H,
+    ) -> Result<impl Iterator<Item = [u8; 32]> + 'a, MerkleBlockError> {
+        if self.total_transactions == 0 {
+            return Err(MerkleBlockError::NoTransactions);
+        }
+        if self.total_transactions > MAX_TRANSACTIONS {
+            return Err(MerkleBlockError::TooManyTransactions);
+        }
+        let total_hashes = self.total_hashes();
+        if total_hashes > self.total_transactions as usize || self.flags.len() * 8 < total_hashes {
+            return Err(MerkleBlockError::BadFormat);
+        }
+
+        let mut traversal = Traversal::new(self);
+        let root = traversal.hash(traversal.height, 0, &hasher)?;
+        // the flag bits are padded to a whole byte
+        if traversal.bits_used.div_ceil(8) != self.flags.len()
+            || traversal.hashes_used != total_hashes
+        {
+            return Err(MerkleBlockError::Unused);
+        }
+        if &root != self.header.merkle_root() {
+            return Err(MerkleBlockError::Mismatch);
+        }
+        Ok(Matches::new(Traversal::new(self)))
+    }
+}
+
+impl<'a> AsRef<[u8]> for MerkleBlock<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// The depth first traversal of a partial merkle tree, consuming flag bits and hashes.
+struct Traversal<'a> {
+    total_transactions: u32,
+    height: u32,
+    hashes: &'a [u8],
+    flags: &'a [u8],
+    bits_used: usize,
+    hashes_used: usize,
+}
+
+impl<'a> Traversal<'a> {
+    fn new(merkle_block: &MerkleBlock<'a>) -> Self {
+        let mut traversal = Traversal {
+            total_transactions: merkle_block.total_transactions,
+            height: 0,
+            hashes: merkle_block.hashes,
+            flags: merkle_block.flags,
+            bits_used: 0,
+            hashes_used: 0,
+        };
+        while traversal.width(traversal.height) > 1 {
+            traversal.height += 1;
+        }
+        traversal
+    }
+
+    /// The number of nodes at the given height of the tree.
+    fn width(&self, height: u32) -> u32 {
+        (self.total_transactions + (1 << height) - 1) >> height
+    }
+
+    fn next_bit(&mut self) -> Result<bool, MerkleBlockError> {
+        let byte = self
+            .flags
+            .get(self.bits_used / 8)
+            .ok_or(MerkleBlockError::Overflow)?;
+        let bit = byte & (1 << (self.bits_used % 8)) != 0;
+        self.bits_used += 1;
+        Ok(bit)
+    }
+
+    fn next_hash(&mut self) -> Result<[u8; 32], MerkleBlockError> {
+        let start = self.hashes_used * 32;
+        let hash = self
+            .hashes
+            .get(start..start + 32)
+            .ok_or(MerkleBlockError::Overflow)?;
+        self.hashes_used += 1;
+        Ok(hash.try_into().expect("32 bytes"))
+    }
+
+    /// Returns the hash of the node at the given height and position, descending in its subtrees
+    /// if flagged as parent of a match.
+    fn hash<H: TxidHasher + Clone>(
+        &mut self,
+        height: u32,
+        pos: u32,
+        hasher: &H,
+    ) -> Result<[u8; 32], MerkleBlockError> {
+        let parent_of_match = self.next_bit()?;
+        if height == 0 || !parent_of_match {
+            return self.next_hash();
+        }
+        let left = self.hash(height - 1, pos * 2, hasher)?;
+        let right = if pos * 2 + 1 < self.width(height - 1) {
+            let right = self.hash(height - 1, pos * 2 + 1, hasher)?;
+            if right == left {
+                return Err(MerkleBlockError::IdenticalHashes);
+            }
+            right
+        } else {
+            left
+        };
+        let mut hasher = hasher.clone();
+        hasher.update(&left);
+        hasher.update(&right);
+        Ok(hasher.finalize_double())
+    }
+}
+
+/// Iterator over the matched txids of an already validated partial merkle tree, replaying the
+/// traversal with a stack of the nodes still to visit instead of recursion.
+struct Matches<'a> {
+    traversal: Traversal<'a>,
+    /// Height and position of the nodes to visit.
+    stack: [(u32, u32); MAX_HEIGHT],
+    len: usize,
+}
+
+impl<'a> Matches<'a> {
+    fn new(traversal: Traversal<'a>) -> Self {
+        let mut stack = [(0, 0); MAX_HEIGHT];
+        stack[0] = (traversal.height, 0);
+        Matches {
+            traversal,
+            stack,
+            len: 1,
+        }
+    }
+}
+
+impl<'a> Iterator for Matches<'a> {
+    type Item = [u8; 32];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.len > 0 {
+            self.len -= 1;
+            let (height, pos) = self.stack[self.len];
+            let parent_of_match = self.traversal.next_bit().expect("validated");
+            if height == 0 || !parent_of_match {
+                let hash = self.traversal.next_hash().expect("validated");
+                if height == 0 && parent_of_match {
+                    return Some(hash);
+                }
+            } else {
+                // the right subtree is pushed first, so that the left one is visited first
+                if pos * 2 + 1 < self.traversal.width(height - 1) {
+                    self.stack[self.len] = (height - 1, pos * 2 + 1);
+                    self.len += 1;
+                }
+                self.stack[self.len] = (height - 1, pos * 2);
+                self.len += 1;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "bitcoin_hashes"))]
+mod test {
+    use crate::{bsl::MerkleBlock, MerkleBlockError, Parse};
+    use bitcoin::{
+        consensus::{deserialize, serialize},
+        hashes::{hex::FromHex, Hash},
+        util::merkleblock::{MerkleBlock as BitcoinMerkleBlock, PartialMerkleTree},
+        Txid,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    fn matches(bytes: &[u8]) -> Result<Vec<[u8; 32]>, MerkleBlockError> {
+        let merkle_block = MerkleBlock::parse_all(bytes).unwrap();
+        merkle_block.extract_matches().map(|m| m.collect())
+    }
+
+    #[test]
+    fn merkle_block() {
+        // from rust-bitcoin, `gettxoutproof` of a single transaction in testnet block 0x13b8a
+        let bytes = hex_lit::hex!("0100000090f0a9f110702f808219ebea1173056042a714bad51b916cb6800000000000005275289558f51c9966699404ae2294730c3c9f9bda53523ce50e9b95e558da2fdb261b4d4c86041b1ab1bf930900000005fac7708a6e81b2a986dea60db2663840ed141130848162eb1bd1dee54f309a1b2ee1e12587e497ada70d9bd10d31e83f0a924825b96cb8d04e8936d793fb60db7ad8b910d0c7ba2369bc7f18bb53d80e1869ba2c32274996cebe1ae264bc0e2289189ff0316cdc10511da71da757e553cada9f3b5b1434f3923673adb57d83caac392c38af156d6fc30b55fad4112df2b95531e68114e9ad10011e72f7b7cfdb025700");
+        let merkle_block = MerkleBlock::parse_all(&bytes[..]).unwrap();
+        assert_eq!(merkle_block.total_transactions(), 9);
+        assert_eq!(merkle_block.total_hashes(), 5);
+        assert_eq!(merkle_block.as_ref(), &bytes[..]);
+        let txid =
+            Txid::from_hex("220ebc64e21abece964927322cba69180ed853bb187fbc6923bac7d010b9d87a")
+                .unwrap();
+        assert_eq!(matches(&bytes), Ok(vec![txid.into_inner()]));
+
+        let mut mismatch = bytes;
+        mismatch[36] ^= 1;
+        assert_eq!(matches(&mismatch), Err(MerkleBlockError::Mismatch));
+
+        // the flags start after the header, the number of transactions and the 5 hashes
+        let flags_start = 80 + 4 + 1 + 5 * 32;
+        assert_eq!(bytes[flags_start], 2);
+
+        // one more flag byte
+        let mut unused = bytes.to_vec();
+        unused[flags_start] = 3;
+        unused.push(0);
+        assert_eq!(matches(&unused), Err(MerkleBlockError::Unused));
+
+        // flags requiring more hashes than given
+        let mut overflow = bytes;
+        overflow[flags_start + 1] = 0xff;
+        assert_eq!(matches(&overflow), Err(MerkleBlockError::Overflow));
+
+        let mut no_transactions = bytes;
+        no_transactions[80..84].copy_from_slice(&[0; 4]);
+        assert_eq!(
+            matches(&no_transactions),
+            Err(MerkleBlockError::NoTransactions)
+        );
+        let mut bad_format = bytes;
+        bad_format[80..84].copy_from_slice(&[4, 0, 0, 0]);
+        assert_eq!(matches(&bad_format), Err(MerkleBlockError::BadFormat));
+    }
+
+    #[test]
+    fn merkle_block_from_block() {
+        let block: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        let txids: Vec<Txid> = block.txdata.iter().map(|tx| tx.txid()).collect();
+
+        let predicates: [fn(usize) -> bool; 6] = [
+            |_| false,
+            |_| true,
+            |i| i == 0,
+            |i| i == 2499,
+            |i| i.is_multiple_of(7),
+            |i| i == 3 || i == 1000 || i == 1001,
+        ];
+        for predicate in predicates {
+            let expected: Vec<[u8; 32]> = txids
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| predicate(*i))
+                .map(|(_, txid)| txid.into_inner())
+                .collect();
+            let merkle_block = BitcoinMerkleBlock::from_block_with_predicate(&block, |txid| {
+                predicate(txids.iter().position(|t| t == txid).unwrap())
+            });
+            assert_eq!(matches(&serialize(&merkle_block)), Ok(expected));
+        }
+    }
+
+    #[test]
+    fn merkle_block_malleability() {
+        // from Bitcoin Core pmt_tests, the last two txids are duplicated
+        let txids: Vec<Txid> = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 9, 10]
+            .iter()
+            .map(|i| Txid::from_hex(&format!("{:064x}", i)).unwrap())
+            .collect();
+        let mut flags = [false; 12];
+        flags[9] = true;
+        flags[10] = true;
+        let tree = PartialMerkleTree::from_txids(&txids, &flags);
+
+        let header: bitcoin::BlockHeader =
+            deserialize(&crate::test_common::GENESIS_BLOCK_HEADER).unwrap();
+        let bytes = serialize(&BitcoinMerkleBlock { header, txn: tree });
+        assert_eq!(matches(&bytes), Err(MerkleBlockError::IdenticalHashes));
+    }
+}
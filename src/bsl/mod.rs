@@ -10,29 +10,129 @@
 //! Other than the slice from they have been created these object may contain fields that are needed
 //! from the caller without requiring re-parsing.
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod blk_file;
 mod block;
 mod block_header;
+mod block_header_owned;
+#[cfg(feature = "alloc")]
+mod block_owned;
+#[cfg(feature = "bytes")]
+mod buf;
+#[cfg(feature = "bitcoin_hashes")]
+mod cached_transaction;
+mod compact_block;
+pub mod constants;
+mod encode;
+mod error_offset;
+mod filter;
+mod header_chain;
+#[cfg(feature = "alloc")]
+mod indexed_block;
 mod len;
+mod lock_time;
+mod maybe_owned;
+mod merkle;
+mod merkle_block;
 mod out_point;
+mod out_point_owned;
+mod parse;
+mod parse_limits;
 mod script;
+mod sighash;
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(feature = "std")]
+mod stream;
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub mod testing;
 mod transaction;
+#[cfg(feature = "alloc")]
+mod transaction_owned;
 mod tx_in;
 mod tx_ins;
 mod tx_out;
 mod tx_outs;
+mod txid_hasher;
+mod undo;
+pub mod visitors;
 mod witness;
 mod witnesses;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod writer;
 
-pub use block::Block;
-pub use block_header::BlockHeader;
+#[cfg(feature = "arbitrary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+pub use self::arbitrary::{ArbitraryBlock, ArbitraryTransaction, Corruption};
+pub use blk_file::{xor_slice, BlkFileIter};
+pub use block::{Block, MAX_BLOCK_SIGOPS_COST, MAX_BLOCK_WEIGHT};
+pub use block_header::{BlockHeader, POW_LIMIT, VERSION_BITS_NUM_BITS};
+pub use block_header_owned::BlockHeaderOwned;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use block_owned::BlockOwned;
+pub use compact_block::{BlockTransactions, BlockTransactionsRequest, HeaderAndShortIds};
+pub use encode::Encode;
+pub use filter::{filter_header_with, BlockFilter, FilterHeaders, MAX_FILTER_HEADERS};
+pub use header_chain::{
+    count_signalling, median_time_past, validate_header_chain_with, ChainInfo, MEDIAN_TIME_SPAN,
+    SIGNALLING_PERIOD,
+};
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use indexed_block::IndexedBlock;
 pub use len::parse_len;
 pub use len::Len;
+pub use len::LenKind;
+pub use lock_time::{LockTime, LOCK_TIME_THRESHOLD};
+pub use maybe_owned::{IntoOwned, MaybeOwned};
+pub use merkle_block::MerkleBlock;
 pub use out_point::OutPoint;
-pub use script::Script;
-pub use transaction::Transaction;
+pub use out_point_owned::OutPointOwned;
+pub use parse::{parse, parse_exact};
+pub use parse_limits::ParseLimits;
+pub use script::{Script, ScriptKind};
+pub use sighash::{
+    SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_DEFAULT, SIGHASH_NONE, SIGHASH_SINGLE,
+};
+#[cfg(feature = "stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stats")))]
+pub use stats::{ParseStats, StatsVisitor};
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use stream::AsyncReadParser;
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use stream::ReadParser;
+pub use transaction::{ParseConfig, SizeLimits, Transaction, MAX_MONEY};
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub use transaction_owned::TransactionOwned;
 pub use tx_in::TxIn;
-pub use tx_ins::TxIns;
+pub use tx_ins::{TxInIterator, TxIns};
 pub use tx_out::TxOut;
-pub use tx_outs::TxOuts;
-pub use witness::Witness;
+pub use tx_outs::{TxOutIterator, TxOuts};
+pub use txid_hasher::TxidHasher;
+pub use undo::{BlockUndo, CompressedScript, SpentCoin, TxUndo};
+pub use witness::{Witness, WitnessIterator};
 pub use witnesses::Witnesses;
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub use cached_transaction::CachedTransaction;
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub use compact_block::short_id_with_keys;
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "bitcoin_hashes"))))]
+pub use filter::build_basic_filter;
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub use filter::filter_header;
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub use header_chain::{validate_header_chain, validate_p2p_headers};
+#[cfg(feature = "bitcoin_hashes")]
+pub use sighash::{TapLeafHash, TapLeafTag, TapSighashHash, TapSighashTag};
@@ -12,9 +12,18 @@ impl<'a> AsRef<[u8]> for OutPoint<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for OutPoint<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 impl<'a> Parse<'a> for OutPoint<'a> {
     /// Parse the out point from the given slice
-    fn parse(slice: &'a [u8]) -> SResult<Self> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
         let outpoint = read_slice(slice, 36usize)?;
         Ok(ParseResult::new(
             outpoint.remaining(),
@@ -26,7 +35,7 @@ impl<'a> Parse<'a> for OutPoint<'a> {
 }
 impl<'a> OutPoint<'a> {
     /// Returns the transaction txid of the previous output
-    pub fn txid(&self) -> &[u8] {
+    pub fn txid(&self) -> &'a [u8] {
         &self.slice[..32]
     }
     /// Returns the vout of the previous output
@@ -37,16 +46,23 @@ impl<'a> OutPoint<'a> {
             .expect("slice length ensured by parsing");
         u32::from_le_bytes(arr)
     }
+    /// Returns true if this is the null outpoint (txid all zeros and vout `0xffffffff`) which is
+    /// found in coinbase inputs.
+    pub fn is_null(&self) -> bool {
+        self.txid() == [0u8; 32] && self.vout() == u32::MAX
+    }
 }
 
 #[cfg(feature = "redb")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
 impl<'o> redb::RedbValue for OutPoint<'o> {
-    type SelfType<'a> = OutPoint<'a>
+    type SelfType<'a>
+        = OutPoint<'a>
     where
         Self: 'a;
 
-    type AsBytes<'a> =  &'a [u8]
+    type AsBytes<'a>
+        = &'a [u8]
     where
         Self: 'a;
 
@@ -84,21 +100,21 @@ impl<'o> redb::RedbKey for OutPoint<'o> {
 
 #[cfg(feature = "bitcoin")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
-impl<'a> Into<bitcoin::OutPoint> for &OutPoint<'a> {
-    fn into(self) -> bitcoin::OutPoint {
+impl<'a> From<&OutPoint<'a>> for bitcoin::OutPoint {
+    fn from(out_point: &OutPoint<'a>) -> Self {
         use bitcoin::hashes::Hash;
         bitcoin::OutPoint {
-            txid: bitcoin::Txid::from_inner(self.txid().try_into().unwrap()),
-            vout: self.vout(),
+            txid: bitcoin::Txid::from_inner(out_point.txid().try_into().unwrap()),
+            vout: out_point.vout(),
         }
     }
 }
 
 #[cfg(feature = "bitcoin")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
-impl<'a> Into<bitcoin::OutPoint> for OutPoint<'a> {
-    fn into(self) -> bitcoin::OutPoint {
-        (&self).into()
+impl<'a> From<OutPoint<'a>> for bitcoin::OutPoint {
+    fn from(out_point: OutPoint<'a>) -> Self {
+        (&out_point).into()
     }
 }
 
@@ -123,6 +139,12 @@ mod test {
         let txid: Vec<_> = (0..32).collect();
         let out_point = OutPoint::parse(&vec[..]).unwrap();
         assert_eq!(out_point.parsed().txid(), &txid[..]);
+        assert!(!out_point.parsed().is_null());
+
+        let mut null = [0u8; 36];
+        null[32..].copy_from_slice(&[0xff; 4]);
+        assert!(OutPoint::parse(&null).unwrap().parsed().is_null());
+        assert!(!OutPoint::parse(&[0u8; 36]).unwrap().parsed().is_null());
     }
 
     #[cfg(feature = "redb")]
@@ -0,0 +1,195 @@
+use crate::bsl::{IntoOwned, OutPoint};
+use crate::Parse;
+
+/// An owned out point, keeping a copy of the 36 bytes of a parsed [`OutPoint`].
+///
+/// Unlike [`OutPoint`], as a `redb` key it sorts by txid then by vout, instead of comparing the
+/// serialized bytes which contain the vout in little endian.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OutPointOwned {
+    data: [u8; 36],
+}
+
+impl OutPointOwned {
+    /// Creates the owned out point spending the output `vout` of the transaction `txid`.
+    pub fn new(txid: [u8; 32], vout: u32) -> Self {
+        let mut data = [0u8; 36];
+        data[..32].copy_from_slice(&txid);
+        data[32..].copy_from_slice(&vout.to_le_bytes());
+        OutPointOwned { data }
+    }
+
+    /// Returns the borrowed out point.
+    pub fn as_out_point(&self) -> OutPoint<'_> {
+        OutPoint::parse(&self.data[..])
+            .expect("36 bytes are a valid out point")
+            .parsed_owned()
+    }
+
+    /// Returns the transaction txid of the previous output
+    pub fn txid(&self) -> &[u8] {
+        &self.data[..32]
+    }
+
+    /// Returns the vout of the previous output
+    pub fn vout(&self) -> u32 {
+        self.as_out_point().vout()
+    }
+
+    /// Returns the serialized out point bytes.
+    pub fn into_bytes(self) -> [u8; 36] {
+        self.data
+    }
+}
+
+impl<'a> OutPoint<'a> {
+    /// Copies the 36 bytes of the out point.
+    pub fn to_owned(&self) -> OutPointOwned {
+        self.into()
+    }
+}
+
+impl<'a> IntoOwned for OutPoint<'a> {
+    type Owned = OutPointOwned;
+
+    fn into_owned(self) -> OutPointOwned {
+        self.into()
+    }
+}
+
+impl<'a> From<OutPoint<'a>> for OutPointOwned {
+    fn from(out_point: OutPoint<'a>) -> Self {
+        (&out_point).into()
+    }
+}
+
+impl<'a> From<&OutPoint<'a>> for OutPointOwned {
+    fn from(out_point: &OutPoint<'a>) -> Self {
+        let data = out_point
+            .as_ref()
+            .try_into()
+            .expect("slice length granted during parsing");
+        OutPointOwned { data }
+    }
+}
+
+impl AsRef<[u8]> for OutPointOwned {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+impl redb::RedbValue for OutPointOwned {
+    type SelfType<'a>
+        = OutPointOwned
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        Some(36)
+    }
+
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        OutPoint::parse_all(data)
+            .expect("inserted data is not an OutPoint")
+            .into()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.as_ref()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("OutPointOwned")
+    }
+}
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+impl redb::RedbKey for OutPointOwned {
+    /// Compares the txids bytes, then the vouts as numbers.
+    fn compare(data1: &[u8], data2: &[u8]) -> core::cmp::Ordering {
+        let vout = |data: &[u8]| {
+            OutPoint::parse(data)
+                .expect("inserted data is not an OutPoint")
+                .parsed()
+                .vout()
+        };
+        data1[..32]
+            .cmp(&data2[..32])
+            .then_with(|| vout(data1).cmp(&vout(data2)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{OutPoint, OutPointOwned},
+        Parse,
+    };
+
+    #[test]
+    fn out_point_owned() {
+        let mut bytes = [1u8; 36];
+        bytes[32..].copy_from_slice(&300u32.to_le_bytes());
+        let out_point = OutPoint::parse(&bytes[..]).unwrap().parsed_owned();
+        let owned = OutPointOwned::new([1u8; 32], 300);
+        assert_eq!(OutPointOwned::from(&out_point), owned);
+        assert_eq!(owned.as_out_point(), out_point);
+        assert_eq!(owned.txid(), &[1u8; 32][..]);
+        assert_eq!(owned.vout(), 300);
+        assert_eq!(owned.into_bytes(), bytes);
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    fn out_point_owned_redb_order() {
+        use redb::ReadableTable;
+
+        const TABLE: redb::TableDefinition<OutPointOwned, u32> =
+            redb::TableDefinition::new("outputs");
+        let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        let db = redb::Database::create(path).unwrap();
+
+        // 256 is lower than 1 comparing the little endian bytes
+        let keys = [
+            ([2u8; 32], 1),
+            ([1u8; 32], 256),
+            ([1u8; 32], 1),
+            ([2u8; 32], 0),
+        ];
+        let write_txn = db.begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(TABLE).unwrap();
+            for (txid, vout) in keys {
+                table
+                    .insert(&OutPointOwned::new(txid, vout), &vout)
+                    .unwrap();
+            }
+        }
+        write_txn.commit().unwrap();
+
+        let read_txn = db.begin_read().unwrap();
+        let table = read_txn.open_table(TABLE).unwrap();
+        let sorted: Vec<_> = table
+            .iter()
+            .unwrap()
+            .map(|(k, v)| (k.value().txid()[0], k.value().vout(), v.value()))
+            .collect();
+        assert_eq!(sorted, vec![(1, 1, 1), (1, 256, 256), (2, 0, 0), (2, 1, 1)]);
+    }
+}
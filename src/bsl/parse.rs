@@ -0,0 +1,125 @@
+use crate::{Error, Parse};
+
+/// Parse a `T` from the start of `slice`, returning it along with the remaining bytes.
+///
+/// Like [`Parse::parse`], without the [`crate::ParseResult`] accessors at the call site:
+///
+/// ```
+/// use bitcoin_slices::bsl;
+///
+/// let block = bitcoin_test_data::blocks::mainnet_702861();
+/// let (header, rest): (bsl::BlockHeader, _) = bsl::parse(block).unwrap();
+/// assert_eq!(header.time(), 1633002641);
+/// let tx_count = bsl::parse_len(rest).unwrap();
+/// assert_eq!(tx_count.n(), 2500);
+/// ```
+pub fn parse<'a, T: Parse<'a>>(slice: &'a [u8]) -> Result<(T, &'a [u8]), Error> {
+    let parsed = T::parse(slice)?;
+    let remaining = parsed.remaining();
+    Ok((parsed.parsed_owned(), remaining))
+}
+
+/// Parse a `T` consuming all the bytes of `slice`, returns [`Error::TrailingBytes`] if there
+/// are bytes after it, like [`Parse::parse_all`].
+///
+/// ```
+/// use bitcoin_slices::{bsl, Error};
+///
+/// let bytes = bitcoin_test_data::blocks::mainnet_702861();
+/// let block: bsl::Block = bsl::parse_exact(bytes).unwrap();
+/// assert_eq!(block.total_transactions(), 2500);
+/// let trailing = [bytes, &[0]].concat();
+/// let error = bsl::parse_exact::<bsl::Block>(&trailing[..]).unwrap_err();
+/// assert_eq!(error, Error::TrailingBytes(1));
+/// ```
+pub fn parse_exact<'a, T: Parse<'a>>(slice: &'a [u8]) -> Result<T, Error> {
+    T::parse_all(slice)
+}
+
+#[cfg(test)]
+mod test {
+    use core::fmt::Debug;
+
+    use crate::{
+        bsl::{self, parse, parse_exact},
+        test_common::{GENESIS_BLOCK, GENESIS_BLOCK_HEADER, LEGACY_TX, SEGWIT_TX},
+        Error, Parse,
+    };
+
+    /// Checks the free functions agree with the fields of the [`crate::ParseResult`].
+    fn check<'a, T: Parse<'a> + Clone + PartialEq + Debug>(slice: &'a [u8]) {
+        match (parse::<T>(slice), T::parse(slice)) {
+            (Ok((parsed, remaining)), Ok(result)) => {
+                assert_eq!(remaining, result.remaining());
+                assert_eq!(slice.len() - remaining.len(), result.consumed());
+                assert_eq!(parsed.as_ref(), &slice[..result.consumed()]);
+                assert_eq!(parsed, result.clone().parsed_owned());
+                let expected = match remaining.len() {
+                    0 => Ok(parsed),
+                    trailing => Err(Error::TrailingBytes(trailing as u32)),
+                };
+                assert_eq!(parse_exact::<T>(slice), expected);
+            }
+            (Err(error), Err(expected)) => {
+                assert_eq!(error, expected);
+                assert_eq!(parse_exact::<T>(slice), Err(expected));
+            }
+            (free, result) => panic!("{:?} disagrees with {:?}", free, result),
+        }
+    }
+
+    fn check_all(slice: &[u8]) {
+        check::<bsl::OutPoint>(slice);
+        check::<bsl::Script>(slice);
+        check::<bsl::TxIn>(slice);
+        check::<bsl::TxIns>(slice);
+        check::<bsl::TxOut>(slice);
+        check::<bsl::TxOuts>(slice);
+        check::<bsl::Witness>(slice);
+        check::<bsl::Transaction>(slice);
+        check::<bsl::BlockHeader>(slice);
+        check::<bsl::Block>(slice);
+        check::<bsl::SpentCoin>(slice);
+        check::<bsl::TxUndo>(slice);
+        check::<bsl::BlockUndo>(slice);
+        check::<bsl::MerkleBlock>(slice);
+        check::<bsl::HeaderAndShortIds>(slice);
+        check::<bsl::BlockTransactions>(slice);
+        check::<bsl::BlockTransactionsRequest>(slice);
+        check::<bsl::BlockFilter>(slice);
+        check::<bsl::FilterHeaders>(slice);
+    }
+
+    #[test]
+    fn parse_agrees() {
+        let vectors = [
+            &SEGWIT_TX[..],
+            &LEGACY_TX[..],
+            &GENESIS_BLOCK_HEADER[..],
+            &GENESIS_BLOCK[..],
+        ];
+        for bytes in vectors {
+            let trailing = [bytes, &[0, 1]].concat();
+            for end in 0..=trailing.len() {
+                check_all(&trailing[..end]);
+                check_all(&trailing[end..]);
+            }
+        }
+    }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn parse_agrees_arbitrary(bytes in proptest::collection::vec(0u8.., 0..300)) {
+            check_all(&bytes);
+        }
+
+        #[test]
+        fn parse_agrees_transaction(
+            tx in bsl::testing::transaction(bsl::testing::TxShape::default()),
+            trailing in proptest::collection::vec(0u8.., 0..4),
+        ) {
+            check_all(&[tx, trailing].concat());
+        }
+    }
+}
@@ -0,0 +1,299 @@
+use core::ops::ControlFlow;
+
+use crate::bsl::{Block, LenKind, Transaction, MAX_BLOCK_WEIGHT};
+use crate::{EmptyVisitor, Error, ParseLimit, ParseResult, SResult, Visit, Visitor};
+
+/// Limits checked while parsing with [`Transaction::parse_with_limits`] and
+/// [`Block::parse_with_limits`], failing with [`Error::LimitExceeded`] as soon as a declared count
+/// exceeds them, without walking the data following it.
+///
+/// The default is [`ParseLimits::CONSENSUS`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseLimits {
+    /// The maximum number of inputs of a transaction.
+    pub max_inputs: usize,
+
+    /// The maximum number of outputs of a transaction.
+    pub max_outputs: usize,
+
+    /// The maximum number of elements of a witness.
+    pub max_witness_elements: usize,
+
+    /// The maximum size of a witness element.
+    pub max_witness_element_size: usize,
+
+    /// The maximum size of a transaction, bytes after it are never read.
+    pub max_tx_size: usize,
+
+    /// The maximum size of a block, bytes after it are never read.
+    pub max_block_size: usize,
+}
+
+impl ParseLimits {
+    /// The limits derived from [`MAX_BLOCK_WEIGHT`]: every count or size above them can't fit the
+    /// weight of a valid block, for example an input weights at least 164.
+    pub const CONSENSUS: ParseLimits = ParseLimits {
+        max_inputs: MAX_BLOCK_WEIGHT / 164,
+        max_outputs: MAX_BLOCK_WEIGHT / 36,
+        max_witness_elements: MAX_BLOCK_WEIGHT,
+        max_witness_element_size: MAX_BLOCK_WEIGHT,
+        max_tx_size: MAX_BLOCK_WEIGHT,
+        max_block_size: MAX_BLOCK_WEIGHT,
+    };
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits::CONSENSUS
+    }
+}
+
+/// Breaks the visit as soon as a limit is exceeded, remembering which.
+struct Limiter<'l> {
+    limits: &'l ParseLimits,
+    exceeded: Option<ParseLimit>,
+}
+
+impl<'l> Limiter<'l> {
+    fn check(&mut self, value: usize, max: usize, limit: ParseLimit) {
+        if value > max {
+            self.exceeded = Some(limit);
+        }
+    }
+
+    /// Parse `slice` up to `max_size` with `parse`, converting errors due to the limits.
+    fn visit<'a, T: AsRef<[u8]>, V: Visitor<'a>>(
+        limits: &'l ParseLimits,
+        slice: &'a [u8],
+        max_size: (usize, ParseLimit),
+        visit: &mut V,
+        parse: impl FnOnce(&'a [u8], &mut (Self, &mut V)) -> SResult<'a, T>,
+    ) -> SResult<'a, T> {
+        let (max_size, size_limit) = max_size;
+        let bounded = &slice[..slice.len().min(max_size)];
+        let mut pair = (
+            Limiter {
+                limits,
+                exceeded: None,
+            },
+            visit,
+        );
+        match parse(bounded, &mut pair) {
+            Ok(parsed) => Ok(ParseResult::new(
+                &slice[parsed.consumed()..],
+                parsed.parsed_owned(),
+            )),
+            Err(Error::VisitBreak) if pair.0.exceeded.is_some() => Err(Error::LimitExceeded(
+                pair.0.exceeded.expect("checked in the guard"),
+            )),
+            Err(Error::Needed(_)) if bounded.len() < slice.len() => {
+                Err(Error::LimitExceeded(size_limit))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<'l> Visitor<'_> for Limiter<'l> {
+    fn visit_transaction(&mut self, tx: &Transaction) {
+        let max = self.limits.max_tx_size;
+        self.check(tx.as_ref().len(), max, ParseLimit::TxSize)
+    }
+    fn visit_tx_ins(&mut self, total_inputs: usize) {
+        let max = self.limits.max_inputs;
+        self.check(total_inputs, max, ParseLimit::Inputs)
+    }
+    fn visit_tx_outs(&mut self, total_outputs: usize) {
+        let max = self.limits.max_outputs;
+        self.check(total_outputs, max, ParseLimit::Outputs)
+    }
+    fn visit_witness_total_element(&mut self, witness_total: usize) {
+        let max = self.limits.max_witness_elements;
+        self.check(witness_total, max, ParseLimit::WitnessElements)
+    }
+    fn visit_len(&mut self, kind: LenKind, value: u64, _encoded_len: usize) {
+        // checked before reading the element, so that a huge declared length fails fast
+        if kind == LenKind::WitnessElementLen {
+            let max = self.limits.max_witness_element_size;
+            let value = usize::try_from(value).unwrap_or(usize::MAX);
+            self.check(value, max, ParseLimit::WitnessElementSize)
+        }
+    }
+    fn control_flow(&self) -> ControlFlow<()> {
+        if self.exceeded.is_some() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+impl<'a> Transaction<'a> {
+    /// Parse a transaction like [`crate::Parse::parse`], checking the `limits`.
+    pub fn parse_with_limits(slice: &'a [u8], limits: &ParseLimits) -> SResult<'a, Self> {
+        Self::visit_with_limits(slice, &mut EmptyVisitor {}, limits)
+    }
+
+    /// Visit a transaction like [`Visit::visit`], checking the `limits`.
+    ///
+    /// `visit` receives also the call exceeding a limit, before the visit stops.
+    pub fn visit_with_limits<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        limits: &ParseLimits,
+    ) -> SResult<'a, Self> {
+        let max_size = (limits.max_tx_size, ParseLimit::TxSize);
+        Limiter::visit(limits, slice, max_size, visit, |s, v| Self::visit(s, v))
+    }
+}
+
+impl<'a> Block<'a> {
+    /// Parse a block like [`crate::Parse::parse`], checking the `limits`.
+    pub fn parse_with_limits(slice: &'a [u8], limits: &ParseLimits) -> SResult<'a, Self> {
+        Self::visit_with_limits(slice, &mut EmptyVisitor {}, limits)
+    }
+
+    /// Visit a block like [`Visit::visit`], checking the `limits`, see
+    /// [`Transaction::visit_with_limits`].
+    ///
+    /// The size of the transactions is checked after they are parsed, but the parsing never
+    /// reads past [`ParseLimits::max_block_size`].
+    pub fn visit_with_limits<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        limits: &ParseLimits,
+    ) -> SResult<'a, Self> {
+        let max_size = (limits.max_block_size, ParseLimit::BlockSize);
+        Limiter::visit(limits, slice, max_size, visit, |s, v| Self::visit(s, v))
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use crate::{
+        bsl::{Block, ParseLimits, Transaction, TxIn},
+        test_common::{GENESIS_BLOCK, LEGACY_TX, SEGWIT_TX},
+        Error, Parse, ParseLimit, Visit, Visitor,
+    };
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn parse_with_limits() {
+        let limits = ParseLimits::default();
+        for tx in [&SEGWIT_TX[..], &LEGACY_TX[..]] {
+            assert_eq!(
+                Transaction::parse_with_limits(tx, &limits),
+                Transaction::parse(tx)
+            );
+        }
+        let block = mainnet_702861();
+        assert_eq!(
+            Block::parse_with_limits(block, &limits),
+            Block::parse(block)
+        );
+        let with_trailing = [&SEGWIT_TX[..], &LEGACY_TX[..]].concat();
+        let tx = Transaction::parse_with_limits(&with_trailing, &limits).unwrap();
+        assert_eq!(tx.remaining(), &LEGACY_TX[..]);
+
+        let check = |tx: &[u8], limits: &ParseLimits, limit: ParseLimit| {
+            assert_eq!(
+                Transaction::parse_with_limits(tx, limits),
+                Err(Error::LimitExceeded(limit))
+            );
+        };
+        let zero = ParseLimits {
+            max_inputs: 0,
+            ..ParseLimits::default()
+        };
+        check(&LEGACY_TX, &zero, ParseLimit::Inputs);
+        let zero = ParseLimits {
+            max_outputs: 1,
+            ..ParseLimits::default()
+        };
+        check(&SEGWIT_TX, &zero, ParseLimit::Outputs);
+        let zero = ParseLimits {
+            max_witness_elements: 0,
+            ..ParseLimits::default()
+        };
+        check(&SEGWIT_TX, &zero, ParseLimit::WitnessElements);
+        let zero = ParseLimits {
+            max_witness_element_size: 31,
+            ..ParseLimits::default()
+        };
+        check(&SEGWIT_TX, &zero, ParseLimit::WitnessElementSize);
+        let size = ParseLimits {
+            max_tx_size: LEGACY_TX.len() - 1,
+            ..ParseLimits::default()
+        };
+        check(&LEGACY_TX, &size, ParseLimit::TxSize);
+        let size = ParseLimits {
+            max_tx_size: LEGACY_TX.len(),
+            ..ParseLimits::default()
+        };
+        assert!(Transaction::parse_with_limits(&LEGACY_TX, &size).is_ok());
+        // a truncated transaction is not over the limit
+        assert_eq!(
+            Transaction::parse_with_limits(&LEGACY_TX[..100], &limits),
+            Transaction::parse(&LEGACY_TX[..100])
+        );
+
+        let size = ParseLimits {
+            max_block_size: GENESIS_BLOCK.len() - 1,
+            ..ParseLimits::default()
+        };
+        assert_eq!(
+            Block::parse_with_limits(&GENESIS_BLOCK, &size),
+            Err(Error::LimitExceeded(ParseLimit::BlockSize))
+        );
+        let size = ParseLimits {
+            max_tx_size: 203,
+            ..ParseLimits::default()
+        };
+        assert_eq!(
+            Block::parse_with_limits(&GENESIS_BLOCK, &size),
+            Err(Error::LimitExceeded(ParseLimit::TxSize))
+        );
+    }
+
+    #[test]
+    fn fail_fast() {
+        struct Inputs(usize);
+        impl Visitor<'_> for Inputs {
+            fn visit_tx_in(&mut self, _vin: usize, _tx_in: &TxIn) {
+                self.0 += 1;
+            }
+        }
+
+        // declares u32::MAX inputs followed by valid inputs
+        let input = &LEGACY_TX[5..5 + 149];
+        let mut tx = LEGACY_TX[..4].to_vec();
+        tx.extend_from_slice(&[0xFE, 0xFF, 0xFF, 0xFF, 0xFF]);
+        for _ in 0..100 {
+            tx.extend_from_slice(input);
+        }
+        let mut inputs = Inputs(0);
+        assert_eq!(
+            Transaction::visit_with_limits(&tx, &mut inputs, &ParseLimits::default()),
+            Err(Error::LimitExceeded(ParseLimit::Inputs))
+        );
+        assert_eq!(inputs.0, 0);
+
+        // without limits all the inputs are walked before failing
+        let mut inputs = Inputs(0);
+        assert!(matches!(
+            Transaction::visit(&tx, &mut inputs),
+            Err(Error::Needed(_))
+        ));
+        assert_eq!(inputs.0, 100);
+
+        // declares a huge witness element in a truncated transaction
+        let mut tx = SEGWIT_TX[..185].to_vec();
+        tx.extend_from_slice(&[0xFE, 0xFF, 0xFF, 0xFF, 0x0F]);
+        assert_eq!(
+            Transaction::parse_with_limits(&tx, &ParseLimits::default()),
+            Err(Error::LimitExceeded(ParseLimit::WitnessElementSize))
+        );
+        assert!(matches!(Transaction::parse(&tx), Err(Error::Needed(_))));
+    }
+}
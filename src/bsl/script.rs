@@ -1,6 +1,7 @@
 use crate::{slice::read_slice, Parse, ParseResult, SResult};
 
-use super::len::{parse_len, Len};
+use super::len::{parse_len_with, Len};
+use super::Witness;
 
 /// The Script, this type could be found in transaction outputs as `script_pubkey` or in transaction
 /// inputs as `script_sig`.
@@ -15,8 +16,14 @@ pub struct Script<'a> {
 
 impl<'a> Parse<'a> for Script<'a> {
     /// Parse a script from the slice.
-    fn parse(slice: &'a [u8]) -> SResult<Self> {
-        let Len { consumed, n } = parse_len(slice)?;
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        Self::parse_with(slice, false)
+    }
+}
+impl<'a> Script<'a> {
+    /// Parse a script, if `lenient` accepting a non-minimal length.
+    pub(crate) fn parse_with(slice: &'a [u8], lenient: bool) -> SResult<'a, Self> {
+        let Len { consumed, n } = parse_len_with(slice, lenient)?;
         let n = n as usize;
         let remaining = &slice[consumed..];
         Ok(read_slice(remaining, n)?.map(|s| {
@@ -29,12 +36,286 @@ impl<'a> Parse<'a> for Script<'a> {
             )
         }))
     }
-}
-impl<'a> Script<'a> {
+
     /// return the script bytes (exclude the compact int representing the length)
-    pub fn script(&self) -> &[u8] {
+    pub fn script(&self) -> &'a [u8] {
         &self.slice[self.from..]
     }
+
+    /// Returns the kind of this script, matching the script bytes against the standard output
+    /// templates.
+    pub fn kind(&self) -> ScriptKind {
+        ScriptKind::from_script(self.script())
+    }
+}
+
+/// The kind of a script pubkey, as classified by [`Script::kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptKind {
+    /// Pay to public key hash: `OP_DUP OP_HASH160 <20 bytes> OP_EQUALVERIFY OP_CHECKSIG`
+    P2pkh,
+
+    /// Pay to script hash: `OP_HASH160 <20 bytes> OP_EQUAL`
+    P2sh,
+
+    /// Pay to witness public key hash: `OP_0 <20 bytes>`
+    P2wpkh,
+
+    /// Pay to witness script hash: `OP_0 <32 bytes>`
+    P2wsh,
+
+    /// Pay to taproot: `OP_1 <32 bytes>`
+    P2tr,
+
+    /// Provably unspendable script starting with `OP_RETURN`
+    OpReturn,
+
+    /// Witness program of another version or length: a version opcode `OP_0`..`OP_16` followed by
+    /// a push of 2 to 40 bytes.
+    WitnessUnknown {
+        /// The witness version, from 0 to 16
+        version: u8,
+        /// The length of the witness program, from 2 to 40
+        program_len: u8,
+    },
+
+    /// Any other script, like bare multisig or pay to public key
+    Bare,
+}
+
+impl ScriptKind {
+    pub(crate) fn from_script(script: &[u8]) -> Self {
+        match script {
+            [OP_DUP, OP_HASH160, 20, .., OP_EQUALVERIFY, OP_CHECKSIG] if script.len() == 25 => {
+                ScriptKind::P2pkh
+            }
+            [OP_HASH160, 20, .., OP_EQUAL] if script.len() == 23 => ScriptKind::P2sh,
+            [OP_RETURN, ..] => ScriptKind::OpReturn,
+            _ => match witness_program(script) {
+                Some((0, program)) if program.len() == 20 => ScriptKind::P2wpkh,
+                Some((0, program)) if program.len() == 32 => ScriptKind::P2wsh,
+                Some((1, program)) if program.len() == 32 => ScriptKind::P2tr,
+                Some((version, program)) => ScriptKind::WitnessUnknown {
+                    version,
+                    program_len: program.len() as u8,
+                },
+                None => ScriptKind::Bare,
+            },
+        }
+    }
+}
+
+/// Returns the version and the program of a witness program script: a version opcode
+/// `OP_0`..`OP_16` followed by a push of 2 to 40 bytes.
+pub(crate) fn witness_program(script: &[u8]) -> Option<(u8, &[u8])> {
+    match script {
+        [version @ (OP_0 | OP_1..=OP_16), len @ 2..=40, program @ ..]
+            if program.len() == *len as usize =>
+        {
+            let version = match *version {
+                OP_0 => 0,
+                v => v - OP_1 + 1,
+            };
+            Some((version, program))
+        }
+        _ => None,
+    }
+}
+
+/// Iterator over the instructions of a script, yielding the opcode and the pushed data, if the
+/// opcode is a push.
+///
+/// Like in Bitcoin Core the iteration stops when a push is truncated, use
+/// [`Instructions::position()`] to know where.
+pub(crate) struct Instructions<'a> {
+    script: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Instructions<'a> {
+    pub(crate) fn new(script: &'a [u8]) -> Self {
+        Instructions { script, pos: 0 }
+    }
+
+    /// The position in the script of the next instruction
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn read(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let data = self.script.get(self.pos..end)?;
+        self.pos = end;
+        Some(data)
+    }
+}
+
+impl<'a> Iterator for Instructions<'a> {
+    type Item = (u8, Option<&'a [u8]>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let opcode = *self.script.get(self.pos)?;
+        let start = self.pos;
+        self.pos += 1;
+        let len = match opcode {
+            0..=0x4b => Some(opcode as usize),
+            OP_PUSHDATA1 => self.read(1).map(|d| d[0] as usize),
+            OP_PUSHDATA2 => self
+                .read(2)
+                .map(|d| u16::from_le_bytes([d[0], d[1]]) as usize),
+            OP_PUSHDATA4 => self
+                .read(4)
+                .map(|d| u32::from_le_bytes([d[0], d[1], d[2], d[3]]) as usize),
+            _ => return Some((opcode, None)),
+        };
+        match len.and_then(|len| self.read(len)) {
+            Some(data) => Some((opcode, Some(data))),
+            None => {
+                self.pos = start;
+                None
+            }
+        }
+    }
+}
+
+pub(crate) const OP_PUSHDATA1: u8 = 0x4c;
+pub(crate) const OP_PUSHDATA2: u8 = 0x4d;
+pub(crate) const OP_PUSHDATA4: u8 = 0x4e;
+pub(crate) const OP_0: u8 = 0x00;
+pub(crate) const OP_RETURN: u8 = 0x6a;
+pub(crate) const OP_DUP: u8 = 0x76;
+pub(crate) const OP_EQUAL: u8 = 0x87;
+pub(crate) const OP_EQUALVERIFY: u8 = 0x88;
+pub(crate) const OP_HASH160: u8 = 0xa9;
+pub(crate) const OP_CODESEPARATOR: u8 = 0xab;
+pub(crate) const OP_1: u8 = 0x51;
+pub(crate) const OP_16: u8 = 0x60;
+pub(crate) const OP_CHECKSIG: u8 = 0xac;
+pub(crate) const OP_CHECKSIGVERIFY: u8 = 0xad;
+pub(crate) const OP_CHECKMULTISIG: u8 = 0xae;
+pub(crate) const OP_CHECKMULTISIGVERIFY: u8 = 0xaf;
+
+/// Maximum number of public keys in a multisig, counted as sigops when the count is not accurate.
+const MAX_PUBKEYS_PER_MULTISIG: u32 = 20;
+
+/// Count the signature operations in `script` like Bitcoin Core `GetSigOpCount`.
+///
+/// If `accurate` multisig operations preceded by `OP_1`..`OP_16` count as the number of keys,
+/// otherwise they always count as 20. Counting stops at a truncated push.
+pub(crate) fn sigop_count(script: &[u8], accurate: bool) -> u32 {
+    let mut count = 0u32;
+    let mut last_opcode = None;
+    for (opcode, _) in Instructions::new(script) {
+        count += match opcode {
+            OP_CHECKSIG | OP_CHECKSIGVERIFY => 1,
+            OP_CHECKMULTISIG | OP_CHECKMULTISIGVERIFY => match last_opcode {
+                Some(n @ OP_1..=OP_16) if accurate => (n - OP_1 + 1) as u32,
+                _ => MAX_PUBKEYS_PER_MULTISIG,
+            },
+            _ => 0,
+        };
+        last_opcode = Some(opcode);
+    }
+    count
+}
+
+/// Returns the redeem script of a P2SH input, the data of the last push of its `script_sig`, like
+/// Bitcoin Core does when counting P2SH signature operations.
+///
+/// Returns `None` if `script_sig` is not push only, meaning it contains opcodes other than pushes
+/// and `OP_1NEGATE`..`OP_16`, or has a truncated push. Small integers push an empty redeem script.
+pub(crate) fn p2sh_redeem_script(script_sig: &[u8]) -> Option<&[u8]> {
+    let mut instructions = Instructions::new(script_sig);
+    let mut redeem_script = &[][..];
+    for (opcode, data) in &mut instructions {
+        if opcode > OP_16 {
+            return None;
+        }
+        redeem_script = data.unwrap_or(&[]);
+    }
+    if instructions.position() == script_sig.len() {
+        Some(redeem_script)
+    } else {
+        None
+    }
+}
+
+/// Count the signature operations of spending a witness program like Bitcoin Core
+/// `WitnessSigOps`: 1 for P2WPKH, the accurate count of the witness script, the last witness
+/// element, for P2WSH and 0 for other versions.
+pub(crate) fn witness_sigop_count(version: u8, program: &[u8], witness: Option<Witness>) -> u32 {
+    match (version, program.len()) {
+        (0, 20) => 1,
+        (0, 32) => witness
+            .and_then(|witness| witness.iter().last())
+            .map_or(0, |witness_script| sigop_count(witness_script, true)),
+        _ => 0,
+    }
+}
+
+/// Returns the data of an `OP_RETURN` script, `script` must be `OP_RETURN` followed by at most one
+/// data push (`OP_0` and `OP_PUSHDATA*` included), an empty slice is returned when there is none.
+///
+/// Scripts not starting with `OP_RETURN`, with other opcodes like `OP_1`, more than one push or a
+/// truncated push return `None`.
+pub(crate) fn op_return_payload(script: &[u8]) -> Option<&[u8]> {
+    let (&first, rest) = script.split_first()?;
+    if first != OP_RETURN {
+        return None;
+    }
+    let mut instructions = Instructions::new(rest);
+    let payload = match instructions.next() {
+        None => &[][..],
+        Some((_, Some(data))) => data,
+        Some((_, None)) => return None,
+    };
+    if instructions.position() == rest.len() {
+        Some(payload)
+    } else {
+        None
+    }
+}
+
+/// Returns the hash committed by a BIP141 witness commitment output script, which is `OP_RETURN`,
+/// a 36 bytes push starting with `0xaa21a9ed` and then any data.
+pub(crate) fn witness_commitment(script_pubkey: &[u8]) -> Option<&[u8; 32]> {
+    const PREFIX: [u8; 6] = [OP_RETURN, 0x24, 0xaa, 0x21, 0xa9, 0xed];
+    if script_pubkey.starts_with(&PREFIX) {
+        script_pubkey.get(6..38)?.try_into().ok()
+    } else {
+        None
+    }
+}
+
+/// Returns the block height at the start of a coinbase `script_sig` as required by BIP34, meaning
+/// encoded exactly like Bitcoin Core `CScript() << height`: `OP_1`..`OP_16` for heights up to 16,
+/// otherwise a direct push of the minimal little-endian positive number.
+///
+/// Pushes longer than 3 bytes are rejected: heights from `8_388_608` aren't reached by any network
+/// while a 4 bytes push is how the `nBits` found at the start of pre-BIP34 coinbases looks like.
+pub(crate) fn bip34_height(script_sig: &[u8]) -> Option<u32> {
+    let (&opcode, rest) = script_sig.split_first()?;
+    match opcode {
+        OP_1..=OP_16 => Some((opcode - OP_1 + 1) as u32),
+        1..=3 => {
+            let data = rest.get(..opcode as usize)?;
+            let (&last, init) = data.split_last()?;
+            let minimal = last != 0 || init.last().is_some_and(|b| b & 0x80 != 0);
+            if last & 0x80 != 0 || !minimal {
+                return None;
+            }
+            let height = data
+                .iter()
+                .rev()
+                .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+            if height > 16 {
+                Some(height)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
 }
 
 impl<'a> AsRef<[u8]> for Script<'a> {
@@ -43,15 +324,34 @@ impl<'a> AsRef<[u8]> for Script<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for Script<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{bsl::Script, Error, Parse};
+    use crate::{
+        bsl::{
+            script::{
+                bip34_height, op_return_payload, p2sh_redeem_script, sigop_count,
+                witness_commitment, witness_program, Instructions,
+            },
+            Script, ScriptKind,
+        },
+        Error, Parse,
+    };
+    use hex_lit::hex;
 
     fn check(slice: &[u8], script_slice: &[u8]) {
         let script = Script::parse(slice);
         assert!(script.is_ok());
         let p = script.unwrap();
-        assert_eq!(p.remaining(), &[]);
+        assert_eq!(p.remaining(), &[0u8; 0]);
         assert_eq!(p.parsed().script(), script_slice);
     }
 
@@ -64,4 +364,235 @@ mod test {
         assert_eq!(Script::parse(&[1u8]), Err(Error::Needed(1)));
         assert_eq!(Script::parse(&[100u8]), Err(Error::Needed(100)));
     }
+
+    #[test]
+    fn instructions() {
+        // p2pkh
+        let script = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        let mut iter = Instructions::new(&script[..]);
+        assert_eq!(iter.next(), Some((0x76, None)));
+        assert_eq!(iter.next(), Some((0xa9, None)));
+        assert_eq!(iter.next(), Some((0x14, Some(&script[3..23]))));
+        assert_eq!(iter.next(), Some((0x88, None)));
+        assert_eq!(iter.next(), Some((0xac, None)));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.position(), script.len());
+
+        let script = hex!("004c02aabb4d0100cc4e01000000dd");
+        let ops: Vec<_> = Instructions::new(&script[..]).collect();
+        assert_eq!(
+            ops,
+            vec![
+                (0x00, Some(&[][..])),
+                (0x4c, Some(&[0xaa, 0xbb][..])),
+                (0x4d, Some(&[0xcc][..])),
+                (0x4e, Some(&[0xdd][..])),
+            ]
+        );
+
+        // truncated pushes stop the iteration
+        for script in [&hex!("51020a")[..], &hex!("514c")[..], &hex!("514d01")[..]] {
+            let mut iter = Instructions::new(script);
+            assert_eq!(iter.next(), Some((0x51, None)));
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.position(), 1);
+        }
+    }
+
+    #[test]
+    fn test_sigop_count() {
+        let p2pkh = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        assert_eq!(sigop_count(&p2pkh, false), 1);
+        assert_eq!(sigop_count(&p2pkh, true), 1);
+
+        // 1-of-2 bare multisig
+        let multisig = hex!("512102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b2103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b52ae");
+        assert_eq!(sigop_count(&multisig, false), 20);
+        assert_eq!(sigop_count(&multisig, true), 2);
+
+        // OP_CHECKSIGVERIFY OP_CHECKMULTISIGVERIFY without a preceding small int
+        assert_eq!(sigop_count(&hex!("adaf"), true), 21);
+        // OP_16 OP_CHECKMULTISIG
+        assert_eq!(sigop_count(&hex!("60ae"), true), 16);
+        // opcodes inside pushes are not counted
+        assert_eq!(sigop_count(&hex!("03acadae"), false), 0);
+        // counting stops at the truncated push
+        assert_eq!(sigop_count(&hex!("ac4c05acac"), false), 1);
+    }
+
+    #[test]
+    fn test_p2sh_redeem_script() {
+        assert_eq!(p2sh_redeem_script(&hex!("0000")), Some(&[0u8; 0][..]));
+        assert_eq!(
+            p2sh_redeem_script(&hex!("0002aabb")),
+            Some(&hex!("aabb")[..])
+        );
+        assert_eq!(p2sh_redeem_script(&hex!("4c02aabb51")), Some(&[0u8; 0][..]));
+
+        assert_eq!(p2sh_redeem_script(&[]), Some(&[0u8; 0][..]));
+
+        // not push only and truncated
+        assert_eq!(p2sh_redeem_script(&hex!("01aaac")), None);
+        assert_eq!(p2sh_redeem_script(&hex!("0002aa")), None);
+    }
+
+    #[test]
+    fn test_witness_program() {
+        let p2wpkh = [&hex!("0014")[..], &[1u8; 20][..]].concat();
+        assert_eq!(witness_program(&p2wpkh), Some((0, &[1u8; 20][..])));
+        let p2tr = [&hex!("5120")[..], &[2u8; 32][..]].concat();
+        assert_eq!(witness_program(&p2tr), Some((1, &[2u8; 32][..])));
+
+        assert_eq!(witness_program(&p2wpkh[..21]), None);
+        assert_eq!(witness_program(&hex!("0001aa")), None);
+        assert_eq!(
+            witness_program(&[&hex!("0029")[..], &[0u8; 41][..]].concat()),
+            None
+        );
+        assert_eq!(
+            witness_program(&[&hex!("4f14")[..], &[1u8; 20][..]].concat()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_op_return_payload() {
+        assert_eq!(op_return_payload(&hex!("6a")), Some(&[0u8; 0][..]));
+        assert_eq!(op_return_payload(&hex!("6a00")), Some(&[0u8; 0][..]));
+        assert_eq!(
+            op_return_payload(&hex!("6a02aabb")),
+            Some(&hex!("aabb")[..])
+        );
+        assert_eq!(
+            op_return_payload(&hex!("6a4c02aabb")),
+            Some(&hex!("aabb")[..])
+        );
+
+        // not OP_RETURN
+        assert_eq!(op_return_payload(&[]), None);
+        assert_eq!(op_return_payload(&hex!("51")), None);
+        assert_eq!(op_return_payload(&hex!("016a")), None);
+        // more than one push, non-push opcodes and truncated pushes
+        assert_eq!(op_return_payload(&hex!("6a01aa01bb")), None);
+        assert_eq!(op_return_payload(&hex!("6a51")), None);
+        assert_eq!(op_return_payload(&hex!("6a02aa")), None);
+    }
+
+    #[test]
+    fn test_witness_commitment() {
+        let commitment = [7u8; 32];
+        let script = [&hex!("6a24aa21a9ed")[..], &commitment[..]].concat();
+        assert_eq!(witness_commitment(&script), Some(&commitment));
+        let with_data = [&script[..], &[1u8, 2]].concat();
+        assert_eq!(witness_commitment(&with_data), Some(&commitment));
+
+        assert_eq!(witness_commitment(&script[..37]), None);
+        assert_eq!(witness_commitment(&script[1..]), None);
+        let mut wrong_prefix = script.clone();
+        wrong_prefix[2] = 0xab;
+        assert_eq!(witness_commitment(&wrong_prefix), None);
+    }
+
+    #[test]
+    fn test_bip34_height() {
+        assert_eq!(bip34_height(&hex!("51")), Some(1));
+        assert_eq!(bip34_height(&hex!("60ff")), Some(16));
+        assert_eq!(bip34_height(&hex!("0111")), Some(17));
+        assert_eq!(bip34_height(&hex!("027f00")), None);
+        assert_eq!(bip34_height(&hex!("028000")), Some(128));
+        assert_eq!(bip34_height(&hex!("03fc790300")), Some(227_836));
+        assert_eq!(bip34_height(&hex!("03ffff7f")), Some(8_388_607));
+
+        // empty, OP_0, heights up to 16 not using OP_N, negative, non minimal and truncated
+        assert_eq!(bip34_height(&[]), None);
+        assert_eq!(bip34_height(&hex!("00")), None);
+        assert_eq!(bip34_height(&hex!("0110")), None);
+        assert_eq!(bip34_height(&hex!("0181")), None);
+        assert_eq!(bip34_height(&hex!("03fc7900")), None);
+        assert_eq!(bip34_height(&hex!("03fc79")), None);
+        // 4 bytes pushes, like the nBits at the start of the block 1 coinbase
+        assert_eq!(bip34_height(&hex!("04ffff001d0104")), None);
+        assert_eq!(bip34_height(&hex!("4c01ff")), None);
+    }
+
+    #[test]
+    fn script_kind() {
+        let kind = |script: &[u8]| {
+            let mut bytes = vec![script.len() as u8];
+            bytes.extend(script);
+            Script::parse(&bytes).unwrap().parsed().kind()
+        };
+        use ScriptKind::*;
+        let p2pkh = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        assert_eq!(kind(&p2pkh), P2pkh);
+        assert_eq!(kind(&p2pkh[..24]), Bare);
+        assert_eq!(
+            kind(&hex!("a914748284390f9e263a4b766a75d0633c50426eb87587")),
+            P2sh
+        );
+        assert_eq!(
+            kind(&hex!("a914748284390f9e263a4b766a75d0633c50426eb87588")),
+            Bare
+        );
+        assert_eq!(
+            kind(&hex!("0014751e76e8199196d454941c45d1b3a323f1433bd6")),
+            P2wpkh
+        );
+        assert_eq!(
+            kind(&hex!(
+                "00201863143c14c5166804bd19203356da136c985678cd4d27a1b8c6329604903262"
+            )),
+            P2wsh
+        );
+        assert_eq!(
+            kind(&hex!(
+                "5120a60869f0dbcf1dc659c9cecbaf8050135ea9e8cdc487053f1dc6880949dc684c"
+            )),
+            P2tr
+        );
+        assert_eq!(kind(&hex!("6a")), OpReturn);
+        assert_eq!(
+            kind(&hex!(
+                "6a24aa21a9edf91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a3983704"
+            )),
+            OpReturn
+        );
+
+        // near misses: a 21 bytes v0 program, a 20 bytes v1 program, v16 and a v0 program longer
+        // than its push
+        assert_eq!(
+            kind(&hex!("0015751e76e8199196d454941c45d1b3a323f1433bd6aa")),
+            WitnessUnknown {
+                version: 0,
+                program_len: 21
+            }
+        );
+        assert_eq!(
+            kind(&hex!("5114751e76e8199196d454941c45d1b3a323f1433bd6")),
+            WitnessUnknown {
+                version: 1,
+                program_len: 20
+            }
+        );
+        assert_eq!(
+            kind(&hex!("60020000")),
+            WitnessUnknown {
+                version: 16,
+                program_len: 2
+            }
+        );
+        assert_eq!(
+            kind(&hex!("0014751e76e8199196d454941c45d1b3a323f1433bd6aa")),
+            Bare
+        );
+        // programs of 1 or 41 bytes are not witness programs
+        assert_eq!(kind(&hex!("5101aa")), Bare);
+        let mut long = vec![0x51, 41];
+        long.extend([0u8; 41]);
+        assert_eq!(kind(&long), Bare);
+
+        // bare multisig and empty script
+        assert_eq!(kind(&hex!("512102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b2103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b52ae")), Bare);
+        assert_eq!(kind(&[]), Bare);
+    }
 }
@@ -0,0 +1,896 @@
+use crate::{
+    bsl::{
+        len::encode_len,
+        script::{Instructions, OP_CODESEPARATOR},
+        Transaction, TxIn,
+    },
+    Error,
+};
+
+/// Sign all the inputs and all the outputs.
+pub const SIGHASH_ALL: u32 = 0x01;
+/// Sign all the inputs and none of the outputs.
+pub const SIGHASH_NONE: u32 = 0x02;
+/// Sign all the inputs and the output with the same index of the signed input.
+pub const SIGHASH_SINGLE: u32 = 0x03;
+/// Flag to sign only the input being signed, to be combined with the other sighash types.
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+/// Taproot only, sign like [`SIGHASH_ALL`] but the signature omits the sighash type byte.
+pub const SIGHASH_DEFAULT: u32 = 0x00;
+
+#[cfg(feature = "bitcoin_hashes")]
+mod tagged {
+    use crate::bitcoin_hashes::{sha256t_hash_newtype, Hash, HashEngine};
+
+    /// The SHA-256 midstate value for the [`TapLeafHash`].
+    const MIDSTATE_TAPLEAF: [u8; 32] = [
+        156, 224, 228, 230, 124, 17, 108, 57, 56, 179, 202, 242, 195, 15, 80, 137, 211, 243, 147,
+        108, 71, 99, 110, 96, 125, 179, 62, 234, 221, 198, 240, 201,
+    ];
+
+    /// The SHA-256 midstate value for the [`TapSighashHash`].
+    const MIDSTATE_TAPSIGHASH: [u8; 32] = [
+        245, 4, 164, 37, 215, 248, 120, 59, 19, 99, 134, 138, 227, 229, 86, 88, 110, 238, 148, 93,
+        188, 120, 136, 221, 2, 166, 226, 195, 24, 115, 254, 159,
+    ];
+
+    // BIP341 states the hashes without any reversing
+    sha256t_hash_newtype!(
+        TapLeafHash,
+        TapLeafTag,
+        MIDSTATE_TAPLEAF,
+        64,
+        doc = "Taproot-tagged hash of a tapscript leaf",
+        false
+    );
+    sha256t_hash_newtype!(
+        TapSighashHash,
+        TapSighashTag,
+        MIDSTATE_TAPSIGHASH,
+        64,
+        doc = "Taproot-tagged hash of the taproot signature message",
+        false
+    );
+
+    impl TapLeafHash {
+        /// Returns the leaf hash of `script` with the given `leaf_version`, `0xc0` for tapscript.
+        pub fn from_script(leaf_version: u8, script: &[u8]) -> Self {
+            let mut engine = TapLeafHash::engine();
+            engine.input(&[leaf_version]);
+            super::write_len(&mut |part: &[u8]| engine.input(part), script.len());
+            engine.input(script);
+            TapLeafHash::from_engine(engine)
+        }
+    }
+}
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+pub use tagged::{TapLeafHash, TapLeafTag, TapSighashHash, TapSighashTag};
+
+impl<'a> Transaction<'a> {
+    /// Write the legacy (pre-segwit) signature hash preimage of the input at `input_index` by
+    /// calling `write` with consecutive parts of it. The signature hash is the double sha256 of
+    /// the preimage.
+    ///
+    /// `script_code` is the script being executed, usually the script pubkey of the spent output,
+    /// without the compact int length. `OP_CODESEPARATOR` are removed from it like in Bitcoin Core.
+    ///
+    /// Returns [`Error::InputIndexOutOfRange`] if `input_index` is not a valid input, and
+    /// [`Error::SighashSingleBug`] if `sighash_type` is [`SIGHASH_SINGLE`] and there is no output
+    /// with the same index of the input, in this case Bitcoin Core doesn't hash anything and the
+    /// signature hash is the 32 bytes value `0x0100..00`.
+    pub fn write_legacy_sighash_preimage<F: FnMut(&[u8])>(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        sighash_type: u32,
+        mut write: F,
+    ) -> Result<(), Error> {
+        let total_inputs = self.inputs().len();
+        if input_index >= total_inputs {
+            return Err(Error::InputIndexOutOfRange(
+                u32::try_from(input_index).unwrap_or(u32::MAX),
+            ));
+        }
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let total_outputs = self.outputs().len();
+        if base_type == SIGHASH_SINGLE && input_index >= total_outputs {
+            return Err(Error::SighashSingleBug);
+        }
+
+        write(&self.as_ref()[..4]);
+
+        let write_input = |write: &mut F, tx_in: &TxIn, signed: bool| {
+            write(tx_in.prevout().as_ref());
+            if signed {
+                write_script_code(write, script_code);
+            } else {
+                write(&[0u8]);
+            }
+            if !signed && (base_type == SIGHASH_NONE || base_type == SIGHASH_SINGLE) {
+                write(&[0u8; 4]);
+            } else {
+                write(&tx_in.sequence().to_le_bytes());
+            }
+        };
+        if anyone_can_pay {
+            write(&[1u8]);
+            let tx_in = self.input(input_index).expect("checked above");
+            write_input(&mut write, &tx_in, true);
+        } else {
+            write_len(&mut write, total_inputs);
+            for (i, tx_in) in self.inputs().enumerate() {
+                write_input(&mut write, &tx_in, i == input_index);
+            }
+        }
+
+        match base_type {
+            SIGHASH_NONE => write(&[0u8]),
+            SIGHASH_SINGLE => {
+                write_len(&mut write, input_index + 1);
+                for _ in 0..input_index {
+                    // value -1 and empty script
+                    write(&[0xffu8, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00]);
+                }
+                write(self.output(input_index).expect("checked above").as_ref());
+            }
+            _ => {
+                write_len(&mut write, total_outputs);
+                for tx_out in self.outputs() {
+                    write(tx_out.as_ref());
+                }
+            }
+        }
+
+        write(&self.locktime().to_le_bytes());
+        write(&sighash_type.to_le_bytes());
+        Ok(())
+    }
+
+    /// Returns the legacy (pre-segwit) signature hash preimage in `out`, see
+    /// [`Transaction::write_legacy_sighash_preimage()`].
+    #[cfg(feature = "alloc")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    pub fn legacy_sighash_preimage(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        sighash_type: u32,
+        out: &mut alloc::vec::Vec<u8>,
+    ) -> Result<(), Error> {
+        self.write_legacy_sighash_preimage(input_index, script_code, sighash_type, |part| {
+            out.extend_from_slice(part)
+        })
+    }
+
+    /// Returns the legacy (pre-segwit) signature hash, see
+    /// [`Transaction::write_legacy_sighash_preimage()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn legacy_sighash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        sighash_type: u32,
+    ) -> Result<crate::bitcoin_hashes::sha256d::Hash, Error> {
+        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
+        let mut engine = sha256d::Hash::engine();
+        self.write_legacy_sighash_preimage(input_index, script_code, sighash_type, |part| {
+            engine.input(part)
+        })?;
+        Ok(sha256d::Hash::from_engine(engine))
+    }
+}
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+impl<'a> Transaction<'a> {
+    /// Returns the BIP143 `hashPrevouts`, the double sha256 of all the outpoints spent by this
+    /// transaction.
+    pub fn hash_prevouts(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
+        let mut engine = sha256d::Hash::engine();
+        for tx_in in self.inputs() {
+            engine.input(tx_in.prevout().as_ref());
+        }
+        sha256d::Hash::from_engine(engine)
+    }
+
+    /// Returns the BIP143 `hashSequence`, the double sha256 of the sequences of all the inputs.
+    pub fn hash_sequence(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
+        let mut engine = sha256d::Hash::engine();
+        for tx_in in self.inputs() {
+            engine.input(&tx_in.sequence().to_le_bytes());
+        }
+        sha256d::Hash::from_engine(engine)
+    }
+
+    /// Returns the BIP143 `hashOutputs`, the double sha256 of all the outputs.
+    pub fn hash_outputs(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
+        let mut engine = sha256d::Hash::engine();
+        for tx_out in self.outputs() {
+            engine.input(tx_out.as_ref());
+        }
+        sha256d::Hash::from_engine(engine)
+    }
+
+    /// Returns the BIP143 signature hash for segwit v0 inputs.
+    ///
+    /// `script_code` is the script being executed without the compact int length, for p2wpkh
+    /// inputs it is the corresponding p2pkh script, for p2wsh it is the witness script.
+    /// `value` is the amount of the spent output.
+    ///
+    /// The intermediate hashes are zeroed as specified in BIP143 according to `sighash_type`.
+    /// Returns [`Error::InputIndexOutOfRange`] if `input_index` is not a valid input.
+    pub fn segwit_v0_sighash(
+        &self,
+        input_index: usize,
+        script_code: &[u8],
+        value: u64,
+        sighash_type: u32,
+    ) -> Result<crate::bitcoin_hashes::sha256d::Hash, Error> {
+        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
+        let tx_in = self.input(input_index).ok_or(Error::InputIndexOutOfRange(
+            u32::try_from(input_index).unwrap_or(u32::MAX),
+        ))?;
+        let base_type = sighash_type & 0x1f;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        let single_or_none = base_type == SIGHASH_SINGLE || base_type == SIGHASH_NONE;
+
+        let hash_prevouts = if anyone_can_pay {
+            sha256d::Hash::all_zeros()
+        } else {
+            self.hash_prevouts()
+        };
+        let hash_sequence = if anyone_can_pay || single_or_none {
+            sha256d::Hash::all_zeros()
+        } else {
+            self.hash_sequence()
+        };
+        let hash_outputs = if !single_or_none {
+            self.hash_outputs()
+        } else if base_type == SIGHASH_SINGLE {
+            match self.output(input_index) {
+                Some(tx_out) => sha256d::Hash::hash(tx_out.as_ref()),
+                None => sha256d::Hash::all_zeros(),
+            }
+        } else {
+            sha256d::Hash::all_zeros()
+        };
+
+        let mut engine = sha256d::Hash::engine();
+        engine.input(&self.as_ref()[..4]);
+        engine.input(&hash_prevouts[..]);
+        engine.input(&hash_sequence[..]);
+        engine.input(tx_in.prevout().as_ref());
+        let mut write = |part: &[u8]| engine.input(part);
+        write_len(&mut write, script_code.len());
+        write(script_code);
+        engine.input(&value.to_le_bytes());
+        engine.input(&tx_in.sequence().to_le_bytes());
+        engine.input(&hash_outputs[..]);
+        engine.input(&self.locktime().to_le_bytes());
+        engine.input(&sighash_type.to_le_bytes());
+        Ok(sha256d::Hash::from_engine(engine))
+    }
+}
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+impl<'a> Transaction<'a> {
+    /// Write the BIP341 signature message of the taproot input at `input_index` by calling
+    /// `write` with consecutive parts of it. The message includes the leading epoch byte and the
+    /// signature hash is the [`TapSighashHash`] of it.
+    ///
+    /// `spent_outputs` are the `(value, script_pubkey)` of the outputs spent by every input of the
+    /// transaction in order, they are needed since the transaction doesn't contain them.
+    /// `annex` is the last witness element including the leading `0x50` byte, if present.
+    /// `leaf` is the leaf hash and the position of the last executed `OP_CODESEPARATOR`
+    /// (`0xffffffff` if none) for script path spends, `None` for key path spends.
+    ///
+    /// Returns [`Error::InputIndexOutOfRange`] if `input_index` is not a valid input,
+    /// [`Error::InvalidSighashType`] if `sighash_type` is not defined for taproot,
+    /// [`Error::SpentOutputsMismatch`] if the spent outputs are not as many as the inputs,
+    /// [`Error::InvalidAnnex`] if the annex doesn't start with `0x50` and
+    /// [`Error::SighashSingleBug`] if `sighash_type` is [`SIGHASH_SINGLE`] and there is no output
+    /// with the same index of the input.
+    pub fn write_taproot_sighash_preimage<'s, I, F>(
+        &self,
+        input_index: usize,
+        spent_outputs: I,
+        sighash_type: u32,
+        annex: Option<&[u8]>,
+        leaf: Option<(TapLeafHash, u32)>,
+        mut write: F,
+    ) -> Result<(), Error>
+    where
+        I: IntoIterator<Item = (u64, &'s [u8])>,
+        F: FnMut(&[u8]),
+    {
+        use crate::bitcoin_hashes::{sha256, Hash, HashEngine};
+        let tx_in = self.input(input_index).ok_or(Error::InputIndexOutOfRange(
+            u32::try_from(input_index).unwrap_or(u32::MAX),
+        ))?;
+        if !matches!(sighash_type, 0x00..=0x03 | 0x81..=0x83) {
+            return Err(Error::InvalidSighashType(sighash_type));
+        }
+        if annex.is_some_and(|annex| annex.first() != Some(&0x50)) {
+            return Err(Error::InvalidAnnex);
+        }
+        let base_type = sighash_type & 0x03;
+        let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+        if base_type == SIGHASH_SINGLE && input_index >= self.outputs().len() {
+            return Err(Error::SighashSingleBug);
+        }
+
+        let mut amounts = sha256::Hash::engine();
+        let mut script_pubkeys = sha256::Hash::engine();
+        let mut spent = None;
+        let mut count = 0usize;
+        for (i, (value, script_pubkey)) in spent_outputs.into_iter().enumerate() {
+            amounts.input(&value.to_le_bytes());
+            write_len(
+                &mut |part: &[u8]| script_pubkeys.input(part),
+                script_pubkey.len(),
+            );
+            script_pubkeys.input(script_pubkey);
+            if i == input_index {
+                spent = Some((value, script_pubkey));
+            }
+            count += 1;
+        }
+        let (value, script_pubkey) = match spent {
+            Some(spent) if count == self.inputs().len() => spent,
+            _ => {
+                return Err(Error::SpentOutputsMismatch(
+                    u32::try_from(count).unwrap_or(u32::MAX),
+                ))
+            }
+        };
+
+        write(&[0u8]); // epoch
+        write(&[sighash_type as u8]);
+        write(&self.as_ref()[..4]);
+        write(&self.locktime().to_le_bytes());
+        if !anyone_can_pay {
+            let mut prevouts = sha256::Hash::engine();
+            let mut sequences = sha256::Hash::engine();
+            for tx_in in self.inputs() {
+                prevouts.input(tx_in.prevout().as_ref());
+                sequences.input(&tx_in.sequence().to_le_bytes());
+            }
+            write(&sha256::Hash::from_engine(prevouts)[..]);
+            write(&sha256::Hash::from_engine(amounts)[..]);
+            write(&sha256::Hash::from_engine(script_pubkeys)[..]);
+            write(&sha256::Hash::from_engine(sequences)[..]);
+        }
+        if base_type != SIGHASH_NONE && base_type != SIGHASH_SINGLE {
+            let mut outputs = sha256::Hash::engine();
+            for tx_out in self.outputs() {
+                outputs.input(tx_out.as_ref());
+            }
+            write(&sha256::Hash::from_engine(outputs)[..]);
+        }
+
+        let spend_type = (leaf.is_some() as u8) << 1 | annex.is_some() as u8;
+        write(&[spend_type]);
+        if anyone_can_pay {
+            write(tx_in.prevout().as_ref());
+            write(&value.to_le_bytes());
+            write_len(&mut write, script_pubkey.len());
+            write(script_pubkey);
+            write(&tx_in.sequence().to_le_bytes());
+        } else {
+            write(&(input_index as u32).to_le_bytes());
+        }
+        if let Some(annex) = annex {
+            let mut engine = sha256::Hash::engine();
+            write_len(&mut |part: &[u8]| engine.input(part), annex.len());
+            engine.input(annex);
+            write(&sha256::Hash::from_engine(engine)[..]);
+        }
+        if base_type == SIGHASH_SINGLE {
+            let tx_out = self.output(input_index).expect("checked above");
+            write(&sha256::Hash::hash(tx_out.as_ref())[..]);
+        }
+        if let Some((leaf_hash, code_separator_position)) = leaf {
+            write(&leaf_hash[..]);
+            write(&[0u8]); // key version
+            write(&code_separator_position.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    /// Returns the BIP341 signature hash of the taproot input at `input_index`, see
+    /// [`Transaction::write_taproot_sighash_preimage()`].
+    pub fn taproot_sighash<'s, I>(
+        &self,
+        input_index: usize,
+        spent_outputs: I,
+        sighash_type: u32,
+        annex: Option<&[u8]>,
+        leaf: Option<(TapLeafHash, u32)>,
+    ) -> Result<TapSighashHash, Error>
+    where
+        I: IntoIterator<Item = (u64, &'s [u8])>,
+    {
+        use crate::bitcoin_hashes::{Hash, HashEngine};
+        let mut engine = TapSighashHash::engine();
+        self.write_taproot_sighash_preimage(
+            input_index,
+            spent_outputs,
+            sighash_type,
+            annex,
+            leaf,
+            |part| engine.input(part),
+        )?;
+        Ok(TapSighashHash::from_engine(engine))
+    }
+}
+
+fn write_len<F: FnMut(&[u8])>(write: &mut F, n: usize) {
+    let (buffer, used) = encode_len(n as u64);
+    write(&buffer[..used]);
+}
+
+/// Write the script with its compact int length, removing `OP_CODESEPARATOR`s
+fn write_script_code<F: FnMut(&[u8])>(write: &mut F, script_code: &[u8]) {
+    let separators = Instructions::new(script_code)
+        .filter(|(opcode, _)| *opcode == OP_CODESEPARATOR)
+        .count();
+    write_len(write, script_code.len() - separators);
+
+    let mut begin = 0;
+    let mut instructions = Instructions::new(script_code);
+    while let Some((opcode, _)) = instructions.next() {
+        if opcode == OP_CODESEPARATOR {
+            let end = instructions.position();
+            write(&script_code[begin..end - 1]);
+            begin = end;
+        }
+    }
+    write(&script_code[begin..]);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{Transaction, SIGHASH_ALL, SIGHASH_ANYONECANPAY, SIGHASH_SINGLE},
+        test_common::LEGACY_TX,
+        Error, Parse,
+    };
+    use bitcoin::hashes::{sha256d, Hash};
+    use hex_lit::hex;
+
+    fn legacy_sighash(tx: &Transaction, input_index: usize, script: &[u8], ty: u32) -> [u8; 32] {
+        let mut preimage = vec![];
+        tx.write_legacy_sighash_preimage(input_index, script, ty, |part| {
+            preimage.extend_from_slice(part)
+        })
+        .unwrap();
+        let mut hash = sha256d::Hash::hash(&preimage).into_inner();
+        hash.reverse();
+        hash
+    }
+
+    #[test]
+    fn legacy_sighash_core_vectors() {
+        // from Bitcoin Core sighash.json
+        let vectors = [
+            (&hex!("907c2bc503ade11cc3b04eb2918b6f547b0630ab569273824748c87ea14b0696526c66ba740200000004ab65ababfd1f9bdd4ef073c7afc4ae00da8a66f429c917a0081ad1e1dabce28d373eab81d8628de802000000096aab5253ab52000052ad042b5f25efb33beec9f3364e8a9139e8439d9d7e26529c3c30b6c3fd89f8684cfd68ea0200000009ab53526500636a52ab599ac2fe02a526ed040000000008535300516352515164370e010000000003006300ab2ec229")[..], &hex!("")[..], 2, 1864164639i32, hex!("31af167a6cf3f9d5f6875caa4d31704ceb0eba078d132b78dab52c3b8997317e")),
+            (&hex!("a0aa3126041621a6dea5b800141aa696daf28408959dfb2df96095db9fa425ad3f427f2f6103000000015360290e9c6063fa26912c2e7fb6a0ad80f1c5fea1771d42f12976092e7a85a4229fdb6e890000000001abc109f6e47688ac0e4682988785744602b8c87228fcef0695085edf19088af1a9db126e93000000000665516aac536affffffff8fe53e0806e12dfd05d67ac68f4768fdbe23fc48ace22a5aa8ba04c96d58e2750300000009ac51abac63ab5153650524aa680455ce7b000000000000499e50030000000008636a00ac526563ac5051ee030000000003abacabd2b6fe000000000003516563910fb6b5")[..], &hex!("65")[..], 0, -1391424484, hex!("48d6a1bd2cd9eec54eb866fc71209418a950402b5d7e52363bfb75c98e141175")),
+            (&hex!("73107cbd025c22ebc8c3e0a47b2a760739216a528de8d4dab5d45cbeb3051cebae73b01ca10200000007ab6353656a636affffffffe26816dffc670841e6a6c8c61c586da401df1261a330a6c6b3dd9f9a0789bc9e000000000800ac6552ac6aac51ffffffff0174a8f0010000000004ac52515100000000")[..], &hex!("5163ac63635151ac")[..], 1, 1190874345, hex!("06e328de263a87b09beabe222a21627a6ea5c7f560030da31610c4611f4a46bc")),
+            (&hex!("50818f4c01b464538b1e7e7f5ae4ed96ad23c68c830e78da9a845bc19b5c3b0b20bb82e5e9030000000763526a63655352ffffffff023b3f9c040000000008630051516a6a5163a83caf01000000000553ab65510000000000")[..], &hex!("6aac")[..], 0, 946795545, hex!("746306f322de2b4b58ffe7faae83f6a72433c22f88062cdde881d4dd8a5a4e2d")),
+            (&hex!("a93e93440250f97012d466a6cc24839f572def241c814fe6ae94442cf58ea33eb0fdd9bcc1030000000600636a0065acffffffff5dee3a6e7e5ad6310dea3e5b3ddda1a56bf8de7d3b75889fc024b5e233ec10f80300000007ac53635253ab53ffffffff0160468b04000000000800526a5300ac526a00000000")[..], &hex!("ac00636a53")[..], 1, 1773442520, hex!("5c9d3a2ce9365bb72cfabbaa4579c843bb8abf200944612cf8ae4b56a908bcbd")),
+            (&hex!("d3b7421e011f4de0f1cea9ba7458bf3486bee722519efab711a963fa8c100970cf7488b7bb0200000003525352dcd61b300148be5d05000000000000000000")[..], &hex!("535251536aac536a")[..], 0, -1960128125, hex!("29aa6d2d752d3310eba20442770ad345b7f6a35f96161ede5f07b33e92053e2a")),
+            (&hex!("04bac8c5033460235919a9c63c42b2db884c7c8f2ed8fcd69ff683a0a2cccd9796346a04050200000003655351fcad3a2c5a7cbadeb4ec7acc9836c3f5c3e776e5c566220f7f965cf194f8ef98efb5e3530200000007526a006552526526a2f55ba5f69699ece76692552b399ba908301907c5763d28a15b08581b23179cb01eac03000000075363ab6a516351073942c2025aa98a05000000000765006aabac65abd7ffa6030000000004516a655200000000")[..], &hex!("53ac6365ac526a")[..], 1, 764174870, hex!("bf5fdc314ded2372a0ad078568d76c5064bf2affbde0764c335009e56634481b")),
+            (&hex!("c363a70c01ab174230bbe4afe0c3efa2d7f2feaf179431359adedccf30d1f69efe0c86ed390200000002ab51558648fe0231318b04000000000151662170000000000008ac5300006a63acac00000000")[..], &hex!("")[..], 0, 2146479410, hex!("191ab180b0d753763671717d051f138d4866b7cb0d1d4811472e64de595d2c70")),
+            (&hex!("8d437a7304d8772210a923fd81187c425fc28c17a5052571501db05c7e89b11448b36618cd02000000026a6340fec14ad2c9298fde1477f1e8325e5747b61b7e2ff2a549f3d132689560ab6c45dd43c3010000000963ac00ac000051516a447ed907a7efffebeb103988bf5f947fc688aab2c6a7914f48238cf92c337fad4a79348102000000085352ac526a5152517436edf2d80e3ef06725227c970a816b25d0b58d2cd3c187a7af2cea66d6b27ba69bf33a0300000007000063ab526553f3f0d6140386815d030000000003ab6300de138f00000000000900525153515265abac1f87040300000000036aac6500000000")[..], &hex!("51")[..], 3, -315779667, hex!("b6632ac53578a741ae8c36d8b69e79f39b89913a2c781cdf1bf47a8c29d997a5")),
+        ];
+        for (tx_bytes, script, input_index, ty, expected) in vectors {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let hash = legacy_sighash(&tx, input_index, script, ty as u32);
+            assert_eq!(hash, expected);
+        }
+    }
+
+    #[test]
+    fn legacy_sighash_against_bitcoin() {
+        let tx_bytes = hex!("04bac8c5033460235919a9c63c42b2db884c7c8f2ed8fcd69ff683a0a2cccd9796346a04050200000003655351fcad3a2c5a7cbadeb4ec7acc9836c3f5c3e776e5c566220f7f965cf194f8ef98efb5e3530200000007526a006552526526a2f55ba5f69699ece76692552b399ba908301907c5763d28a15b08581b23179cb01eac03000000075363ab6a516351073942c2025aa98a05000000000765006aabac65abd7ffa6030000000004516a655200000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let tx_bitcoin: bitcoin::Transaction =
+            bitcoin::consensus::deserialize(&tx_bytes[..]).unwrap();
+        let script = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        for ty in [1u32, 2, 3, 0x81, 0x82, 0x83] {
+            for input_index in 0..2 {
+                let mut expected = tx_bitcoin
+                    .signature_hash(input_index, &script.to_vec().into(), ty)
+                    .into_inner();
+                expected.reverse();
+                assert_eq!(legacy_sighash(&tx, input_index, &script, ty), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_sighash_errors() {
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let noop = |_: &[u8]| ();
+        assert_eq!(
+            tx.write_legacy_sighash_preimage(1, &[], SIGHASH_ALL, noop),
+            Err(Error::InputIndexOutOfRange(1))
+        );
+
+        let tx_bytes = hex!("04bac8c5033460235919a9c63c42b2db884c7c8f2ed8fcd69ff683a0a2cccd9796346a04050200000003655351fcad3a2c5a7cbadeb4ec7acc9836c3f5c3e776e5c566220f7f965cf194f8ef98efb5e3530200000007526a006552526526a2f55ba5f69699ece76692552b399ba908301907c5763d28a15b08581b23179cb01eac03000000075363ab6a516351073942c2025aa98a05000000000765006aabac65abd7ffa6030000000004516a655200000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(tx.outputs().len(), 2);
+        assert!(tx
+            .write_legacy_sighash_preimage(1, &[], SIGHASH_SINGLE, noop)
+            .is_ok());
+        assert_eq!(
+            tx.write_legacy_sighash_preimage(2, &[], SIGHASH_SINGLE, noop),
+            Err(Error::SighashSingleBug)
+        );
+        assert_eq!(
+            tx.write_legacy_sighash_preimage(2, &[], SIGHASH_SINGLE | SIGHASH_ANYONECANPAY, noop),
+            Err(Error::SighashSingleBug)
+        );
+    }
+
+    #[test]
+    fn legacy_sighash_codeseparator() {
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let with_separators = hex!("ab76a9ab140389035a9225b3839e2bbf32d826a1e222031fd888acab");
+        let without = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        assert_eq!(
+            legacy_sighash(&tx, 0, &with_separators, SIGHASH_ALL),
+            legacy_sighash(&tx, 0, &without, SIGHASH_ALL)
+        );
+
+        // 0xab inside a push is not an OP_CODESEPARATOR
+        let push_ab = hex!("01ab");
+        let mut expected = vec![];
+        tx.write_legacy_sighash_preimage(0, &push_ab, SIGHASH_ALL, |p| {
+            expected.extend_from_slice(p)
+        })
+        .unwrap();
+        assert!(expected.windows(3).any(|w| w == [0x02, 0x01, 0xab]));
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn bip143_intermediate_hashes() {
+        use bitcoin::hashes::{hex::FromHex, Hash};
+        // BIP143 shows hashes in byte order, not in the usual reversed display order
+        let h = |s: &str| sha256d::Hash::from_slice(&Vec::<u8>::from_hex(s).unwrap()).unwrap();
+
+        // native p2wpkh example from BIP143
+        let tx_bytes = hex!("0100000002fff7f7881a8099afa6940d42d1e7f6362bec38171ea3edf433541db4e4ad969f0000000000eeffffffef51e1b804cc89d182d279655c3aa89e815b1b309fe287d9b2b55d57b90ec68a0100000000ffffffff02202cb206000000001976a9148280b37df378db99f66f85c95a783a76ac7a6d5988ac9093510d000000001976a9143bde42dbee7e4dbe6a21b2d50ce2f0167faa815988ac11000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(
+            tx.hash_prevouts(),
+            h("96b827c8483d4e9b96712b6713a7b68d6e8003a781feba36c31143470b4efd37")
+        );
+        assert_eq!(
+            tx.hash_sequence(),
+            h("52b0a642eea2fb7ae638c36f6252b6750293dbe574a806984b8e4d8548339a3b")
+        );
+        assert_eq!(
+            tx.hash_outputs(),
+            h("863ef3e1a92afbfdb97f31ad0fc7683ee943e9abcf2501590ff8f6551f47e5e5")
+        );
+        let script_code = hex!("76a9141d0f172a0ecb48aee1be1f2687d2963ae33f71a188ac");
+        assert_eq!(
+            tx.segwit_v0_sighash(1, &script_code, 600_000_000, SIGHASH_ALL)
+                .unwrap(),
+            h("c37af31116d1b27caf68aae9e3ac82f1477929014d5b917657d0eb49478cb670")
+        );
+
+        // p2sh-p2wsh example from BIP143
+        let tx_bytes = hex!("010000000136641869ca081e70f394c6948e8af409e18b619df2ed74aa106c1ca29787b96e0100000000ffffffff0200e9a435000000001976a914389ffce9cd9ae88dcc0631e88a821ffdbe9bfe2688acc0832f05000000001976a9147480a33f950689af511e6e84c138dbbd3c3ee41588ac00000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let witness_script = hex!("56210307b8ae49ac90a048e9b53357a2354b3334e9c8bee813ecb98e99a7e07e8c3ba32103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b21034b8113d703413d57761b8b9781957b8c0ac1dfe69f492580ca4195f50376ba4a21033400f6afecb833092a9a21cfdf1ed1376e58c5d1f47de74683123987e967a8f42103a6d48b1131e94ba04d9737d61acdaa1322008af9602b3b14862c07a1789aac162102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b56ae");
+        let value = 987654321;
+        let expected = [
+            (
+                0x01,
+                "185c0be5263dce5b4bb50a047973c1b6272bfbd0103a89444597dc40b248ee7c",
+            ),
+            (
+                0x02,
+                "e9733bc60ea13c95c6527066bb975a2ff29a925e80aa14c213f686cbae5d2f36",
+            ),
+            (
+                0x03,
+                "1e1f1c303dc025bd664acb72e583e933fae4cff9148bf78c157d1e8f78530aea",
+            ),
+            (
+                0x81,
+                "2a67f03e63a6a422125878b40b82da593be8d4efaafe88ee528af6e5a9955c6e",
+            ),
+            (
+                0x82,
+                "781ba15f3779d5542ce8ecb5c18716733a5ee42a6f51488ec96154934e2c890a",
+            ),
+            (
+                0x83,
+                "511e8e52ed574121fc1b654970395502128263f62662e076dc6baf05c2e6a99b",
+            ),
+        ];
+        for (ty, expected) in expected {
+            assert_eq!(
+                tx.segwit_v0_sighash(0, &witness_script, value, ty).unwrap(),
+                h(expected),
+                "sighash type {:x}",
+                ty
+            );
+        }
+        assert_eq!(
+            tx.segwit_v0_sighash(1, &witness_script, value, SIGHASH_ALL),
+            Err(Error::InputIndexOutOfRange(1))
+        );
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn bip143_against_bitcoin() {
+        use bitcoin::util::sighash::SighashCache;
+        let tx_bytes = hex!("04bac8c5033460235919a9c63c42b2db884c7c8f2ed8fcd69ff683a0a2cccd9796346a04050200000003655351fcad3a2c5a7cbadeb4ec7acc9836c3f5c3e776e5c566220f7f965cf194f8ef98efb5e3530200000007526a006552526526a2f55ba5f69699ece76692552b399ba908301907c5763d28a15b08581b23179cb01eac03000000075363ab6a516351073942c2025aa98a05000000000765006aabac65abd7ffa6030000000004516a655200000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let tx_bitcoin: bitcoin::Transaction =
+            bitcoin::consensus::deserialize(&tx_bytes[..]).unwrap();
+        let mut cache = SighashCache::new(&tx_bitcoin);
+        let script = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        for ty in [1u32, 2, 3, 0x81, 0x82, 0x83] {
+            for input_index in 0..3 {
+                let expected = cache
+                    .segwit_signature_hash(
+                        input_index,
+                        &script.to_vec().into(),
+                        1000,
+                        bitcoin::EcdsaSighashType::from_consensus(ty),
+                    )
+                    .unwrap();
+                let sighash = tx
+                    .segwit_v0_sighash(input_index, &script, 1000, ty)
+                    .unwrap();
+                assert_eq!(&sighash[..], &expected[..]);
+            }
+        }
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[allow(clippy::too_many_arguments)]
+    fn check_taproot_sighash(
+        tx_hex: &str,
+        spent_outputs_hex: &str,
+        input_index: usize,
+        expected: &str,
+        sighash_type: u32,
+        annex_hex: Option<&str>,
+        script_hex: Option<&str>,
+        leaf_hash_hex: Option<&str>,
+    ) {
+        use crate::bsl::{TapLeafHash, TxOuts};
+        use bitcoin::hashes::hex::FromHex;
+        let from_hex = |s: &str| Vec::<u8>::from_hex(s).unwrap();
+
+        let tx_bytes = from_hex(tx_hex);
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let spent_outputs_bytes = from_hex(spent_outputs_hex);
+        let spent_outputs: Vec<_> = TxOuts::parse(&spent_outputs_bytes[..])
+            .unwrap()
+            .parsed_owned()
+            .iter()
+            .collect();
+        let annex = annex_hex.map(from_hex);
+        let leaf_hash = match (script_hex, leaf_hash_hex) {
+            (Some(script), _) => Some(TapLeafHash::from_script(0xc0, &from_hex(script))),
+            (_, Some(leaf_hash)) => Some(TapLeafHash::from_slice(&from_hex(leaf_hash)).unwrap()),
+            _ => None,
+        };
+
+        let hash = tx
+            .taproot_sighash(
+                input_index,
+                spent_outputs
+                    .iter()
+                    .map(|tx_out| (tx_out.value(), tx_out.script_pubkey())),
+                sighash_type,
+                annex.as_deref(),
+                leaf_hash.map(|leaf_hash| (leaf_hash, 0xffffffff)),
+            )
+            .unwrap();
+        assert_eq!(&hash[..], &from_hex(expected)[..]);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn taproot_tagged_hashes() {
+        use crate::bsl::{TapSighashHash, TapSighashTag};
+        use bitcoin::hashes::{sha256, sha256t::Tag, HashEngine};
+
+        for (tag, midstate) in [
+            (&b"TapSighash"[..], TapSighashTag::engine().midstate()),
+            (&b"TapLeaf"[..], crate::bsl::TapLeafTag::engine().midstate()),
+        ] {
+            let tag_hash = sha256::Hash::hash(tag);
+            let mut engine = sha256::Hash::engine();
+            engine.input(&tag_hash[..]);
+            engine.input(&tag_hash[..]);
+            assert_eq!(engine.midstate(), midstate);
+        }
+
+        let bytes = hex!("00011b96877db45ffa23b307e9f0ac87b80ef9a80b4c5f0db3fbe734422453e83cc5576f3d542c5d4898fb2b696c15d43332534a7c1d1255fda38993545882df92c3e353ff6d36fbfadc4d168452afd8467f02fe53d71714fcea5dfe2ea759bd00185c4cb02bc76d42620393ca358a1a713f4997f9fc222911890afb3fe56c6a19b202df7bffdcfad08003821294279043746631b00e2dc5e52a111e213bbfe6ef09a19428d418dab0d50000000000");
+        assert_eq!(
+            TapSighashHash::hash(&bytes[..]).into_inner(),
+            hex!("04e808aad07a40b3767a1442fead79af6ef7e7c9316d82dec409bb31e77699b0")
+        );
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn taproot_sighash_key_path() {
+        use crate::bsl::{SIGHASH_DEFAULT, SIGHASH_NONE};
+        // from Bitcoin Core test framework
+        let vectors = [
+            ("020000000164eb050a5e3da0c2a65e4786f26d753b7bc69691fabccafb11f7acef36641f1846010000003101b2b404392a22000000000017a9147f2bde86fe78bf68a0544a4f290e12f0b7e0a08c87580200000000000017a91425d11723074ecfb96a0a83c3956bfaf362ae0c908758020000000000001600147e20f938993641de67bb0cdd71682aa34c4d29ad5802000000000000160014c64984dc8761acfa99418bd6bedc79b9287d652d72000000", "01365724000000000023542156b39dab4f8f3508e0432cfb41fab110170acaa2d4c42539cb90a4dc7c093bc500", 0, "33ca0ebfb4a945eeee9569fc0f5040221275f88690b7f8592ada88ce3bdf6703", SIGHASH_DEFAULT),
+            ("0200000002fff49be59befe7566050737910f6ccdc5e749c7f8860ddc140386463d88c5ad0f3000000002cf68eb4a3d67f9d4c079249f7e4f27b8854815cb1ed13842d4fbf395f9e217fd605ee24090100000065235d9203f458520000000000160014b6d48333bb13b4c644e57c43a9a26df3a44b785e58020000000000001976a914eea9461a9e1e3f765d3af3e726162e0229fe3eb688ac58020000000000001976a9143a8869c9f2b5ea1d4ff3aeeb6a8fb2fffb1ad5fe88ac0ad7125c", "02591f220000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece48fb310000000000225120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece", 1, "626ab955d58c9a8a600a0c580549d06dc7da4e802eb2a531f62a588e430967a8", SIGHASH_ALL),
+            ("0200000001350005f65aa830ced2079df348e2d8c2bdb4f10e2dde6a161d8a07b40d1ad87dae000000001611d0d603d9dc0e000000000017a914459b6d7d6bbb4d8837b4bf7e9a4556f952da2f5c8758020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88ac58020000000000001976a9141dd70e1299ffc2d5b51f6f87de9dfe9398c33cbb88aca71c1f4f", "01c4811000000000002251201bf9297d0a2968ae6693aadd0fa514717afefd218087a239afb7418e2d22e65c", 0, "dfa9437f9c9a1d1f9af271f79f2f5482f287cdb0d2e03fa92c8a9b216cc6061c", SIGHASH_ALL | SIGHASH_ANYONECANPAY),
+            ("020000000185bed1a6da2bffbd60ec681a1bfb71c5111d6395b99b3f8b2bf90167111bcb18f5010000007c83ace802ded24a00000000001600142c4698f9f7a773866879755aa78c516fb332af8e5802000000000000160014d38639dfbac4259323b98a472405db0c461b31fa61073747", "0144c84d0000000000225120e3f2107989c88e67296ab2faca930efa2e3a5bd3ff0904835a11c9e807458621", 0, "3129de36a5d05fff97ffca31eb75fcccbbbc27b3147a7a36a9e4b45d8b625067", SIGHASH_NONE),
+            ("eb93dbb901028c8515589dac980b6e7f8e4088b77ed866ca0d6d210a7218b6fd0f6b22dd6d7300000000eb4740a9047efc0e0000000000160014913da2128d8fcf292b3691db0e187414aa1783825802000000000000160014913da2128d8fcf292b3691db0e187414aa178382580200000000000017a9143dd27f01c6f7ef9bb9159937b17f17065ed01a0c875802000000000000160014d7630e19df70ada9905ede1722b800c0005f246641000000", "013fed110000000000225120eb536ae8c33580290630fc495046e998086a64f8f33b93b07967d9029b265c55", 0, "2441e8b0e063a2083ee790f14f2045022f07258ddde5ee01de543c9e789d80ae", SIGHASH_NONE | SIGHASH_ANYONECANPAY),
+            ("02000000017836b409a5fed32211407e44b971591f2032053f14701fb5b3a30c0ff382f2cc9c0100000061ac55f60288fb5600000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ac58020000000000001976a9144ea02f6f182b082fb6ce47e36bbde390b6a41b5088ace4000000", "01efa558000000000022512007071ea3dc7e331b0687d0193d1e6d6ed10e645ef36f10ef8831d5e522ac9e80", 0, "30239345177cadd0e3ea413d49803580abb6cb27971b481b7788a78d35117a88", SIGHASH_SINGLE),
+            ("0100000001aa6deae89d5e0aaca58714fc76ef6f3c8284224888089232d4e663843ed3ab3eae010000008b6657a60450cb4c0000000000160014a3d42b5413ef0c0701c4702f3cd7d4df222c147058020000000000001976a91430b4ed8723a4ee8992aa2c8814cfe5c3ad0ab9d988ac5802000000000000160014365b1166a6ed0a5e8e9dff17a6d00bbb43454bc758020000000000001976a914bc98c51a84fe7fad5dc380eb8b39586eff47241688ac4f313247", "0107af4e00000000002251202c36d243dfc06cb56a248e62df27ecba7417307511a81ae61aa41c597a929c69", 0, "bf9c83f26c6dd16449e4921f813f551c4218e86f2ec906ca8611175b41b566df", SIGHASH_SINGLE | SIGHASH_ANYONECANPAY),
+        ];
+        for (tx, spent_outputs, input_index, expected, ty) in vectors {
+            check_taproot_sighash(
+                tx,
+                spent_outputs,
+                input_index,
+                expected,
+                ty,
+                None,
+                None,
+                None,
+            );
+        }
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn taproot_sighash_annex_and_script_path() {
+        // annex
+        check_taproot_sighash(
+            "0200000001df8123752e8f37d132c4e9f1ff7e4f9b986ade9211267e9ebd5fd22a5e718dec6d01000000ce4023b903cb7b23000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787580200000000000017a914afd0d512a2c5c2b40e25669e9cc460303c325b8b87580200000000000017a914a18b36ea7a094db2f4940fc09edf154e86de7bd787f6020000",
+            "01ea49260000000000225120ab5e9800806bf18cb246edcf5fe63441208fe955a4b5a35bbff65f5db622a010",
+            0,
+            "3b003000add359a364a156e73e02846782a59d0d95ca8c4638aaad99f2ef915c",
+            SIGHASH_SINGLE | SIGHASH_ANYONECANPAY,
+            Some("507b979802e62d397acb29f56743a791894b99372872fc5af06a4f6e8d242d0615cda53062bb20e6ec79756fe39183f0c128adfe85559a8fa042b042c018aa8010143799e44f0893c40e1e"),
+            None,
+            None,
+        );
+
+        // script path, with the leaf script and with the leaf hash
+        let tx = "020000000189fc651483f9296b906455dd939813bf086b1bbe7c77635e157c8e14ae29062195010000004445b5c7044561320000000000160014331414dbdada7fb578f700f38fb69995fc9b5ab958020000000000001976a914268db0a8104cc6d8afd91233cc8b3d1ace8ac3ef88ac580200000000000017a914ec00dcb368d6a693e11986d265f659d2f59e8be2875802000000000000160014c715799a49a0bae3956df9c17cb4440a673ac0df6f010000";
+        let spent_outputs = "011bec34000000000022512028055142ea437db73382e991861446040b61dd2185c4891d7daf6893d79f7182";
+        let expected = "d66de5274a60400c7b08c86ba6b7f198f40660079edf53aca89d2a9501317f2e";
+        check_taproot_sighash(
+            tx,
+            spent_outputs,
+            0,
+            expected,
+            SIGHASH_ALL,
+            None,
+            Some("20cc4e1107aea1d170c5ff5b6817e1303010049724fb3caa7941792ea9d29b3e2bacab"),
+            None,
+        );
+        check_taproot_sighash(
+            tx,
+            spent_outputs,
+            0,
+            expected,
+            SIGHASH_ALL,
+            None,
+            None,
+            Some("15a2530514e399f8b5cf0b3d3112cf5b289eaa3e308ba2071b58392fdc6da68a"),
+        );
+
+        // annex and script path
+        check_taproot_sighash(
+            "020000000132fb72cb8fba496755f027a9743e2d698c831fdb8304e4d1a346ac92cbf51acba50100000026bdc7df044aad34000000000017a9144fa2554ed6174586854fa3bc01de58dcf33567d0875802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab95802000000000000160014950367e1e62cdf240b35b883fc2f5e39f0eb9ab958020000000000001600141b31217d48ccc8760dcc0710fade5866d628e733a02d5122",
+            "011458360000000000225120a7baec3fb9f84614e3899fcc010c638f80f13539344120e1f4d8b68a9a011a13",
+            0,
+            "a0042aa434f9a75904b64043f2a283f8b4c143c7f4f7f49a6cbe5b9f745f4c15",
+            SIGHASH_ALL,
+            Some("50a6272b470e1460e3332ade7bb14b81671c564fb6245761bd5bd531394b28860e0b3808ab229fb51791fb6ae6fa82d915b2efb8f6df83ae1f5ab3db13e30928875e2a22b749d89358de481f19286cd4caa792ce27f9559082d227a731c5486882cc707f83da361c51b7aadd9a0cf68fe7480c410fa137b454482d9a1ebf0f96d760b4d61426fc109c6e8e99a508372c45caa7b000a41f8251305da3f206c1849985ba03f3d9592832b4053afbd23ab25d0465df0bc25a36c223aacf8e04ec736a418c72dc319e4da3e972e349713ca600965e7c665f2090d5a70e241ac164115a1f5639f28b1773327715ca307ace64a2de7f0e3df70a2ffee3857689f909c0dad46d8a20fa373a4cc6eed6d4c9806bf146f0d76baae1"),
+            Some("7520ab9160dd8299dc1367659be3e8f66781fe440d52940c7f8d314a89b9f2698d406ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6ead6eadac"),
+            None,
+        );
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn taproot_sighash_errors() {
+        use crate::bsl::SIGHASH_DEFAULT;
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let script_pubkey =
+            hex!("5120f25ad35583ea31998d968871d7de1abd2a52f6fe4178b54ea158274806ff4ece");
+        let spent = [(100_000_000u64, &script_pubkey[..])];
+        let sighash = |input_index, spent: &[(u64, &[u8])], ty, annex| {
+            tx.taproot_sighash(input_index, spent.iter().copied(), ty, annex, None)
+        };
+
+        let default = sighash(0, &spent, SIGHASH_DEFAULT, None).unwrap();
+        let all = sighash(0, &spent, SIGHASH_ALL, None).unwrap();
+        assert_ne!(default, all, "the sighash type byte is committed");
+
+        assert_eq!(
+            sighash(1, &spent, SIGHASH_ALL, None),
+            Err(Error::InputIndexOutOfRange(1))
+        );
+        assert_eq!(
+            sighash(0, &spent, 0x04, None),
+            Err(Error::InvalidSighashType(0x04))
+        );
+        assert_eq!(
+            sighash(0, &spent, 0x80, None),
+            Err(Error::InvalidSighashType(0x80))
+        );
+        assert_eq!(
+            sighash(0, &[], SIGHASH_ALL, None),
+            Err(Error::SpentOutputsMismatch(0))
+        );
+        assert_eq!(
+            sighash(0, &[spent[0], spent[0]], SIGHASH_ALL, None),
+            Err(Error::SpentOutputsMismatch(2))
+        );
+        assert_eq!(
+            sighash(0, &spent, SIGHASH_ALL, Some(&[0x51])),
+            Err(Error::InvalidAnnex)
+        );
+        assert_eq!(
+            sighash(0, &spent, SIGHASH_ALL, Some(&[])),
+            Err(Error::InvalidAnnex)
+        );
+
+        let tx_bytes = hex!("020000000185bed1a6da2bffbd60ec681a1bfb71c5111d6395b99b3f8b2bf90167111bcb18f5010000007c83ace80000000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(
+            tx.taproot_sighash(0, spent.iter().copied(), SIGHASH_SINGLE, None, None),
+            Err(Error::SighashSingleBug)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn legacy_sighash_preimage() {
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let mut preimage = vec![];
+        tx.legacy_sighash_preimage(0, &[0x51], SIGHASH_ALL, &mut preimage)
+            .unwrap();
+        let mut expected = vec![];
+        expected.extend_from_slice(&LEGACY_TX[..41]);
+        expected.extend_from_slice(&[1, 0x51]);
+        expected.extend_from_slice(&LEGACY_TX[41 + 1 + 0x6c..]);
+        expected.extend_from_slice(&[1, 0, 0, 0]);
+        assert_eq!(preimage, expected);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn legacy_sighash_hash() {
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let script = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        let mut expected = legacy_sighash(&tx, 0, &script, SIGHASH_ALL);
+        expected.reverse();
+        assert_eq!(
+            &tx.legacy_sighash(0, &script, SIGHASH_ALL).unwrap()[..],
+            &expected[..]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+use core::fmt;
+use core::ops::ControlFlow;
+
+use crate::{bsl, Error, SResult, Visit, Visitor};
+
+const ERROR_VARIANTS: usize = 20;
+
+/// The names of the [`Error`] variants, in the order of [`error_index()`].
+const ERROR_NAMES: [&str; ERROR_VARIANTS] = [
+    "Needed",
+    "UnknownSegwitFlag",
+    "SegwitFlagWithoutWitnesses",
+    "NonMinimalVarInt",
+    "BufferTooSmall",
+    "InputIndexOutOfRange",
+    "SighashSingleBug",
+    "InvalidSighashType",
+    "SpentOutputsMismatch",
+    "InvalidAnnex",
+    "TooLarge",
+    "TrailingBytes",
+    "UnsupportedVersion",
+    "MissingCoinbase",
+    "InvalidMagic",
+    "IndexOverflow",
+    "MissingPrevout",
+    "VisitBreak",
+    "LimitExceeded",
+    "InvalidAmount",
+];
+
+fn error_index(error: &Error) -> usize {
+    match error {
+        Error::Needed(_) => 0,
+        Error::UnknownSegwitFlag(_) => 1,
+        Error::SegwitFlagWithoutWitnesses => 2,
+        Error::NonMinimalVarInt => 3,
+        Error::BufferTooSmall(_) => 4,
+        Error::InputIndexOutOfRange(_) => 5,
+        Error::SighashSingleBug => 6,
+        Error::InvalidSighashType(_) => 7,
+        Error::SpentOutputsMismatch(_) => 8,
+        Error::InvalidAnnex => 9,
+        Error::TooLarge => 10,
+        Error::TrailingBytes(_) => 11,
+        Error::UnsupportedVersion(_) => 12,
+        Error::MissingCoinbase => 13,
+        Error::InvalidMagic => 14,
+        Error::IndexOverflow => 15,
+        Error::MissingPrevout => 16,
+        Error::VisitBreak => 17,
+        Error::LimitExceeded(_) => 18,
+        Error::InvalidAmount(_) => 19,
+    }
+}
+
+/// Counters of the parsing work, to find where the time goes parsing many blocks.
+///
+/// There is no global state, every [`StatsVisitor`] keeps its own counters, so that pipelines
+/// parsing in many threads can [`ParseStats::merge()`] them at the end.
+///
+/// As a [`Visitor`] it counts only the objects visited, the bytes and the errors are counted by
+/// [`ParseStats::record()`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseStats {
+    /// The bytes consumed by the objects parsed successfully.
+    pub bytes: u64,
+    /// The number of blocks visited.
+    pub blocks: u64,
+    /// The number of transactions visited.
+    pub transactions: u64,
+    /// The number of witnesses visited, one for every input of segwit transactions.
+    pub witnesses: u64,
+    errors: [u64; ERROR_VARIANTS],
+}
+
+impl ParseStats {
+    /// Counts the bytes consumed if `result` is ok, the error otherwise.
+    pub fn record<T: AsRef<[u8]>>(&mut self, result: &SResult<'_, T>) {
+        match result {
+            Ok(parsed) => self.bytes += parsed.consumed() as u64,
+            Err(error) => self.errors[error_index(error)] += 1,
+        }
+    }
+
+    /// Returns the number of errors recorded of the same variant of `error`, ignoring the value
+    /// contained.
+    pub fn errors(&self, error: &Error) -> u64 {
+        self.errors[error_index(error)]
+    }
+
+    /// Returns the number of errors recorded, of any variant.
+    pub fn total_errors(&self) -> u64 {
+        self.errors.iter().sum()
+    }
+
+    /// Adds the counters of `other` to these.
+    pub fn merge(&mut self, other: &ParseStats) {
+        self.bytes += other.bytes;
+        self.blocks += other.blocks;
+        self.transactions += other.transactions;
+        self.witnesses += other.witnesses;
+        for (count, other) in self.errors.iter_mut().zip(other.errors.iter()) {
+            *count += other;
+        }
+    }
+}
+
+impl fmt::Display for ParseStats {
+    /// A one line summary, like `285 bytes, 1 blocks, 1 transactions, 0 witnesses, 1 errors
+    /// (Needed: 1)`, the errors variants never recorded are omitted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes, {} blocks, {} transactions, {} witnesses, {} errors",
+            self.bytes,
+            self.blocks,
+            self.transactions,
+            self.witnesses,
+            self.total_errors()
+        )?;
+        let mut separator = " (";
+        for (name, count) in ERROR_NAMES.iter().zip(self.errors.iter()) {
+            if *count > 0 {
+                write!(f, "{}{}: {}", separator, name, count)?;
+                separator = ", ";
+            }
+        }
+        if self.total_errors() > 0 {
+            f.write_str(")")?;
+        }
+        Ok(())
+    }
+}
+
+impl Visitor<'_> for ParseStats {
+    fn visit_block_begin(&mut self, _total_transactions: usize) {
+        self.blocks += 1;
+    }
+    fn visit_transaction(&mut self, _tx: &bsl::Transaction) {
+        self.transactions += 1;
+    }
+    fn visit_witness(&mut self, _vin: usize) {
+        self.witnesses += 1;
+    }
+}
+
+/// Forwards every call to the wrapped visitor, counting them in a [`ParseStats`].
+///
+/// The hints, like [`Visitor::need_witnesses()`], are the ones of the wrapped visitor, so the
+/// objects skipped by it are not counted.
+///
+/// ```
+/// use bitcoin_slices::{bsl, bsl::StatsVisitor, EmptyVisitor};
+///
+/// let mut visitor = StatsVisitor::new(EmptyVisitor {});
+/// let block = bitcoin_test_data::blocks::mainnet_702861();
+/// visitor.visit::<bsl::Block>(block).unwrap();
+/// assert_eq!(visitor.stats().transactions, 2500);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct StatsVisitor<V> {
+    stats: ParseStats,
+    visitor: V,
+}
+
+impl<V> StatsVisitor<V> {
+    /// Wraps `visitor` with zeroed counters.
+    pub fn new(visitor: V) -> Self {
+        StatsVisitor {
+            stats: ParseStats::default(),
+            visitor,
+        }
+    }
+
+    /// Returns the counters.
+    pub fn stats(&self) -> &ParseStats {
+        &self.stats
+    }
+
+    /// Returns the wrapped visitor.
+    pub fn visitor(&self) -> &V {
+        &self.visitor
+    }
+
+    /// Returns the wrapped visitor and the counters.
+    pub fn into_parts(self) -> (V, ParseStats) {
+        (self.visitor, self.stats)
+    }
+}
+
+impl<'a, V: Visitor<'a>> StatsVisitor<V> {
+    /// Visits a `T` in `slice` with this visitor, recording the bytes consumed or the error.
+    pub fn visit<T: Visit<'a>>(&mut self, slice: &'a [u8]) -> SResult<'a, T> {
+        let result = T::visit(slice, self);
+        self.stats.record(&result);
+        result
+    }
+}
+
+macro_rules! impl_visitor_stats {
+    (@fns () [$($name:ident($($arg:ident: $ty:ty),*);)+] [$($hint:ident;)+]) => {
+        $(
+            fn $name(&mut self, $($arg: $ty),*) {
+                (&mut self.stats, &mut self.visitor).$name($($arg),*)
+            }
+        )+
+        $(
+            fn $hint(&self) -> bool {
+                self.visitor.$hint()
+            }
+        )+
+        fn control_flow(&self) -> ControlFlow<()> {
+            self.visitor.control_flow()
+        }
+    };
+}
+
+impl<'a, V: Visitor<'a>> Visitor<'a> for StatsVisitor<V> {
+    crate::visit::visitor_fns!(impl_visitor_stats());
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    use crate::{
+        bsl::{visitors::CountingVisitor, Block, ParseStats, StatsVisitor, Transaction},
+        test_common::SEGWIT_TX,
+        EmptyVisitor, Error,
+    };
+
+    #[test]
+    fn stats_visitor() {
+        let mut visitor = StatsVisitor::new(CountingVisitor::default());
+        let block = mainnet_702861();
+        visitor.visit::<Block>(block).unwrap();
+        visitor.visit::<Transaction>(&SEGWIT_TX[..]).unwrap();
+        visitor.visit::<Transaction>(&SEGWIT_TX[..100]).unwrap_err();
+        visitor
+            .visit::<Transaction>(&[1, 0, 0, 0, 0xfd, 1, 0][..])
+            .unwrap_err();
+
+        let block_bitcoin: bitcoin::Block = bitcoin::consensus::deserialize(block).unwrap();
+        let witnesses: usize = block_bitcoin
+            .txdata
+            .iter()
+            .filter(|tx| tx.input.iter().any(|input| !input.witness.is_empty()))
+            .map(|tx| tx.input.len())
+            .sum();
+
+        let (counting, stats) = visitor.into_parts();
+        assert_eq!(stats.bytes, block.len() as u64 + 222);
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.transactions, 2500 + 1);
+        assert_eq!(stats.witnesses, witnesses as u64 + 1);
+        assert_eq!(stats.errors(&Error::Needed(1)), 1);
+        assert_eq!(stats.errors(&Error::NonMinimalVarInt), 1);
+        assert_eq!(stats.errors(&Error::TrailingBytes(0)), 0);
+        assert_eq!(stats.total_errors(), 2);
+        assert_eq!(stats.transactions, counting.transactions as u64);
+        assert_eq!(stats.witnesses, counting.witnesses as u64);
+
+        let mut merged = ParseStats::default();
+        merged.merge(&stats);
+        merged.merge(&stats);
+        assert_eq!(merged.transactions, 2 * stats.transactions);
+        assert_eq!(merged.errors(&Error::Needed(1)), 2);
+    }
+
+    #[test]
+    fn stats_display() {
+        let mut visitor = StatsVisitor::new(EmptyVisitor {});
+        assert_eq!(
+            visitor.stats().to_string(),
+            "0 bytes, 0 blocks, 0 transactions, 0 witnesses, 0 errors"
+        );
+        visitor.visit::<Transaction>(&SEGWIT_TX[..]).unwrap();
+        visitor.visit::<Transaction>(&SEGWIT_TX[..2]).unwrap_err();
+        visitor.visit::<Transaction>(&SEGWIT_TX[..3]).unwrap_err();
+        visitor
+            .visit::<Transaction>(&[1, 0, 0, 0, 0xfd, 1, 0][..])
+            .unwrap_err();
+        assert_eq!(
+            visitor.stats().to_string(),
+            "222 bytes, 0 blocks, 1 transactions, 1 witnesses, 3 errors (Needed: 2, NonMinimalVarInt: 1)"
+        );
+    }
+}
@@ -0,0 +1,445 @@
+use alloc::vec::Vec;
+use std::io::{ErrorKind, Read};
+
+use crate::bsl::{BlockHeader, BlockHeaderOwned, Transaction, TransactionOwned, MAX_BLOCK_WEIGHT};
+use crate::{Error, Parse, StreamError};
+
+/// The initial size of the buffer, grown when an object doesn't fit.
+const INITIAL_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Parses an object from the start of a slice, returning it and the bytes consumed.
+pub(crate) type ParseFn<O> = fn(&[u8]) -> Result<(O, usize), Error>;
+
+/// The buffer holding the bytes read from a stream and not yet parsed, independent of the kind of
+/// the stream.
+///
+/// Reading happens in two steps: [`StreamBuffer::space`] returns where to read and
+/// [`StreamBuffer::commit`] makes the read bytes part of the buffer, so that if the read is not
+/// completed the buffer is left untouched.
+#[derive(Debug)]
+pub(crate) struct StreamBuffer {
+    data: Vec<u8>,
+    /// The start of the bytes not yet parsed.
+    start: usize,
+    /// The end of the bytes read.
+    end: usize,
+    max_size: usize,
+}
+
+impl StreamBuffer {
+    pub(crate) fn new(max_size: usize) -> Self {
+        StreamBuffer {
+            data: Vec::new(),
+            start: 0,
+            end: 0,
+            max_size,
+        }
+    }
+
+    /// Returns the bytes read and not yet parsed.
+    pub(crate) fn buffered(&self) -> &[u8] {
+        &self.data[self.start..self.end]
+    }
+
+    /// Parse an object from the buffered bytes with `parse`, which returns the object and the
+    /// bytes consumed.
+    ///
+    /// Returns `Ok(Err(needed))` if more bytes are needed to parse the object.
+    pub(crate) fn try_next<O>(&mut self, parse: ParseFn<O>) -> Result<Result<O, usize>, Error> {
+        match parse(self.buffered()) {
+            Ok((object, consumed)) => {
+                self.start += consumed;
+                Ok(Ok(object))
+            }
+            Err(Error::Needed(needed)) => Ok(Err(needed as usize)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the space to read at least `needed` more bytes into, moving or growing the buffer
+    /// if needed.
+    ///
+    /// Fails with [`StreamError::ItemTooLarge`] if the buffered bytes plus `needed` are more than
+    /// the maximum size.
+    pub(crate) fn space(&mut self, needed: usize) -> Result<&mut [u8], StreamError> {
+        let buffered = self.end - self.start;
+        if buffered.saturating_add(needed) > self.max_size {
+            return Err(StreamError::ItemTooLarge);
+        }
+        if self.data.len() - self.end < needed {
+            self.data.copy_within(self.start..self.end, 0);
+            self.start = 0;
+            self.end = buffered;
+            let min_len = buffered + needed;
+            if self.data.len() < min_len {
+                let len = (self.data.len() * 2)
+                    .max(INITIAL_BUFFER_SIZE)
+                    .min(self.max_size)
+                    .max(min_len);
+                self.data.resize(len, 0);
+            }
+        }
+        Ok(&mut self.data[self.end..])
+    }
+
+    /// Adds the `read` bytes written in the slice returned by [`StreamBuffer::space`].
+    pub(crate) fn commit(&mut self, read: usize) {
+        self.end += read;
+    }
+}
+
+/// Parses a transaction copying it out of the buffer.
+pub(crate) fn parse_transaction(slice: &[u8]) -> Result<(TransactionOwned, usize), Error> {
+    let tx = Transaction::parse(slice)?;
+    Ok((tx.parsed().into(), tx.consumed()))
+}
+
+/// Parses a block header copying it out of the buffer.
+pub(crate) fn parse_block_header(slice: &[u8]) -> Result<(BlockHeaderOwned, usize), Error> {
+    let header = BlockHeader::parse(slice)?;
+    Ok((header.parsed().into(), header.consumed()))
+}
+
+/// Parses consecutive objects, like the transactions of a block, from a [`Read`] stream, without
+/// reading the whole stream in memory.
+///
+/// Only the bytes of the object being parsed are kept in an internal buffer, then copied out in
+/// an owned object. The buffer grows as needed up to the maximum size given at construction.
+///
+/// Since the stream is read in chunks, the buffer may contain bytes after the last parsed object,
+/// see [`ReadParser::buffered`].
+#[derive(Debug)]
+pub struct ReadParser<R> {
+    reader: R,
+    buffer: StreamBuffer,
+}
+
+impl<R: Read> ReadParser<R> {
+    /// Creates the parser reading from `reader`, with a maximum object size of
+    /// [`MAX_BLOCK_WEIGHT`] bytes, which no transaction can exceed.
+    pub fn new(reader: R) -> Self {
+        Self::with_max_size(reader, MAX_BLOCK_WEIGHT)
+    }
+
+    /// Creates the parser reading fom `reader`, objects bigger than `max_size` bytes fail with
+    /// [`StreamError::ItemTooLarge`].
+    pub fn with_max_size(reader: R, max_size: usize) -> Self {
+        ReadParser {
+            reader,
+            buffer: StreamBuffer::new(max_size),
+        }
+    }
+
+    /// Returns the next transaction, or `None` if the stream ended after the previous object.
+    pub fn next_transaction(&mut self) -> Result<Option<TransactionOwned>, StreamError> {
+        self.next(parse_transaction)
+    }
+
+    /// Returns the next block header, or `None` if the stream ended after the previous object.
+    pub fn next_block_header(&mut self) -> Result<Option<BlockHeaderOwned>, StreamError> {
+        self.next(parse_block_header)
+    }
+
+    /// Returns the bytes read from the stream and not yet parsed.
+    pub fn buffered(&self) -> &[u8] {
+        self.buffer.buffered()
+    }
+
+    /// Returns the underlying reader, the bytes in [`ReadParser::buffered`] are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn next<O>(&mut self, parse: ParseFn<O>) -> Result<Option<O>, StreamError> {
+        loop {
+            let mut needed = match self.buffer.try_next(parse)? {
+                Ok(object) => return Ok(Some(object)),
+                Err(needed) => needed,
+            };
+            // `needed` bytes at least are necessary before the parsing can progress
+            while needed > 0 {
+                let space = self.buffer.space(needed)?;
+                let read = match self.reader.read(space) {
+                    Ok(0) if self.buffer.buffered().is_empty() => return Ok(None),
+                    Ok(0) => return Err(StreamError::UnexpectedEof),
+                    Ok(read) => read,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e.into()),
+                };
+                self.buffer.commit(read);
+                needed = needed.saturating_sub(read);
+            }
+        }
+    }
+}
+
+/// Parses consecutive objects from an [`AsyncRead`](tokio::io::AsyncRead) stream, the async
+/// counterpart of [`ReadParser`].
+///
+/// The futures returned are cancellation safe: if dropped before completion no object is lost,
+/// the bytes already read are kept in the buffer and parsed by the next call.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct AsyncReadParser<R> {
+    reader: R,
+    buffer: StreamBuffer,
+}
+
+#[cfg(feature = "tokio")]
+impl<R: tokio::io::AsyncRead + Unpin> AsyncReadParser<R> {
+    /// Creates the parser reading from `reader`, with a maximum object size of
+    /// [`MAX_BLOCK_WEIGHT`] bytes, which no transaction can exceed.
+    pub fn new(reader: R) -> Self {
+        Self::with_max_size(reader, MAX_BLOCK_WEIGHT)
+    }
+
+    /// Creates the parser reading fom `reader`, objects bigger than `max_size` bytes fail with
+    /// [`StreamError::ItemTooLarge`].
+    pub fn with_max_size(reader: R, max_size: usize) -> Self {
+        AsyncReadParser {
+            reader,
+            buffer: StreamBuffer::new(max_size),
+        }
+    }
+
+    /// Returns the next transaction, or `None` if the stream ended after the previous object.
+    pub async fn next_transaction(&mut self) -> Result<Option<TransactionOwned>, StreamError> {
+        self.next(parse_transaction).await
+    }
+
+    /// Returns the next block header, or `None` if the stream ended after the previous object.
+    pub async fn next_block_header(&mut self) -> Result<Option<BlockHeaderOwned>, StreamError> {
+        self.next(parse_block_header).await
+    }
+
+    /// Returns the bytes read from the stream and not yet parsed.
+    pub fn buffered(&self) -> &[u8] {
+        self.buffer.buffered()
+    }
+
+    /// Returns the underlying reader, the bytes in [`AsyncReadParser::buffered`] are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    async fn next<O>(&mut self, parse: ParseFn<O>) -> Result<Option<O>, StreamError> {
+        use tokio::io::AsyncReadExt;
+        loop {
+            let mut needed = match self.buffer.try_next(parse)? {
+                Ok(object) => return Ok(Some(object)),
+                Err(needed) => needed,
+            };
+            while needed > 0 {
+                let space = self.buffer.space(needed)?;
+                // `read` is cancellation safe and the buffer is updated only after it completes
+                let read = match self.reader.read(space).await {
+                    Ok(0) if self.buffer.buffered().is_empty() => return Ok(None),
+                    Ok(0) => return Err(StreamError::UnexpectedEof),
+                    Ok(read) => read,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e.into()),
+                };
+                self.buffer.commit(read);
+                needed = needed.saturating_sub(read);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{self, Read};
+
+    use crate::{
+        bsl::{ReadParser, Transaction, TransactionOwned},
+        test_common::{GENESIS_BLOCK, GENESIS_BLOCK_HEADER, GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+        Error, Parse, StreamError,
+    };
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    /// Returns at most `chunk` bytes for every read.
+    struct Throttled<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+    impl<'a> Read for Throttled<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(self.chunk).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn owned(tx: &[u8]) -> TransactionOwned {
+        Transaction::parse_all(tx).unwrap().into()
+    }
+
+    #[test]
+    fn read_parser() {
+        let data = [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]].concat();
+        for chunk in [1, 2, 7, 1000] {
+            let mut parser = ReadParser::new(Throttled { data: &data, chunk });
+            for tx in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+                assert_eq!(parser.next_transaction().unwrap(), Some(owned(tx)));
+            }
+            assert_eq!(parser.next_transaction().unwrap(), None);
+            assert_eq!(parser.next_transaction().unwrap(), None);
+        }
+
+        let mut parser = ReadParser::new(Throttled {
+            data: &GENESIS_BLOCK,
+            chunk: 1,
+        });
+        let header = parser.next_block_header().unwrap().unwrap();
+        assert_eq!(header.as_ref(), &GENESIS_BLOCK_HEADER[..]);
+        assert_eq!(parser.buffered(), &[] as &[u8]);
+        let mut count = [0u8; 1];
+        parser.into_inner().read_exact(&mut count).unwrap();
+        assert_eq!(count[0], 1);
+
+        let mut parser = ReadParser::new(&GENESIS_BLOCK[..]);
+        parser.next_block_header().unwrap().unwrap();
+        assert_eq!(parser.buffered(), &GENESIS_BLOCK[80..]);
+    }
+
+    #[test]
+    fn read_parser_block() {
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        // skip the header and the transaction count
+        let txs = &block_bytes[83..];
+        for chunk in [13, 1000, 1 << 20] {
+            let mut parser = ReadParser::new(Throttled { data: txs, chunk });
+            for tx_bitcoin in block.txdata.iter() {
+                let tx = parser.next_transaction().unwrap().unwrap();
+                assert_eq!(tx.as_ref(), &serialize(tx_bitcoin)[..]);
+            }
+            assert_eq!(parser.next_transaction().unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn read_parser_errors() {
+        let truncated = &LEGACY_TX[..LEGACY_TX.len() - 1];
+        let mut parser = ReadParser::new(Throttled {
+            data: truncated,
+            chunk: 1,
+        });
+        assert!(matches!(
+            parser.next_transaction(),
+            Err(StreamError::UnexpectedEof)
+        ));
+
+        let mut invalid = SEGWIT_TX;
+        invalid[5] = 2;
+        let mut parser = ReadParser::new(&invalid[..]);
+        assert!(matches!(
+            parser.next_transaction(),
+            Err(StreamError::Parse(Error::UnknownSegwitFlag(2)))
+        ));
+
+        let data = [&GENESIS_TX[..], &LEGACY_TX[..]].concat();
+        let mut parser = ReadParser::with_max_size(
+            Throttled {
+                data: &data,
+                chunk: 3,
+            },
+            210,
+        );
+        assert_eq!(parser.next_transaction().unwrap(), Some(owned(&GENESIS_TX)));
+        assert_eq!(parser.next_transaction().unwrap(), Some(owned(&LEGACY_TX)));
+        let mut parser = ReadParser::with_max_size(&data[..], 210);
+        assert_eq!(parser.next_transaction().unwrap(), Some(owned(&GENESIS_TX)));
+        assert_eq!(parser.next_transaction().unwrap(), Some(owned(&LEGACY_TX)));
+        let mut parser = ReadParser::with_max_size(&LEGACY_TX[..], 150);
+        assert!(matches!(
+            parser.next_transaction(),
+            Err(StreamError::ItemTooLarge)
+        ));
+
+        struct Failing;
+        impl Read for Failing {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+        }
+        let mut parser = ReadParser::new(Failing);
+        assert!(matches!(parser.next_transaction(), Err(StreamError::Io(_))));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_read_parser() {
+        use crate::bsl::AsyncReadParser;
+        use bitcoin::secp256k1::rand::{rngs::StdRng, Rng, SeedableRng};
+        use tokio::io::AsyncWriteExt;
+
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        // the header and the transactions, without the transactions count between them
+        let data = [&block_bytes[..80], &block_bytes[83..]].concat();
+        let (mut writer, reader) = tokio::io::duplex(4096);
+        // seeded so that a failure can be reproduced with the same chunks
+        let mut rng = StdRng::seed_from_u64(702861);
+        let mut chunks = vec![];
+        let mut remaining = &data[..];
+        while !remaining.is_empty() {
+            let len = rng.gen_range(1..2000).min(remaining.len());
+            chunks.push(remaining[..len].to_vec());
+            remaining = &remaining[len..];
+        }
+        let write = tokio::spawn(async move {
+            for chunk in chunks {
+                writer.write_all(&chunk).await.unwrap();
+            }
+        });
+
+        let mut parser = AsyncReadParser::new(reader);
+        let header = parser.next_block_header().await.unwrap().unwrap();
+        assert_eq!(header.as_ref(), &block_bytes[..80]);
+        for tx_bitcoin in block.txdata.iter() {
+            let tx = parser.next_transaction().await.unwrap().unwrap();
+            assert_eq!(tx.as_ref(), &serialize(tx_bitcoin)[..]);
+        }
+        assert_eq!(parser.next_transaction().await.unwrap(), None);
+        write.await.unwrap();
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_read_parser_cancel() {
+        use crate::bsl::AsyncReadParser;
+        use core::future::Future;
+        use core::task::{Context, Poll, Waker};
+        use tokio::io::AsyncWriteExt;
+
+        let (mut writer, reader) = tokio::io::duplex(4096);
+        let mut parser = AsyncReadParser::new(reader);
+        writer.write_all(&LEGACY_TX[..100]).await.unwrap();
+        {
+            // the future reads the available bytes and waits for the others, then it's dropped
+            let future = core::pin::pin!(parser.next_transaction());
+            let mut context = Context::from_waker(Waker::noop());
+            assert!(matches!(future.poll(&mut context), Poll::Pending));
+        }
+        assert_eq!(parser.buffered(), &LEGACY_TX[..100]);
+        writer.write_all(&LEGACY_TX[100..]).await.unwrap();
+        drop(writer);
+        assert_eq!(
+            parser.next_transaction().await.unwrap(),
+            Some(owned(&LEGACY_TX))
+        );
+        assert_eq!(parser.next_transaction().await.unwrap(), None);
+
+        let (mut writer, reader) = tokio::io::duplex(4096);
+        let mut parser = AsyncReadParser::new(reader);
+        writer.write_all(&LEGACY_TX[..100]).await.unwrap();
+        drop(writer);
+        assert!(matches!(
+            parser.next_transaction().await,
+            Err(StreamError::UnexpectedEof)
+        ));
+    }
+}
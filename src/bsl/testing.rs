@@ -0,0 +1,176 @@
+//! [`mod@proptest`] strategies generating valid consensus serialized transactions and blocks, for
+//! property tests.
+//!
+//! ```
+//! use bitcoin_slices::{bsl, proptest::prelude::*};
+//!
+//! proptest!(|(tx in bsl::testing::transaction(bsl::testing::TxShape::default()))| {
+//!     let (_, remaining) = bsl::parse::<bsl::Transaction>(&tx).unwrap();
+//!     prop_assert!(remaining.is_empty());
+//! });
+//! ```
+
+use core::ops::RangeInclusive;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use std::vec::Vec;
+
+use crate::bsl::writer::{TxParts, Writer};
+
+/// The shape of the transactions generated by [`transaction()`] and [`block()`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TxShape {
+    /// The range of the number of inputs.
+    pub inputs: RangeInclusive<usize>,
+
+    /// The range of the number of outputs.
+    pub outputs: RangeInclusive<usize>,
+
+    /// The probability of a transaction with inputs to be segwit, transactions with no inputs are
+    /// always serialized with the segwit marker, thus they aren't valid for consensus.
+    pub segwit_probability: f64,
+
+    /// The range of the length of the script sigs and the script pubkeys.
+    pub script_len: RangeInclusive<usize>,
+
+    /// The range of the number of elements of a witness.
+    ///
+    /// A segwit transaction with inputs has at least a non-empty witness, so if all the generated
+    /// ones are empty the last one gets an empty element, even if this range excludes 1.
+    pub witness_elements: RangeInclusive<usize>,
+
+    /// The range of the length of the witness elements.
+    pub witness_element_len: RangeInclusive<usize>,
+}
+
+impl Default for TxShape {
+    /// Small transactions, reaching edge cases like no outputs and 255 elements witnesses.
+    fn default() -> Self {
+        TxShape {
+            inputs: 0..=4,
+            outputs: 0..=4,
+            segwit_probability: 0.5,
+            script_len: 0..=100,
+            witness_elements: 0..=255,
+            witness_element_len: 0..=80,
+        }
+    }
+}
+
+/// Returns a strategy generating a valid consensus serialized transaction of the given `shape`.
+pub fn transaction(shape: TxShape) -> impl Strategy<Value = Vec<u8>> {
+    let input = (
+        vec(any::<u8>(), 36),
+        vec(any::<u8>(), shape.script_len.clone()),
+        any::<u32>(),
+    );
+    let output = (any::<u64>(), vec(any::<u8>(), shape.script_len.clone()));
+    let witness = vec(
+        vec(any::<u8>(), shape.witness_element_len.clone()),
+        shape.witness_elements.clone(),
+    );
+    (
+        any::<i32>(),
+        vec(input, shape.inputs.clone()),
+        vec(output, shape.outputs.clone()),
+        prop::bool::weighted(shape.segwit_probability),
+        any::<u32>(),
+    )
+        .prop_flat_map(move |(version, inputs, outputs, segwit, locktime)| {
+            let witnesses = if segwit || inputs.is_empty() {
+                inputs.len()
+            } else {
+                0
+            };
+            (vec(witness.clone(), witnesses)).prop_map(move |witnesses| {
+                let mut writer = Writer::default();
+                writer.push_transaction(&TxParts {
+                    version,
+                    inputs: inputs.clone(),
+                    outputs: outputs.clone(),
+                    segwit,
+                    witnesses,
+                    locktime,
+                });
+                writer.bytes
+            })
+        })
+}
+
+/// Returns a strategy generating a valid consensus serialized block, with an arbitrary header and
+/// a number of transactions in the `transactions` range, of the given `shape`.
+///
+/// The block is valid for parsing, no consensus rule is checked, like the merkle root.
+pub fn block(
+    shape: TxShape,
+    transactions: RangeInclusive<usize>,
+) -> impl Strategy<Value = Vec<u8>> {
+    (vec(any::<u8>(), 80), vec(transaction(shape), transactions)).prop_map(
+        |(header, transactions)| {
+            let mut writer = Writer::default();
+            writer.push_bytes(&header);
+            writer.push_len(transactions.len());
+            for tx in transactions {
+                writer.push_bytes(&tx);
+            }
+            writer.bytes
+        },
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::consensus::deserialize;
+    use proptest::prelude::*;
+
+    use super::{block, transaction, TxShape};
+    use crate::{
+        bsl::{Block, Transaction},
+        Parse,
+    };
+
+    proptest! {
+        #[test]
+        fn transaction_parse(tx in transaction(TxShape::default())) {
+            let parsed = Transaction::parse(&tx).unwrap();
+            prop_assert_eq!(parsed.consumed(), tx.len());
+            let reparsed = Transaction::parse_all(parsed.parsed().as_ref()).unwrap();
+            prop_assert_eq!(&reparsed, parsed.parsed());
+
+            let tx_bitcoin: bitcoin::Transaction = deserialize(&tx).unwrap();
+            prop_assert_eq!(reparsed.input_count(), tx_bitcoin.input.len());
+            prop_assert_eq!(reparsed.output_count(), tx_bitcoin.output.len());
+            #[cfg(feature = "bitcoin_hashes")]
+            prop_assert_eq!(
+                &reparsed.txid()[..],
+                &tx_bitcoin.txid()[..]
+            );
+        }
+
+        #[test]
+        fn block_parse(block in block(TxShape::default(), 1..=8)) {
+            let parsed = Block::parse(&block).unwrap();
+            prop_assert_eq!(parsed.consumed(), block.len());
+            let reparsed = Block::parse_all(parsed.parsed().as_ref()).unwrap();
+            prop_assert_eq!(&reparsed, parsed.parsed());
+
+            let block_bitcoin: bitcoin::Block = deserialize(&block).unwrap();
+            prop_assert_eq!(reparsed.total_transactions(), block_bitcoin.txdata.len());
+        }
+
+        #[test]
+        fn transaction_shape(tx in transaction(TxShape {
+            inputs: 1..=1,
+            outputs: 0..=0,
+            segwit_probability: 1.0,
+            witness_elements: 255..=255,
+            witness_element_len: 0..=1,
+            ..TxShape::default()
+        })) {
+            let tx = Transaction::parse_all(&tx).unwrap();
+            prop_assert!(tx.has_witness());
+            prop_assert_eq!(tx.output_count(), 0);
+            prop_assert_eq!(tx.witness(0).unwrap().iter().len(), 255);
+        }
+    }
+}
@@ -1,32 +1,155 @@
+use crate::visit::check;
 use core::num::NonZeroU32;
+use core::ops::RangeInclusive;
 
 use crate::{
-    bsl::{TxIns, TxOuts, Witnesses},
+    bsl::script::{
+        bip34_height, op_return_payload, p2sh_redeem_script, sigop_count, witness_program,
+        witness_sigop_count,
+    },
+    bsl::{
+        len::parse_len_with, LockTime, OutPoint, ScriptKind, TxIn, TxInIterator, TxIns, TxOut,
+        TxOutIterator, TxOuts, TxidHasher, Witness, Witnesses,
+    },
     number::{I32, U32, U8},
-    Error, Parse, ParseResult, SResult, Visit, Visitor,
+    AmountError, EmptyVisitor, Error, FeeError, Parse, ParseResult, SResult, SizeLimitError, Visit,
+    Visitor,
 };
 
+/// The maximum amount of satoshis, 21 millions bitcoins, a transaction with an output value or a
+/// sum of the output values greater than this is invalid, see [`Transaction::check_amounts`].
+pub const MAX_MONEY: u64 = 21_000_000 * 100_000_000;
+
 /// A Bitcoin transaction
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Transaction<'a> {
-    slice: &'a [u8],
+    pub(crate) slice: &'a [u8],
 
     /// The length of the slice inlcuding all inputs and outputs of the transaction.
-    /// If some the tx is segwit
-    inputs_outputs_len: Option<NonZeroU32>,
+    /// If some the tx is segwit, and it has at least one non-empty witness since parsing fails with
+    /// [`Error::SegwitFlagWithoutWitnesses`] otherwise.
+    pub(crate) inputs_outputs_len: Option<NonZeroU32>,
+
+    /// The number of inputs, a `u32` is enough since every input is at least 41 bytes.
+    pub(crate) input_count: u32,
+
+    /// The number of outputs, a `u32` is enough since every output is at least 9 bytes.
+    pub(crate) output_count: u32,
 }
 
 impl<'a> Visit<'a> for Transaction<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        Self::visit_with_config(slice, visit, &ParseConfig::default())
+    }
+}
+
+/// Options for parsing transactions with [`Transaction::parse_with_config`], or the transactions
+/// of a block with [`crate::bsl::Block::parse_with_config`], the default is the same behavior of
+/// [`Parse::parse`].
+///
+/// The other functions parse with the default, like [`Transaction::parse_with_offset`] and
+/// [`Transaction::parse_with_limits`], except `TransactionOwned` and `BlockOwned` which re-parse
+/// their bytes, from a database or a deserialization, accepting what any config accepts. A
+/// [`crate::bsl::Block`] keeps the config it was parsed with to visit again its transactions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseConfig {
+    /// If some, parsing fails with [`Error::UnsupportedVersion`] if the transaction version is not
+    /// in the range, like `Some(1..=3)`.
+    pub allowed_versions: Option<RangeInclusive<i32>>,
+
+    /// Accept legacy serialized transactions with zero inputs, see
+    /// [`Transaction::parse_zero_inputs`].
+    pub allow_zero_inputs: bool,
+
+    /// Accept compact ints not in their shortest encoding, like `0xfd0100` for 1, instead of
+    /// failing with [`Error::NonMinimalVarInt`], as found in some non-consensus data.
+    ///
+    /// The structure is still fully validated, and the transaction keeps the bytes as found, so
+    /// the consumed bytes and the txid are computed on the non-minimal encoding.
+    pub allow_non_minimal_compact_sizes: bool,
+
+    /// Fail with [`Error::InvalidAmount`] if an output value or the sum of the output values is
+    /// greater than [`MAX_MONEY`], see [`Transaction::check_amounts`].
+    ///
+    /// The outputs are walked again after parsing them, before [`Visitor::visit_transaction`].
+    pub check_amounts: bool,
+}
+
+impl<'a> Transaction<'a> {
+    /// Parse a transaction like [`Parse::parse`], but accepting legacy serialized transactions
+    /// with zero inputs, as produced by some tools, instead of failing with
+    /// [`Error::UnknownSegwitFlag`].
+    ///
+    /// An empty input list followed by `0x01` is still parsed as the segwit marker and flag, so a
+    /// zero inputs legacy transaction with exactly one output can't be parsed.
+    pub fn parse_zero_inputs(slice: &'a [u8]) -> SResult<'a, Self> {
+        Self::visit_zero_inputs(slice, &mut EmptyVisitor {})
+    }
+
+    /// Visit a transaction like [`Visit::visit`], but accepting legacy serialized transactions
+    /// with zero inputs, see [`Transaction::parse_zero_inputs`].
+    pub fn visit_zero_inputs<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+    ) -> SResult<'a, Self> {
+        let config = ParseConfig {
+            allow_zero_inputs: true,
+            ..Default::default()
+        };
+        Self::visit_with_config(slice, visit, &config)
+    }
+
+    /// Returns the length of the transaction at the start of `slice`, validating it like
+    /// [`Parse::parse`].
+    ///
+    /// This is parsing with the [`EmptyVisitor`], whose callbacks are compiled away, so it costs
+    /// the same, but it doesn't require the caller to deal with the [`ParseResult`].
+    pub fn skip(slice: &'a [u8]) -> Result<usize, Error> {
+        Ok(Self::parse(slice)?.consumed())
+    }
+
+    /// Parse a transaction like [`Parse::parse`], with the options in `config`.
+    pub fn parse_with_config(slice: &'a [u8], config: &ParseConfig) -> SResult<'a, Self> {
+        Self::visit_with_config(slice, &mut EmptyVisitor {}, config)
+    }
+
+    /// Visit a transaction like [`Visit::visit`], with the options in `config`.
+    pub fn visit_with_config<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        config: &ParseConfig,
+    ) -> SResult<'a, Self> {
+        Self::visit_at(slice, 0, visit, config)
+    }
+
+    /// Visit the transaction at position `tx_index` in a block.
+    pub(crate) fn visit_at<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        tx_index: usize,
+        visit: &'b mut V,
+        config: &ParseConfig,
+    ) -> SResult<'a, Self> {
         let version = I32::parse(slice)?;
-        let inputs = TxIns::visit(version.remaining(), visit)?;
-        if inputs.parsed().is_empty() {
-            let segwit_flag = U8::parse(inputs.remaining())?;
+        let remaining = version.remaining();
+        if let Some(allowed_versions) = config.allowed_versions.as_ref() {
+            let version: i32 = version.parsed_owned().into();
+            if !allowed_versions.contains(&version) {
+                return Err(Error::UnsupportedVersion(version));
+            }
+        }
+        // the segwit marker is a zero inputs count, it's not visited as such
+        let lenient = config.allow_non_minimal_compact_sizes;
+        let zero_inputs_legacy =
+            config.allow_zero_inputs && remaining.get(1).is_some_and(|flag| *flag != 1);
+        if remaining.first() == Some(&0) && !zero_inputs_legacy {
+            let segwit_flag = U8::parse(&remaining[1..])?;
             let segwit_flag_u8 = segwit_flag.parsed().into();
             if segwit_flag_u8 == 1 {
-                let inputs = TxIns::visit(segwit_flag.remaining(), visit)?;
-                let outputs = TxOuts::visit(inputs.remaining(), visit)?;
-                let witnesses = Witnesses::visit(outputs.remaining(), inputs.parsed().n(), visit)?;
+                let inputs = TxIns::visit_with(segwit_flag.remaining(), visit, lenient)?;
+                let outputs = TxOuts::visit_at(inputs.remaining(), tx_index, visit, lenient)?;
+                let total_inputs = inputs.parsed().n();
+                let witnesses =
+                    Witnesses::visit_with(outputs.remaining(), total_inputs, visit, lenient)?;
 
                 if !inputs.parsed().is_empty() && witnesses.parsed().all_empty() {
                     return Err(Error::SegwitFlagWithoutWitnesses);
@@ -34,32 +157,57 @@ impl<'a> Visit<'a> for Transaction<'a> {
 
                 let _locktime = U32::parse(witnesses.remaining())?;
                 let consumed = 10 + inputs.consumed() + outputs.consumed() + witnesses.consumed();
-                let inputs_outputs_len =
-                    inputs.parsed().as_ref().len() + outputs.parsed().as_ref().len();
+                let inputs_outputs_len = checked_inputs_outputs_len(
+                    inputs.parsed().as_ref().len() + outputs.parsed().as_ref().len(),
+                )?;
 
                 let tx = Transaction {
                     slice: &slice[..consumed],
-                    inputs_outputs_len: NonZeroU32::new(inputs_outputs_len as u32), // inputs_outputs_len is at least 2 bytes if both empty, they contain the compact int len
+                    inputs_outputs_len: Some(inputs_outputs_len),
+                    input_count: inputs.parsed().n() as u32,
+                    output_count: outputs.parsed().n() as u32,
                 };
+                if config.check_amounts {
+                    tx.check_amounts().map_err(Error::InvalidAmount)?;
+                }
                 visit.visit_transaction(&tx);
+                check(visit)?;
                 Ok(ParseResult::new(&slice[consumed..], tx))
             } else {
                 Err(Error::UnknownSegwitFlag(segwit_flag_u8))
             }
         } else {
-            let outputs = TxOuts::visit(inputs.remaining(), visit)?;
+            let inputs = TxIns::visit_with(remaining, visit, lenient)?;
+            let outputs = TxOuts::visit_at(inputs.remaining(), tx_index, visit, lenient)?;
             let _locktime = U32::parse(outputs.remaining())?;
             let consumed = inputs.consumed() + outputs.consumed() + 8;
 
             let tx = Transaction {
                 slice: &slice[..consumed],
                 inputs_outputs_len: None,
+                input_count: inputs.parsed().n() as u32,
+                output_count: outputs.parsed().n() as u32,
             };
+            if config.check_amounts {
+                tx.check_amounts().map_err(Error::InvalidAmount)?;
+            }
             visit.visit_transaction(&tx);
+            check(visit)?;
             Ok(ParseResult::new(&slice[consumed..], tx))
         }
     }
 }
+
+/// Converts the length of the inputs and outputs region of a segwit transaction, returning
+/// [`Error::TooLarge`] if it doesn't fit in a `u32` instead of truncating it.
+fn checked_inputs_outputs_len(len: usize) -> Result<NonZeroU32, Error> {
+    // inputs_outputs_len is at least 2 bytes if both empty, they contain the compact int len
+    u32::try_from(len)
+        .ok()
+        .and_then(NonZeroU32::new)
+        .ok_or(Error::TooLarge)
+}
+
 impl<'a> Transaction<'a> {
     /// Returns the transaction version.
     pub fn version(&self) -> i32 {
@@ -78,6 +226,360 @@ impl<'a> Transaction<'a> {
             .into()
     }
 
+    /// Returns the transaction locktime interpreted as a block height or a unix timestamp.
+    ///
+    /// Note the locktime is enforced only if [`Transaction::is_lock_time_enabled()`].
+    pub fn lock_time_kind(&self) -> LockTime {
+        self.locktime().into()
+    }
+
+    /// Returns true if the locktime is enforced, meaning at least one input has a sequence
+    /// different from `0xffffffff`, otherwise the locktime is ignored.
+    pub fn is_lock_time_enabled(&self) -> bool {
+        self.inputs().any(|tx_in| tx_in.sequence() != u32::MAX)
+    }
+
+    /// Returns true if the transaction can be included in a block at the given `height` and with
+    /// the given `block_time`, as checked by Bitcoin Core `IsFinalTx`.
+    ///
+    /// A transaction is final if the locktime is zero, if it's lower than `height` or
+    /// `block_time` according to its kind, or if all the inputs sequences are `0xffffffff`.
+    pub fn is_final(&self, height: u32, block_time: u32) -> bool {
+        let is_lock_time_passed = match self.lock_time_kind() {
+            LockTime::Blocks(0) => true,
+            LockTime::Blocks(n) => n < height,
+            LockTime::Seconds(n) => n < block_time,
+        };
+        is_lock_time_passed || !self.is_lock_time_enabled()
+    }
+
+    /// Returns true if this is a coinbase transaction, meaning it has exactly one input spending
+    /// the null outpoint (txid all zeros and vout `0xffffffff`).
+    pub fn is_coinbase(&self) -> bool {
+        let inputs = &self.slice[self.inputs_offset()..];
+        match parse_len_with(inputs, true) {
+            Ok(len) if len.n() == 1 => OutPoint::parse(&inputs[len.consumed()..])
+                .expect("slice length granted during parsing")
+                .parsed()
+                .is_null(),
+            _ => false,
+        }
+    }
+
+    /// Returns the block height encoded at the start of the script sig of a coinbase transaction as
+    /// required by BIP34, or `None` if this is not a coinbase or the height is not found.
+    ///
+    /// The height must be encoded exactly as Bitcoin Core does, so that pre-BIP34 coinbases
+    /// starting with an arbitrary push return `None`. Heights of 8_388_608 or more, needing a
+    /// 4 bytes push, are not supported, since that's also how the `nBits` pushed at the start of
+    /// early coinbases looks like.
+    pub fn coinbase_height(&self) -> Option<u32> {
+        if !self.is_coinbase() {
+            return None;
+        }
+        bip34_height(self.input(0)?.script_sig())
+    }
+
+    /// Returns the BIP141 witness reserved value of a coinbase transaction: the single 32 bytes
+    /// element of the witness of its input.
+    ///
+    /// Returns `None` if this is not a coinbase, it has no witness, or the witness has more
+    /// elements or an element of a different length.
+    pub fn witness_reserved_value(&self) -> Option<&'a [u8; 32]> {
+        if !self.is_coinbase() {
+            return None;
+        }
+        let mut witness = self.witness(0)?.iter();
+        match (witness.next(), witness.next()) {
+            (Some(reserved_value), None) => reserved_value.try_into().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the transaction is serialized with the segwit marker, flag and witnesses.
+    ///
+    /// Segwit serialized transactions always have at least one non-empty witness, parsing fails
+    /// with [`Error::SegwitFlagWithoutWitnesses`] otherwise.
+    pub fn has_witness(&self) -> bool {
+        self.inputs_outputs_len.is_some()
+    }
+
+    /// Returns the number of inputs of this transaction, computed during parsing.
+    pub fn input_count(&self) -> usize {
+        self.input_count as usize
+    }
+
+    /// Returns the number of outputs of this transaction, computed during parsing.
+    pub fn output_count(&self) -> usize {
+        self.output_count as usize
+    }
+
+    /// Returns a lazy iterator over the inputs of this transaction.
+    ///
+    /// If possible is better to use [`Visitor::visit_tx_in`] to avoid double pass, however, it may
+    /// be conveniet to iterate in case you already have validated the slice, for example some data
+    /// in a db.
+    pub fn inputs(&self) -> TxInIterator<'a> {
+        TxInIterator::new(&self.slice[self.inputs_offset()..])
+    }
+
+    /// Returns the `n`th input of this transaction, or `None` if `n` is out of range.
+    ///
+    /// Inputs are walked up to the requested one, witnesses are never touched.
+    pub fn input(&self, n: usize) -> Option<TxIn<'a>> {
+        self.inputs().nth(n)
+    }
+
+    /// Returns a lazy iterator over the outpoints spent by this transaction, in input order.
+    ///
+    /// Scripts are skipped and witnesses are never touched. A coinbase transaction yields its
+    /// single null outpoint, use [`OutPoint::is_null`] to filter it out.
+    pub fn spent_outpoints(&self) -> impl ExactSizeIterator<Item = OutPoint<'a>> {
+        self.inputs().map(|tx_in| tx_in.prevout().clone())
+    }
+
+    /// Returns the witness of the `n`th input of this transaction, or `None` if `n` is out of range
+    /// or the transaction has no witnesses.
+    ///
+    /// Inputs and outputs are skipped and witnesses are walked up to the requested one.
+    pub fn witness(&self, n: usize) -> Option<Witness<'a>> {
+        let len = self.inputs_outputs_len?;
+        if n >= self.input_count() {
+            return None;
+        }
+        let mut remaining = &self.slice[6 + len.get() as usize..];
+        for _ in 0..n {
+            remaining = Witness::parse_with(remaining, true)
+                .expect("granted during parsing")
+                .remaining();
+        }
+        Some(
+            Witness::parse_with(remaining, true)
+                .expect("granted during parsing")
+                .parsed_owned(),
+        )
+    }
+
+    /// Returns whether the witness of the `n`th input ends with an element looking like a taproot
+    /// annex, see [`Witness::annex`], or `None` if `n` is out of range.
+    ///
+    /// Only the syntactic pattern is checked, the previous output would be needed to know if the
+    /// input spends a taproot output.
+    pub fn input_has_annex(&self, n: usize) -> Option<bool> {
+        if n >= self.input_count() {
+            return None;
+        }
+        Some(self.witness(n).is_some_and(|w| w.annex().is_some()))
+    }
+
+    /// Returns true if the witness of any input ends with an element looking like a taproot annex,
+    /// see [`Transaction::input_has_annex`].
+    pub fn any_annex(&self) -> bool {
+        let len = match self.inputs_outputs_len {
+            Some(len) => len,
+            None => return false,
+        };
+        let mut remaining = &self.slice[6 + len.get() as usize..];
+        (0..self.input_count()).any(|_| {
+            let witness = Witness::parse_with(remaining, true).expect("granted during parsing");
+            remaining = witness.remaining();
+            witness.parsed().annex().is_some()
+        })
+    }
+
+    /// Returns a lazy iterator over the outputs of this transaction, inputs are skipped once when
+    /// creating the iterator.
+    ///
+    /// If possible is better to use [`Visitor::visit_tx_out`] to avoid double pass, however, it
+    /// may be conveniet to iterate in case you already have validated the slice, for example some
+    /// data in a db.
+    pub fn outputs(&self) -> TxOutIterator<'a> {
+        TxOutIterator::new(&self.slice[self.outputs_offset()..])
+    }
+
+    /// Returns the `n`th output of this transaction, or `None` if `n` is out of range.
+    ///
+    /// Inputs are skipped and outputs are walked up to the requested one, witnesses are never
+    /// touched.
+    pub fn output(&self, n: usize) -> Option<TxOut<'a>> {
+        self.outputs().nth(n)
+    }
+
+    /// Returns the kind of the script pubkey of the `n`th output, or `None` if `n` is out of range,
+    /// see [`crate::bsl::Script::kind`].
+    pub fn output_script_kind(&self, n: usize) -> Option<ScriptKind> {
+        self.output(n).map(|tx_out| tx_out.script_kind())
+    }
+
+    /// Returns the sum of the values of all the outputs (satoshi), or `None` if the sum overflows a
+    /// `u64`.
+    pub fn total_output_value(&self) -> Option<u64> {
+        self.outputs()
+            .try_fold(0u64, |acc, tx_out| acc.checked_add(tx_out.value()))
+    }
+
+    /// Checks the output values like Bitcoin Core `CheckTransaction`, every value and their sum
+    /// must not be greater than [`MAX_MONEY`], returns the error of the first output violating it.
+    ///
+    /// Transactions with such values parse fine, but they are invalid for consensus.
+    pub fn check_amounts(&self) -> Result<(), AmountError> {
+        let mut total = 0u64;
+        for (i, tx_out) in self.outputs().enumerate() {
+            let i = i as u32;
+            if tx_out.value() > MAX_MONEY {
+                return Err(AmountError::OutputTooLarge(i));
+            }
+            total = total
+                .checked_add(tx_out.value())
+                .filter(|total| *total <= MAX_MONEY)
+                .ok_or(AmountError::TotalTooLarge(i))?;
+        }
+        Ok(())
+    }
+
+    /// Returns a lazy iterator over the `OP_RETURN` outputs of this transaction, yielding the output
+    /// index and the pushed data, an empty slice for a bare `OP_RETURN`.
+    ///
+    /// Only scripts made of `OP_RETURN` followed by a single data push are considered, non-standard
+    /// ones with more pushes or other opcodes are skipped.
+    pub fn op_return_outputs(&self) -> impl Iterator<Item = (usize, &'a [u8])> {
+        self.outputs()
+            .enumerate()
+            .filter_map(|(i, tx_out)| op_return_payload(tx_out.script_pubkey()).map(|d| (i, d)))
+    }
+
+    /// Returns the fee paid by this transaction (satoshi), the value of every spent previous output
+    /// is asked to `lookup`.
+    ///
+    /// Returns [`FeeError::MissingPrevout`] with the input index at the first previous output not
+    /// found, the lookup is not called for the following ones. Coinbase transactions are not
+    /// special cased, the lookup is called with the null outpoint.
+    pub fn fee<F>(&self, mut lookup: F) -> Result<u64, FeeError>
+    where
+        F: FnMut(&OutPoint) -> Option<u64>,
+    {
+        let mut total_input_value = 0u64;
+        for (i, out_point) in self.spent_outpoints().enumerate() {
+            let value = lookup(&out_point).ok_or(FeeError::MissingPrevout(i))?;
+            total_input_value = total_input_value
+                .checked_add(value)
+                .ok_or(FeeError::Overflow)?;
+        }
+        let total_output_value = self.total_output_value().ok_or(FeeError::Overflow)?;
+        total_input_value
+            .checked_sub(total_output_value)
+            .ok_or(FeeError::Negative)
+    }
+
+    /// Returns true if the transaction signals replaceability as defined in BIP125, meaning at
+    /// least one input has a sequence lower than `0xfffffffe`.
+    ///
+    /// Only the inputs are walked, witnesses are never touched. Coinbase transactions are not
+    /// special cased, even if they can't be in the mempool and their sequence is meaningless.
+    pub fn signals_rbf(&self) -> bool {
+        self.inputs().any(|tx_in| tx_in.sequence() < 0xfffffffe)
+    }
+
+    /// Returns the number of legacy signature operations in the script sigs and the script pubkeys
+    /// of the transaction, as counted by Bitcoin Core `GetLegacySigOpCount`.
+    ///
+    /// Multisig operations always count as 20 and the redeem scripts of P2SH inputs are not
+    /// inspected, like in legacy counting.
+    pub fn legacy_sigop_count(&self) -> u32 {
+        let inputs = self
+            .inputs()
+            .map(|tx_in| sigop_count(tx_in.script_sig(), false));
+        let outputs = self
+            .outputs()
+            .map(|tx_out| sigop_count(tx_out.script_pubkey(), false));
+        inputs.chain(outputs).sum()
+    }
+
+    /// Returns the signature operations cost of the transaction, as counted by Bitcoin Core
+    /// `GetTransactionSigOpCost` with P2SH and segwit active: legacy and P2SH redeem script
+    /// signature operations count 4, witness ones count 1.
+    ///
+    /// `prevout_script` must return the script pubkey of the previous output spent by an input,
+    /// [`Error::MissingPrevout`] is returned otherwise. It's not called for coinbase transactions.
+    pub fn sigop_cost<'p, F>(&self, mut prevout_script: F) -> Result<u64, Error>
+    where
+        F: FnMut(&OutPoint) -> Option<&'p [u8]>,
+    {
+        let mut cost = self.legacy_sigop_count() as u64 * 4;
+        if self.is_coinbase() {
+            return Ok(cost);
+        }
+        let mut witnesses = self
+            .inputs_outputs_len
+            .map(|len| &self.slice[6 + len.get() as usize..]);
+        for tx_in in self.inputs() {
+            let witness = witnesses.as_mut().map(|remaining| {
+                let witness = Witness::parse_with(remaining, true).expect("granted during parsing");
+                *remaining = witness.remaining();
+                witness.parsed_owned()
+            });
+            let script_pubkey = prevout_script(tx_in.prevout()).ok_or(Error::MissingPrevout)?;
+            let redeem_script = match ScriptKind::from_script(script_pubkey) {
+                ScriptKind::P2sh => p2sh_redeem_script(tx_in.script_sig()),
+                _ => None,
+            };
+            if let Some(redeem_script) = redeem_script {
+                cost += sigop_count(redeem_script, true) as u64 * 4;
+            }
+            let program =
+                witness_program(script_pubkey).or_else(|| redeem_script.and_then(witness_program));
+            if let Some((version, program)) = program {
+                cost += witness_sigop_count(version, program, witness) as u64;
+            }
+        }
+        Ok(cost)
+    }
+
+    /// Returns the weight of the transaction, as defined in BIP141: the size of the transaction
+    /// without witnesses multiplied by 3 plus the total size.
+    pub fn weight(&self) -> usize {
+        self.base_size() * 3 + self.total_size()
+    }
+
+    /// Returns the size of the transaction serialized without the segwit marker, flag and
+    /// witnesses. For legacy transactions it's equal to [`Transaction::total_size()`].
+    pub fn base_size(&self) -> usize {
+        self.txid_preimage_segments().map(<[u8]>::len).sum()
+    }
+
+    /// Returns the size of the full serialized transaction, including the eventual segwit marker,
+    /// flag and witnesses.
+    pub fn total_size(&self) -> usize {
+        self.slice.len()
+    }
+
+    /// Checks the transaction against the policy size `limits`, and that it has at least one input
+    /// and one output, returning the first limit violated.
+    ///
+    /// Only sizes are checked, they are all known from the slice without hashing or visiting.
+    pub fn check_standard_size_limits(&self, limits: &SizeLimits) -> Result<(), SizeLimitError> {
+        if self.input_count() == 0 {
+            return Err(SizeLimitError::NoInputs);
+        }
+        if self.output_count() == 0 {
+            return Err(SizeLimitError::NoOutputs);
+        }
+        let weight = self.weight();
+        if weight > limits.max_weight {
+            return Err(SizeLimitError::WeightTooHigh(weight));
+        }
+        let base_size = self.base_size();
+        if base_size < limits.min_base_size {
+            return Err(SizeLimitError::BaseSizeTooSmall(base_size));
+        }
+        Ok(())
+    }
+
+    /// Returns the virtual size of the transaction, that is the weight divided by 4 rounded up.
+    pub fn vsize(&self) -> usize {
+        self.weight().div_ceil(4)
+    }
+
     /// Return the txid preimage, or the data that must be fed to the hashing function (double sha256)
     /// to obtain the txid.
     /// It is a tuple of 3 because for segwit transactions they are 3 non-contiguos bytes slices and
@@ -94,19 +596,79 @@ impl<'a> Transaction<'a> {
         }
     }
 
+    /// Return the txid preimage as an iterator of contiguous slices to be fed in order to the hashing
+    /// function, 3 for segwit transactions and 1 for legacy ones, see
+    /// [`Transaction::txid_preimage()`].
+    pub fn txid_preimage_segments(&self) -> impl ExactSizeIterator<Item = &'a [u8]> {
+        let (a, b, c) = self.txid_preimage();
+        let segments = if self.has_witness() { 3 } else { 1 };
+        [a, b, c].into_iter().take(segments)
+    }
+
+    /// Returns true if `other` has the same txid of this transaction.
+    ///
+    /// Unlike `==`, which compares the whole serialization, witnesses are ignored, so a segwit
+    /// transaction and its witness stripped serialization are equal. The txid preimages are
+    /// compared directly, streaming their segments, without hashing nor allocating.
+    pub fn eq_by_txid(&self, other: &Transaction) -> bool {
+        if self.base_size() != other.base_size() {
+            return false;
+        }
+        let mut others = other.txid_preimage_segments();
+        let mut other_segment: &[u8] = &[];
+        for mut segment in self.txid_preimage_segments() {
+            while !segment.is_empty() {
+                if other_segment.is_empty() {
+                    other_segment = others.next().expect("same base size");
+                    continue;
+                }
+                let len = segment.len().min(other_segment.len());
+                if segment[..len] != other_segment[..len] {
+                    return false;
+                }
+                segment = &segment[len..];
+                other_segment = &other_segment[len..];
+            }
+        }
+        true
+    }
+
+    /// Write the transaction without the segwit marker, flag and witnesses into `out`, returning
+    /// the number of bytes written. For legacy transactions the slice is simply copied.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if `out` can't contain the stripped transaction.
+    pub fn write_stripped(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let len = self.base_size();
+        if out.len() < len {
+            return Err(Error::BufferTooSmall(
+                u32::try_from(len).unwrap_or(u32::MAX),
+            ));
+        }
+        let mut written = 0;
+        for part in self.txid_preimage_segments() {
+            out[written..written + part.len()].copy_from_slice(part);
+            written += part.len();
+        }
+        Ok(written)
+    }
+
+    /// Return the transaction identifier computed with the given double sha256 `hasher`, in byte
+    /// order (not reversed), see [`TxidHasher`].
+    pub fn txid_with<H: TxidHasher>(&self, mut hasher: H) -> [u8; 32] {
+        for segment in self.txid_preimage_segments() {
+            hasher.update(segment);
+        }
+        hasher.finalize_double()
+    }
+
     /// Return the transaction identifier.
     /// If the transaction is legacy (non-segwit) this identifier could be malleated, meaning
     /// the same transaction effect could have different identifiers.
     #[cfg(feature = "bitcoin_hashes")]
     #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
     pub fn txid(&self) -> crate::bitcoin_hashes::sha256d::Hash {
-        use crate::bitcoin_hashes::{sha256d, Hash, HashEngine};
-        let (a, b, c) = self.txid_preimage();
-        let mut engine = sha256d::Hash::engine();
-        engine.input(a);
-        engine.input(b);
-        engine.input(c);
-        sha256d::Hash::from_engine(engine)
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        sha256d::Hash::from_inner(self.txid_with(sha256d::Hash::engine()))
     }
 
     /// Calculate the txid using the sha2 crate.
@@ -118,13 +680,126 @@ impl<'a> Transaction<'a> {
     ) -> crate::sha2::digest::generic_array::GenericArray<u8, crate::sha2::digest::typenum::U32>
     {
         use crate::sha2::{Digest, Sha256};
-        let (a, b, c) = self.txid_preimage();
-        let mut hasher = Sha256::new();
-        hasher.update(a);
-        hasher.update(b);
-        hasher.update(c);
-        let hash = hasher.finalize();
-        Sha256::digest(&hash[..])
+        self.txid_with(Sha256::new()).into()
+    }
+
+    /// Calculate the wtxid, the double hash of the full serialized transaction, with the
+    /// given [`TxidHasher`], see [`Transaction::wtxid()`].
+    pub fn wtxid_with<H: TxidHasher>(&self, mut hasher: H) -> [u8; 32] {
+        hasher.update(self.slice);
+        hasher.finalize_double()
+    }
+
+    /// Return the witness transaction identifier, the hash of the full serialized transaction
+    /// including segwit marker, flag and witnesses.
+    /// For legacy (non-segwit) transactions this is equal to [`Transaction::txid()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn wtxid(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        sha256d::Hash::hash(self.slice)
+    }
+
+    /// Calculate the wtxid using the sha2 crate.
+    /// NOTE: the result type is not displayed backwards when converted to string.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    pub fn wtxid_sha2(
+        &self,
+    ) -> crate::sha2::digest::generic_array::GenericArray<u8, crate::sha2::digest::typenum::U32>
+    {
+        use crate::sha2::{Digest, Sha256};
+        let first = Sha256::digest(self.slice);
+        Sha256::digest(&first[..])
+    }
+
+    /// Return the normalized transaction identifier, the double sha256 of the transaction
+    /// serialized without witnesses and with all the script sigs empty.
+    /// Unlike the txid it can't be malleated by changing the script sigs, so it can be used to
+    /// detect malleated duplicates of legacy transactions.
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn ntxid(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        sha256d::Hash::from_inner(self.ntxid_with(sha256d::Hash::engine()))
+    }
+
+    /// Calculate the ntxid using the sha2 crate, see [`Transaction::ntxid()`].
+    /// NOTE: the result type is not displayed backwards when converted to string.
+    #[cfg(feature = "sha2")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+    pub fn ntxid_sha2(
+        &self,
+    ) -> crate::sha2::digest::generic_array::GenericArray<u8, crate::sha2::digest::typenum::U32>
+    {
+        use crate::sha2::{Digest, Sha256};
+        self.ntxid_with(Sha256::new()).into()
+    }
+
+    /// Return the normalized transaction identifier computed with the given double sha256
+    /// `hasher`, in byte order (not reversed), see [`Transaction::ntxid()`].
+    pub fn ntxid_with<H: TxidHasher>(&self, mut hasher: H) -> [u8; 32] {
+        let inputs_offset = self.inputs_offset();
+        let outputs_offset = self.outputs_offset();
+        let outputs_end = match self.inputs_outputs_len {
+            Some(len) => inputs_offset + len.get() as usize,
+            None => self.slice.len() - 4,
+        };
+        let consumed = parse_len_with(&self.slice[inputs_offset..], true)
+            .expect("granted during parsing")
+            .consumed;
+
+        hasher.update(&self.slice[..4]);
+        hasher.update(&self.slice[inputs_offset..inputs_offset + consumed]);
+        for tx_in in self.inputs() {
+            hasher.update(tx_in.prevout().as_ref());
+            hasher.update(&[0u8]);
+            hasher.update(&tx_in.sequence().to_le_bytes());
+        }
+        hasher.update(&self.slice[outputs_offset..outputs_end]);
+        hasher.update(&self.slice[self.slice.len() - 4..]);
+        hasher.finalize_double()
+    }
+
+    /// The offset in the slice where the transaction inputs start, after the version and the
+    /// eventual segwit marker and flag.
+    fn inputs_offset(&self) -> usize {
+        if self.inputs_outputs_len.is_some() {
+            6
+        } else {
+            4
+        }
+    }
+
+    /// The offset in the slice where the transaction outputs start, it requires walking the
+    /// inputs.
+    fn outputs_offset(&self) -> usize {
+        let inputs_offset = self.inputs_offset();
+        let inputs =
+            TxIns::parse_with(&self.slice[inputs_offset..], true).expect("granted during parsing");
+        inputs_offset + inputs.consumed()
+    }
+}
+
+/// Size limits of standard transactions, used in [`Transaction::check_standard_size_limits`].
+///
+/// The default values are the ones of Bitcoin Core policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// The maximum weight, `400_000` by default.
+    pub max_weight: usize,
+
+    /// The minimum size without witnesses, `65` by default. Bitcoin Core used `82` before version
+    /// 25.0, the size of the smallest transaction with one input and a P2WPKH output.
+    pub min_base_size: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        SizeLimits {
+            max_weight: 400_000,
+            min_base_size: 65,
+        }
     }
 }
 
@@ -134,43 +809,1033 @@ impl<'a> AsRef<[u8]> for Transaction<'a> {
     }
 }
 
+crate::hex::impl_hex_fmt!(Transaction);
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for Transaction<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> TryFrom<&Transaction<'a>> for bitcoin::Transaction {
+    type Error = bitcoin::consensus::encode::Error;
+
+    /// Deserialize the transaction slice with rust-bitcoin, it doesn't fail for transactions
+    /// parsed by this crate unless they exceed rust-bitcoin allocation limits.
+    fn try_from(tx: &Transaction<'a>) -> Result<Self, Self::Error> {
+        bitcoin::consensus::deserialize(tx.as_ref())
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl<'a> TryFrom<Transaction<'a>> for bitcoin::Transaction {
+    type Error = bitcoin::consensus::encode::Error;
+
+    fn try_from(tx: Transaction<'a>) -> Result<Self, Self::Error> {
+        (&tx).try_into()
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{bsl::Transaction, test_common::GENESIS_TX, Parse};
-    use bitcoin::consensus::deserialize;
+    use crate::{
+        bsl::{Block, LockTime, ParseConfig, ScriptKind, SizeLimits, Transaction, TxIn, MAX_MONEY},
+        test_common::{GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+        AmountError, Error, FeeError, Parse, SizeLimitError, Visit, Visitor,
+    };
+    use bitcoin::consensus::{deserialize, serialize};
+    use bitcoin_test_data::blocks::mainnet_702861;
     use hex_lit::hex;
 
+    #[test]
+    fn skip() {
+        let data = [&SEGWIT_TX[..], &LEGACY_TX[..]].concat();
+        assert_eq!(Transaction::skip(&data), Ok(SEGWIT_TX.len()));
+        assert_eq!(Transaction::skip(&LEGACY_TX), Ok(LEGACY_TX.len()));
+        assert_eq!(
+            Transaction::skip(&LEGACY_TX[..LEGACY_TX.len() - 1]),
+            Err(Error::Needed(1))
+        );
+        let mut invalid = SEGWIT_TX;
+        invalid[5] = 2;
+        assert_eq!(
+            Transaction::skip(&invalid),
+            Err(Error::UnknownSegwitFlag(2))
+        );
+    }
+
+    #[test]
+    fn needed_truncated() {
+        let data = [&SEGWIT_TX[..], &LEGACY_TX[..]].concat();
+        for tx in [&SEGWIT_TX[..], &LEGACY_TX[..], &GENESIS_TX[..]] {
+            for len in 0..tx.len() {
+                let needed = Transaction::parse(&tx[..len])
+                    .unwrap_err()
+                    .needed()
+                    .unwrap();
+                assert!(len + needed.get() as usize <= tx.len());
+            }
+        }
+        // reading exactly the needed bytes converges without reading the following tx
+        let mut len = 0;
+        while let Err(e) = Transaction::parse(&data[..len]) {
+            len += e.needed().unwrap().get() as usize;
+        }
+        assert_eq!(len, SEGWIT_TX.len());
+    }
+
     #[test]
     fn parse_genesis_transaction() {
         let tx = Transaction::parse(&GENESIS_TX[..]).unwrap();
-        assert_eq!(tx.remaining(), &[][..]);
+        assert_eq!(tx.remaining(), &[0u8; 0][..]);
         assert_eq!(tx.parsed().as_ref(), &GENESIS_TX[..]);
         assert_eq!(tx.consumed(), 204);
         assert_eq!(tx.parsed().version(), 1);
         assert_eq!(tx.parsed().locktime(), 0);
 
         check_hash(
-            &tx.parsed(),
+            tx.parsed(),
             hex!("4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"),
         );
     }
 
     #[test]
     fn parse_segwit_transaction() {
-        let segwit_tx = hex!("010000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff3603da1b0e00045503bd5704c7dd8a0d0ced13bb5785010800000000000a636b706f6f6c122f4e696e6a61506f6f6c2f5345475749542fffffffff02b4e5a212000000001976a914876fbb82ec05caa6af7a3b5e5a983aae6c6cc6d688ac0000000000000000266a24aa21a9edf91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a39837040120000000000000000000000000000000000000000000000000000000000000000000000000");
+        let segwit_tx = SEGWIT_TX;
         let tx = Transaction::parse(&segwit_tx[..]).unwrap();
-        assert_eq!(tx.remaining(), &[]);
+        assert_eq!(tx.remaining(), &[0u8; 0]);
         assert_eq!(tx.parsed().as_ref(), &segwit_tx[..]);
         assert_eq!(tx.consumed(), 222);
         assert_eq!(tx.parsed().version(), 1);
         assert_eq!(tx.parsed().locktime(), 0);
 
         check_hash(
-            &tx.parsed(),
+            tx.parsed(),
             hex!("4be105f158ea44aec57bf12c5817d073a712ab131df6f37786872cfc70734188"), // testnet tx
         );
     }
 
+    #[test]
+    fn fmt_transaction() {
+        use bitcoin::hashes::hex::ToHex;
+        for bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(bytes).unwrap().parsed_owned();
+            assert_eq!(format!("{:x}", tx), bytes.to_hex());
+            assert_eq!(format!("{:X}", tx), bytes.to_hex().to_uppercase());
+            assert_eq!(tx.to_string(), bytes.to_hex());
+        }
+    }
+
+    #[test]
+    fn check_standard_size_limits() {
+        // one input with empty script sig and one output with a script of `script_len` bytes
+        fn tx_bytes(script_len: usize) -> Vec<u8> {
+            let mut tx_bytes = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff010000000000000000").to_vec();
+            if script_len < 0xfd {
+                tx_bytes.push(script_len as u8);
+            } else {
+                tx_bytes.push(0xfe);
+                tx_bytes.extend((script_len as u32).to_le_bytes());
+            }
+            tx_bytes.resize(tx_bytes.len() + script_len, 0x6a);
+            tx_bytes.extend([0u8; 4]);
+            tx_bytes
+        }
+        let check = |tx_bytes: &[u8], limits: &SizeLimits| {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            tx.check_standard_size_limits(limits)
+        };
+        let limits = SizeLimits::default();
+
+        assert_eq!(tx_bytes(5).len(), 65);
+        assert_eq!(check(&tx_bytes(5), &limits), Ok(()));
+        assert_eq!(
+            check(&tx_bytes(4), &limits),
+            Err(SizeLimitError::BaseSizeTooSmall(64))
+        );
+        let limits_82 = SizeLimits {
+            min_base_size: 82,
+            ..Default::default()
+        };
+        assert_eq!(check(&tx_bytes(22), &limits_82), Ok(()));
+        assert_eq!(
+            check(&tx_bytes(21), &limits_82),
+            Err(SizeLimitError::BaseSizeTooSmall(81))
+        );
+
+        assert_eq!(tx_bytes(99_936).len(), 100_000);
+        assert_eq!(check(&tx_bytes(99_936), &limits), Ok(()));
+        assert_eq!(
+            check(&tx_bytes(99_937), &limits),
+            Err(SizeLimitError::WeightTooHigh(400_004))
+        );
+
+        assert_eq!(check(&SEGWIT_TX[..], &limits), Ok(()));
+        assert_eq!(check(&LEGACY_TX[..], &limits), Ok(()));
+
+        let tx_bytes = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff0000000000");
+        assert_eq!(check(&tx_bytes, &limits), Err(SizeLimitError::NoOutputs));
+        let tx_bytes = hex!("0100000000020000000000000000016a0100000000000000016a00000000");
+        let tx = Transaction::parse_zero_inputs(&tx_bytes[..])
+            .unwrap()
+            .parsed_owned();
+        assert_eq!(
+            tx.check_standard_size_limits(&limits),
+            Err(SizeLimitError::NoInputs)
+        );
+    }
+
+    #[test]
+    fn eq_by_txid() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let mut stripped = [0u8; 256];
+        let len = tx.write_stripped(&mut stripped).unwrap();
+        let stripped_tx = Transaction::parse(&stripped[..len]).unwrap().parsed_owned();
+        assert!(!stripped_tx.has_witness());
+        assert_ne!(tx, stripped_tx);
+        assert!(tx.eq_by_txid(&stripped_tx));
+        assert!(stripped_tx.eq_by_txid(&tx));
+        assert!(tx.eq_by_txid(&tx));
+
+        let legacy_tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert!(!tx.eq_by_txid(&legacy_tx));
+        assert!(!legacy_tx.eq_by_txid(&stripped_tx));
+
+        // same length, different locktime
+        let mut other = SEGWIT_TX;
+        other[SEGWIT_TX.len() - 1] = 1;
+        let other_tx = Transaction::parse(&other[..]).unwrap().parsed_owned();
+        assert!(!tx.eq_by_txid(&other_tx));
+        assert!(!stripped_tx.eq_by_txid(&other_tx));
+    }
+
+    #[test]
+    fn parse_all() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse_all(tx_bytes).unwrap();
+            assert_eq!(tx.as_ref(), tx_bytes);
+
+            let mut bytes = tx_bytes.to_vec();
+            bytes.push(0);
+            assert_eq!(Transaction::parse_all(&bytes), Err(Error::TrailingBytes(1)));
+            bytes.extend([0u8; 9]);
+            assert_eq!(
+                Transaction::parse_all(&bytes),
+                Err(Error::TrailingBytes(10))
+            );
+        }
+    }
+
+    #[test]
+    fn txid_preimage_segments() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let (a, b, c) = tx.txid_preimage();
+            let segments: Vec<_> = tx.txid_preimage_segments().collect();
+            let concat = segments.concat();
+            assert_eq!(concat, [a, b, c].concat());
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            let mut stripped = tx_bitcoin.clone();
+            stripped.input.iter_mut().for_each(|i| i.witness.clear());
+            assert_eq!(concat, serialize(&stripped));
+            let expected = if tx.has_witness() { 3 } else { 1 };
+            assert_eq!(tx.txid_preimage_segments().len(), expected);
+            assert_eq!(segments.len(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_with_config() {
+        let config = ParseConfig {
+            allowed_versions: Some(1..=3),
+            ..Default::default()
+        };
+        for version in [0i32, 0x7fffffff, -1, 4] {
+            let mut tx_bytes = LEGACY_TX;
+            tx_bytes[..4].copy_from_slice(&version.to_le_bytes());
+            assert_eq!(
+                Transaction::parse_with_config(&tx_bytes[..], &config),
+                Err(Error::UnsupportedVersion(version))
+            );
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap();
+            assert_eq!(tx.parsed().version(), version);
+            assert_eq!(
+                Transaction::parse_with_config(&tx_bytes[..], &ParseConfig::default()),
+                Ok(tx)
+            );
+        }
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            assert_eq!(
+                Transaction::parse_with_config(tx_bytes, &config),
+                Transaction::parse(tx_bytes)
+            );
+        }
+
+        let config = ParseConfig {
+            allowed_versions: Some(0..=0),
+            allow_zero_inputs: true,
+            ..Default::default()
+        };
+        let tx_bytes = hex!("0000000000020000000000000000016a0100000000000000016a00000000");
+        let tx = Transaction::parse_with_config(&tx_bytes[..], &config).unwrap();
+        assert_eq!(tx.parsed().version(), 0);
+        assert_eq!(tx.parsed().input_count(), 0);
+    }
+
+    #[test]
+    fn parse_zero_inputs() {
+        // legacy serialization, no inputs and two outputs
+        let tx_bytes = hex!("0100000000020000000000000000016a0100000000000000016a00000000");
+        assert_eq!(
+            Transaction::parse(&tx_bytes[..]),
+            Err(Error::UnknownSegwitFlag(2))
+        );
+        let tx = Transaction::parse_zero_inputs(&tx_bytes[..]).unwrap();
+        assert_eq!(tx.remaining(), &[0u8; 0][..]);
+        let tx = tx.parsed_owned();
+        assert_eq!(tx.as_ref(), &tx_bytes[..]);
+        assert_eq!(tx.input_count(), 0);
+        assert_eq!(tx.output_count(), 2);
+        assert!(!tx.has_witness());
+        assert_eq!(tx.total_output_value(), Some(1));
+        assert_eq!(tx.txid_preimage().0, &tx_bytes[..]);
+
+        // no inputs and no outputs
+        let tx_bytes = hex!("01000000000000000000");
+        assert_eq!(
+            Transaction::parse(&tx_bytes[..]),
+            Err(Error::UnknownSegwitFlag(0))
+        );
+        let tx = Transaction::parse_zero_inputs(&tx_bytes[..]).unwrap();
+        assert_eq!(tx.parsed().output_count(), 0);
+
+        // one output is indistinguishable from the segwit marker and flag
+        let tx_bytes = hex!("01000000000100000000000000000000000000");
+        assert_eq!(
+            Transaction::parse_zero_inputs(&tx_bytes[..]),
+            Transaction::parse(&tx_bytes[..])
+        );
+
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            assert_eq!(
+                Transaction::parse_zero_inputs(tx_bytes),
+                Transaction::parse(tx_bytes)
+            );
+        }
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn wtxid() {
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap();
+        assert_eq!(tx.parsed().wtxid(), tx.parsed().txid());
+
+        let segwit_tx = SEGWIT_TX;
+        let tx = Transaction::parse(&segwit_tx[..]).unwrap();
+        assert_ne!(tx.parsed().wtxid(), tx.parsed().txid());
+        let tx_bitcoin: bitcoin::Transaction = deserialize(&segwit_tx[..]).unwrap();
+        assert_eq!(&tx.parsed().wtxid()[..], &tx_bitcoin.wtxid()[..]);
+    }
+
+    #[cfg(feature = "sha2")]
+    #[test]
+    fn wtxid_sha2() {
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap();
+        assert_eq!(tx.parsed().wtxid_sha2(), tx.parsed().txid_sha2());
+
+        let segwit_tx = SEGWIT_TX;
+        let tx = Transaction::parse(&segwit_tx[..]).unwrap();
+        let tx_bitcoin: bitcoin::Transaction = deserialize(&segwit_tx[..]).unwrap();
+        assert_eq!(&tx.parsed().wtxid_sha2()[..], &tx_bitcoin.wtxid()[..]);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn ntxid() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(&tx.ntxid()[..], &tx_bitcoin.ntxid()[..]);
+            #[cfg(feature = "sha2")]
+            assert_eq!(&tx.ntxid_sha2()[..], &tx_bitcoin.ntxid()[..]);
+        }
+
+        // malleated LEGACY_TX, an `OP_0 OP_DROP` is prepended to the script sig
+        let malleated = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006e0075493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000");
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let malleated = Transaction::parse(&malleated[..]).unwrap().parsed_owned();
+        assert_ne!(tx.txid(), malleated.txid());
+        assert_eq!(tx.ntxid(), malleated.ntxid());
+
+        struct Check<'a>(&'a mut usize);
+        impl<'a> Visitor<'_> for Check<'a> {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                let tx_bitcoin: bitcoin::Transaction = deserialize(tx.as_ref()).unwrap();
+                assert_eq!(&tx.ntxid()[..], &tx_bitcoin.ntxid()[..]);
+                *self.0 += 1;
+            }
+        }
+        let mut total = 0;
+        Block::visit(mainnet_702861(), &mut Check(&mut total)).unwrap();
+        assert_eq!(total, 2500);
+    }
+
+    #[test]
+    fn weight() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(tx.weight(), tx_bitcoin.weight());
+            assert_eq!(tx.vsize(), tx_bitcoin.vsize());
+        }
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.weight(), 204 * 4);
+        assert_eq!(tx.vsize(), 204);
+    }
+
+    #[test]
+    fn sizes() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(tx.base_size(), tx_bitcoin.strippedsize());
+            assert_eq!(tx.total_size(), tx_bitcoin.size());
+            assert_eq!(tx.base_size() * 3 + tx.total_size(), tx_bitcoin.weight());
+            assert_eq!(tx.base_size() * 3 + tx.total_size(), tx.weight());
+        }
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.base_size(), tx.total_size());
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert!(tx.base_size() < tx.total_size());
+    }
+
+    #[test]
+    fn coinbase_height() {
+        // block 1 coinbase, before BIP34
+        let tx_bytes = hex!("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0704ffff001d0104ffffffff0100f2052a0100000043410496b538e853519c726a2c91e61ec11600ae1390813a627c66fb8be7947be63c52da7589379515d4e0a604f8141781e62294721166bf621e73a82cbf2342c858eeac00000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert!(tx.is_coinbase());
+        assert_eq!(tx.coinbase_height(), None);
+
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.coinbase_height(), None);
+
+        // a coinbase with the script sig starting like the one of block 227_836
+        let tx_bytes = hex!("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff0603fc790301aaffffffff0100f2052a010000000000000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(tx.coinbase_height(), Some(227_836));
+
+        // testnet block 100_000 coinbase, the height is pushed with the sign byte 0x01
+        let tx_bytes = hex!("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff3703a08601000427f1001c046a510100522cfabe6d6d0000000000000000000068692066726f6d20706f6f6c7365727665726aac1eeeed88ffffffff0100f2052a010000001976a914912e2b234f941f30b18afbb4fa46171214bf66c888ac00000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(tx.coinbase_height(), Some(100_000));
+
+        let tx = Transaction::parse(&mainnet_702861()[83..])
+            .unwrap()
+            .parsed_owned();
+        assert!(tx.is_coinbase());
+        assert_eq!(tx.coinbase_height(), Some(702_861));
+        let block_bitcoin: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        assert_eq!(
+            block_bitcoin.bip34_block_height().ok(),
+            tx.coinbase_height().map(u64::from)
+        );
+
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.coinbase_height(), Some(924_634));
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.coinbase_height(), None);
+    }
+
+    #[test]
+    fn is_coinbase() {
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert!(tx.is_coinbase());
+
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert!(tx.is_coinbase());
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert!(!tx.is_coinbase());
+
+        // two inputs, the first spending the null outpoint
+        let tx_bytes = hex!("01000000020000000000000000000000000000000000000000000000000000000000000000ffffffff00ffffffffa15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff0100e1f505000000000000000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert!(!tx.is_coinbase());
+    }
+
+    #[test]
+    fn spent_outpoints() {
+        let two_inputs = hex!("01000000020000000000000000000000000000000000000000000000000000000000000000ffffffff00ffffffffa15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff0100e1f505000000000000000000");
+        for tx_bytes in [
+            &GENESIS_TX[..],
+            &SEGWIT_TX[..],
+            &LEGACY_TX[..],
+            &two_inputs[..],
+        ] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(tx.spent_outpoints().len(), tx_bitcoin.input.len());
+            for (out_point, tx_in) in tx.spent_outpoints().zip(tx_bitcoin.input.iter()) {
+                assert_eq!(out_point.txid(), &tx_in.previous_output.txid[..]);
+                assert_eq!(out_point.vout(), tx_in.previous_output.vout);
+                assert_eq!(out_point.is_null(), tx_in.previous_output.is_null());
+            }
+        }
+
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.spent_outpoints().filter(|o| !o.is_null()).count(), 0);
+        let tx = Transaction::parse(&two_inputs[..]).unwrap().parsed_owned();
+        assert_eq!(tx.spent_outpoints().filter(|o| !o.is_null()).count(), 1);
+    }
+
+    #[test]
+    fn input() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            for (i, expected) in tx_bitcoin.input.iter().enumerate() {
+                let tx_in = tx.input(i).unwrap();
+                assert_eq!(tx_in.script_sig(), expected.script_sig.as_bytes());
+                assert_eq!(tx_in.sequence(), expected.sequence.0);
+                assert_eq!(tx_in.prevout().vout(), expected.previous_output.vout);
+            }
+            assert!(tx.input(tx_bitcoin.input.len()).is_none());
+        }
+    }
+
+    #[test]
+    fn output() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let tx_bitcoin: bitcoin::Transaction = deserialize(&SEGWIT_TX[..]).unwrap();
+        assert_eq!(tx_bitcoin.output.len(), 2);
+        for (i, expected) in tx_bitcoin.output.iter().enumerate() {
+            let tx_out = tx.output(i).unwrap();
+            assert_eq!(tx_out.value(), expected.value);
+            assert_eq!(tx_out.script_pubkey(), expected.script_pubkey.as_bytes());
+        }
+        assert!(tx.output(2).is_none());
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.output(0).unwrap().value(), 100_000_000);
+        assert!(tx.output(1).is_none());
+    }
+
+    #[test]
+    fn inputs() {
+        struct Collect<'a>(Vec<Vec<u8>>, &'a mut usize);
+        impl<'a> Visitor<'_> for Collect<'a> {
+            fn visit_tx_in(&mut self, _vin: usize, tx_in: &TxIn) {
+                self.0.push(tx_in.as_ref().to_vec());
+            }
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                let inputs = tx.inputs();
+                assert_eq!(inputs.len(), self.0.len());
+                for (tx_in, expected) in inputs.zip(self.0.iter()) {
+                    assert_eq!(tx_in.as_ref(), &expected[..]);
+                }
+                self.0.clear();
+                *self.1 += 1;
+            }
+        }
+        let mut total = 0;
+        Block::visit(mainnet_702861(), &mut Collect(vec![], &mut total)).unwrap();
+        assert_eq!(total, 2500);
+
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let mut inputs = tx.inputs();
+        assert_eq!(inputs.size_hint(), (1, Some(1)));
+        assert!(inputs.next().unwrap().prevout().is_null());
+        assert!(inputs.next().is_none());
+    }
+
+    #[test]
+    fn outputs() {
+        let block: bitcoin::Block = deserialize(mainnet_702861()).unwrap();
+        for tx_bitcoin in block.txdata.iter() {
+            let tx_bytes = serialize(tx_bitcoin);
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+            let outputs = tx.outputs();
+            assert_eq!(outputs.len(), tx_bitcoin.output.len());
+            for (tx_out, expected) in outputs.zip(tx_bitcoin.output.iter()) {
+                assert_eq!(tx_out.value(), expected.value);
+                assert_eq!(tx_out.script_pubkey(), expected.script_pubkey.as_bytes());
+            }
+        }
+
+        // segwit with no outputs, the last input ends where outputs begin
+        let tx_bytes = hex!("0200000000010100000000000000000000000000000000000000000000000000000000000000000000000000ffffffff0001010100000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let tx_bitcoin: bitcoin::Transaction = deserialize(&tx_bytes[..]).unwrap();
+        assert_eq!(tx_bitcoin.output.len(), 0);
+        assert_eq!(tx.outputs().len(), 0);
+        assert!(tx.outputs().next().is_none());
+        assert!(tx.output(0).is_none());
+    }
+
+    #[test]
+    fn has_witness() {
+        for (tx_bytes, expected) in [
+            (&GENESIS_TX[..], false),
+            (&SEGWIT_TX[..], true),
+            (&LEGACY_TX[..], false),
+        ] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(tx.has_witness(), expected);
+            assert_eq!(
+                tx.has_witness(),
+                tx_bitcoin.input.iter().any(|i| !i.witness.is_empty())
+            );
+        }
+    }
+
+    #[test]
+    fn input_output_count() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            assert_eq!(tx.input_count(), tx_bitcoin.input.len());
+            assert_eq!(tx.output_count(), tx_bitcoin.output.len());
+            assert_eq!(tx.input_count(), tx.inputs().len());
+            assert_eq!(tx.output_count(), tx.outputs().len());
+        }
+
+        struct Check<'a>(&'a mut usize);
+        impl<'a> Visitor<'_> for Check<'a> {
+            fn visit_transaction(&mut self, tx: &Transaction) {
+                assert_eq!(tx.input_count(), tx.inputs().count());
+                assert_eq!(tx.output_count(), tx.outputs().count());
+                *self.0 += 1;
+            }
+        }
+        let mut total = 0;
+        Block::visit(mainnet_702861(), &mut Check(&mut total)).unwrap();
+        assert_eq!(total, 2500);
+    }
+
+    #[test]
+    fn witness() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let tx_bitcoin: bitcoin::Transaction = deserialize(&SEGWIT_TX[..]).unwrap();
+        let witness = tx.witness(0).unwrap();
+        let elements: Vec<_> = witness.iter().collect();
+        let expected: Vec<_> = tx_bitcoin.input[0].witness.iter().collect();
+        assert_eq!(elements, expected);
+        assert!(tx.witness(1).is_none());
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert!(tx.witness(0).is_none());
+    }
+
+    #[test]
+    fn annex() {
+        // inputs: key path spend, script path spend with annex, script path spend without annex
+        let mut tx_bitcoin: bitcoin::Transaction = deserialize(&LEGACY_TX[..]).unwrap();
+        let mut input = tx_bitcoin.input[0].clone();
+        input.script_sig = bitcoin::Script::new();
+        let signature = vec![1u8; 64];
+        let script =
+            hex!("20d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8ac").to_vec();
+        let control_block =
+            hex!("c0d85a959b0290bf19bb89ed43c916be835475d013da4b362117393e25a48229b8").to_vec();
+        let witnesses = [
+            vec![signature.clone()],
+            vec![
+                signature.clone(),
+                script.clone(),
+                control_block.clone(),
+                vec![0x50, 0xaa],
+            ],
+            vec![signature, script, control_block],
+        ];
+        tx_bitcoin.input = witnesses
+            .iter()
+            .map(|w| {
+                let mut input = input.clone();
+                input.witness = bitcoin::Witness::from_vec(w.clone());
+                input
+            })
+            .collect();
+        let tx_bytes = serialize(&tx_bitcoin);
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+
+        assert_eq!(tx.input_has_annex(0), Some(false));
+        assert_eq!(tx.input_has_annex(1), Some(true));
+        assert_eq!(tx.input_has_annex(2), Some(false));
+        assert_eq!(tx.input_has_annex(3), None);
+        assert!(tx.any_annex());
+        assert_eq!(tx.witness(1).unwrap().annex(), Some(&[0x50, 0xaa][..]));
+
+        tx_bitcoin.input.remove(1);
+        let tx_bytes = serialize(&tx_bitcoin);
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert!(!tx.any_annex());
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.input_has_annex(0), Some(false));
+        assert_eq!(tx.input_has_annex(1), None);
+        assert!(!tx.any_annex());
+    }
+
+    #[test]
+    fn fee() {
+        use std::collections::HashMap;
+
+        let mut utxos: HashMap<[u8; 36], u64> = HashMap::new();
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let prevout: [u8; 36] = tx.input(0).unwrap().prevout().as_ref().try_into().unwrap();
+        let fee = |utxos: &HashMap<[u8; 36], u64>| tx.fee(|o| utxos.get(o.as_ref()).copied());
+        assert_eq!(fee(&utxos), Err(FeeError::MissingPrevout(0)));
+
+        utxos.insert(prevout, 100_010_000);
+        assert_eq!(fee(&utxos), Ok(10_000));
+
+        utxos.insert(prevout, 100_000_000);
+        assert_eq!(fee(&utxos), Ok(0));
+
+        utxos.insert(prevout, 99_999_999);
+        assert_eq!(fee(&utxos), Err(FeeError::Negative));
+
+        // two inputs, the lookup stops at the first missing one
+        let tx_bytes = hex!("01000000020000000000000000000000000000000000000000000000000000000000000000ffffffff00ffffffffa15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff0100e1f505000000000000000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let mut calls = 0;
+        let result = tx.fee(|_| {
+            calls += 1;
+            None
+        });
+        assert_eq!(result, Err(FeeError::MissingPrevout(0)));
+        assert_eq!(calls, 1);
+        assert_eq!(
+            tx.fee(|o| (!o.is_null()).then_some(1)),
+            Err(FeeError::MissingPrevout(0))
+        );
+        assert_eq!(tx.fee(|_| Some(u64::MAX)), Err(FeeError::Overflow));
+        assert_eq!(tx.fee(|_| Some(50_000_000)), Ok(0));
+    }
+
+    #[test]
+    fn op_return_outputs() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let op_returns: Vec<_> = tx.op_return_outputs().collect();
+        assert_eq!(
+            op_returns,
+            vec![(
+                1,
+                &hex!("aa21a9edf91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a3983704")[..]
+            )]
+        );
+
+        for tx_bytes in [&GENESIS_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            assert_eq!(tx.op_return_outputs().count(), 0);
+        }
+
+        // outputs: bare OP_RETURN, OP_RETURN with two pushes, p2pkh-like OP_DUP, OP_RETURN push
+        let tx_bytes = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff040000000000000000016a0000000000000000056a01aa01bb000000000000000001760000000000000000046a02ccdd00000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        let op_returns: Vec<_> = tx.op_return_outputs().collect();
+        assert_eq!(op_returns, vec![(0, &[0u8; 0][..]), (3, &hex!("ccdd")[..])]);
+    }
+
+    #[test]
+    fn output_script_kind() {
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.output_script_kind(0), Some(ScriptKind::P2pkh));
+        assert_eq!(tx.output_script_kind(1), Some(ScriptKind::OpReturn));
+        assert_eq!(tx.output_script_kind(2), None);
+
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.output_script_kind(0), Some(ScriptKind::Bare));
+    }
+
+    #[test]
+    fn total_output_value() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            let expected: u64 = tx_bitcoin.output.iter().map(|o| o.value).sum();
+            assert_eq!(tx.total_output_value(), Some(expected));
+        }
+
+        let tx_bytes = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff02ffffffffffffffff0001000000000000000000000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(tx.outputs().len(), 2);
+        assert_eq!(tx.total_output_value(), None);
+    }
+
+    #[test]
+    fn check_amounts() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            assert_eq!(tx.check_amounts(), Ok(()));
+        }
+
+        // a legacy transaction with the given output values and empty script pubkeys
+        let with_values = |values: &[u64]| {
+            let mut tx = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece0100000000ffffffff").to_vec();
+            tx.push(values.len() as u8);
+            for value in values {
+                tx.extend_from_slice(&value.to_le_bytes());
+                tx.push(0);
+            }
+            tx.extend_from_slice(&[0u8; 4]);
+            tx
+        };
+        let config = ParseConfig {
+            check_amounts: true,
+            ..Default::default()
+        };
+        let cases = [
+            (&[MAX_MONEY][..], Ok(())),
+            (&[MAX_MONEY - 1, 1], Ok(())),
+            (&[MAX_MONEY + 1], Err(AmountError::OutputTooLarge(0))),
+            (&[1, u64::MAX], Err(AmountError::OutputTooLarge(1))),
+            (&[MAX_MONEY, 1], Err(AmountError::TotalTooLarge(1))),
+            (
+                &[MAX_MONEY / 2 + 1, MAX_MONEY / 2 + 1, 1],
+                Err(AmountError::TotalTooLarge(1)),
+            ),
+        ];
+        for (values, expected) in cases {
+            let tx_bytes = with_values(values);
+            let tx = Transaction::parse_all(&tx_bytes[..]).unwrap();
+            assert_eq!(tx.check_amounts(), expected, "{:?}", values);
+            let parsed = Transaction::parse_with_config(&tx_bytes[..], &config);
+            match expected {
+                Ok(()) => assert_eq!(parsed.unwrap().parsed(), &tx),
+                Err(e) => assert_eq!(parsed, Err(Error::InvalidAmount(e))),
+            }
+        }
+        let tx = Transaction::parse_with_config(&SEGWIT_TX[..], &config).unwrap();
+        assert_eq!(tx.consumed(), SEGWIT_TX.len());
+    }
+
+    #[test]
+    fn lock_time_kind() {
+        let sequence_offset = LEGACY_TX.len() - 4 - 34 - 1 - 4;
+        let locktime_offset = LEGACY_TX.len() - 4;
+        for (locktime, expected) in [
+            (0u32, LockTime::Blocks(0)),
+            (499_999_999, LockTime::Blocks(499_999_999)),
+            (500_000_000, LockTime::Seconds(500_000_000)),
+        ] {
+            let mut tx_bytes = LEGACY_TX;
+            tx_bytes[locktime_offset..].copy_from_slice(&locktime.to_le_bytes());
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+            assert_eq!(tx.lock_time_kind(), expected);
+            assert!(!tx.is_lock_time_enabled(), "all sequences are final");
+
+            tx_bytes[sequence_offset..sequence_offset + 4]
+                .copy_from_slice(&[0xfe, 0xff, 0xff, 0xff]);
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+            assert_eq!(tx.lock_time_kind(), expected);
+            assert!(tx.is_lock_time_enabled());
+        }
+    }
+
+    #[test]
+    fn is_final() {
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert!(tx.is_final(0, 0));
+
+        // segwit transaction, inputs start after the marker and the flag
+        let sequence_offset = 6 + 1 + 36 + 1 + 0x36;
+        let locktime_offset = SEGWIT_TX.len() - 4;
+        let mut tx_bytes = SEGWIT_TX;
+        tx_bytes[sequence_offset..sequence_offset + 4].copy_from_slice(&[0xfe, 0xff, 0xff, 0xff]);
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert!(tx.is_lock_time_enabled());
+        assert_eq!(tx.locktime(), 0);
+        assert!(tx.is_final(0, 0));
+
+        for (locktime, height, block_time, expected) in [
+            (100u32, 100u32, 0u32, false),
+            (100, 101, 0, true),
+            (100, 99, u32::MAX, false),
+            (499_999_999, 500_000_000, 0, true),
+            (500_000_000, u32::MAX, 500_000_000, false),
+            (500_000_000, 0, 500_000_001, true),
+        ] {
+            tx_bytes[locktime_offset..].copy_from_slice(&locktime.to_le_bytes());
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+            assert_eq!(tx.locktime(), locktime);
+            assert_eq!(tx.is_final(height, block_time), expected);
+        }
+
+        // all sequences final, the locktime is ignored
+        tx_bytes[sequence_offset..sequence_offset + 4].copy_from_slice(&[0xff; 4]);
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert!(tx.is_final(0, 0));
+    }
+
+    #[test]
+    fn legacy_sigop_count() {
+        // the genesis output is p2pk, the coinbase script sig has no sigops
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.legacy_sigop_count(), 1);
+        // the script sig only pushes, the output is p2pkh
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.legacy_sigop_count(), 1);
+        // p2pkh and op_return outputs, witnesses are not counted
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.legacy_sigop_count(), 1);
+
+        // LEGACY_TX with an additional 1-of-2 bare multisig output
+        let tx_bytes = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0200e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac102700000000000047512102d8b661b0b3302ee2f162b09e07a55ad5dfbe673a9f01d9f0c19617681024306b2103b28f0c28bfab54554ae8c658ac5c3e0ce6e79ad336331f78c428dd43eea8449b52ae00000000");
+        let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(tx.output_count(), 2);
+        assert_eq!(tx.legacy_sigop_count(), 21);
+    }
+
+    #[test]
+    fn sigop_cost() {
+        use bitcoin::blockdata::{opcodes::all::*, script::Builder};
+        use bitcoin::{PackedLockTime, Script, Sequence, Witness};
+
+        // the cases of Bitcoin Core `GetTxSigOpCost` test
+        let key = [2u8; 33];
+        let multisig = Builder::new()
+            .push_int(1)
+            .push_slice(&key)
+            .push_slice(&key)
+            .push_int(2)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script();
+        let p2wpkh = Builder::new()
+            .push_int(0)
+            .push_slice(&[3u8; 20])
+            .into_script();
+        let sig = [0x30u8; 71];
+        let spending = |script_sig: Script, witness: Vec<Vec<u8>>| {
+            let tx = bitcoin::Transaction {
+                version: 2,
+                lock_time: PackedLockTime(0),
+                input: vec![bitcoin::TxIn {
+                    previous_output: bitcoin::OutPoint::new(bitcoin::hashes::Hash::all_zeros(), 0),
+                    script_sig,
+                    sequence: Sequence::MAX,
+                    witness: Witness::from_vec(witness),
+                }],
+                output: vec![bitcoin::TxOut {
+                    value: 0,
+                    script_pubkey: Script::new(),
+                }],
+            };
+            serialize(&tx)
+        };
+        let cost = |tx_bytes: &[u8], script_pubkey: &Script| {
+            let tx = Transaction::parse_all(tx_bytes).unwrap();
+            tx.sigop_cost(|prevout| {
+                assert_eq!(prevout.vout(), 0);
+                Some(script_pubkey.as_bytes())
+            })
+        };
+        let push = |script: &Script| Builder::new().push_slice(script.as_bytes()).into_script();
+
+        // the sigops of the spent bare multisig are not counted
+        let script_sig = Builder::new().push_int(0).push_int(0).into_script();
+        assert_eq!(cost(&spending(script_sig, vec![]), &multisig), Ok(0));
+
+        // multisig nested in P2SH
+        let script_sig = Builder::new()
+            .push_int(0)
+            .push_int(0)
+            .push_slice(multisig.as_bytes())
+            .into_script();
+        let tx = spending(script_sig, vec![]);
+        assert_eq!(cost(&tx, &multisig.to_p2sh()), Ok(2 * 4));
+        // the redeem script is not inspected if the output is not P2SH
+        assert_eq!(cost(&tx, &multisig), Ok(0));
+
+        // P2WPKH, also nested in P2SH
+        let witness = vec![sig.to_vec(), key.to_vec()];
+        let tx = spending(Script::new(), witness.clone());
+        assert_eq!(cost(&tx, &p2wpkh), Ok(1));
+        let tx = spending(push(&p2wpkh), witness.clone());
+        assert_eq!(cost(&tx, &p2wpkh.to_p2sh()), Ok(1));
+        // the witness program must be in the redeem script, not in another push
+        let script_sig = Builder::new()
+            .push_slice(p2wpkh.as_bytes())
+            .push_int(0)
+            .into_script();
+        assert_eq!(
+            cost(&spending(script_sig, witness), &p2wpkh.to_p2sh()),
+            Ok(0)
+        );
+
+        // P2WSH multisig, also nested in P2SH
+        let witness = vec![vec![], sig.to_vec(), multisig.to_bytes()];
+        let p2wsh = multisig.to_v0_p2wsh();
+        let tx = spending(Script::new(), witness.clone());
+        assert_eq!(cost(&tx, &p2wsh), Ok(2));
+        let tx = spending(push(&p2wsh), witness);
+        assert_eq!(cost(&tx, &p2wsh.to_p2sh()), Ok(2));
+        // no witness script
+        assert_eq!(cost(&spending(Script::new(), vec![]), &p2wsh), Ok(0));
+
+        // legacy sigops in the outputs are counted, the coinbase doesn't need the prevouts
+        let tx = Transaction::parse(&GENESIS_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.sigop_cost(|_| unreachable!()), Ok(4));
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        assert_eq!(tx.sigop_cost(|_| None), Err(Error::MissingPrevout));
+    }
+
+    #[test]
+    fn signals_rbf() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            assert!(!tx.signals_rbf());
+        }
+
+        let sequence_offset = LEGACY_TX.len() - 4 - 34 - 1 - 4;
+        for (sequence, expected) in [
+            (0xffffffffu32, false),
+            (0xfffffffe, false),
+            (0xfffffffd, true),
+            (0, true),
+        ] {
+            let mut tx_bytes = LEGACY_TX;
+            tx_bytes[sequence_offset..sequence_offset + 4].copy_from_slice(&sequence.to_le_bytes());
+            let tx = Transaction::parse(&tx_bytes[..]).unwrap().parsed_owned();
+            assert_eq!(tx.input(0).unwrap().sequence(), sequence);
+            assert_eq!(tx.signals_rbf(), expected);
+        }
+    }
+
+    #[test]
+    fn write_stripped() {
+        let mut buffer = [0u8; 300];
+
+        let tx = Transaction::parse(&LEGACY_TX[..]).unwrap().parsed_owned();
+        let written = tx.write_stripped(&mut buffer).unwrap();
+        assert_eq!(&buffer[..written], &LEGACY_TX[..]);
+
+        let tx = Transaction::parse(&SEGWIT_TX[..]).unwrap().parsed_owned();
+        let written = tx.write_stripped(&mut buffer).unwrap();
+        assert_eq!(written * 3 + tx.as_ref().len(), tx.weight());
+        let stripped: bitcoin::Transaction = deserialize(&buffer[..written]).unwrap();
+        let mut expected: bitcoin::Transaction = deserialize(&SEGWIT_TX[..]).unwrap();
+        for input in expected.input.iter_mut() {
+            input.witness.clear();
+        }
+        assert_eq!(stripped, expected);
+        assert_eq!(serialize(&expected), &buffer[..written]);
+
+        assert_eq!(
+            tx.write_stripped(&mut buffer[..written - 1]),
+            Err(Error::BufferTooSmall(written as u32))
+        );
+    }
+
     #[test]
     fn parse_nonminimal_transaction() {
         let first_part =  hex!("020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff310349ce0b04db6fd2632f466f756e6472792055534120506f6f6c202364726f70676f6c642f1e284d6da44c000000000000ffffffff02311b662500000000");
@@ -195,12 +1860,106 @@ mod test {
 
         assert!(Transaction::parse(&tx[..]).is_ok());
         assert!(Transaction::parse(&tx_nonminimal[..]).is_err());
+        let strict = ParseConfig::default();
+        assert_eq!(
+            Transaction::parse_with_config(&tx_nonminimal[..], &strict),
+            Err(Error::NonMinimalVarInt)
+        );
+
+        let lenient = ParseConfig {
+            allow_non_minimal_compact_sizes: true,
+            ..Default::default()
+        };
+        let minimal = Transaction::parse(&tx[..]).unwrap();
+        assert_eq!(
+            Transaction::parse_with_config(&tx[..], &lenient),
+            Ok(minimal.clone())
+        );
+        let minimal = minimal.parsed_owned();
+
+        let parsed = Transaction::parse_with_config(&tx_nonminimal[..], &lenient).unwrap();
+        assert_eq!(parsed.consumed(), tx_nonminimal.len());
+        assert!(parsed.remaining().is_empty());
+        let parsed = parsed.parsed_owned();
+        assert_eq!(parsed.as_ref(), &tx_nonminimal[..]);
+        assert_eq!(parsed.input_count(), minimal.input_count());
+        assert!(parsed.is_coinbase());
+        assert_eq!(parsed.witness(0), minimal.witness(0));
+        let outputs = parsed.outputs().map(|o| (o.value(), o.script_pubkey()));
+        let expected = minimal.outputs().map(|o| (o.value(), o.script_pubkey()));
+        assert!(outputs.eq(expected));
+        assert_eq!(
+            parsed.outputs().next().unwrap().as_ref(),
+            &[
+                &first_part[first_part.len() - 8..],
+                &varint_nonminimal[..],
+                &last_part[..22]
+            ]
+            .concat()[..]
+        );
+
+        // the txid preimage keeps the non-minimal bytes
+        let mut stripped = [0u8; 256];
+        let minimal_len = minimal.write_stripped(&mut stripped).unwrap();
+        let len = parsed.write_stripped(&mut stripped).unwrap();
+        let stripped = &stripped[..len];
+        assert_eq!(len, minimal_len + 2);
+        assert!(stripped.windows(3).any(|w| w == varint_nonminimal));
+        #[cfg(feature = "bitcoin_hashes")]
+        {
+            use crate::bitcoin_hashes::{sha256d, Hash};
+            assert_eq!(parsed.txid(), sha256d::Hash::hash(stripped));
+            assert_ne!(parsed.txid(), minimal.txid());
+        }
+
+        // a segwit transaction whose only witness has zero elements in a non-minimal count
+        let empty_witness = [
+            &hex!("020000000001")[..],
+            &hex!("01")[..],
+            &[0u8; 37][..],
+            &hex!("ffffffff00")[..],
+            &hex!("fd0000")[..],
+            &[0u8; 4][..],
+        ]
+        .concat();
+        assert_eq!(
+            Transaction::parse_with_config(&empty_witness[..], &lenient),
+            Err(Error::SegwitFlagWithoutWitnesses)
+        );
+    }
+
+    #[test]
+    fn checked_inputs_outputs_len() {
+        use super::checked_inputs_outputs_len;
+        assert_eq!(checked_inputs_outputs_len(2).unwrap().get(), 2);
+        assert_eq!(
+            checked_inputs_outputs_len(u32::MAX as usize).unwrap().get(),
+            u32::MAX
+        );
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(
+            checked_inputs_outputs_len(u32::MAX as usize + 1),
+            Err(Error::TooLarge)
+        );
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn transaction_bitcoin() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let expected: bitcoin::Transaction = deserialize(tx_bytes).unwrap();
+            let tx_bitcoin = bitcoin::Transaction::try_from(&tx).unwrap();
+            assert_eq!(tx_bitcoin, expected);
+            let tx_bitcoin: bitcoin::Transaction = tx.try_into().unwrap();
+            assert_eq!(serialize(&tx_bitcoin), tx_bytes);
+        }
     }
 
     #[cfg(target_pointer_width = "64")]
     #[test]
     fn size_of() {
-        assert_eq!(std::mem::size_of::<Transaction>(), 24);
+        assert_eq!(std::mem::size_of::<Transaction>(), 32);
     }
 
     #[cfg(all(not(feature = "sha2"), not(feature = "bitcoin_hashes")))]
@@ -242,6 +2001,13 @@ mod bench {
         });
     }
 
+    #[bench]
+    pub fn tx_skip(bh: &mut Bencher) {
+        bh.iter(|| {
+            black_box(Transaction::skip(&BENCH_TX[..]).unwrap());
+        });
+    }
+
     #[bench]
     pub fn tx_deserialize_bitcoin(bh: &mut Bencher) {
         bh.iter(|| {
@@ -0,0 +1,437 @@
+use alloc::vec::Vec;
+use core::num::NonZeroU32;
+
+use crate::bsl::{IntoOwned, Transaction};
+use crate::{Error, Parse};
+
+/// The options to parse again the bytes of a [`TransactionOwned`] or of a
+/// [`crate::bsl::BlockOwned`], from a database or a deserialization, which may come from a
+/// transaction parsed with any [`crate::bsl::ParseConfig`], so that storing it doesn't fail.
+#[cfg(any(feature = "bitcoin", feature = "serde", feature = "redb"))]
+pub(crate) const REPARSE_CONFIG: crate::bsl::ParseConfig = crate::bsl::ParseConfig {
+    allowed_versions: None,
+    allow_zero_inputs: true,
+    allow_non_minimal_compact_sizes: true,
+    check_amounts: false,
+};
+
+/// An owned Bitcoin transaction, keeping a copy of the bytes of a parsed [`Transaction`].
+///
+/// Useful to keep a transaction past the lifetime of the buffer it has been parsed from, the
+/// metadata computed during parsing is kept so [`TransactionOwned::as_transaction()`] is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionOwned {
+    data: Vec<u8>,
+    inputs_outputs_len: Option<NonZeroU32>,
+    input_count: u32,
+    output_count: u32,
+}
+
+impl TransactionOwned {
+    /// Returns the borrowed transaction.
+    pub fn as_transaction(&self) -> Transaction<'_> {
+        Transaction {
+            slice: &self.data[..],
+            inputs_outputs_len: self.inputs_outputs_len,
+            input_count: self.input_count,
+            output_count: self.output_count,
+        }
+    }
+
+    /// Returns the transaction version.
+    pub fn version(&self) -> i32 {
+        self.as_transaction().version()
+    }
+
+    /// Returns the transaction locktime.
+    pub fn locktime(&self) -> u32 {
+        self.as_transaction().locktime()
+    }
+
+    /// Return the transaction identifier, see [`Transaction::txid()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn txid(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        self.as_transaction().txid()
+    }
+
+    /// Return the witness transaction identifier, see [`Transaction::wtxid()`].
+    #[cfg(feature = "bitcoin_hashes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+    pub fn wtxid(&self) -> crate::bitcoin_hashes::sha256d::Hash {
+        self.as_transaction().wtxid()
+    }
+
+    /// Returns the serialized transaction bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Parse the transaction in `data` keeping it, with [`REPARSE_CONFIG`], returns also the
+    /// number of trailing bytes after the transaction, which are dropped.
+    #[cfg(any(feature = "bitcoin", feature = "serde"))]
+    fn parse_vec(mut data: Vec<u8>) -> Result<(Self, usize), Error> {
+        let parsed = Transaction::parse_with_config(&data[..], &REPARSE_CONFIG)?;
+        let (consumed, remaining) = (parsed.consumed(), parsed.remaining().len());
+        let tx = parsed.parsed_owned();
+        let (inputs_outputs_len, input_count, output_count) =
+            (tx.inputs_outputs_len, tx.input_count, tx.output_count);
+        data.truncate(consumed);
+        let tx = TransactionOwned {
+            data,
+            inputs_outputs_len,
+            input_count,
+            output_count,
+        };
+        Ok((tx, remaining))
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<'a> Transaction<'a> {
+    /// Parse the transaction in `data` keeping it without copying, fails with
+    /// [`Error::TrailingBytes`] if there are bytes after the transaction, like [`Parse::parse_all`].
+    ///
+    /// On error `data` is given back along with the error, so the buffer isn't lost.
+    pub fn parse_owned(data: Vec<u8>) -> Result<TransactionOwned, (Vec<u8>, Error)> {
+        let tx = match Transaction::parse_all(&data[..]) {
+            Ok(tx) => tx,
+            Err(e) => return Err((data, e)),
+        };
+        let (inputs_outputs_len, input_count, output_count) =
+            (tx.inputs_outputs_len, tx.input_count, tx.output_count);
+        Ok(TransactionOwned {
+            data,
+            inputs_outputs_len,
+            input_count,
+            output_count,
+        })
+    }
+
+    /// Copies the transaction bytes, keeping the metadata computed during parsing.
+    pub fn to_owned(&self) -> TransactionOwned {
+        self.into()
+    }
+}
+
+impl<'a> IntoOwned for Transaction<'a> {
+    type Owned = TransactionOwned;
+
+    fn into_owned(self) -> TransactionOwned {
+        self.into()
+    }
+}
+
+impl<'a> From<Transaction<'a>> for TransactionOwned {
+    fn from(tx: Transaction<'a>) -> Self {
+        TransactionOwned {
+            data: tx.slice.to_vec(),
+            inputs_outputs_len: tx.inputs_outputs_len,
+            input_count: tx.input_count,
+            output_count: tx.output_count,
+        }
+    }
+}
+
+impl<'a> From<&Transaction<'a>> for TransactionOwned {
+    fn from(tx: &Transaction<'a>) -> Self {
+        tx.clone().into()
+    }
+}
+
+impl AsRef<[u8]> for TransactionOwned {
+    fn as_ref(&self) -> &[u8] {
+        &self.data[..]
+    }
+}
+
+#[cfg(feature = "redb")]
+#[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
+impl redb::RedbValue for TransactionOwned {
+    type SelfType<'a>
+        = TransactionOwned
+    where
+        Self: 'a;
+
+    type AsBytes<'a>
+        = &'a [u8]
+    where
+        Self: 'a;
+
+    fn fixed_width() -> Option<usize> {
+        None
+    }
+
+    /// Parse the stored bytes, panics if they are not a Transaction, like with a corrupted database.
+    fn from_bytes<'a>(data: &'a [u8]) -> Self::SelfType<'a>
+    where
+        Self: 'a,
+    {
+        Transaction::parse_with_config(data, &REPARSE_CONFIG)
+            .and_then(|parsed| parsed.require_empty())
+            .expect("inserted data is not a Transaction")
+            .into()
+    }
+
+    fn as_bytes<'a, 'b: 'a>(value: &'a Self::SelfType<'b>) -> Self::AsBytes<'a>
+    where
+        Self: 'a,
+        Self: 'b,
+    {
+        value.as_ref()
+    }
+
+    fn type_name() -> redb::TypeName {
+        redb::TypeName::new("TransactionOwned")
+    }
+}
+
+#[cfg(feature = "bitcoin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
+impl TryFrom<&bitcoin::Transaction> for TransactionOwned {
+    type Error = Error;
+
+    /// Serialize the rust-bitcoin transaction and parse it, fails if the serialized transaction
+    /// can't be parsed back.
+    fn try_from(tx: &bitcoin::Transaction) -> Result<Self, Self::Error> {
+        TransactionOwned::parse_vec(bitcoin::consensus::serialize(tx)).map(|(tx, _)| tx)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl serde::Serialize for TransactionOwned {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'de> serde::Deserialize<'de> for TransactionOwned {
+    /// Deserialize from hex for human readable formats, from bytes otherwise, the transaction is
+    /// parsed and trailing bytes are rejected.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{self, Error as _};
+
+        struct BytesVisitor;
+        impl<'de> de::Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                f.write_str("a serialized transaction as hex string or bytes")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                crate::hex::decode(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+        }
+
+        let data = if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)?
+        } else {
+            deserializer.deserialize_byte_buf(BytesVisitor)?
+        };
+        match TransactionOwned::parse_vec(data) {
+            Ok((tx, 0)) => Ok(tx),
+            Ok((_, trailing)) => Err(D::Error::custom(format_args!(
+                "invalid transaction: {} trailing bytes",
+                trailing
+            ))),
+            Err(e) => Err(D::Error::custom(format_args!("invalid transaction: {}", e))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{Transaction, TransactionOwned},
+        test_common::{GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+        Error, Parse,
+    };
+
+    /// [`LEGACY_TX`] with the inputs count encoded in 3 bytes, parsed leniently.
+    #[cfg(any(feature = "serde", feature = "redb"))]
+    fn non_minimal() -> TransactionOwned {
+        let bytes = [&LEGACY_TX[..4], &[0xFD, 1, 0], &LEGACY_TX[5..]].concat();
+        let config = crate::bsl::ParseConfig {
+            allow_non_minimal_compact_sizes: true,
+            ..Default::default()
+        };
+        let tx = Transaction::parse_with_config(&bytes, &config).unwrap();
+        assert!(tx.remaining().is_empty());
+        tx.parsed().into()
+    }
+
+    #[test]
+    fn transaction_owned() {
+        let vectors = [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]];
+        let mut owned = vec![];
+        for tx_bytes in vectors {
+            let buffer = tx_bytes.to_vec();
+            let tx = Transaction::parse(&buffer[..]).unwrap().parsed_owned();
+            assert_eq!(
+                TransactionOwned::from(&tx),
+                TransactionOwned::from(tx.clone())
+            );
+            owned.push(TransactionOwned::from(tx));
+        }
+        // the owned transactions outlive the buffers
+        for (owned, tx_bytes) in owned.into_iter().zip(vectors) {
+            let expected = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            assert_eq!(owned.as_transaction(), expected);
+            assert_eq!(owned.as_ref(), tx_bytes);
+            assert_eq!(owned.version(), expected.version());
+            assert_eq!(owned.locktime(), expected.locktime());
+            #[cfg(feature = "bitcoin_hashes")]
+            {
+                assert_eq!(owned.txid(), expected.txid());
+                assert_eq!(owned.wtxid(), expected.wtxid());
+            }
+            assert_eq!(
+                Transaction::parse_owned(tx_bytes.to_vec()),
+                Ok(owned.clone())
+            );
+            assert_eq!(owned.into_bytes(), tx_bytes);
+        }
+
+        let truncated = LEGACY_TX[..LEGACY_TX.len() - 1].to_vec();
+        assert_eq!(
+            Transaction::parse_owned(truncated.clone()),
+            Err((truncated, Error::Needed(1)))
+        );
+        let mut with_trailing = SEGWIT_TX.to_vec();
+        with_trailing.push(0);
+        assert_eq!(
+            Transaction::parse_owned(with_trailing.clone()),
+            Err((with_trailing, Error::TrailingBytes(1)))
+        );
+    }
+
+    #[cfg(feature = "bitcoin")]
+    #[test]
+    fn transaction_owned_bitcoin() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx_bitcoin: bitcoin::Transaction =
+                bitcoin::consensus::deserialize(tx_bytes).unwrap();
+            let owned = TransactionOwned::try_from(&tx_bitcoin).unwrap();
+            let expected = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            assert_eq!(owned.as_transaction(), expected);
+            let back = bitcoin::Transaction::try_from(owned.as_transaction()).unwrap();
+            assert_eq!(back, tx_bitcoin);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transaction_owned_serde() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let owned = TransactionOwned::from(&tx);
+            let hex = tx_bytes
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>();
+
+            let json = serde_json::to_string(&tx).unwrap();
+            assert_eq!(json, format!("\"{}\"", hex));
+            assert_eq!(serde_json::to_string(&owned).unwrap(), json);
+            let back: TransactionOwned = serde_json::from_str(&json).unwrap();
+            assert_eq!(back, owned);
+            let back: TransactionOwned = serde_json::from_str(&json.to_uppercase()).unwrap();
+            assert_eq!(back, owned);
+
+            let bin = bincode::serialize(&tx).unwrap();
+            assert_eq!(&bin[8..], tx_bytes);
+            assert_eq!(bincode::serialize(&owned).unwrap(), bin);
+            let back: TransactionOwned = bincode::deserialize(&bin).unwrap();
+            assert_eq!(back, owned);
+        }
+
+        // a transaction parsed leniently round trips
+        let owned = non_minimal();
+        let json = serde_json::to_string(&owned).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TransactionOwned>(&json).unwrap(),
+            owned
+        );
+        let bin = bincode::serialize(&owned).unwrap();
+        assert_eq!(
+            bincode::deserialize::<TransactionOwned>(&bin).unwrap(),
+            owned
+        );
+
+        let err = serde_json::from_str::<TransactionOwned>("\"0g\"").unwrap_err();
+        assert!(err.to_string().contains("invalid value"), "{}", err);
+        let err = serde_json::from_str::<TransactionOwned>("\"01000000\"").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("invalid transaction: unexpected end of data, 1 more bytes needed"),
+            "{}",
+            err
+        );
+        let mut with_trailing = LEGACY_TX.to_vec();
+        with_trailing.push(0);
+        let err = bincode::deserialize::<TransactionOwned>(
+            &bincode::serialize(&serde_bytes_like(&with_trailing)).unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(err.to_string(), "invalid transaction: 1 trailing bytes");
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    fn transaction_owned_redb() {
+        use redb::ReadableTable;
+
+        const TABLE: redb::TableDefinition<&str, TransactionOwned> =
+            redb::TableDefinition::new("transactions");
+        let path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
+        let db = redb::Database::create(path).unwrap();
+        let tx = TransactionOwned::from(Transaction::parse_all(&SEGWIT_TX[..]).unwrap());
+
+        let write_txn = db.begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(TABLE).unwrap();
+            table.insert("segwit", &tx).unwrap();
+            table.insert("non-minimal", &non_minimal()).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let read_txn = db.begin_read().unwrap();
+        let table = read_txn.open_table(TABLE).unwrap();
+        assert_eq!(table.get("segwit").unwrap().unwrap().value(), tx);
+        let value = table.get("non-minimal").unwrap().unwrap().value();
+        assert_eq!(value, non_minimal());
+    }
+
+    #[cfg(feature = "redb")]
+    #[test]
+    #[should_panic(expected = "inserted data is not a Transaction")]
+    fn transaction_owned_redb_corrupted() {
+        use redb::RedbValue;
+
+        TransactionOwned::from_bytes(&SEGWIT_TX[..100]);
+    }
+
+    #[cfg(feature = "serde")]
+    fn serde_bytes_like(bytes: &[u8]) -> impl serde::Serialize + '_ {
+        struct Bytes<'a>(&'a [u8]);
+        impl<'a> serde::Serialize for Bytes<'a> {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.serialize_bytes(self.0)
+            }
+        }
+        Bytes(bytes)
+    }
+}
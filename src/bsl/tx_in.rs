@@ -14,9 +14,15 @@ pub struct TxIn<'a> {
 }
 
 impl<'a> Parse<'a> for TxIn<'a> {
-    fn parse(slice: &'a [u8]) -> SResult<Self> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        Self::parse_with(slice, false)
+    }
+}
+impl<'a> TxIn<'a> {
+    /// Parse a transaction input, if `lenient` accepting a non-minimal script sig length.
+    pub(crate) fn parse_with(slice: &'a [u8], lenient: bool) -> SResult<'a, Self> {
         let out_point = OutPoint::parse(slice)?;
-        let script = Script::parse(out_point.remaining())?;
+        let script = Script::parse_with(out_point.remaining(), lenient)?;
         let sequence = U32::parse(script.remaining())?;
         let consumed = script.consumed() + 40;
         let tx_in = TxIn {
@@ -27,14 +33,13 @@ impl<'a> Parse<'a> for TxIn<'a> {
         };
         Ok(ParseResult::new(sequence.remaining(), tx_in))
     }
-}
-impl<'a> TxIn<'a> {
+
     /// Returns the previous output index spent by this transaction input
-    pub fn prevout(&self) -> &OutPoint {
+    pub fn prevout(&self) -> &OutPoint<'a> {
         &self.prevout
     }
     /// Return the script sig of this transaction input
-    pub fn script_sig(&self) -> &[u8] {
+    pub fn script_sig(&self) -> &'a [u8] {
         self.script_sig.script()
     }
     /// Returns the sequence of this transaction input
@@ -49,6 +54,17 @@ impl<'a> AsRef<[u8]> for TxIn<'a> {
     }
 }
 
+crate::hex::impl_hex_fmt!(TxIn);
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for TxIn<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use hex_lit::hex;
@@ -87,4 +103,20 @@ mod test {
 
         assert_eq!(TxIn::parse(&tx_in_bytes[..20]), Err(Error::Needed(16)));
     }
+
+    #[test]
+    fn fmt_tx_in() {
+        let tx_in_bytes = hex!(
+            "a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000000100ffffffff"
+        );
+        let tx_in = TxIn::parse(&tx_in_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(
+            tx_in.to_string(),
+            "a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000000100ffffffff"
+        );
+        assert_eq!(
+            format!("{:X}", tx_in),
+            "A15D57094AA7A21A28CB20B59AAB8FC7D1149A3BDBCDDBA9C622E4F5F6A99ECE010000000100FFFFFFFF"
+        );
+    }
 }
@@ -1,6 +1,7 @@
-use super::len::{parse_len, Len};
+use super::len::{parse_len_with, Len, LenKind};
 use crate::bsl::TxIn;
-use crate::{Parse, ParseResult, SResult, Visit, Visitor};
+use crate::visit::check;
+use crate::{EmptyVisitor, ParseResult, SResult, Visit, Visitor};
 
 /// The transaction inputs of a transaction
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,17 +11,36 @@ pub struct TxIns<'a> {
 }
 
 impl<'a> Visit<'a> for TxIns<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
-        let Len { mut consumed, n } = parse_len(slice)?;
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        Self::visit_with(slice, visit, false)
+    }
+}
+impl<'a> TxIns<'a> {
+    /// Visit the inputs, if `lenient` accepting non-minimal compact ints.
+    pub(crate) fn visit_with<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        lenient: bool,
+    ) -> SResult<'a, Self> {
+        let Len { mut consumed, n } = parse_len_with(slice, lenient)?;
         let mut remaining = &slice[consumed..];
         let total_inputs = n as usize;
+        visit.visit_len(LenKind::TxInCount, n, consumed);
+        check(visit)?;
         visit.visit_tx_ins(total_inputs);
+        check(visit)?;
 
         for i in 0..total_inputs {
-            let tx_in = TxIn::parse(remaining)?;
+            let tx_in = TxIn::parse_with(remaining, lenient)?;
             remaining = tx_in.remaining();
             consumed += tx_in.consumed();
-            visit.visit_tx_in(i, tx_in.parsed())
+            let script_sig = tx_in.parsed().script_sig();
+            // the input is the 36 bytes out point, the script with its length and the 4 bytes sequence
+            let encoded_len = tx_in.consumed() - 40 - script_sig.len();
+            visit.visit_len(LenKind::ScriptSigLen, script_sig.len() as u64, encoded_len);
+            check(visit)?;
+            visit.visit_tx_in(i, tx_in.parsed());
+            check(visit)?;
         }
 
         Ok(ParseResult::new(
@@ -31,25 +51,99 @@ impl<'a> Visit<'a> for TxIns<'a> {
             },
         ))
     }
-}
-impl<'a> TxIns<'a> {
+
+    /// Parse the inputs, if `lenient` accepting non-minimal compact ints.
+    pub(crate) fn parse_with(slice: &'a [u8], lenient: bool) -> SResult<'a, Self> {
+        Self::visit_with(slice, &mut EmptyVisitor {}, lenient)
+    }
+
     /// Returns if there are no transaction inputs
     pub fn is_empty(&self) -> bool {
-        self.slice[0] == 0
+        self.n == 0
     }
     /// Return the number of transaction inputs
     pub fn n(&self) -> usize {
         self.n
     }
+    /// Returns an iterator over [`TxIn`]
+    ///
+    /// If possible is better to use [`Visitor::visit_tx_in`] to avoid double pass, however, it may
+    /// be conveniet to iterate in case you already have validated the slice, for example some data
+    /// in a db.
+    pub fn iter(&self) -> TxInIterator<'a> {
+        TxInIterator::new(self.slice)
+    }
+}
+
+impl<'a> IntoIterator for &TxIns<'a> {
+    type Item = TxIn<'a>;
+    type IntoIter = TxInIterator<'a>;
+
+    /// Returns an iterator over [`TxIn`]
+    ///
+    /// If possible is better to use [`Visitor::visit_tx_in`] to avoid double pass, however, it may
+    /// be conveniet to iterate in case you already have validated the slice, for example some data
+    /// in a db.
+    fn into_iter(self) -> TxInIterator<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the [`TxIn`]s of already validated transaction inputs, created with
+/// [`TxIns::iter()`].
+pub struct TxInIterator<'a> {
+    elements: usize,
+    remaining: &'a [u8],
+}
+
+impl<'a> TxInIterator<'a> {
+    /// Creates the iterator from a slice starting with the compact int of the number of inputs.
+    /// The slice must have been already validated, or the iterator may panic, non-minimal compact
+    /// ints are accepted since they may have been parsed in lenient mode.
+    pub(crate) fn new(slice: &'a [u8]) -> Self {
+        let len = parse_len_with(slice, true).expect("len granted by parsing");
+        TxInIterator {
+            elements: len.n() as usize,
+            remaining: &slice[len.consumed()..],
+        }
+    }
 }
 
+impl<'a> Iterator for TxInIterator<'a> {
+    type Item = TxIn<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.elements == 0 {
+            None
+        } else {
+            let tx_in = TxIn::parse_with(self.remaining, true).expect("granted from parsing");
+            self.remaining = tx_in.remaining();
+            self.elements -= 1;
+            Some(tx_in.parsed_owned())
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.elements, Some(self.elements))
+    }
+}
+
+impl<'a> ExactSizeIterator for TxInIterator<'a> {}
+
 impl<'a> AsRef<[u8]> for TxIns<'a> {
     fn as_ref(&self) -> &[u8] {
         self.slice
     }
 }
 
-impl<'a> TxIns<'a> {}
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for TxIns<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
 
 #[cfg(test)]
 mod test {
@@ -99,7 +193,7 @@ mod test {
         tx_ins.extend(&tx_in_bytes);
 
         struct VisitTxIns(usize);
-        impl Visitor for VisitTxIns {
+        impl Visitor<'_> for VisitTxIns {
             fn visit_tx_in(&mut self, vin: usize, tx_in: &TxIn) {
                 assert_eq!(vin, self.0);
                 self.0 += 1;
@@ -112,7 +206,7 @@ mod test {
         TxIns::visit(&tx_ins[..], &mut VisitTxIns(0)).unwrap();
 
         struct IsMine(Vec<u8>, bool);
-        impl Visitor for IsMine {
+        impl Visitor<'_> for IsMine {
             fn visit_tx_in(&mut self, _vin: usize, tx_in: &TxIn) {
                 assert_eq!(tx_in.sequence(), 4294967295u32);
                 if tx_in.script_sig() == self.0 {
@@ -129,6 +223,34 @@ mod test {
         assert!(!is_mine.1);
     }
 
+    #[test]
+    fn iter_tx_ins() {
+        let tx_in_bytes = hex!(
+            "a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000000100ffffffff"
+        );
+        let mut tx_ins_bytes = vec![2u8];
+        tx_ins_bytes.extend(&tx_in_bytes);
+        tx_ins_bytes.extend(&tx_in_bytes);
+        *tx_ins_bytes.last_mut().unwrap() = 0;
+        let tx_ins = TxIns::parse(&tx_ins_bytes[..]).unwrap().parsed_owned();
+
+        let mut iter = tx_ins.iter();
+        assert_eq!(iter.len(), 2);
+        let tx_in = iter.next().unwrap();
+        assert_eq!(tx_in.as_ref(), &tx_in_bytes[..]);
+        assert_eq!(tx_in.sequence(), 0xffffffff);
+        assert_eq!(iter.len(), 1);
+        let tx_in = iter.next().unwrap();
+        assert_eq!(tx_in.sequence(), 0x00ffffff);
+        assert!(iter.next().is_none());
+
+        for (i, tx_in) in (&tx_ins).into_iter().enumerate() {
+            assert_eq!(tx_in.prevout().vout(), 1);
+            assert_eq!(tx_in.script_sig(), &[0]);
+            assert!(i < 2);
+        }
+    }
+
     #[cfg(target_pointer_width = "64")]
     #[test]
     fn size_of() {
@@ -1,4 +1,4 @@
-use crate::bsl::Script;
+use crate::bsl::{Script, ScriptKind};
 use crate::number::U64;
 use crate::{Parse, ParseResult, SResult};
 
@@ -10,9 +10,15 @@ pub struct TxOut<'a> {
     script_pubkey: Script<'a>,
 }
 impl<'a> Parse<'a> for TxOut<'a> {
-    fn parse(slice: &'a [u8]) -> SResult<Self> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        Self::parse_with(slice, false)
+    }
+}
+impl<'a> TxOut<'a> {
+    /// Parse a transaction output, if `lenient` accepting a non-minimal script pubkey length.
+    pub(crate) fn parse_with(slice: &'a [u8], lenient: bool) -> SResult<'a, Self> {
         let value = U64::parse(slice)?;
-        let script = Script::parse(value.remaining())?;
+        let script = Script::parse_with(value.remaining(), lenient)?;
         let consumed = value.consumed() + script.consumed();
         let remaining = script.remaining();
         let tx_out = TxOut {
@@ -22,16 +28,23 @@ impl<'a> Parse<'a> for TxOut<'a> {
         };
         Ok(ParseResult::new(remaining, tx_out))
     }
-}
-impl<'a> TxOut<'a> {
+
     /// Return the amount of this output (satoshi)
     pub fn value(&self) -> u64 {
         self.value
     }
     /// Return the script pubkey of this output
-    pub fn script_pubkey(&self) -> &[u8] {
+    pub fn script_pubkey(&self) -> &'a [u8] {
         self.script_pubkey.script()
     }
+    /// Return the script pubkey of this output, including the compact int of its length
+    pub(crate) fn script(&self) -> &Script<'a> {
+        &self.script_pubkey
+    }
+    /// Return the kind of the script pubkey of this output, see [`Script::kind`]
+    pub fn script_kind(&self) -> ScriptKind {
+        self.script_pubkey.kind()
+    }
 }
 
 impl<'a> AsRef<[u8]> for TxOut<'a> {
@@ -40,14 +53,27 @@ impl<'a> AsRef<[u8]> for TxOut<'a> {
     }
 }
 
+crate::hex::impl_hex_fmt!(TxOut);
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for TxOut<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 #[cfg(feature = "redb")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
 impl<'o> redb::RedbValue for TxOut<'o> {
-    type SelfType<'a> = TxOut<'a>
+    type SelfType<'a>
+        = TxOut<'a>
     where
         Self: 'a;
 
-    type AsBytes<'a> =  &'a [u8]
+    type AsBytes<'a>
+        = &'a [u8]
     where
         Self: 'a;
 
@@ -79,20 +105,20 @@ impl<'o> redb::RedbValue for TxOut<'o> {
 
 #[cfg(feature = "bitcoin")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
-impl<'a> Into<bitcoin::TxOut> for &TxOut<'a> {
-    fn into(self) -> bitcoin::TxOut {
+impl<'a> From<&TxOut<'a>> for bitcoin::TxOut {
+    fn from(tx_out: &TxOut<'a>) -> Self {
         bitcoin::TxOut {
-            value: self.value(),
-            script_pubkey: self.script_pubkey().to_vec().into(),
+            value: tx_out.value(),
+            script_pubkey: tx_out.script_pubkey().to_vec().into(),
         }
     }
 }
 
 #[cfg(feature = "bitcoin")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
-impl<'a> Into<bitcoin::TxOut> for TxOut<'a> {
-    fn into(self) -> bitcoin::TxOut {
-        (&self).into()
+impl<'a> From<TxOut<'a>> for bitcoin::TxOut {
+    fn from(tx_out: TxOut<'a>) -> Self {
+        (&tx_out).into()
     }
 }
 
@@ -116,6 +142,15 @@ mod test {
         );
     }
 
+    #[test]
+    fn fmt_tx_out() {
+        let tx_out_bytes = hex!("ffffffffffffffff0100");
+        let tx_out = TxOut::parse(&tx_out_bytes[..]).unwrap().parsed_owned();
+        assert_eq!(format!("{:x}", tx_out), "ffffffffffffffff0100");
+        assert_eq!(format!("{:X}", tx_out), "FFFFFFFFFFFFFFFF0100");
+        assert_eq!(tx_out.to_string(), "ffffffffffffffff0100");
+    }
+
     #[cfg(feature = "redb")]
     #[test]
     fn test_tx_out_redb() {
@@ -1,6 +1,8 @@
-use super::len::{parse_len, Len};
+use super::len::{parse_len_with, Len, LenKind};
+use super::script::Instructions;
 use crate::bsl::TxOut;
-use crate::{Parse, ParseResult, SResult, Visit, Visitor};
+use crate::visit::check;
+use crate::{EmptyVisitor, ParseResult, SResult, Visit, Visitor};
 
 /// The transaction outputs of a transaction
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,17 +12,50 @@ pub struct TxOuts<'a> {
 }
 
 impl<'a> Visit<'a> for TxOuts<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
-        let Len { mut consumed, n } = parse_len(slice)?;
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        Self::visit_at(slice, 0, visit, false)
+    }
+}
+impl<'a> TxOuts<'a> {
+    /// Visit the outputs of the transaction at position `tx_index` in a block, if `lenient`
+    /// accepting non-minimal compact ints.
+    pub(crate) fn visit_at<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        tx_index: usize,
+        visit: &'b mut V,
+        lenient: bool,
+    ) -> SResult<'a, Self> {
+        if !visit.need_tx_outs() {
+            return Self::visit_at(slice, tx_index, &mut EmptyVisitor {}, lenient);
+        }
+        let Len { mut consumed, n } = parse_len_with(slice, lenient)?;
         let mut remaining = &slice[consumed..];
         let total_outputs = n as usize;
+        visit.visit_len(LenKind::TxOutCount, n, consumed);
+        check(visit)?;
         visit.visit_tx_outs(total_outputs);
+        check(visit)?;
 
         for i in 0..total_outputs {
-            let tx_out = TxOut::parse(remaining)?;
+            let tx_out = TxOut::parse_with(remaining, lenient)?;
             remaining = tx_out.remaining();
             consumed += tx_out.consumed();
+            let script = tx_out.parsed().script();
+            let script_len = script.script().len();
+            // the output is the 8 bytes value and the script with its length
+            let encoded_len = tx_out.consumed() - 8 - script_len;
+            visit.visit_len(LenKind::ScriptPubKeyLen, script_len as u64, encoded_len);
+            check(visit)?;
             visit.visit_tx_out(i, tx_out.parsed());
+            check(visit)?;
+            visit.visit_script_pubkey(tx_index, i, script);
+            check(visit)?;
+            if visit.want_script_opcodes() {
+                for (opcode, push) in Instructions::new(script.script()) {
+                    visit.visit_script_opcode(opcode, push);
+                    check(visit)?;
+                }
+            }
         }
         Ok(ParseResult::new(
             &slice[consumed..],
@@ -30,36 +65,30 @@ impl<'a> Visit<'a> for TxOuts<'a> {
             },
         ))
     }
-}
-impl<'a> TxOuts<'a> {
+
     /// If there are no outputs.
     pub fn is_empty(&self) -> bool {
-        self.slice[0] == 0
+        self.n == 0
     }
     /// The number of outputs.
     pub fn n(&self) -> usize {
         self.n
     }
-    /// Returns an iterator over [`bitcoin::TxOut`]
+    /// Returns an iterator over [`TxOut`]
     ///
     /// If possible is better to use [`Visitor::visit_tx_out`] to avoid double pass, however, it may
     /// be conveniet to iterate in case you already have validated the slice, for example some data
     /// in a db.
-    pub fn iter(&self) -> TxOutIterator<'_> {
-        let len = parse_len(self.slice).expect("len granted by parsing");
-        TxOutIterator {
-            elements: len.n() as usize,
-            offset: len.consumed(),
-            tx_outs: self,
-        }
+    pub fn iter(&self) -> TxOutIterator<'a> {
+        TxOutIterator::new(self.slice)
     }
 }
 
-impl<'a> IntoIterator for &'a TxOuts<'a> {
+impl<'a> IntoIterator for &TxOuts<'a> {
     type Item = TxOut<'a>;
     type IntoIter = TxOutIterator<'a>;
 
-    /// Returns an iterator over [`bitcoin::TxOut`]
+    /// Returns an iterator over [`TxOut`]
     ///
     /// If possible is better to use [`Visitor::visit_tx_out`] to avoid double pass, however, it may
     /// be conveniet to iterate in case you already have validated the slice, for example some data
@@ -69,22 +98,36 @@ impl<'a> IntoIterator for &'a TxOuts<'a> {
     }
 }
 
+/// Iterator over the [`TxOut`]s of already validated transaction outputs, created with
+/// [`TxOuts::iter()`].
 pub struct TxOutIterator<'a> {
     elements: usize,
-    offset: usize,
-    tx_outs: &'a TxOuts<'a>,
+    remaining: &'a [u8],
+}
+
+impl<'a> TxOutIterator<'a> {
+    /// Creates the iterator from a slice starting with the compact int of the number of outputs.
+    /// The slice must have been already validated, or the iterator may panic, non-minimal compact
+    /// ints are accepted since they may have been parsed in lenient mode.
+    pub(crate) fn new(slice: &'a [u8]) -> Self {
+        let len = parse_len_with(slice, true).expect("len granted by parsing");
+        TxOutIterator {
+            elements: len.n() as usize,
+            remaining: &slice[len.consumed()..],
+        }
+    }
 }
 
 impl<'a> Iterator for TxOutIterator<'a> {
     type Item = TxOut<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.offset >= self.tx_outs.as_ref().len() {
+        if self.elements == 0 {
             None
         } else {
-            let tx_out =
-                TxOut::parse(&self.tx_outs.slice[self.offset..]).expect("granted from parsing");
-            self.offset += tx_out.consumed();
+            let tx_out = TxOut::parse_with(self.remaining, true).expect("granted from parsing");
+            self.remaining = tx_out.remaining();
+            self.elements -= 1;
             Some(tx_out.parsed_owned())
         }
     }
@@ -102,14 +145,25 @@ impl<'a> AsRef<[u8]> for TxOuts<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for TxOuts<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 #[cfg(feature = "redb")]
 #[cfg_attr(docsrs, doc(cfg(feature = "redb")))]
 impl<'o> redb::RedbValue for TxOuts<'o> {
-    type SelfType<'a> = TxOuts<'a>
+    type SelfType<'a>
+        = TxOuts<'a>
     where
         Self: 'a;
 
-    type AsBytes<'a> =  &'a [u8]
+    type AsBytes<'a>
+        = &'a [u8]
     where
         Self: 'a;
 
@@ -121,7 +175,7 @@ impl<'o> redb::RedbValue for TxOuts<'o> {
     where
         Self: 'a,
     {
-        let n = parse_len(&data)
+        let n = parse_len_with(data, true)
             .expect("inserted data is not a valid TxOuts")
             .n() as usize;
         TxOuts { slice: data, n }
@@ -145,9 +199,12 @@ mod test {
     use hex_lit::hex;
 
     use crate::{
-        bsl::{TxOut, TxOuts},
+        bsl::{Block, Script, Transaction, TxOut, TxOuts},
+        test_common::LEGACY_TX,
         Error, Parse, ParseResult, Visit, Visitor,
     };
+    use bitcoin::consensus::deserialize;
+    use bitcoin_test_data::blocks::mainnet_702861;
 
     #[test]
     fn parse_tx_outs() {
@@ -181,7 +238,7 @@ mod test {
         let tx_outs = tx_outs_bytes();
 
         struct VisitTxOuts(usize);
-        impl Visitor for VisitTxOuts {
+        impl Visitor<'_> for VisitTxOuts {
             fn visit_tx_out(&mut self, vout: usize, tx_out: &TxOut) {
                 assert_eq!(vout, self.0);
                 self.0 += 1;
@@ -195,7 +252,7 @@ mod test {
         TxOuts::visit(&tx_outs[..], &mut VisitTxOuts(0)).unwrap();
 
         struct IsMine(Vec<u8>, bool);
-        impl Visitor for IsMine {
+        impl Visitor<'_> for IsMine {
             fn visit_tx_out(&mut self, _vout: usize, tx_out: &TxOut) {
                 assert_eq!(tx_out.value(), u64::MAX);
                 if tx_out.script_pubkey() == self.0 {
@@ -212,6 +269,65 @@ mod test {
         assert!(!visitor.1);
     }
 
+    #[test]
+    fn visit_script_opcodes() {
+        #[derive(Default)]
+        struct Scripts {
+            want_opcodes: bool,
+            scripts: Vec<(usize, usize, Vec<u8>)>,
+            opcodes: Vec<(u8, Option<Vec<u8>>)>,
+        }
+        impl Visitor<'_> for Scripts {
+            fn visit_script_pubkey(&mut self, tx_index: usize, vout: usize, script: &Script) {
+                self.scripts
+                    .push((tx_index, vout, script.script().to_vec()));
+            }
+            fn want_script_opcodes(&self) -> bool {
+                self.want_opcodes
+            }
+            fn visit_script_opcode(&mut self, opcode: u8, push: Option<&[u8]>) {
+                self.opcodes.push((opcode, push.map(|p| p.to_vec())));
+            }
+        }
+
+        let p2pkh = hex!("76a9140389035a9225b3839e2bbf32d826a1e222031fd888ac");
+        let mut visitor = Scripts::default();
+        Transaction::visit(&LEGACY_TX[..], &mut visitor).unwrap();
+        assert_eq!(visitor.scripts, vec![(0, 0, p2pkh.to_vec())]);
+        assert!(visitor.opcodes.is_empty());
+
+        let mut visitor = Scripts {
+            want_opcodes: true,
+            ..Default::default()
+        };
+        Transaction::visit(&LEGACY_TX[..], &mut visitor).unwrap();
+        let expected = vec![
+            (0x76, None), // OP_DUP
+            (0xa9, None), // OP_HASH160
+            (0x14, Some(p2pkh[3..23].to_vec())),
+            (0x88, None), // OP_EQUALVERIFY
+            (0xac, None), // OP_CHECKSIG
+        ];
+        assert_eq!(visitor.opcodes, expected);
+
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let mut visitor = Scripts::default();
+        Block::visit(block_bytes, &mut visitor).unwrap();
+        let expected: Vec<_> = block
+            .txdata
+            .iter()
+            .enumerate()
+            .flat_map(|(i, tx)| {
+                tx.output
+                    .iter()
+                    .enumerate()
+                    .map(move |(vout, o)| (i, vout, o.script_pubkey.to_bytes()))
+            })
+            .collect();
+        assert_eq!(visitor.scripts, expected);
+    }
+
     #[test]
     fn iter_tx_outs() {
         let mut tx_outs_bytes = tx_outs_bytes();
@@ -219,9 +335,11 @@ mod test {
         tx_outs_bytes.push(1);
         let tx_outs = TxOuts::parse(&tx_outs_bytes[..]).unwrap().parsed_owned();
         let mut iter = tx_outs.iter();
+        assert_eq!(iter.len(), 2);
         let tx_out = iter.next().unwrap();
         assert_eq!(tx_out.value(), 0xffffffffffffffff);
         assert_eq!(tx_out.script_pubkey(), &[0]);
+        assert_eq!(iter.len(), 1);
 
         let tx_out = iter.next().unwrap();
         assert_eq!(tx_out.value(), 0xffffffffffffffff);
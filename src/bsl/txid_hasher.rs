@@ -0,0 +1,84 @@
+/// A double sha256 hasher, allowing to compute transaction identifiers with any sha256
+/// implementation via [`crate::bsl::Transaction::txid_with()`], and block hashes via
+/// [`crate::bsl::BlockHeader::block_hash_with()`].
+///
+/// It's implemented for the `bitcoin_hashes` and `sha2` engines when the respective features are
+/// enabled.
+pub trait TxidHasher {
+    /// Feed `data` into the hasher.
+    fn update(&mut self, data: &[u8]);
+
+    /// Returns the sha256 of the sha256 of all the data fed, in byte order (not reversed).
+    fn finalize_double(self) -> [u8; 32];
+}
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+impl TxidHasher for crate::bitcoin_hashes::sha256::HashEngine {
+    fn update(&mut self, data: &[u8]) {
+        crate::bitcoin_hashes::HashEngine::input(self, data)
+    }
+
+    fn finalize_double(self) -> [u8; 32] {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        sha256d::Hash::from_engine(self).into_inner()
+    }
+}
+
+#[cfg(feature = "sha2")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+impl TxidHasher for crate::sha2::Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        crate::sha2::Digest::update(self, data)
+    }
+
+    fn finalize_double(self) -> [u8; 32] {
+        use crate::sha2::{Digest, Sha256};
+        let hash = self.finalize();
+        Sha256::digest(&hash[..]).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{Transaction, TxidHasher},
+        test_common::{GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+        Parse,
+    };
+    use bitcoin::hashes::{sha256d, Hash};
+
+    /// Collects the data instead of hashing it incrementally, like an external implementation
+    struct Collect(Vec<u8>);
+    impl TxidHasher for Collect {
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data)
+        }
+
+        fn finalize_double(self) -> [u8; 32] {
+            sha256d::Hash::hash(&self.0).into_inner()
+        }
+    }
+
+    #[test]
+    fn txid_with() {
+        for tx_bytes in [&GENESIS_TX[..], &SEGWIT_TX[..], &LEGACY_TX[..]] {
+            let tx = Transaction::parse(tx_bytes).unwrap().parsed_owned();
+            let tx_bitcoin: bitcoin::Transaction =
+                bitcoin::consensus::deserialize(tx_bytes).unwrap();
+            let expected = tx_bitcoin.txid().into_inner();
+
+            assert_eq!(tx.txid_with(Collect(vec![])), expected);
+            #[cfg(feature = "bitcoin_hashes")]
+            assert_eq!(
+                tx.txid_with(crate::bitcoin_hashes::sha256d::Hash::engine()),
+                expected
+            );
+            #[cfg(feature = "sha2")]
+            assert_eq!(
+                tx.txid_with(<crate::sha2::Sha256 as crate::sha2::Digest>::new()),
+                expected
+            );
+        }
+    }
+}
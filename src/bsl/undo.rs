@@ -0,0 +1,485 @@
+use super::len::{parse_len, Len};
+use crate::visit::check;
+use crate::{Error, Parse, ParseResult, SResult, Visit, Visitor};
+
+/// The undo data of a block: for every transaction except the coinbase, the coins it spends.
+///
+/// Only the serialized `CBlockUndo` is parsed, the framing of the `rev*.dat` files with network
+/// magic, length and trailing checksum is not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockUndo<'a> {
+    slice: &'a [u8],
+    total_tx_undos: usize,
+}
+
+impl<'a> Visit<'a> for BlockUndo<'a> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        let Len { mut consumed, n } = parse_len(slice)?;
+        let total_tx_undos = n as usize;
+        let mut remaining = &slice[consumed..];
+
+        visit.visit_block_undo(total_tx_undos);
+        check(visit)?;
+        for _ in 0..total_tx_undos {
+            let tx_undo = TxUndo::visit(remaining, visit)?;
+            remaining = tx_undo.remaining();
+            consumed += tx_undo.consumed();
+        }
+
+        let (slice, remaining) = slice.split_at(consumed);
+        let parsed = BlockUndo {
+            slice,
+            total_tx_undos,
+        };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> BlockUndo<'a> {
+    /// Returns the number of transactions undo data, which is the number of transactions in the
+    /// block minus the coinbase.
+    pub fn total_tx_undos(&self) -> usize {
+        self.total_tx_undos
+    }
+}
+
+impl<'a> AsRef<[u8]> for BlockUndo<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// The undo data of a transaction: the coins spent by its inputs, in the same order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TxUndo<'a> {
+    slice: &'a [u8],
+    total_spent: usize,
+}
+
+impl<'a> Visit<'a> for TxUndo<'a> {
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self> {
+        let Len { mut consumed, n } = parse_len(slice)?;
+        let total_spent = n as usize;
+        let mut remaining = &slice[consumed..];
+
+        visit.visit_tx_undo(total_spent);
+        check(visit)?;
+        for vin in 0..total_spent {
+            let coin = SpentCoin::parse(remaining)?;
+            remaining = coin.remaining();
+            consumed += coin.consumed();
+            visit.visit_spent_coin(vin, coin.parsed());
+            check(visit)?;
+        }
+
+        let (slice, remaining) = slice.split_at(consumed);
+        let parsed = TxUndo { slice, total_spent };
+        Ok(ParseResult::new(remaining, parsed))
+    }
+}
+
+impl<'a> TxUndo<'a> {
+    /// Returns the number of coins spent, equal to the number of inputs of the transaction.
+    pub fn total_spent(&self) -> usize {
+        self.total_spent
+    }
+}
+
+impl<'a> AsRef<[u8]> for TxUndo<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// A previous output spent by a transaction input, as stored in the undo data with Bitcoin Core
+/// compression of the amount and of the script pubkey.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpentCoin<'a> {
+    slice: &'a [u8],
+    height: u32,
+    coinbase: bool,
+    value: u64,
+    script: CompressedScript<'a>,
+}
+
+/// The script pubkey of a [`SpentCoin`], common templates are stored without the opcodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CompressedScript<'a> {
+    /// A pay to public key hash script, with the contained 20 bytes hash.
+    P2pkh(&'a [u8; 20]),
+
+    /// A pay to script hash script, with the contained 20 bytes hash.
+    P2sh(&'a [u8; 20]),
+
+    /// A pay to public key script with a compressed public key, which is `key_prefix` (`0x02` or
+    /// `0x03`) followed by `x`.
+    P2pk {
+        /// The first byte of the compressed public key.
+        key_prefix: u8,
+        /// The x coordinate of the public key.
+        x: &'a [u8; 32],
+    },
+
+    /// A pay to public key script with an uncompressed public key, stored in its compressed form
+    /// `key_prefix` (`0x02` or `0x03`) followed by `x`. Rebuilding the script requires computing
+    /// the y coordinate from the curve equation.
+    P2pkUncompressed {
+        /// The first byte of the compressed public key, encoding the parity of the y coordinate.
+        key_prefix: u8,
+        /// The x coordinate of the public key.
+        x: &'a [u8; 32],
+    },
+
+    /// Any other script, stored uncompressed.
+    Other(&'a [u8]),
+}
+
+impl<'a> Parse<'a> for SpentCoin<'a> {
+    fn parse(slice: &'a [u8]) -> SResult<'a, Self> {
+        let (code, mut consumed) = read_varint(slice, u32::MAX as u64)?;
+        let height = (code >> 1) as u32;
+        if height > 0 {
+            // unused version, always zero in recent versions
+            consumed += read_varint(&slice[consumed..], u32::MAX as u64)?.1;
+        }
+        let (value, value_len) = read_varint(&slice[consumed..], u64::MAX)?;
+        consumed += value_len;
+        let (size, size_len) = read_varint(&slice[consumed..], u32::MAX as u64)?;
+        consumed += size_len;
+
+        let rest = &slice[consumed..];
+        let script_len = match size {
+            0 | 1 => 20,
+            2..=5 => 32,
+            n => (n - SPECIAL_SCRIPTS) as usize,
+        };
+        if rest.len() < script_len {
+            return Err(Error::Needed((script_len - rest.len()) as u32));
+        }
+        let data = &rest[..script_len];
+        let script = match size {
+            0 => CompressedScript::P2pkh(data.try_into().expect("20 bytes")),
+            1 => CompressedScript::P2sh(data.try_into().expect("20 bytes")),
+            2 | 3 => CompressedScript::P2pk {
+                key_prefix: size as u8,
+                x: data.try_into().expect("32 bytes"),
+            },
+            4 | 5 => CompressedScript::P2pkUncompressed {
+                key_prefix: size as u8 - 2,
+                x: data.try_into().expect("32 bytes"),
+            },
+            _ => CompressedScript::Other(data),
+        };
+        consumed += script_len;
+
+        let coin = SpentCoin {
+            slice: &slice[..consumed],
+            height,
+            coinbase: code & 1 == 1,
+            value: decompress_amount(value),
+            script,
+        };
+        Ok(ParseResult::new(&slice[consumed..], coin))
+    }
+}
+
+impl<'a> SpentCoin<'a> {
+    /// Returns the height of the block containing the transaction which created this coin.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns true if this coin has been created by a coinbase transaction.
+    pub fn is_coinbase(&self) -> bool {
+        self.coinbase
+    }
+
+    /// Returns the amount of this coin (satoshi).
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Returns the script pubkey of this coin, in its compressed form.
+    pub fn script(&self) -> &CompressedScript<'a> {
+        &self.script
+    }
+}
+
+impl<'a> AsRef<[u8]> for SpentCoin<'a> {
+    fn as_ref(&self) -> &[u8] {
+        self.slice
+    }
+}
+
+/// The number of script templates compressed, these sizes are used as markers.
+const SPECIAL_SCRIPTS: u64 = 6;
+
+/// Reads a number in Bitcoin Core `VARINT` encoding (not the compact int of the consensus
+/// serialization), returning it and the bytes consumed. Numbers greater than `max` are rejected
+/// with [`Error::TooLarge`].
+fn read_varint(slice: &[u8], max: u64) -> Result<(u64, usize), Error> {
+    let mut n = 0u64;
+    for (i, byte) in slice.iter().enumerate() {
+        if n > max >> 7 {
+            return Err(Error::TooLarge);
+        }
+        n = (n << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            return Ok((n, i + 1));
+        }
+        if n == max {
+            return Err(Error::TooLarge);
+        }
+        n += 1;
+    }
+    Err(Error::Needed(1))
+}
+
+/// Decompress an amount like Bitcoin Core `DecompressAmount`.
+fn decompress_amount(x: u64) -> u64 {
+    if x == 0 {
+        return 0;
+    }
+    let mut x = x - 1;
+    let mut e = x % 10;
+    x /= 10;
+    let mut n = if e < 9 {
+        let d = (x % 9) + 1;
+        x /= 9;
+        x.wrapping_mul(10).wrapping_add(d)
+    } else {
+        x + 1
+    };
+    while e > 0 {
+        n = n.wrapping_mul(10);
+        e -= 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decompress_amount, read_varint};
+    use crate::{
+        bsl::{BlockUndo, CompressedScript, SpentCoin},
+        Error, Parse, Visit, Visitor,
+    };
+    use hex_lit::hex;
+
+    /// Bitcoin Core `WriteVarInt`
+    fn write_varint(mut n: u64, out: &mut Vec<u8>) {
+        let mut tmp = vec![];
+        loop {
+            tmp.push((n & 0x7f) as u8 | if tmp.is_empty() { 0 } else { 0x80 });
+            if n <= 0x7f {
+                break;
+            }
+            n = (n >> 7) - 1;
+        }
+        out.extend(tmp.iter().rev());
+    }
+
+    /// Bitcoin Core `CompressAmount`
+    fn compress_amount(mut n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        let mut e = 0;
+        while n.is_multiple_of(10) && e < 9 {
+            n /= 10;
+            e += 1;
+        }
+        if e < 9 {
+            let d = n % 10;
+            n /= 10;
+            1 + (n * 9 + d - 1) * 10 + e
+        } else {
+            1 + (n - 1) * 10 + 9
+        }
+    }
+
+    fn coin(height: u32, coinbase: bool, value: u64, script: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        write_varint(height as u64 * 2 + coinbase as u64, &mut out);
+        if height > 0 {
+            write_varint(0, &mut out);
+        }
+        write_varint(compress_amount(value), &mut out);
+        out.extend(script);
+        out
+    }
+
+    #[test]
+    fn test_read_varint() {
+        // vectors from Bitcoin Core serialize_tests
+        for (n, bytes) in [
+            (0u64, &hex!("00")[..]),
+            (0x7f, &hex!("7f")[..]),
+            (0x80, &hex!("8000")[..]),
+            (0x1234, &hex!("a334")[..]),
+            (0xffff, &hex!("82fe7f")[..]),
+            (0x123456, &hex!("c7e756")[..]),
+            (0x80123456, &hex!("86ffc7e756")[..]),
+            (0xffffffff, &hex!("8efefefe7f")[..]),
+            (0x7fffffffffffffff, &hex!("fefefefefefefefe7f")[..]),
+            (0xffffffffffffffff, &hex!("80fefefefefefefefe7f")[..]),
+        ] {
+            assert_eq!(read_varint(bytes, u64::MAX), Ok((n, bytes.len())), "{}", n);
+            let mut encoded = vec![];
+            write_varint(n, &mut encoded);
+            assert_eq!(&encoded[..], bytes);
+        }
+        assert_eq!(
+            read_varint(&hex!("8efefefe7f"), u32::MAX as u64).unwrap().0,
+            0xffffffff
+        );
+        assert_eq!(
+            read_varint(&hex!("8efefeff00"), u32::MAX as u64),
+            Err(Error::TooLarge)
+        );
+        assert_eq!(
+            read_varint(&hex!("8ffefefe7f"), u32::MAX as u64),
+            Err(Error::TooLarge)
+        );
+        assert_eq!(
+            read_varint(&hex!("80fefefefefefefeff00"), u64::MAX),
+            Err(Error::TooLarge)
+        );
+        assert_eq!(read_varint(&hex!("8080"), u64::MAX), Err(Error::Needed(1)));
+        assert_eq!(read_varint(&[], u64::MAX), Err(Error::Needed(1)));
+    }
+
+    #[test]
+    fn test_decompress_amount() {
+        // vectors from Bitcoin Core compress_tests
+        const COIN: u64 = 100_000_000;
+        for (value, compressed) in [
+            (0, 0x0),
+            (1, 0x1),
+            (1_000_000, 0x7),
+            (COIN, 0x9),
+            (50 * COIN, 0x32),
+            (21_000_000 * COIN, 0x1406f40),
+        ] {
+            assert_eq!(decompress_amount(compressed), value);
+            assert_eq!(compress_amount(value), compressed);
+        }
+        for value in (0..100_000).chain((0..100_000).map(|i| i * 1_000_000_007)) {
+            assert_eq!(decompress_amount(compress_amount(value)), value);
+        }
+    }
+
+    #[test]
+    fn parse_spent_coin() {
+        let hash = [1u8; 20];
+        let x = [2u8; 32];
+
+        let bytes = coin(0, false, 1, &[&[0u8][..], &hash[..]].concat());
+        let coin_parsed = SpentCoin::parse_all(&bytes).unwrap();
+        assert_eq!(coin_parsed.height(), 0);
+        assert!(!coin_parsed.is_coinbase());
+        assert_eq!(coin_parsed.value(), 1);
+        assert_eq!(coin_parsed.script(), &CompressedScript::P2pkh(&hash));
+
+        let bytes = coin(100, true, 5_000_000_000, &[&[1u8][..], &hash[..]].concat());
+        assert_eq!(&bytes[..4], &hex!("80490032"));
+        let coin_parsed = SpentCoin::parse_all(&bytes).unwrap();
+        assert_eq!(coin_parsed.height(), 100);
+        assert!(coin_parsed.is_coinbase());
+        assert_eq!(coin_parsed.value(), 5_000_000_000);
+        assert_eq!(coin_parsed.script(), &CompressedScript::P2sh(&hash));
+
+        for (size, expected) in [
+            (
+                2u8,
+                CompressedScript::P2pk {
+                    key_prefix: 2,
+                    x: &x,
+                },
+            ),
+            (
+                3,
+                CompressedScript::P2pk {
+                    key_prefix: 3,
+                    x: &x,
+                },
+            ),
+            (
+                4,
+                CompressedScript::P2pkUncompressed {
+                    key_prefix: 2,
+                    x: &x,
+                },
+            ),
+            (
+                5,
+                CompressedScript::P2pkUncompressed {
+                    key_prefix: 3,
+                    x: &x,
+                },
+            ),
+        ] {
+            let bytes = coin(700_000, false, 12_345, &[&[size][..], &x[..]].concat());
+            let coin_parsed = SpentCoin::parse_all(&bytes).unwrap();
+            assert_eq!(coin_parsed.height(), 700_000);
+            assert_eq!(coin_parsed.value(), 12_345);
+            assert_eq!(coin_parsed.script(), &expected);
+        }
+
+        let script = hex!("5120dd0382d691dead3ae2e5a1d2306deec88d51ec4b6744dc3b404ee0ad427ebfc8");
+        let mut compressed = vec![];
+        write_varint(script.len() as u64 + 6, &mut compressed);
+        compressed.extend(&script);
+        let bytes = coin(800_000, false, 330, &compressed);
+        let coin_parsed = SpentCoin::parse_all(&bytes).unwrap();
+        assert_eq!(coin_parsed.script(), &CompressedScript::Other(&script));
+
+        assert_eq!(
+            SpentCoin::parse(&bytes[..bytes.len() - 1]),
+            Err(Error::Needed(1))
+        );
+        let bytes = coin(1, false, 1, &[&[2u8][..], &x[..10]].concat());
+        assert_eq!(SpentCoin::parse(&bytes), Err(Error::Needed(22)));
+    }
+
+    #[test]
+    fn visit_block_undo() {
+        struct Spent(Vec<(usize, usize, u64)>, usize, usize);
+        impl Visitor<'_> for Spent {
+            fn visit_block_undo(&mut self, total_tx_undos: usize) {
+                self.1 = total_tx_undos;
+            }
+            fn visit_tx_undo(&mut self, _total_spent: usize) {
+                self.2 += 1;
+            }
+            fn visit_spent_coin(&mut self, vin: usize, coin: &SpentCoin) {
+                self.0.push((self.2 - 1, vin, coin.value()));
+            }
+        }
+
+        let p2pkh = [&[0u8][..], &[1u8; 20][..]].concat();
+        let mut bytes = vec![2u8];
+        bytes.push(2);
+        bytes.extend(coin(10, true, 5_000_000_000, &p2pkh));
+        bytes.extend(coin(20, false, 1_000, &p2pkh));
+        bytes.push(1);
+        bytes.extend(coin(30, false, 123_456_789, &p2pkh));
+
+        let mut spent = Spent(vec![], 0, 0);
+        let block_undo = BlockUndo::visit(&bytes, &mut spent).unwrap();
+        assert_eq!(block_undo.remaining(), &[0u8; 0][..]);
+        assert_eq!(block_undo.parsed().total_tx_undos(), 2);
+        assert_eq!(spent.1, 2);
+        assert_eq!(
+            spent.0,
+            vec![(0, 0, 5_000_000_000), (0, 1, 1_000), (1, 0, 123_456_789)]
+        );
+
+        let empty = BlockUndo::parse_all(&[0u8]).unwrap();
+        assert_eq!(empty.total_tx_undos(), 0);
+        assert_eq!(
+            BlockUndo::parse(&bytes[..bytes.len() - 1]),
+            Err(Error::Needed(1))
+        );
+    }
+}
@@ -0,0 +1,316 @@
+//! Ready made [`Visitor`]s for common tasks, also examples of how to implement the trait.
+//!
+//! ```
+//! use bitcoin_slices::{bsl, bsl::visitors::CountingVisitor, Visit};
+//!
+//! let mut counting = CountingVisitor::default();
+//! bsl::Block::visit(bitcoin_test_data::blocks::mainnet_702861(), &mut counting).unwrap();
+//! assert_eq!(counting.transactions, 2500);
+//! ```
+
+use core::ops::{ControlFlow, Range};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::{bsl, bsl::TxidHasher, Visitor};
+
+/// Counts the calls of every visitor function.
+///
+/// The undo data functions are counted too, when visiting [`bsl::BlockUndo`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CountingVisitor {
+    /// The number of block headers visited.
+    pub block_headers: usize,
+    /// The number of blocks visited.
+    pub blocks: usize,
+    /// The number of transactions visited.
+    pub transactions: usize,
+    /// The number of transaction inputs visited.
+    pub tx_ins: usize,
+    /// The number of transaction outputs visited.
+    pub tx_outs: usize,
+    /// The number of witnesses visited, one for every input of segwit transactions.
+    pub witnesses: usize,
+    /// The number of witness elements visited.
+    pub witness_elements: usize,
+    /// The number of transactions undo data visited.
+    pub tx_undos: usize,
+    /// The number of spent coins visited.
+    pub spent_coins: usize,
+}
+
+impl Visitor<'_> for CountingVisitor {
+    fn visit_block_header(&mut self, _header: &bsl::BlockHeader) {
+        self.block_headers += 1;
+    }
+    fn visit_block_begin(&mut self, _total_transactions: usize) {
+        self.blocks += 1;
+    }
+    fn visit_transaction(&mut self, _tx: &bsl::Transaction) {
+        self.transactions += 1;
+    }
+    fn visit_tx_in(&mut self, _vin: usize, _tx_in: &bsl::TxIn) {
+        self.tx_ins += 1;
+    }
+    fn visit_tx_out(&mut self, _vout: usize, _tx_out: &bsl::TxOut) {
+        self.tx_outs += 1;
+    }
+    fn visit_witness(&mut self, _vin: usize) {
+        self.witnesses += 1;
+    }
+    fn visit_witness_element(&mut self, _witness_i: usize, _element: &[u8]) {
+        self.witness_elements += 1;
+    }
+    fn visit_tx_undo(&mut self, _total_spent: usize) {
+        self.tx_undos += 1;
+    }
+    fn visit_spent_coin(&mut self, _vin: usize, _coin: &bsl::SpentCoin) {
+        self.spent_coins += 1;
+    }
+}
+
+/// Collects the txids of the visited transactions, in byte order (not reversed).
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "bitcoin_hashes"))))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TxidCollector {
+    /// The txids collected.
+    pub txids: Vec<[u8; 32]>,
+}
+
+#[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+impl Visitor<'_> for TxidCollector {
+    fn visit_block_begin(&mut self, total_transactions: usize) {
+        self.txids.reserve(total_transactions);
+    }
+    fn visit_transaction(&mut self, tx: &bsl::Transaction) {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        self.txids.push(tx.txid_with(sha256d::Hash::engine()));
+    }
+}
+
+/// Collects the previous outputs spent by the visited inputs, as txid in byte order (not
+/// reversed) and output index.
+///
+/// The null previous outputs of coinbase inputs are not collected.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OutpointCollector {
+    /// The previous outputs collected.
+    pub outpoints: Vec<([u8; 32], u32)>,
+}
+
+#[cfg(feature = "alloc")]
+impl Visitor<'_> for OutpointCollector {
+    fn visit_tx_in(&mut self, _vin: usize, tx_in: &bsl::TxIn) {
+        let prevout = tx_in.prevout();
+        if !prevout.is_null() {
+            let txid = prevout.txid().try_into().expect("32 bytes");
+            self.outpoints.push((txid, prevout.vout()));
+        }
+    }
+}
+
+/// Finds the transaction with a given txid in a block, breaking the visit once found.
+///
+/// Meant to be used to [`crate::Visit::visit()`] a [`bsl::Block`], returning
+/// [`crate::Error::VisitBreak`] when the transaction is found, the transactions preceding it are hashed but nothing is deserialized.
+///
+/// ```
+/// # #[cfg(feature = "bitcoin_hashes")] {
+/// use bitcoin_slices::{bsl, bsl::visitors::FindTxidVisitor, Error, Visit};
+///
+/// let block = bitcoin_test_data::blocks::mainnet_702861();
+/// let mut find = FindTxidVisitor::new([0u8; 32]);
+/// match bsl::Block::visit(block, &mut find) {
+///     Ok(_) | Err(Error::VisitBreak) => (),
+///     Err(e) => panic!("invalid block {:?}", e),
+/// }
+/// assert_eq!(find.found(), None);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FindTxidVisitor<H> {
+    txid: [u8; 32],
+    hasher: H,
+    index: usize,
+    found: Option<(usize, Range<usize>)>,
+}
+
+#[cfg(feature = "bitcoin_hashes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bitcoin_hashes")))]
+impl FindTxidVisitor<crate::bitcoin_hashes::sha256::HashEngine> {
+    /// Creates a visitor looking for `txid`, in byte order (not reversed), see
+    /// [`FindTxidVisitor::new_with()`].
+    pub fn new(txid: [u8; 32]) -> Self {
+        use crate::bitcoin_hashes::{sha256d, Hash};
+        Self::new_with(txid, sha256d::Hash::engine())
+    }
+}
+
+impl<H: TxidHasher + Clone> FindTxidVisitor<H> {
+    /// Creates a visitor looking for `txid`, in byte order (not reversed), computing the txids
+    /// with clones of `hasher`.
+    pub fn new_with(txid: [u8; 32], hasher: H) -> Self {
+        FindTxidVisitor {
+            txid,
+            hasher,
+            index: 0,
+            found: None,
+        }
+    }
+
+    /// Returns the index of the transaction found and its byte range in the visited block.
+    pub fn found(&self) -> Option<(usize, Range<usize>)> {
+        self.found.clone()
+    }
+}
+
+impl<H: TxidHasher + Clone> Visitor<'_> for FindTxidVisitor<H> {
+    fn visit_transaction_at(&mut self, offset: usize, tx: &bsl::Transaction) {
+        if self.found.is_some() {
+            return;
+        }
+        if tx.txid_with(self.hasher.clone()) == self.txid {
+            self.found = Some((self.index, offset..offset + tx.as_ref().len()));
+            return;
+        }
+        self.index += 1;
+    }
+    fn control_flow(&self) -> ControlFlow<()> {
+        if self.found.is_some() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bsl::{visitors::CountingVisitor, Block, Transaction},
+        test_common::{LEGACY_TX, SEGWIT_TX},
+        Visit,
+    };
+    use bitcoin::consensus::deserialize;
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    #[test]
+    fn counting_visitor() {
+        let mut counting = CountingVisitor::default();
+        Transaction::visit(&SEGWIT_TX[..], &mut counting).unwrap();
+        let expected = CountingVisitor {
+            transactions: 1,
+            tx_ins: 1,
+            tx_outs: 2,
+            witnesses: 1,
+            witness_elements: 1,
+            ..Default::default()
+        };
+        assert_eq!(counting, expected);
+
+        let mut counting = CountingVisitor::default();
+        Transaction::visit(&LEGACY_TX[..], &mut counting).unwrap();
+        assert_eq!((counting.tx_ins, counting.witnesses), (1, 0));
+
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let mut counting = CountingVisitor::default();
+        Block::visit(block_bytes, &mut counting).unwrap();
+        assert_eq!((counting.block_headers, counting.blocks), (1, 1));
+        assert_eq!(counting.transactions, block.txdata.len());
+        let inputs = block.txdata.iter().map(|tx| tx.input.len()).sum::<usize>();
+        assert_eq!(counting.tx_ins, inputs);
+        let outputs = block.txdata.iter().map(|tx| tx.output.len()).sum::<usize>();
+        assert_eq!(counting.tx_outs, outputs);
+        let elements = block
+            .txdata
+            .iter()
+            .flat_map(|tx| tx.input.iter())
+            .map(|input| input.witness.len())
+            .sum::<usize>();
+        assert_eq!(counting.witness_elements, elements);
+    }
+
+    #[cfg(all(feature = "alloc", feature = "bitcoin_hashes"))]
+    #[test]
+    fn txid_collector() {
+        use crate::bsl::visitors::TxidCollector;
+        use bitcoin::hashes::Hash;
+
+        let mut collector = TxidCollector::default();
+        Transaction::visit(&SEGWIT_TX[..], &mut collector).unwrap();
+        let tx: bitcoin::Transaction = deserialize(&SEGWIT_TX).unwrap();
+        assert_eq!(collector.txids, vec![tx.txid().into_inner()]);
+
+        let block_bytes = mainnet_702861();
+        let mut collector = TxidCollector::default();
+        let block = Block::visit(block_bytes, &mut collector)
+            .unwrap()
+            .parsed_owned();
+        let mut txids = vec![];
+        block.txids_into(&mut txids);
+        assert_eq!(collector.txids, txids);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn outpoint_collector() {
+        use crate::bsl::visitors::OutpointCollector;
+        use bitcoin::hashes::Hash;
+
+        // a coinbase
+        let mut collector = OutpointCollector::default();
+        Transaction::visit(&SEGWIT_TX[..], &mut collector).unwrap();
+        assert_eq!(collector.outpoints, vec![]);
+
+        Transaction::visit(&LEGACY_TX[..], &mut collector).unwrap();
+        let tx: bitcoin::Transaction = deserialize(&LEGACY_TX).unwrap();
+        let prevout = tx.input[0].previous_output;
+        assert_eq!(
+            collector.outpoints,
+            vec![(prevout.txid.into_inner(), prevout.vout)]
+        );
+
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let mut collector = OutpointCollector::default();
+        Block::visit(block_bytes, &mut collector).unwrap();
+        let expected: Vec<_> = block.txdata[1..]
+            .iter()
+            .flat_map(|tx| tx.input.iter())
+            .map(|input| {
+                (
+                    input.previous_output.txid.into_inner(),
+                    input.previous_output.vout,
+                )
+            })
+            .collect();
+        assert_eq!(collector.outpoints, expected);
+    }
+
+    #[cfg(feature = "bitcoin_hashes")]
+    #[test]
+    fn find_txid_visitor() {
+        use crate::{bsl::visitors::FindTxidVisitor, Error};
+        use bitcoin::consensus::serialize;
+        use bitcoin::hashes::Hash;
+
+        let block_bytes = mainnet_702861();
+        let block: bitcoin::Block = deserialize(block_bytes).unwrap();
+        let last = block.txdata.last().unwrap();
+        let mut find = FindTxidVisitor::new(last.txid().into_inner());
+        assert_eq!(Block::visit(block_bytes, &mut find), Err(Error::VisitBreak));
+        let (index, range) = find.found().unwrap();
+        assert_eq!(index, 2499);
+        assert_eq!(&block_bytes[range.clone()], &serialize(last)[..]);
+        assert_eq!(range.end, block_bytes.len());
+
+        let mut find = FindTxidVisitor::new([0u8; 32]);
+        assert!(Block::visit(block_bytes, &mut find).is_ok());
+        assert_eq!(find.found(), None);
+    }
+}
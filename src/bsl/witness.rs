@@ -1,6 +1,7 @@
-use super::len::{parse_len, Len};
-use crate::Visit;
+use super::len::{parse_len_with, Len, LenKind};
+use crate::visit::check;
 use crate::{slice::read_slice, ParseResult, SResult, Visitor};
+use crate::{EmptyVisitor, Visit};
 
 /// A single witness associated with a single transaction input.
 /// Logically is a vector of bytes vector.
@@ -15,19 +16,46 @@ impl<'a> AsRef<[u8]> for Witness<'a> {
     }
 }
 
+crate::hex::impl_hex_fmt!(Witness);
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for Witness<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 impl<'a> Visit<'a> for Witness<'a> {
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Witness<'a>> {
-        let Len { mut consumed, n } = parse_len(slice)?;
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Witness<'a>> {
+        Self::visit_with(slice, visit, false)
+    }
+}
+impl<'a> Witness<'a> {
+    /// Visit a witness, if `lenient` accepting non-minimal compact ints.
+    pub(crate) fn visit_with<'b, V: Visitor<'a>>(
+        slice: &'a [u8],
+        visit: &'b mut V,
+        lenient: bool,
+    ) -> SResult<'a, Witness<'a>> {
+        let Len { mut consumed, n } = parse_len_with(slice, lenient)?;
         let mut remaining = &slice[consumed..];
         let witness_total_element = n as usize;
 
+        visit.visit_len(LenKind::WitnessElementCount, n, consumed);
+        check(visit)?;
         visit.visit_witness_total_element(witness_total_element);
+        check(visit)?;
         for i in 0..witness_total_element {
-            let len = parse_len(remaining)?;
+            let len = parse_len_with(remaining, lenient)?;
+            visit.visit_len(LenKind::WitnessElementLen, len.n(), len.consumed());
+            check(visit)?;
             let sl = read_slice(&remaining[len.consumed()..], len.n() as usize)?;
             remaining = sl.remaining();
             consumed += len.slice_len();
             visit.visit_witness_element(i, sl.parsed());
+            check(visit)?;
         }
 
         let witness = Witness {
@@ -35,14 +63,95 @@ impl<'a> Visit<'a> for Witness<'a> {
         };
         Ok(ParseResult::new(&slice[consumed..], witness))
     }
-}
-impl<'a> Witness<'a> {
+
+    /// Parse a witness, if `lenient` accepting non-minimal compact ints.
+    pub(crate) fn parse_with(slice: &'a [u8], lenient: bool) -> SResult<'a, Witness<'a>> {
+        Self::visit_with(slice, &mut EmptyVisitor {}, lenient)
+    }
+
     /// If this witness contain no elements
     pub fn is_empty(&self) -> bool {
-        self.slice[0] == 0
+        // the count may be non-minimal if parsed leniently
+        parse_len_with(self.slice, true)
+            .expect("granted during parsing")
+            .n()
+            == 0
+    }
+
+    /// Returns an iterator over the elements of this witness.
+    ///
+    /// If possible is better to use [`Visitor::visit_witness_element`] to avoid double pass,
+    /// however, it may be conveniet to iterate in case you already have validated the slice.
+    pub fn iter(&self) -> WitnessIterator<'a> {
+        WitnessIterator::new(self.slice)
+    }
+
+    /// Returns the last element if it looks like a taproot annex: there are at least two elements
+    /// and the last starts with `0x50`.
+    ///
+    /// This is only the syntactic pattern, the element is an annex only if the input spends a
+    /// taproot output, which can't be known without the previous output.
+    pub fn annex(&self) -> Option<&'a [u8]> {
+        let iter = self.iter();
+        if iter.len() < 2 {
+            return None;
+        }
+        iter.last().filter(|last| last.first() == Some(&0x50))
+    }
+}
+
+impl<'a> IntoIterator for &Witness<'a> {
+    type Item = &'a [u8];
+    type IntoIter = WitnessIterator<'a>;
+
+    fn into_iter(self) -> WitnessIterator<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the elements of an already validated [`Witness`], created with
+/// [`Witness::iter()`].
+pub struct WitnessIterator<'a> {
+    elements: usize,
+    remaining: &'a [u8],
+}
+
+impl<'a> WitnessIterator<'a> {
+    /// Creates the iterator from a slice starting with the compact int of the number of elements.
+    /// The slice must have been already validated, or the iterator may panic, non-minimal compact
+    /// ints are accepted since they may have been parsed in lenient mode.
+    fn new(slice: &'a [u8]) -> Self {
+        let len = parse_len_with(slice, true).expect("len granted by parsing");
+        WitnessIterator {
+            elements: len.n() as usize,
+            remaining: &slice[len.consumed()..],
+        }
+    }
+}
+
+impl<'a> Iterator for WitnessIterator<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.elements == 0 {
+            None
+        } else {
+            let len = parse_len_with(self.remaining, true).expect("granted from parsing");
+            let end = len.slice_len();
+            let element = &self.remaining[len.consumed()..end];
+            self.remaining = &self.remaining[end..];
+            self.elements -= 1;
+            Some(element)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.elements, Some(self.elements))
     }
 }
 
+impl<'a> ExactSizeIterator for WitnessIterator<'a> {}
+
 #[cfg(test)]
 mod test {
     use crate::{bsl::Witness, Parse, ParseResult, Visit, Visitor};
@@ -62,11 +171,51 @@ mod test {
         );
     }
 
+    #[test]
+    fn witness_iter() {
+        let witness = hex!("0300010a02bbcc");
+        let witness = Witness::parse(&witness[..]).unwrap().parsed_owned();
+        let elements: Vec<_> = witness.iter().collect();
+        assert_eq!(
+            elements,
+            vec![&[0u8; 0][..], &[0x0a][..], &[0xbb, 0xcc][..]]
+        );
+        assert_eq!(witness.iter().len(), 3);
+
+        let witness = hex!("00");
+        let witness = Witness::parse(&witness[..]).unwrap().parsed_owned();
+        assert_eq!(witness.iter().next(), None);
+    }
+
+    #[test]
+    fn witness_annex() {
+        let annex = |bytes: &[u8]| {
+            let witness = Witness::parse(bytes).unwrap().parsed_owned();
+            witness.annex().map(|a| a.to_vec())
+        };
+        assert_eq!(annex(&hex!("02010a025001")), Some(vec![0x50, 0x01]));
+        assert_eq!(annex(&hex!("0201500100")), None);
+        assert_eq!(annex(&hex!("020100025001")), Some(vec![0x50, 0x01]));
+        // a single element is the key path signature, even if it starts with 0x50
+        assert_eq!(annex(&hex!("01025001")), None);
+        assert_eq!(annex(&hex!("00")), None);
+        assert_eq!(annex(&hex!("02000100")), None);
+    }
+
+    #[test]
+    fn fmt_witness() {
+        let witness = hex!("02010a01ff");
+        let witness = Witness::parse(&witness[..]).unwrap().parsed_owned();
+        assert_eq!(format!("{:x}", witness), "02010a01ff");
+        assert_eq!(format!("{:X}", witness), "02010A01FF");
+        assert_eq!(witness.to_string(), "02010a01ff");
+    }
+
     #[test]
     fn visit_witness() {
         let witness = hex!("0201000101");
         struct WitnessVisititor(usize);
-        impl Visitor for WitnessVisititor {
+        impl Visitor<'_> for WitnessVisititor {
             fn visit_witness_total_element(&mut self, witness_total: usize) {
                 assert_eq!(witness_total, 2);
             }
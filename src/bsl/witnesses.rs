@@ -1,5 +1,6 @@
 use crate::bsl::Witness;
-use crate::{ParseResult, SResult, Visit};
+use crate::visit::check;
+use crate::{ParseResult, SResult};
 
 /// Struct containining all the Witness in the tx (which is the same number as the inputs)
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -13,25 +14,49 @@ impl<'a> AsRef<[u8]> for Witnesses<'a> {
     }
 }
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'a> serde::Serialize for Witnesses<'a> {
+    /// Serialize as lowercase hex for human readable formats, as bytes otherwise.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::hex::serialize(self.as_ref(), serializer)
+    }
+}
+
 impl<'a> Witnesses<'a> {
     /// Parse the witnesses in the slice
-    pub fn parse(slice: &'a [u8], total_inputs: usize) -> SResult<Self> {
+    pub fn parse(slice: &'a [u8], total_inputs: usize) -> SResult<'a, Self> {
         Self::visit(slice, total_inputs, &mut crate::visit::EmptyVisitor {})
     }
     /// Visit the witnesses in the slice
-    pub fn visit<'b, V: crate::Visitor>(
+    pub fn visit<'b, V: crate::Visitor<'a>>(
         slice: &'a [u8],
         total_inputs: usize,
         visit: &'b mut V,
     ) -> SResult<'a, Self> {
+        Self::visit_with(slice, total_inputs, visit, false)
+    }
+    /// Visit the witnesses in the slice, if `lenient` accepting non-minimal compact ints.
+    pub(crate) fn visit_with<'b, V: crate::Visitor<'a>>(
+        slice: &'a [u8],
+        total_inputs: usize,
+        visit: &'b mut V,
+        lenient: bool,
+    ) -> SResult<'a, Self> {
+        if !visit.need_witnesses() {
+            let empty = &mut crate::visit::EmptyVisitor {};
+            return Self::visit_with(slice, total_inputs, empty, lenient);
+        }
         let mut remaining = slice;
         let mut consumed = 0;
         let mut all_empty = true;
         for i in 0..total_inputs {
             visit.visit_witness(i);
+            check(visit)?;
 
-            let witness = Witness::visit(remaining, visit)?;
+            let witness = Witness::visit_with(remaining, visit, lenient)?;
             visit.visit_witness_end();
+            check(visit)?;
 
             remaining = witness.remaining();
             consumed += witness.consumed();
@@ -61,7 +86,7 @@ mod test {
     fn parse_witnesses() {
         let witnesses_bytes = hex!("0101000201000100"); // first witness is [[0]], second witness is [[0][0]]
         let witnesses = Witnesses::parse(&witnesses_bytes[..], 2).unwrap();
-        assert_eq!(witnesses.remaining(), &[][..]);
+        assert_eq!(witnesses.remaining(), &[0u8; 0][..]);
         assert_eq!(witnesses.parsed().as_ref(), &witnesses_bytes[..]);
         assert_eq!(witnesses.consumed(), 8);
     }
@@ -74,7 +99,7 @@ mod test {
             witness_vin: usize,
             witness_el_i: usize,
         }
-        impl Visitor for V {
+        impl Visitor<'_> for V {
             fn visit_witness(&mut self, vin: usize) {
                 assert_eq!(vin, self.witness_vin);
             }
@@ -82,7 +107,7 @@ mod test {
                 match self.witness_vin {
                     0 => assert_eq!(witness_total, 1),
                     1 => assert_eq!(witness_total, 2),
-                    _ => assert!(false),
+                    _ => panic!(),
                 }
             }
             fn visit_witness_element(&mut self, _witness_i: usize, witness_element: &[u8]) {
@@ -90,7 +115,7 @@ mod test {
                     (0, 0) => assert_eq!(witness_element, &[0u8]),
                     (1, 0) => assert_eq!(witness_element, &[1u8]),
                     (1, 1) => assert_eq!(witness_element, &[2u8]),
-                    _ => assert!(false),
+                    _ => panic!(),
                 }
                 self.witness_el_i += 1;
             }
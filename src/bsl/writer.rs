@@ -0,0 +1,87 @@
+use alloc::vec::Vec;
+
+use crate::bsl::len::encode_len;
+
+/// The parts of a transaction input, previous output, script sig and sequence.
+pub(crate) type Input = (Vec<u8>, Vec<u8>, u32);
+
+/// The parts of a transaction output, value and script pubkey.
+pub(crate) type Output = (u64, Vec<u8>);
+
+/// The parts of a generated transaction, serialized with [`Writer::push_transaction()`].
+pub(crate) struct TxParts {
+    pub(crate) version: i32,
+    pub(crate) inputs: Vec<Input>,
+    pub(crate) outputs: Vec<Output>,
+    /// Ignored when there are no inputs, such transactions are always segwit.
+    pub(crate) segwit: bool,
+    /// The witnesses of the inputs, the missing ones are empty.
+    pub(crate) witnesses: Vec<Vec<Vec<u8>>>,
+    pub(crate) locktime: u32,
+}
+
+/// Builds a consensus serialization remembering where the compact ints are, shared by the
+/// `arbitrary` and the `proptest` generators.
+#[derive(Default)]
+pub(crate) struct Writer {
+    pub(crate) bytes: Vec<u8>,
+    /// The offsets of the compact ints in `bytes`.
+    pub(crate) lens: Vec<usize>,
+}
+
+impl Writer {
+    pub(crate) fn push_len(&mut self, n: usize) {
+        self.lens.push(self.bytes.len());
+        let (buffer, used) = encode_len(n as u64);
+        self.bytes.extend_from_slice(&buffer[..used]);
+    }
+
+    pub(crate) fn push_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn push_var_bytes(&mut self, bytes: &[u8]) {
+        self.push_len(bytes.len());
+        self.push_bytes(bytes);
+    }
+
+    /// Serializes `tx` so that it's valid for parsing.
+    ///
+    /// A transaction with no inputs is serialized with the segwit marker, since in the legacy
+    /// serialization the zero inputs count would be read as the marker. A segwit transaction needs
+    /// a non-empty witness, so if all the witnesses are empty the last one gets an empty element.
+    pub(crate) fn push_transaction(&mut self, tx: &TxParts) {
+        self.push_bytes(&tx.version.to_le_bytes());
+        let segwit = tx.segwit || tx.inputs.is_empty();
+        if segwit {
+            self.push_bytes(&[0, 1]);
+        }
+        self.push_len(tx.inputs.len());
+        for (prevout, script_sig, sequence) in tx.inputs.iter() {
+            self.push_bytes(prevout);
+            self.push_var_bytes(script_sig);
+            self.push_bytes(&sequence.to_le_bytes());
+        }
+        self.push_len(tx.outputs.len());
+        for (value, script_pubkey) in tx.outputs.iter() {
+            self.push_bytes(&value.to_le_bytes());
+            self.push_var_bytes(script_pubkey);
+        }
+        if segwit {
+            let witness = |i: usize| tx.witnesses.get(i).map_or(&[][..], |w| &w[..]);
+            let all_empty = (0..tx.inputs.len()).all(|i| witness(i).is_empty());
+            for i in 0..tx.inputs.len() {
+                if all_empty && i == tx.inputs.len() - 1 {
+                    self.push_len(1);
+                    self.push_var_bytes(&[]);
+                } else {
+                    self.push_len(witness(i).len());
+                    for element in witness(i) {
+                        self.push_var_bytes(element);
+                    }
+                }
+            }
+        }
+        self.push_bytes(&tx.locktime.to_le_bytes());
+    }
+}
@@ -1,5 +1,9 @@
+use core::fmt;
+use core::num::NonZeroU32;
+
 /// All possible error variants in the crate
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
     /// This bytes are needed to complete the parsing of the current element.
     ///
@@ -8,6 +12,10 @@ pub enum Error {
     /// element parsed is the hash of 32 bytes, but to complete the object you would need another
     /// 4 bytes of the `vout`.
     ///
+    /// It's never zero and it's never more than the bytes missing to complete the object, so
+    /// reading this many bytes before retrying never reads past the object. The other variants
+    /// are returned for malformed data, which more bytes can't fix, see [`Error::needed()`].
+    ///
     /// Note also it's an u32 instead of an usize to save space on 64 bits system and
     /// significantly improve performance.
     Needed(u32),
@@ -21,10 +29,803 @@ pub enum Error {
     /// The decoded varint is not in it's minimal form, eg. `0xFD0100` it's decoded as `1` but it's
     /// minimal encoding is `0x01`
     NonMinimalVarInt,
+
+    /// The buffer given to write into is too small, the contained value is the length required.
+    BufferTooSmall(u32),
+
+    /// The requested input index is not in the transaction, the contained value is the index.
+    InputIndexOutOfRange(u32),
+
+    /// Signing an input with `SIGHASH_SINGLE` without an output with the same index, Bitcoin Core
+    /// doesn't hash any data and the signature hash is the value `1`.
+    ///
+    /// For taproot inputs such signature is simply invalid.
+    SighashSingleBug,
+
+    /// The sighash type is not valid, the contained value is the sighash type.
+    InvalidSighashType(u32),
+
+    /// The spent outputs given are not as many as the transaction inputs, the contained value is
+    /// the number of spent outputs given.
+    SpentOutputsMismatch(u32),
+
+    /// The taproot annex doesn't start with the `0x50` byte.
+    InvalidAnnex,
+
+    /// The parsed object is too large to be represented, for example a transaction with inputs
+    /// and outputs longer than `u32::MAX` bytes.
+    TooLarge,
+
+    /// The slice contains bytes after the parsed object, the contained value is their number.
+    ///
+    /// Returned only by [`crate::Parse::parse_all`].
+    TrailingBytes(u32),
+
+    /// The transaction version is not in the allowed range, the contained value is the version.
+    ///
+    /// Returned only by [`crate::bsl::Transaction::parse_with_config`] and
+    /// [`crate::bsl::Transaction::visit_with_config`].
+    UnsupportedVersion(i32),
+
+    /// The block has no transactions, so it has no coinbase.
+    ///
+    /// Returned only by [`crate::bsl::Block::coinbase`].
+    MissingCoinbase,
+
+    /// A record of a block file doesn't start with the expected network magic.
+    ///
+    /// Returned only by [`crate::bsl::BlkFileIter`].
+    InvalidMagic,
+
+    /// A differentially encoded transaction index of a compact block message doesn't fit in 16
+    /// bits, or the compact block has more than `u16::MAX` transactions.
+    IndexOverflow,
+
+    /// The lookup didn't find the previous output spent by an input.
+    ///
+    /// Returned only by [`crate::bsl::Transaction::sigop_cost`] and
+    /// [`crate::bsl::Block::sigop_cost`].
+    MissingPrevout,
+
+    /// The visit has been stopped by [`crate::Visitor::control_flow`] returning
+    /// [`core::ops::ControlFlow::Break`].
+    VisitBreak,
+
+    /// A declared count or a size exceeds the contained limit of the [`crate::bsl::ParseLimits`]
+    /// given.
+    LimitExceeded(ParseLimit),
+
+    /// An output value or the sum of the output values is greater than [`crate::bsl::MAX_MONEY`].
+    ///
+    /// Returned only by [`crate::bsl::Transaction::parse_with_config`] and
+    /// [`crate::bsl::Transaction::visit_with_config`] with [`crate::bsl::ParseConfig::check_amounts`].
+    InvalidAmount(AmountError),
+}
+
+impl Error {
+    /// Returns the bytes needed if the data is truncated, `None` if it's malformed.
+    pub fn needed(&self) -> Option<NonZeroU32> {
+        match self {
+            Error::Needed(needed) => NonZeroU32::new(*needed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Needed(needed) => {
+                write!(f, "unexpected end of data, {} more bytes needed", needed)
+            }
+            Error::UnknownSegwitFlag(flag) => write!(f, "unknown segwit flag {:#04x}", flag),
+            Error::SegwitFlagWithoutWitnesses => {
+                f.write_str("segwit flag set but all the witnesses are empty")
+            }
+            Error::NonMinimalVarInt => f.write_str("non-minimal compact size encoding"),
+            Error::BufferTooSmall(required) => {
+                write!(f, "buffer too small, {} bytes required", required)
+            }
+            Error::InputIndexOutOfRange(index) => write!(f, "input index {} out of range", index),
+            Error::SighashSingleBug => f.write_str("SIGHASH_SINGLE without a corresponding output"),
+            Error::InvalidSighashType(ty) => write!(f, "invalid sighash type {:#x}", ty),
+            Error::SpentOutputsMismatch(given) => write!(
+                f,
+                "{} spent outputs given, not as many as the transaction inputs",
+                given
+            ),
+            Error::InvalidAnnex => f.write_str("taproot annex doesn't start with 0x50"),
+            Error::TooLarge => f.write_str("object too large to be represented"),
+            Error::TrailingBytes(trailing) => {
+                write!(f, "{} trailing bytes after the object", trailing)
+            }
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported transaction version {}", version)
+            }
+            Error::MissingCoinbase => f.write_str("block without transactions, missing coinbase"),
+            Error::InvalidMagic => f.write_str("invalid network magic in block file"),
+            Error::IndexOverflow => f.write_str("compact block transaction index overflow"),
+            Error::MissingPrevout => f.write_str("previous output spent by an input not found"),
+            Error::VisitBreak => f.write_str("visit stopped by the visitor"),
+            Error::LimitExceeded(limit) => write!(f, "parse limit exceeded: {}", limit),
+            Error::InvalidAmount(amount) => write!(f, "invalid amount: {}", amount),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for Error {}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl From<Error> for std::io::Error {
+    /// Truncated data, [`Error::Needed`], is an [`std::io::ErrorKind::UnexpectedEof`], the other
+    /// errors are [`std::io::ErrorKind::InvalidData`], the original error is kept as inner error.
+    fn from(e: Error) -> Self {
+        let kind = match e {
+            Error::Needed(_) => std::io::ErrorKind::UnexpectedEof,
+            _ => std::io::ErrorKind::InvalidData,
+        };
+        std::io::Error::new(kind, e)
+    }
+}
+
+/// An [`Error`] with the offset and the context of the element which failed to parse, returned
+/// by [`crate::bsl::Transaction::parse_with_offset`] and [`crate::bsl::Block::parse_with_offset`].
+///
+/// The offset and the context are computed visiting again the slice with the same parser only
+/// after the parsing failed, so the other parsing functions, returning just the [`Error`], pay
+/// nothing for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OffsetError {
+    /// The offset from the start of the slice given of the element which failed to parse, like an
+    /// input, an output or a compact int, or of the whole transaction if the error isn't about a
+    /// single element, like [`Error::UnknownSegwitFlag`].
+    pub offset: usize,
+
+    /// The parsing error.
+    pub error: Error,
+
+    /// The index in the block of the transaction which failed to parse, `None` parsing a single
+    /// transaction or outside the transactions, like in the block header.
+    pub transaction: Option<u32>,
+
+    /// The kind and the index of the transaction element containing the failure, `None` outside
+    /// inputs, outputs and witnesses, like in the version or in the inputs count.
+    ///
+    /// It's only available through the `parse_with_offset` functions, parsing or visiting
+    /// [`crate::bsl::TxIns`], [`crate::bsl::TxOuts`] or [`crate::bsl::Witnesses`] returns just the
+    /// [`Error`].
+    pub element: Option<(ElementKind, u32)>,
+}
+
+impl fmt::Display for OffsetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.error, self.offset)?;
+        if let Some((kind, index)) = self.element {
+            write!(f, " in {} {}", kind, index)?;
+        }
+        if let Some(transaction) = self.transaction {
+            write!(f, " of transaction {}", transaction)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for OffsetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// The kind of the transaction element containing the failure in [`OffsetError::element`], only
+/// returned by the `parse_with_offset` functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementKind {
+    /// A transaction input, with its previous output, script sig and sequence.
+    Input,
+
+    /// A transaction output, with its value and script pubkey.
+    Output,
+
+    /// The witness of an input, with its elements count and elements.
+    Witness,
+}
+
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ElementKind::Input => "input",
+            ElementKind::Output => "output",
+            ElementKind::Witness => "witness",
+        })
+    }
+}
+
+/// The limit of [`crate::bsl::ParseLimits`] exceeded, contained in [`Error::LimitExceeded`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseLimit {
+    /// [`crate::bsl::ParseLimits::max_inputs`]
+    Inputs,
+
+    /// [`crate::bsl::ParseLimits::max_outputs`]
+    Outputs,
+
+    /// [`crate::bsl::ParseLimits::max_witness_elements`]
+    WitnessElements,
+
+    /// [`crate::bsl::ParseLimits::max_witness_element_size`]
+    WitnessElementSize,
+
+    /// [`crate::bsl::ParseLimits::max_tx_size`]
+    TxSize,
+
+    /// [`crate::bsl::ParseLimits::max_block_size`]
+    BlockSize,
+}
+
+impl fmt::Display for ParseLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseLimit::Inputs => "too many inputs",
+            ParseLimit::Outputs => "too many outputs",
+            ParseLimit::WitnessElements => "too many witness elements",
+            ParseLimit::WitnessElementSize => "witness element too large",
+            ParseLimit::TxSize => "transaction too large",
+            ParseLimit::BlockSize => "block too large",
+        })
+    }
+}
+
+/// Errors computing the fee of a transaction with [`crate::bsl::Transaction::fee`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FeeError {
+    /// The lookup didn't find the previous output spent by the input with the contained index.
+    MissingPrevout(usize),
+
+    /// The sum of the outputs values is greater than the sum of the spent previous outputs values.
+    Negative,
+
+    /// The sum of the inputs or of the outputs values overflows a `u64`.
+    Overflow,
+}
+
+impl fmt::Display for FeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeeError::MissingPrevout(index) => {
+                write!(f, "missing previous output spent by input {}", index)
+            }
+            FeeError::Negative => f.write_str("outputs value greater than inputs value"),
+            FeeError::Overflow => f.write_str("inputs or outputs value overflows"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for FeeError {}
+
+/// An output value invalid for consensus, returned by
+/// [`crate::bsl::Transaction::check_amounts`] and contained in [`Error::InvalidAmount`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    /// The value of the output with the contained index is greater than
+    /// [`crate::bsl::MAX_MONEY`].
+    OutputTooLarge(u32),
+
+    /// The sum of the output values up to the output with the contained index is greater than
+    /// [`crate::bsl::MAX_MONEY`].
+    TotalTooLarge(u32),
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmountError::OutputTooLarge(index) => {
+                write!(f, "value of output {} greater than MAX_MONEY", index)
+            }
+            AmountError::TotalTooLarge(index) => {
+                write!(f, "total value greater than MAX_MONEY at output {}", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for AmountError {}
+
+/// The compact target `nBits` of a block header can't be decoded, returned by
+/// [`crate::bsl::BlockHeader::target`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TargetError {
+    /// The sign bit is set and the mantissa is not zero.
+    Negative,
+
+    /// The target doesn't fit in 256 bits.
+    Overflow,
+}
+
+impl fmt::Display for TargetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TargetError::Negative => "negative compact target",
+            TargetError::Overflow => "compact target overflows 256 bits",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for TargetError {}
+
+/// The proof of work of a block header is not valid, returned by
+/// [`crate::bsl::BlockHeader::validate_pow`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PowError {
+    /// The compact target can't be decoded.
+    InvalidTarget(TargetError),
+
+    /// The target is zero.
+    ZeroTarget,
+
+    /// The target is greater than the proof of work limit.
+    TargetAboveLimit,
+
+    /// The block hash is greater than the target.
+    HashAboveTarget,
+}
+
+impl fmt::Display for PowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PowError::InvalidTarget(e) => write!(f, "invalid target: {}", e),
+            PowError::ZeroTarget => f.write_str("zero target"),
+            PowError::TargetAboveLimit => f.write_str("target above the proof of work limit"),
+            PowError::HashAboveTarget => f.write_str("block hash above the target"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for PowError {}
+
+impl From<TargetError> for PowError {
+    fn from(e: TargetError) -> Self {
+        PowError::InvalidTarget(e)
+    }
+}
+
+/// The merkle root of the transactions doesn't verify, returned by
+/// [`crate::bsl::Block::verify_merkle_root`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleError {
+    /// The computed merkle root is different from the one in the block header.
+    Mismatch,
+
+    /// The merkle root matches, but the transactions list is mutated by duplicating some of the
+    /// last transactions (CVE-2012-2459), so the block is invalid while another one with the same
+    /// hash could be valid.
+    Mutated,
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MerkleError::Mismatch => "merkle root mismatch",
+            MerkleError::Mutated => "mutated transactions list (CVE-2012-2459)",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for MerkleError {}
+
+/// The BIP141 witness commitment of a block doesn't verify, returned by
+/// [`crate::bsl::Block::verify_witness_commitment`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WitnessCommitmentError {
+    /// The block has no transactions, thus no coinbase.
+    NoTransactions,
+
+    /// The coinbase has a witness commitment, but its input witness is not a single 32 bytes
+    /// element, the witness reserved value.
+    InvalidReservedValue,
+
+    /// The committed hash is different from the one computed from the witness merkle root and the
+    /// witness reserved value.
+    Mismatch,
+
+    /// The coinbase has no witness commitment, but some transaction has witness data.
+    UnexpectedWitness,
+}
+
+impl fmt::Display for WitnessCommitmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WitnessCommitmentError::NoTransactions => "block without transactions",
+            WitnessCommitmentError::InvalidReservedValue => "invalid witness reserved value",
+            WitnessCommitmentError::Mismatch => "witness commitment mismatch",
+            WitnessCommitmentError::UnexpectedWitness => {
+                "witness data without a witness commitment"
+            }
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for WitnessCommitmentError {}
+
+/// The partial merkle tree of a BIP37 merkle block is not valid, returned by
+/// [`crate::bsl::MerkleBlock::extract_matches_with`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MerkleBlockError {
+    /// The total number of transactions is zero.
+    NoTransactions,
+
+    /// The total number of transactions is greater than the maximum that fits in a block.
+    TooManyTransactions,
+
+    /// There are more hashes than transactions, or less flag bits than hashes.
+    BadFormat,
+
+    /// The traversal of the tree needs more flag bits or hashes than given.
+    Overflow,
+
+    /// Some flag bits, excluding the padding of the last byte, or hashes are not used by the
+    /// traversal of the tree.
+    Unused,
+
+    /// Two sibling nodes have the same hash, which happens when the last transactions are
+    /// duplicated (CVE-2012-2459).
+    IdenticalHashes,
+
+    /// The computed merkle root is different from the one in the block header.
+    Mismatch,
+}
+
+impl fmt::Display for MerkleBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MerkleBlockError::NoTransactions => "merkle block without transactions",
+            MerkleBlockError::TooManyTransactions => "too many transactions in merkle block",
+            MerkleBlockError::BadFormat => "bad format of the partial merkle tree",
+            MerkleBlockError::Overflow => "partial merkle tree traversal overflows",
+            MerkleBlockError::Unused => "unused flag bits or hashes in the partial merkle tree",
+            MerkleBlockError::IdenticalHashes => {
+                "identical sibling hashes in the partial merkle tree (CVE-2012-2459)"
+            }
+            MerkleBlockError::Mismatch => "merkle root mismatch",
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for MerkleBlockError {}
+
+/// A chain of block headers is not valid, returned by [`crate::bsl::validate_header_chain_with`], the
+/// contained value is the index of the invalid header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderChainError {
+    /// The slice ends in the middle of the header.
+    Truncated(usize),
+
+    /// The header is followed by a transaction count different from zero in the P2P encoding.
+    NonZeroTxCount(usize),
+
+    /// The previous block hash of the header is not the hash of the previous header, or the
+    /// expected one for the first header.
+    Disconnected(usize),
+}
+
+impl fmt::Display for HeaderChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderChainError::Truncated(index) => write!(f, "header {} truncated", index),
+            HeaderChainError::NonZeroTxCount(index) => {
+                write!(f, "non-zero transaction count after header {}", index)
+            }
+            HeaderChainError::Disconnected(index) => {
+                write!(f, "header {} not connected to the previous one", index)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for HeaderChainError {}
+
+/// The policy size limit violated by a transaction, returned by
+/// [`crate::bsl::Transaction::check_standard_size_limits`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizeLimitError {
+    /// The transaction has no inputs.
+    NoInputs,
+
+    /// The transaction has no outputs.
+    NoOutputs,
+
+    /// The transaction weight is greater than the maximum, the contained value is the weight.
+    WeightTooHigh(usize),
+
+    /// The transaction size without witnesses is lower than the minimum, the contained value is
+    /// the size.
+    BaseSizeTooSmall(usize),
+}
+
+impl fmt::Display for SizeLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SizeLimitError::NoInputs => f.write_str("transaction without inputs"),
+            SizeLimitError::NoOutputs => f.write_str("transaction without outputs"),
+            SizeLimitError::WeightTooHigh(weight) => {
+                write!(f, "transaction weight {} too high", weight)
+            }
+            SizeLimitError::BaseSizeTooSmall(size) => {
+                write!(f, "transaction size without witnesses {} too small", size)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl std::error::Error for SizeLimitError {}
+
+/// Errors reading objects from a stream with [`crate::bsl::ReadParser`]
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[derive(Debug)]
+pub enum StreamError {
+    /// Reading from the stream failed.
+    Io(std::io::Error),
+
+    /// The bytes read from the stream are not a valid object.
+    Parse(Error),
+
+    /// The stream ended in the middle of an object.
+    UnexpectedEof,
+
+    /// The object is bigger than the maximum size of the buffer given at construction.
+    ItemTooLarge,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamError::Io(e) => write!(f, "reading the stream failed: {}", e),
+            StreamError::Parse(e) => write!(f, "invalid object in the stream: {}", e),
+            StreamError::UnexpectedEof => f.write_str("stream ended in the middle of an object"),
+            StreamError::ItemTooLarge => f.write_str("object bigger than the buffer"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for StreamError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StreamError::Io(e) => Some(e),
+            StreamError::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for StreamError {
+    fn from(e: std::io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for StreamError {
+    fn from(e: Error) -> Self {
+        StreamError::Parse(e)
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use super::{
+        AmountError, ElementKind, Error, FeeError, HeaderChainError, MerkleBlockError, OffsetError,
+        ParseLimit, PowError, SizeLimitError, TargetError,
+    };
+
+    #[test]
+    fn needed() {
+        assert_eq!(Error::Needed(3).needed().map(|n| n.get()), Some(3));
+        assert_eq!(Error::NonMinimalVarInt.needed(), None);
+    }
+
+    #[test]
+    fn truncated_only_needed() {
+        use crate::{
+            bsl,
+            number::{U16, U32, U64},
+            test_common::{GENESIS_BLOCK, GENESIS_BLOCK_HEADER, GENESIS_TX, LEGACY_TX, SEGWIT_TX},
+            Parse,
+        };
+
+        macro_rules! consumed {
+            ($ty:ty) => {
+                |slice| <$ty>::parse(slice).map(|parsed| parsed.consumed())
+            };
+        }
+        type ParseFn = fn(&[u8]) -> Result<usize, Error>;
+        let cases: [(&str, &[u8], ParseFn); 14] = [
+            ("U16", &SEGWIT_TX[..2], consumed!(U16)),
+            ("U32", &SEGWIT_TX[..4], consumed!(U32)),
+            ("U64", &SEGWIT_TX[103..111], consumed!(U64)),
+            ("Len 3 bytes", &[0xfd, 0xfd, 0], |slice| {
+                bsl::parse_len(slice).map(|len| len.consumed())
+            }),
+            ("Len 5 bytes", &[0xfe, 0, 0, 1, 0], |slice| {
+                bsl::parse_len(slice).map(|len| len.consumed())
+            }),
+            ("Len 9 bytes", &[0xff, 0, 0, 0, 0, 1, 0, 0, 0], |slice| {
+                bsl::parse_len(slice).map(|len| len.consumed())
+            }),
+            ("TxIns", &SEGWIT_TX[6..102], consumed!(bsl::TxIns)),
+            ("TxOuts", &SEGWIT_TX[102..184], consumed!(bsl::TxOuts)),
+            ("Witnesses", &SEGWIT_TX[184..218], |slice| {
+                bsl::Witnesses::parse(slice, 1).map(|parsed| parsed.consumed())
+            }),
+            (
+                "segwit Transaction",
+                &SEGWIT_TX,
+                consumed!(bsl::Transaction),
+            ),
+            (
+                "legacy Transaction",
+                &LEGACY_TX,
+                consumed!(bsl::Transaction),
+            ),
+            (
+                "genesis Transaction",
+                &GENESIS_TX,
+                consumed!(bsl::Transaction),
+            ),
+            (
+                "BlockHeader",
+                &GENESIS_BLOCK_HEADER,
+                consumed!(bsl::BlockHeader),
+            ),
+            ("Block", &GENESIS_BLOCK, consumed!(bsl::Block)),
+        ];
+        for (name, valid, parse) in cases {
+            assert_eq!(parse(valid), Ok(valid.len()), "{}", name);
+            for len in 0..valid.len() {
+                let error = parse(&valid[..len]).unwrap_err();
+                assert!(
+                    error.needed().is_some(),
+                    "{} truncated at {}: {:?}",
+                    name,
+                    len,
+                    error
+                );
+            }
+        }
+
+        // every prefix would take too long
+        let block = bitcoin_test_data::blocks::mainnet_702861();
+        for len in (0..block.len()).step_by(4099) {
+            let error = bsl::Block::parse(&block[..len]).unwrap_err();
+            assert!(error.needed().is_some(), "truncated at {}", len);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let messages = [
+            (
+                Error::Needed(3),
+                "unexpected end of data, 3 more bytes needed",
+            ),
+            (Error::UnknownSegwitFlag(3), "unknown segwit flag 0x03"),
+            (Error::NonMinimalVarInt, "non-minimal compact size encoding"),
+            (Error::TrailingBytes(1), "1 trailing bytes after the object"),
+            (Error::InvalidSighashType(0x84), "invalid sighash type 0x84"),
+            (
+                Error::LimitExceeded(ParseLimit::Inputs),
+                "parse limit exceeded: too many inputs",
+            ),
+            (
+                Error::InvalidAmount(AmountError::OutputTooLarge(1)),
+                "invalid amount: value of output 1 greater than MAX_MONEY",
+            ),
+        ];
+        for (error, message) in messages {
+            assert_eq!(error.to_string(), message);
+        }
+        assert_eq!(
+            FeeError::MissingPrevout(2).to_string(),
+            "missing previous output spent by input 2"
+        );
+        assert_eq!(
+            PowError::InvalidTarget(TargetError::Negative).to_string(),
+            "invalid target: negative compact target"
+        );
+        assert_eq!(
+            MerkleBlockError::Unused.to_string(),
+            "unused flag bits or hashes in the partial merkle tree"
+        );
+        assert_eq!(
+            HeaderChainError::Disconnected(4).to_string(),
+            "header 4 not connected to the previous one"
+        );
+        assert_eq!(
+            SizeLimitError::WeightTooHigh(400_001).to_string(),
+            "transaction weight 400001 too high"
+        );
+        let mut error = OffsetError {
+            offset: 5,
+            error: Error::UnknownSegwitFlag(2),
+            transaction: None,
+            element: None,
+        };
+        assert_eq!(error.to_string(), "unknown segwit flag 0x02 at offset 5");
+        error.error = Error::NonMinimalVarInt;
+        error.element = Some((ElementKind::Input, 3));
+        error.transaction = Some(7);
+        assert_eq!(
+            error.to_string(),
+            "non-minimal compact size encoding at offset 5 in input 3 of transaction 7"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn std_error() {
+        let error = OffsetError {
+            offset: 0,
+            error: Error::Needed(1),
+            transaction: None,
+            element: None,
+        };
+        let boxed: Box<dyn std::error::Error> = Box::new(error);
+        let source = boxed.source().unwrap();
+        assert_eq!(source.to_string(), Error::Needed(1).to_string());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn into_io_error() {
+        use std::io;
+
+        let cases = [
+            (Error::Needed(3), io::ErrorKind::UnexpectedEof),
+            (Error::NonMinimalVarInt, io::ErrorKind::InvalidData),
+            (Error::UnknownSegwitFlag(2), io::ErrorKind::InvalidData),
+        ];
+        for (error, kind) in cases {
+            let io_error = io::Error::from(error.clone());
+            assert_eq!(io_error.kind(), kind);
+            assert_eq!(io_error.to_string(), error.to_string());
+            assert_eq!(io_error.get_ref().unwrap().downcast_ref(), Some(&error));
+            let inner = io_error.into_inner().unwrap().downcast::<Error>().unwrap();
+            assert_eq!(*inner, error);
+        }
+
+        fn parse(slice: &[u8]) -> io::Result<usize> {
+            Ok(crate::bsl::parse::<crate::bsl::TxOut>(slice)?.0.value() as usize)
+        }
+        assert_eq!(
+            parse(&[0u8; 5]).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
 
     #[cfg(target_pointer_width = "64")]
     #[test]
@@ -0,0 +1,115 @@
+//! Minimal hex encoding and decoding, encoding doesn't allocate so it works in no_std.
+
+use core::fmt;
+
+/// Displays the wrapped bytes as hex, lowercase with `Display`.
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl<'a> fmt::LowerHex for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::UpperHex for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Hex<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Implements `LowerHex`, `UpperHex` and `Display` (as lowercase hex) of the consensus encoded
+/// bytes returned by `as_ref()`.
+macro_rules! impl_hex_fmt {
+    ($type:ident) => {
+        impl<'a> core::fmt::LowerHex for $type<'a> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::LowerHex::fmt(&crate::hex::Hex(self.as_ref()), f)
+            }
+        }
+
+        impl<'a> core::fmt::UpperHex for $type<'a> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::UpperHex::fmt(&crate::hex::Hex(self.as_ref()), f)
+            }
+        }
+
+        /// Displays the consensus encoded bytes as lowercase hex.
+        impl<'a> core::fmt::Display for $type<'a> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::LowerHex::fmt(self, f)
+            }
+        }
+    };
+}
+pub(crate) use impl_hex_fmt;
+
+/// Decode the hex string `s`, both lowercase and uppercase are accepted.
+/// Returns `None` if `s` has odd length or contains non hex chars.
+#[cfg(all(feature = "serde", feature = "alloc"))]
+pub(crate) fn decode(s: &str) -> Option<alloc::vec::Vec<u8>> {
+    fn nibble(c: u8) -> Option<u8> {
+        match c {
+            b'0'..=b'9' => Some(c - b'0'),
+            b'a'..=b'f' => Some(c - b'a' + 10),
+            b'A'..=b'F' => Some(c - b'A' + 10),
+            _ => None,
+        }
+    }
+    let chunks = s.as_bytes().chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    chunks
+        .map(|pair| Some(nibble(pair[0])? << 4 | nibble(pair[1])?))
+        .collect()
+}
+
+/// Serialize `bytes` as a lowercase hex string for human readable formats, as bytes otherwise.
+#[cfg(feature = "serde")]
+pub(crate) fn serialize<S: serde::Serializer>(
+    bytes: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_str(&Hex(bytes))
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Hex;
+    use hex_lit::hex;
+
+    #[test]
+    fn hex() {
+        assert_eq!(Hex(&[]).to_string(), "");
+        assert_eq!(Hex(&hex!("00ff0a")).to_string(), "00ff0a");
+        assert_eq!(format!("{:x}", Hex(&hex!("00ff0a"))), "00ff0a");
+        assert_eq!(format!("{:X}", Hex(&hex!("00ff0a"))), "00FF0A");
+    }
+
+    #[cfg(all(feature = "serde", feature = "alloc"))]
+    #[test]
+    fn decode() {
+        use super::decode;
+        assert_eq!(decode(""), Some(vec![]));
+        assert_eq!(decode("00ff0a"), Some(hex!("00ff0a").to_vec()));
+        assert_eq!(decode("00FF0A"), Some(hex!("00ff0a").to_vec()));
+        assert_eq!(decode("0"), None);
+        assert_eq!(decode("0g"), None);
+    }
+}
@@ -8,15 +8,29 @@
 #[cfg(bench)]
 extern crate test;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(all(feature = "std", not(test)))]
+extern crate std;
+
 pub mod bsl;
 mod error;
+mod hex;
 pub mod number;
 mod parse_result;
 mod slice;
 mod visit;
 
-pub use error::Error;
-pub use parse_result::ParseResult;
+pub use error::{
+    AmountError, ElementKind, Error, FeeError, HeaderChainError, MerkleBlockError, MerkleError,
+    OffsetError, ParseLimit, PowError, SizeLimitError, TargetError, WitnessCommitmentError,
+};
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub use error::StreamError;
+pub use parse_result::{ParseResult, Parsed};
 pub use slice::read_slice;
 pub use visit::{EmptyVisitor, Parse, Visit, Visitor};
 
@@ -39,22 +53,52 @@ pub use redb;
 #[cfg_attr(docsrs, doc(cfg(feature = "bitcoin")))]
 pub use bitcoin;
 
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use serde;
+
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub use tokio;
+
+#[cfg(feature = "bytes")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bytes")))]
+pub use bytes;
+
+#[cfg(feature = "arbitrary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+pub use arbitrary;
+
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+pub use proptest;
+
+/// Test vectors and helpers shared by tests and benches
 #[cfg(any(test, bench))]
 pub mod test_common {
     use hex_lit::hex;
 
     use crate::ParseResult;
 
+    /// The mainnet genesis transaction
     pub const GENESIS_TX: [u8; 204] = hex!("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
+    /// A testnet segwit coinbase transaction
+    pub const SEGWIT_TX: [u8; 222] = hex!("010000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff3603da1b0e00045503bd5704c7dd8a0d0ced13bb5785010800000000000a636b706f6f6c122f4e696e6a61506f6f6c2f5345475749542fffffffff02b4e5a212000000001976a914876fbb82ec05caa6af7a3b5e5a983aae6c6cc6d688ac0000000000000000266a24aa21a9edf91c46b49eb8a29089980f02ee6b57e7d63d33b18b4fddac2bcd7db2a39837040120000000000000000000000000000000000000000000000000000000000000000000000000");
+    /// A mainnet legacy transaction with one input and one output
+    pub const LEGACY_TX: [u8; 193] = hex!("0100000001a15d57094aa7a21a28cb20b59aab8fc7d1149a3bdbcddba9c622e4f5f6a99ece010000006c493046022100f93bb0e7d8db7bd46e40132d1f8242026e045f03a0efe71bbb8e3f475e970d790221009337cd7f1f929f00cc6ff01f03729b069a7c21b59b1736ddfee5db5946c5da8c0121033b9b137ee87d5a812d6f506efdd37f0affa7ffc310711c06c7f3e097c9447c52ffffffff0100e1f505000000001976a9140389035a9225b3839e2bbf32d826a1e222031fd888ac00000000");
+    /// The mainnet genesis block header
     pub const GENESIS_BLOCK_HEADER: [u8; 80] = hex!("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c");
+    /// The mainnet genesis block
     pub const GENESIS_BLOCK: [u8;285] = hex!("0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000");
 
     impl<'a, T: AsRef<[u8]>> ParseResult<'a, T> {
+        /// Creates a `ParseResult` with no remaining bytes
         pub fn new_exact(parsed: T) -> Self {
             ParseResult::new(&[], parsed)
         }
     }
 
+    /// Returns the given array in reverse order
     pub fn reverse(arr: [u8; 32]) -> [u8; 32] {
         let mut ret = arr;
         ret.reverse();
@@ -46,7 +46,7 @@ macro_rules! impl_number {
         pub struct $newtype([u8; $size]);
 
         impl<'a> Visit<'a> for $newtype {
-            fn visit<'b, V: crate::Visitor>(
+            fn visit<'b, V: crate::Visitor<'a>>(
                 slice: &'a [u8],
                 _visit: &'b mut V,
             ) -> SResult<'a, Self> {
@@ -1,3 +1,5 @@
+use crate::{Error, Parse};
+
 /// Every `parse` or `visit` functions on success return this struct.
 /// It contains the object parsed `T` the remaining bytes (empty slice if all bytes in the slice are
 /// consumed), and the bytes consumed.
@@ -32,4 +34,119 @@ impl<'a, T: AsRef<[u8]>> ParseResult<'a, T> {
     pub fn consumed(&self) -> usize {
         self.parsed.as_ref().len()
     }
+    /// Transform the object parsed with `f`, keeping the remaining and the consumed bytes.
+    pub fn map_parsed<U, F: FnOnce(T) -> U>(self, f: F) -> Parsed<'a, U> {
+        Parsed::from(self).map_parsed(f)
+    }
+    /// Parse `U` from the remaining bytes, returning both objects and the bytes consumed by both.
+    pub fn parse_next<U: Parse<'a>>(self) -> Result<Parsed<'a, (T, U)>, Error> {
+        Parsed::from(self).parse_next()
+    }
+    /// Returns the object parsed, or [`Error::TrailingBytes`] if there are remaining bytes.
+    pub fn require_empty(self) -> Result<T, Error> {
+        Parsed::from(self).require_empty()
+    }
+}
+
+/// An object parsed from a slice with the remaining bytes and the number of bytes consumed,
+/// returned by the combinators of [`ParseResult`].
+///
+/// Unlike [`ParseResult`], the object parsed doesn't need to be the consumed bytes, so it can be
+/// a value converted from them or a tuple of objects parsed one after the other.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parsed<'a, T> {
+    remaining: &'a [u8],
+    consumed: usize,
+    parsed: T,
+}
+
+impl<'a, T> Parsed<'a, T> {
+    /// returns the remaining slice, which is empty if all the bytes in the slice have been used.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.remaining
+    }
+    /// returns a reference of the object parsed
+    pub fn parsed(&self) -> &T {
+        &self.parsed
+    }
+    /// returns the object parsed owned
+    pub fn parsed_owned(self) -> T {
+        self.parsed
+    }
+    /// returns the byte used to parse `T`
+    pub fn consumed(&self) -> usize {
+        self.consumed
+    }
+    /// Transform the object parsed with `f`, keeping the remaining and the consumed bytes.
+    pub fn map_parsed<U, F: FnOnce(T) -> U>(self, f: F) -> Parsed<'a, U> {
+        Parsed {
+            remaining: self.remaining,
+            consumed: self.consumed,
+            parsed: f(self.parsed),
+        }
+    }
+    /// Parse `U` from the remaining bytes, returning both objects and the bytes consumed by both.
+    pub fn parse_next<U: Parse<'a>>(self) -> Result<Parsed<'a, (T, U)>, Error> {
+        let next = U::parse(self.remaining)?;
+        Ok(Parsed {
+            remaining: next.remaining(),
+            consumed: self.consumed + next.consumed(),
+            parsed: (self.parsed, next.parsed_owned()),
+        })
+    }
+    /// Returns the object parsed, or [`Error::TrailingBytes`] if there are remaining bytes.
+    pub fn require_empty(self) -> Result<T, Error> {
+        match self.remaining.len() {
+            0 => Ok(self.parsed),
+            n => Err(Error::TrailingBytes(u32::try_from(n).unwrap_or(u32::MAX))),
+        }
+    }
+}
+
+impl<'a, T: AsRef<[u8]>> From<ParseResult<'a, T>> for Parsed<'a, T> {
+    fn from(result: ParseResult<'a, T>) -> Self {
+        Parsed {
+            remaining: result.remaining,
+            consumed: result.consumed(),
+            parsed: result.parsed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        number::{I32, U32, U8},
+        Error, Parse,
+    };
+
+    #[test]
+    fn combinators() {
+        let bytes = [0xff, 0xff, 0xff, 0xff, 2, 3, 0, 0, 0, 9];
+        let parsed = I32::parse(&bytes[..])
+            .unwrap()
+            .parse_next::<U8>()
+            .unwrap()
+            .parse_next::<U32>()
+            .unwrap()
+            .map_parsed(|((a, b), c)| (i32::from(a), u8::from(b), u32::from(c)));
+        assert_eq!(parsed.parsed(), &(-1, 2, 3));
+        assert_eq!(parsed.consumed(), 9);
+        assert_eq!(parsed.remaining(), &[9]);
+        assert_eq!(parsed.clone().require_empty(), Err(Error::TrailingBytes(1)));
+
+        let parsed = U8::parse(&bytes[9..]).unwrap().map_parsed(u8::from);
+        assert_eq!((parsed.consumed(), parsed.require_empty()), (1, Ok(9)));
+        assert_eq!(
+            U8::parse(&bytes[9..]).unwrap().require_empty(),
+            Ok(9.into())
+        );
+        assert_eq!(
+            U8::parse(&bytes[..]).unwrap().require_empty(),
+            Err(Error::TrailingBytes(9))
+        );
+
+        let result = I32::parse(&bytes[..6]).unwrap().parse_next::<U8>();
+        assert_eq!(result.unwrap().parse_next::<U32>(), Err(Error::Needed(3)));
+    }
 }
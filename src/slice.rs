@@ -1,7 +1,7 @@
 use crate::{Error, ParseResult, SResult};
 
 /// Return a slice legnth `len` from `from` if it's long enough, error otherwise.
-pub fn read_slice(from: &[u8], len: usize) -> SResult<&[u8]> {
+pub fn read_slice(from: &[u8], len: usize) -> SResult<'_, &[u8]> {
     if from.len() < len {
         let needed = len - from.len();
         Err(Error::Needed(u32::try_from(needed).unwrap_or(u32::MAX)))
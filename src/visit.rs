@@ -1,4 +1,6 @@
-use crate::SResult;
+use core::ops::ControlFlow;
+
+use crate::{Error, SResult};
 
 use super::bsl;
 
@@ -11,6 +13,12 @@ pub trait Parse<'a>: Sized + AsRef<[u8]> {
     /// Parse the object from the slice
     fn parse(slice: &'a [u8]) -> SResult<'a, Self>;
 
+    /// Parse the object from the slice like [`Parse::parse`], returning
+    /// [`Error::TrailingBytes`] if the slice contains other bytes after the object.
+    fn parse_all(slice: &'a [u8]) -> Result<Self, Error> {
+        Self::parse(slice)?.require_empty()
+    }
+
     /// Return the serialized len of this object
     fn len(&self) -> usize {
         self.as_ref().len()
@@ -23,14 +31,14 @@ pub trait Parse<'a>: Sized + AsRef<[u8]> {
 #[allow(clippy::len_without_is_empty)]
 pub trait Visit<'a>: Sized + AsRef<[u8]> {
     /// Visit the object from the slice while calling methods on the given visitor
-    fn visit<'b, V: Visitor>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self>;
+    fn visit<'b, V: Visitor<'a>>(slice: &'a [u8], visit: &'b mut V) -> SResult<'a, Self>;
 
     /// Self visit calling methods on the given visitor.
     ///
     /// It's generally better to avoid a double pass and visit directly the first passing through
     /// the slice. However, there are case where the slice has already been validated, for example
     /// inserted in a db and you need to visit again.
-    fn self_visit<'b, V: Visitor>(&'a self, visit: &'b mut V) -> SResult<'a, Self> {
+    fn self_visit<'b, V: Visitor<'a>>(&'a self, visit: &'b mut V) -> SResult<'a, Self> {
         Self::visit(self.as_ref(), visit)
     }
 
@@ -53,40 +61,129 @@ impl<'a, T: Visit<'a>> Parse<'a> for T {
 ///
 /// Not every function is called, for example `visit_block_header` is obviously not called when
 /// parsing a transasction.
+///
+/// After every function [`Visitor::control_flow()`] is called, returning [`ControlFlow::Break`]
+/// stops the visit without calling further functions, with the `visit()` returning
+/// [`Error::VisitBreak`].
+///
+/// The objects passed to the functions borrow the visited slice with lifetime `'a`, so that an
+/// implementation of `Visitor<'a>` can keep the slices they return, like
+/// [`bsl::TxOut::script_pubkey()`], after the visit. A visitor keeping nothing can be implemented
+/// as `impl Visitor<'_> for MyVisitor`.
+///
+/// Tuples of up to 4 visitors are visitors too, so that more visitors can be run in one pass.
+///
 /// Traits with single function would have been more precies, however, it would have required the
 /// user to implement those trait with an empty block which was considered too verbose. Morever it
 /// looks a single trait with many functions is more perfomant.
 #[allow(unused)]
-pub trait Visitor {
+pub trait Visitor<'a> {
     /// Visit the block header, called from [`bsl::Block::visit()`] and [`bsl::BlockHeader::visit()`]
-    fn visit_block_header(&mut self, header: &bsl::BlockHeader) {}
+    fn visit_block_header(&mut self, header: &bsl::BlockHeader<'a>) {}
     /// Visit the number of transactions in a block, called from [`bsl::Block::visit()`]
     fn visit_block_begin(&mut self, total_transactions: usize) {}
+    /// Finishing visiting the block, called from [`bsl::Block::visit()`] after the last
+    /// transaction
+    fn visit_block_end(&mut self) {}
 
     /// Visit a transaction, called from  [`bsl::Block::visit()`] and  [`bsl::Transaction::visit()`]
     ///
     /// Note you can't access inputs and outputs from the transaction, you need [`Visitor::visit_tx_ins()`]
     /// or [`Visitor::visit_tx_outs()`]
-    fn visit_transaction(&mut self, tx: &bsl::Transaction) {}
+    fn visit_transaction(&mut self, tx: &bsl::Transaction<'a>) {}
+
+    /// Visit a transaction of a block, called from [`bsl::Block::visit()`] after
+    /// [`Visitor::visit_transaction()`].
+    ///
+    /// `offset` is the position of the transaction relative to the start of the slice passed to
+    /// [`bsl::Block::visit()`], so that `&slice[offset..offset + tx.as_ref().len()]` is the
+    /// transaction.
+    fn visit_transaction_at(&mut self, offset: usize, tx: &bsl::Transaction<'a>) {}
 
     /// We are going to visit `total_inputs` transaction inputs
     fn visit_tx_ins(&mut self, total_inputs: usize) {}
     /// Visit transaction input at position `vin`
-    fn visit_tx_in(&mut self, vin: usize, tx_in: &bsl::TxIn) {}
+    fn visit_tx_in(&mut self, vin: usize, tx_in: &bsl::TxIn<'a>) {}
 
+    /// Returns false to skip the calls about the transaction outputs, from
+    /// [`Visitor::visit_tx_outs()`] to [`Visitor::visit_script_opcode()`], the outputs are still
+    /// parsed.
+    ///
+    /// Useful for visitors which can't be inlined, like `&mut dyn Visitor<'_>`, to avoid the calls.
+    /// It's a hint: the calls are still made in a tuple with a visitor needing them.
+    fn need_tx_outs(&self) -> bool {
+        true
+    }
     /// We are going to visit `total_outputs` transaction outputs
     fn visit_tx_outs(&mut self, total_outputs: usize) {}
     /// Visit transaction output at position `vout`
-    fn visit_tx_out(&mut self, vout: usize, tx_out: &bsl::TxOut) {}
+    fn visit_tx_out(&mut self, vout: usize, tx_out: &bsl::TxOut<'a>) {}
+    /// Visit the script pubkey of the output at position `vout`, called after
+    /// [`Visitor::visit_tx_out()`].
+    ///
+    /// `tx_index` is the position of the transaction in the block when visiting a
+    /// [`bsl::Block`], 0 otherwise.
+    fn visit_script_pubkey(&mut self, tx_index: usize, vout: usize, script: &bsl::Script<'a>) {}
+    /// Returns true to tokenize the script pubkeys, calling [`Visitor::visit_script_opcode()`] for
+    /// every instruction, default to false so that the scripts are not tokenized.
+    fn want_script_opcodes(&self) -> bool {
+        false
+    }
+    /// Visit an instruction of the script pubkey visited by the last
+    /// [`Visitor::visit_script_pubkey()`], `push` contains the pushed data if the opcode is a
+    /// push, called only if [`Visitor::want_script_opcodes()`] returns true.
+    ///
+    /// Like in Bitcoin Core the tokenization stops at a truncated push.
+    fn visit_script_opcode(&mut self, opcode: u8, push: Option<&'a [u8]>) {}
 
+    /// Returns false to skip the calls about the witnesses, from [`Visitor::visit_witness()`] to
+    /// [`Visitor::visit_witness_end()`], the witnesses are still parsed, see
+    /// [`Visitor::need_tx_outs()`].
+    fn need_witnesses(&self) -> bool {
+        true
+    }
     /// We are going to visit the witnes of the `vin` input
     fn visit_witness(&mut self, vin: usize) {}
     /// The following witness has `witness_total` element
     fn visit_witness_total_element(&mut self, witness_total: usize) {}
     /// Visiting the `witness_i`ith element of this witness: `witness_element`
-    fn visit_witness_element(&mut self, witness_i: usize, witness_element: &[u8]) {}
+    fn visit_witness_element(&mut self, witness_i: usize, witness_element: &'a [u8]) {}
     /// Finishing visiting this witness
     fn visit_witness_end(&mut self) {}
+
+    /// We are going to visit the undo data of `total_tx_undos` transactions, called from
+    /// [`bsl::BlockUndo::visit()`]
+    fn visit_block_undo(&mut self, total_tx_undos: usize) {}
+    /// We are going to visit the `total_spent` coins spent by a transaction, called from
+    /// [`bsl::TxUndo::visit()`]
+    fn visit_tx_undo(&mut self, total_spent: usize) {}
+    /// Visit the coin spent by the input at position `vin`
+    fn visit_spent_coin(&mut self, vin: usize, coin: &bsl::SpentCoin<'a>) {}
+
+    /// Visit a compact int just read, of `kind`, decoding to `value` and occupying `encoded_len`
+    /// bytes, called before the functions visiting the objects it's the length or count of.
+    ///
+    /// The lengths of the skipped objects, see [`Visitor::need_tx_outs()`], are not visited.
+    fn visit_len(&mut self, kind: bsl::LenKind, value: u64, encoded_len: usize) {}
+
+    /// Called after every other function, returns [`ControlFlow::Break`] to stop the visit, for
+    /// example after finding what the visitor was looking for, default to
+    /// [`ControlFlow::Continue`].
+    ///
+    /// The visitor usually records in a field that it's done in the function called before and
+    /// reads it here, so a visitor reused for another visit must reset it.
+    fn control_flow(&self) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+/// Checks the visitor [`Visitor::control_flow()`] after calling one of its functions, `?` on it
+/// stops the visit after a `Break`.
+pub(crate) fn check<'a, V: Visitor<'a> + ?Sized>(visit: &V) -> Result<(), Error> {
+    match visit.control_flow() {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(Error::VisitBreak),
+    }
 }
 
 /// A visitor with all empty function.
@@ -94,4 +191,349 @@ pub trait Visitor {
 /// When `visit()` is present in structs, the `parse()` method is constructed by calling `visit`
 /// with this empty visitor.
 pub struct EmptyVisitor {}
-impl Visitor for EmptyVisitor {}
+impl Visitor<'_> for EmptyVisitor {}
+
+/// Calls `$callback!(@fns $ctx [...] [...])` with the signatures of all the [`Visitor`] visit
+/// functions and the names of the ones returning `bool` hints, so that forwarding implementations
+/// can't miss one. The callbacks implement [`Visitor::control_flow()`] themselves.
+macro_rules! visitor_fns {
+    ($callback:ident $ctx:tt) => {
+        $callback!(@fns $ctx [
+            visit_block_header(header: &bsl::BlockHeader<'a>);
+            visit_block_begin(total_transactions: usize);
+            visit_block_end();
+            visit_transaction(tx: &bsl::Transaction<'a>);
+            visit_transaction_at(offset: usize, tx: &bsl::Transaction<'a>);
+            visit_tx_ins(total_inputs: usize);
+            visit_tx_in(vin: usize, tx_in: &bsl::TxIn<'a>);
+            visit_tx_outs(total_outputs: usize);
+            visit_tx_out(vout: usize, tx_out: &bsl::TxOut<'a>);
+            visit_script_pubkey(tx_index: usize, vout: usize, script: &bsl::Script<'a>);
+            visit_script_opcode(opcode: u8, push: Option<&'a [u8]>);
+            visit_witness(vin: usize);
+            visit_witness_total_element(witness_total: usize);
+            visit_witness_element(witness_i: usize, witness_element: &'a [u8]);
+            visit_witness_end();
+            visit_block_undo(total_tx_undos: usize);
+            visit_tx_undo(total_spent: usize);
+            visit_spent_coin(vin: usize, coin: &bsl::SpentCoin<'a>);
+            visit_len(kind: bsl::LenKind, value: u64, encoded_len: usize);
+        ] [
+            want_script_opcodes;
+            need_tx_outs;
+            need_witnesses;
+        ]);
+    };
+}
+
+#[cfg(feature = "stats")]
+pub(crate) use visitor_fns;
+
+/// Implements [`Visitor`] for tuples of visitors, calling every function on all of them in order.
+///
+/// The tuple continues only if all the visitors continue, but a visitor breaking doesn't skip the
+/// call to the visitors following it in the tuple for the same function, since the control flow is
+/// checked after it's called on all of them.
+macro_rules! impl_visitor_tuple {
+    ($($visitor:ident)+) => {
+        impl<'a, $($visitor: Visitor<'a>),+> Visitor<'a> for ($($visitor,)+) {
+            visitor_fns!(impl_visitor_tuple ($($visitor)+));
+        }
+    };
+    (@fns $visitors:tt [$($name:ident($($arg:ident: $ty:ty),*);)+] [$($hint:ident;)+]) => {
+        $(
+            #[allow(non_snake_case)]
+            fn $name(&mut self, $($arg: $ty),*) {
+                impl_visitor_tuple!(@call self $visitors $name($($arg),*))
+            }
+        )+
+        $(
+            #[allow(non_snake_case)]
+            fn $hint(&self) -> bool {
+                impl_visitor_tuple!(@any self $visitors $hint)
+            }
+        )+
+        #[allow(non_snake_case)]
+        fn control_flow(&self) -> ControlFlow<()> {
+            if impl_visitor_tuple!(@break self $visitors) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    };
+    (@call $self:ident ($($visitor:ident)+) $name:ident $args:tt) => {{
+        let ($($visitor,)+) = $self;
+        $(
+            $visitor.$name $args;
+        )+
+    }};
+    (@any $self:ident ($($visitor:ident)+) $hint:ident) => {{
+        let ($($visitor,)+) = $self;
+        false $(|| $visitor.$hint())+
+    }};
+    (@break $self:ident ($($visitor:ident)+)) => {{
+        let ($($visitor,)+) = $self;
+        false $(|| $visitor.control_flow().is_break())+
+    }};
+}
+
+/// Implements the [`Visitor`] functions by forwarding them to `**self`.
+macro_rules! impl_visitor_deref {
+    (@fns () [$($name:ident($($arg:ident: $ty:ty),*);)+] [$($hint:ident;)+]) => {
+        $(
+            fn $name(&mut self, $($arg: $ty),*) {
+                (**self).$name($($arg),*)
+            }
+        )+
+        $(
+            fn $hint(&self) -> bool {
+                (**self).$hint()
+            }
+        )+
+        fn control_flow(&self) -> ControlFlow<()> {
+            (**self).control_flow()
+        }
+    };
+}
+
+/// Implements the [`Visitor`] functions by calling them on every element of `self`, in order,
+/// like the tuples implementation.
+#[cfg(feature = "alloc")]
+macro_rules! impl_visitor_fan_out {
+    (@fns () [$($name:ident($($arg:ident: $ty:ty),*);)+] [$($hint:ident;)+]) => {
+        $(
+            fn $name(&mut self, $($arg: $ty),*) {
+                for visitor in self.iter_mut() {
+                    visitor.$name($($arg),*);
+                }
+            }
+        )+
+        $(
+            fn $hint(&self) -> bool {
+                self.iter().any(|visitor| visitor.$hint())
+            }
+        )+
+        fn control_flow(&self) -> ControlFlow<()> {
+            if self.iter().any(|visitor| visitor.control_flow().is_break()) {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    };
+}
+
+/// Allows passing `&mut dyn Visitor<'_>`, or reusing a visitor by reference, where a visitor is
+/// expected.
+impl<'a, V: Visitor<'a> + ?Sized> Visitor<'a> for &mut V {
+    visitor_fns!(impl_visitor_deref());
+}
+
+/// Allows using `Box<dyn Visitor<'_>>`, for example for visitors chosen at runtime.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<'a, V: Visitor<'a> + ?Sized> Visitor<'a> for alloc::boxed::Box<V> {
+    visitor_fns!(impl_visitor_deref());
+}
+
+/// Calls every function on all the visitors in order, with the same semantics of the tuples
+/// implementation, useful with a dynamic number of visitors like `Vec<Box<dyn Visitor<'_>>>`.
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+impl<'a, V: Visitor<'a>> Visitor<'a> for alloc::vec::Vec<V> {
+    visitor_fns!(impl_visitor_fan_out());
+}
+
+impl_visitor_tuple!(A B);
+impl_visitor_tuple!(A B C);
+impl_visitor_tuple!(A B C D);
+
+#[cfg(test)]
+mod test {
+    use core::ops::ControlFlow;
+
+    use crate::{bsl, Error, Visit, Visitor};
+    use bitcoin_test_data::blocks::mainnet_702861;
+
+    /// Records the sequence of functions called, optionally breaking at the given output.
+    #[derive(Default)]
+    struct Log {
+        calls: Vec<(&'static str, usize)>,
+        outputs: usize,
+        break_at_output: Option<usize>,
+    }
+    impl Visitor<'_> for Log {
+        fn visit_block_begin(&mut self, total_transactions: usize) {
+            self.calls.push(("block_begin", total_transactions));
+        }
+        fn visit_transaction_at(&mut self, offset: usize, _tx: &bsl::Transaction) {
+            self.calls.push(("transaction_at", offset));
+        }
+        fn visit_tx_in(&mut self, vin: usize, _tx_in: &bsl::TxIn) {
+            self.calls.push(("tx_in", vin));
+        }
+        fn visit_tx_out(&mut self, vout: usize, _tx_out: &bsl::TxOut) {
+            self.calls.push(("tx_out", vout));
+            self.outputs += 1;
+        }
+        fn visit_witness_element(&mut self, witness_i: usize, _: &[u8]) {
+            self.calls.push(("witness_element", witness_i));
+        }
+        fn visit_block_end(&mut self) {
+            self.calls.push(("block_end", 0));
+        }
+        fn control_flow(&self) -> ControlFlow<()> {
+            if Some(self.outputs) == self.break_at_output {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+    }
+
+    #[test]
+    fn visitor_tuples() {
+        let mut alone = Log::default();
+        bsl::Block::visit(mainnet_702861(), &mut alone).unwrap();
+        assert_eq!(alone.calls.first(), Some(&("block_begin", 2500)));
+        assert_eq!(alone.calls.last(), Some(&("block_end", 0)));
+
+        let mut pair = (Log::default(), Log::default());
+        bsl::Block::visit(mainnet_702861(), &mut pair).unwrap();
+        assert_eq!(pair.0.calls, alone.calls);
+        assert_eq!(pair.1.calls, alone.calls);
+
+        let mut quadruple = (
+            Log::default(),
+            Log::default(),
+            Log::default(),
+            Log::default(),
+        );
+        bsl::Block::visit(mainnet_702861(), &mut quadruple).unwrap();
+        assert_eq!(quadruple.3.calls, alone.calls);
+
+        // the first breaking stops the visit, the second still sees the call
+        let mut pair = (
+            Log {
+                break_at_output: Some(3),
+                ..Default::default()
+            },
+            Log::default(),
+        );
+        let result = bsl::Block::visit(mainnet_702861(), &mut pair);
+        assert_eq!(result, Err(Error::VisitBreak));
+        assert_eq!(pair.0.calls, pair.1.calls);
+        assert_eq!(pair.1.outputs, 3);
+    }
+
+    #[test]
+    fn need_hints() {
+        struct Hinted {
+            need_tx_outs: bool,
+            need_witnesses: bool,
+            transactions: usize,
+            tx_outs_calls: usize,
+            witnesses_calls: usize,
+        }
+        impl Hinted {
+            fn new(need_tx_outs: bool, need_witnesses: bool) -> Self {
+                Hinted {
+                    need_tx_outs,
+                    need_witnesses,
+                    transactions: 0,
+                    tx_outs_calls: 0,
+                    witnesses_calls: 0,
+                }
+            }
+        }
+        impl Visitor<'_> for Hinted {
+            fn need_tx_outs(&self) -> bool {
+                self.need_tx_outs
+            }
+            fn need_witnesses(&self) -> bool {
+                self.need_witnesses
+            }
+            fn visit_transaction(&mut self, _tx: &bsl::Transaction) {
+                self.transactions += 1;
+            }
+            fn visit_tx_outs(&mut self, _total_outputs: usize) {
+                self.tx_outs_calls += 1;
+            }
+            fn visit_witness_element(&mut self, _: usize, _: &[u8]) {
+                self.witnesses_calls += 1;
+            }
+        }
+
+        let block = mainnet_702861();
+        for (need_tx_outs, need_witnesses) in [(true, true), (false, true), (true, false)] {
+            let mut hinted = Hinted::new(need_tx_outs, need_witnesses);
+            let mut visitor: &mut dyn Visitor<'_> = &mut hinted;
+            let consumed = bsl::Block::visit(block, &mut visitor).unwrap().consumed();
+            assert_eq!(consumed, block.len());
+            assert_eq!(hinted.transactions, 2500);
+            assert_eq!(hinted.tx_outs_calls > 0, need_tx_outs);
+            assert_eq!(hinted.witnesses_calls > 0, need_witnesses);
+        }
+
+        // in a tuple the hints are combined, the calls are made if any visitor needs them
+        let mut pair = (Hinted::new(false, false), Hinted::new(true, false));
+        bsl::Block::visit(block, &mut pair).unwrap();
+        assert_eq!(pair.0.tx_outs_calls, pair.1.tx_outs_calls);
+        assert_eq!(pair.0.tx_outs_calls, 2500);
+        assert_eq!(pair.0.witnesses_calls, 0);
+    }
+
+    #[test]
+    fn borrowing_visitor() {
+        struct ScriptPubkeys<'a>(Vec<&'a [u8]>);
+        impl<'a> Visitor<'a> for ScriptPubkeys<'a> {
+            fn visit_tx_out(&mut self, _vout: usize, tx_out: &bsl::TxOut<'a>) {
+                self.0.push(tx_out.script_pubkey());
+            }
+        }
+
+        let block = mainnet_702861();
+        let script_pubkeys = {
+            let mut visitor = ScriptPubkeys(vec![]);
+            bsl::Block::visit(block, &mut visitor).unwrap();
+            visitor.0
+        };
+        let block_bitcoin: bitcoin::Block = bitcoin::consensus::deserialize(block).unwrap();
+        let expected: Vec<_> = block_bitcoin
+            .txdata
+            .iter()
+            .flat_map(|tx| tx.output.iter())
+            .map(|output| output.script_pubkey.as_bytes())
+            .collect();
+        assert_eq!(script_pubkeys, expected);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn dyn_visitors() {
+        use crate::bsl::visitors::CountingVisitor;
+
+        let mut alone = Log::default();
+        bsl::Block::visit(mainnet_702861(), &mut alone).unwrap();
+
+        let mut log = Log::default();
+        let mut counting = CountingVisitor::default();
+        {
+            let mut plugins: Vec<Box<dyn Visitor<'_> + '_>> =
+                vec![Box::new(&mut log), Box::new(&mut counting)];
+            bsl::Block::visit(mainnet_702861(), &mut plugins).unwrap();
+        }
+        assert_eq!(log.calls, alone.calls);
+        assert_eq!(counting.transactions, 2500);
+
+        let mut log = Log {
+            break_at_output: Some(3),
+            ..Default::default()
+        };
+        let mut visitor: &mut dyn Visitor<'_> = &mut log;
+        let result = bsl::Block::visit(mainnet_702861(), &mut visitor);
+        assert_eq!(result, Err(Error::VisitBreak));
+        assert_eq!(log.outputs, 3);
+    }
+}